@@ -0,0 +1,103 @@
+#ifndef JKCC_H
+#define JKCC_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ What a call returned.
+ */
+typedef enum JkccStatus {
+  /*
+   The call succeeded.
+   */
+  JKCC_STATUS_OK = 0,
+  /*
+   A required pointer was null.
+   */
+  JKCC_STATUS_NULL_ARGUMENT = 1,
+  /*
+   An input string was not valid UTF-8.
+   */
+  JKCC_STATUS_INVALID_UTF8 = 2,
+  /*
+   The input document was not valid JSON.
+   */
+  JKCC_STATUS_INVALID_JSON = 3,
+  /*
+   A key was rejected by the converter's options.
+   */
+  JKCC_STATUS_CONVERSION_FAILED = 4,
+  /*
+   The library panicked; this is a bug.
+   */
+  JKCC_STATUS_PANIC = 5,
+} JkccStatus;
+
+/*
+ A converter built by [`jkcc_converter_new`].
+ */
+typedef struct JkccConverter JkccConverter;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Builds a converter from a [`ConversionSpec`] written as JSON, such as
+ `{"case": "snake_case"}`. Returns null if `spec_json` is null, or is not a
+ valid spec.
+
+ # Safety
+
+ `spec_json` must be null or point to a NUL-terminated string.
+ */
+struct JkccConverter *jkcc_converter_new(const char *spec_json);
+
+/*
+ Converts the keys of the JSON document `json`.
+
+ On success, stores the converted document in `*out`. Otherwise stores a
+ message explaining the failure in `*err`, if `err` is not null. Whatever is
+ stored must be released with [`jkcc_string_free`].
+
+ # Safety
+
+ `converter` must be null or come from [`jkcc_converter_new`] and not have
+ been freed. `json` must be null or point to a NUL-terminated string, and
+ `out` and `err` must be null or valid for writes.
+ */
+enum JkccStatus jkcc_convert(const struct JkccConverter *converter,
+                             const char *json,
+                             char **out,
+                             char **err);
+
+/*
+ Releases a string returned by the library. Does nothing if `s` is null.
+
+ # Safety
+
+ `s` must be null or a string returned by the library that has not been
+ freed yet.
+ */
+void jkcc_string_free(char *s);
+
+/*
+ Releases a converter. Does nothing if `converter` is null.
+
+ # Safety
+
+ `converter` must be null or come from [`jkcc_converter_new`] and not have
+ been freed yet.
+ */
+void jkcc_converter_free(struct JkccConverter *converter);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* JKCC_H */
@@ -0,0 +1,356 @@
+//! An `actix-web` middleware converting the keys of JSON request and response
+//! bodies, and an extractor doing the same for a single route.
+
+use crate::prelude::*;
+use crate::{Converter, DropReason, DroppedKey, Error};
+use ::actix_web::body::{self, BoxBody, MessageBody};
+use ::actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use ::actix_web::error::PayloadError;
+use ::actix_web::http::header::{self, HeaderMap, HeaderValue};
+use ::actix_web::http::StatusCode;
+use ::actix_web::web::{Bytes, Data};
+use ::actix_web::{FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError};
+use core::fmt;
+use core::future::{ready, Future, Ready};
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use futures_util::StreamExt;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::rc::Rc;
+use std::sync::Arc;
+
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Why a body couldn't be converted. Responds with `413 Payload Too Large`
+/// for bodies over the limit, `500 Internal Server Error` for a missing
+/// converter, and `400 Bad Request` otherwise.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CaseConvertError {
+    /// The body is larger than the limit, in bytes.
+    TooLarge(usize),
+
+    /// The body couldn't be read.
+    Payload(PayloadError),
+
+    /// The body isn't valid JSON.
+    Parse(serde_json::Error),
+
+    /// The converter rejected a key.
+    Convert(Error),
+
+    /// Two keys of an object were converted to the same key.
+    Collision(DroppedKey),
+
+    /// The converted document doesn't fit the extracted type.
+    Deserialize(Error),
+
+    /// No `Data<Converter<'static>>` was registered for a
+    /// [`CaseNormalizedJson`] extractor.
+    MissingConverter,
+}
+
+impl fmt::Display for CaseConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaseConvertError::TooLarge(limit) => write!(f, "body is larger than {} bytes", limit),
+            CaseConvertError::Payload(err) => write!(f, "failed to read body: {}", err),
+            CaseConvertError::Parse(err) => write!(f, "invalid JSON body: {}", err),
+            CaseConvertError::Convert(err) => write!(f, "failed to convert keys: {}", err),
+            CaseConvertError::Collision(dropped) => write!(f, "conflicting keys: {}", dropped),
+            CaseConvertError::Deserialize(err) => write!(f, "{}", err),
+            CaseConvertError::MissingConverter => write!(f, "no converter is registered"),
+        }
+    }
+}
+
+impl std::error::Error for CaseConvertError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CaseConvertError::Payload(err) => Some(err),
+            CaseConvertError::Parse(err) => Some(err),
+            CaseConvertError::Convert(err) | CaseConvertError::Deserialize(err) => Some(err),
+            CaseConvertError::TooLarge(_)
+            | CaseConvertError::Collision(_)
+            | CaseConvertError::MissingConverter => None,
+        }
+    }
+}
+
+impl ResponseError for CaseConvertError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            CaseConvertError::TooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            CaseConvertError::MissingConverter => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// Converts JSON request bodies before the handlers see them, and JSON
+/// response bodies before the clients do.
+///
+/// Requests and responses whose `content-type` isn't `application/json` or
+/// `application/*+json` are passed through untouched. Responses that fail to
+/// convert are replaced by a `500 Internal Server Error`.
+#[derive(Clone)]
+pub struct CaseConvertMiddleware {
+    inbound: Arc<Converter<'static>>,
+    outbound: Arc<Converter<'static>>,
+    body_limit: usize,
+}
+
+impl CaseConvertMiddleware {
+    /// Converts request bodies with `inbound` and response bodies with
+    /// `outbound`. Bodies are limited to 2 MiB.
+    pub fn new(inbound: Converter<'static>, outbound: Converter<'static>) -> Self {
+        CaseConvertMiddleware {
+            inbound: Arc::new(inbound),
+            outbound: Arc::new(outbound),
+            body_limit: DEFAULT_BODY_LIMIT,
+        }
+    }
+
+    /// Sets the largest body, in bytes, that is buffered to be converted.
+    pub fn with_body_limit(&mut self, limit: usize) {
+        self.body_limit = limit;
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CaseConvertMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Transform = CaseConvertService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, ()>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CaseConvertService {
+            service: Rc::new(service),
+            config: self.clone(),
+        }))
+    }
+}
+
+/// The service wrapped by a [`CaseConvertMiddleware`].
+pub struct CaseConvertService<S> {
+    service: Rc<S>,
+    config: CaseConvertMiddleware,
+}
+
+impl<S, B> Service<ServiceRequest> for CaseConvertService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            if is_json(req.headers()) {
+                let payload = req.take_payload();
+                let converted = read_payload(payload, config.body_limit)
+                    .await
+                    .and_then(|body| convert_body(&body, &config.inbound));
+                let converted = match converted {
+                    Ok(converted) => converted,
+                    Err(err) => return Ok(req.error_response(err)),
+                };
+                set_content_length(req.headers_mut(), &converted);
+                req.set_payload(Payload::from(converted));
+            }
+
+            let res = service.call(req).await?;
+            if !is_json(res.headers()) {
+                return Ok(res.map_into_boxed_body());
+            }
+            let (req, res) = res.into_parts();
+            let (mut res, body) = res.into_parts();
+            let converted = match body::to_bytes_limited(body, config.body_limit).await {
+                Ok(Ok(body)) => {
+                    convert_body(&body, &config.outbound).map_err(|err| err.to_string())
+                }
+                Ok(Err(err)) => Err(format!("failed to read body: {}", err.into())),
+                Err(_) => Err(CaseConvertError::TooLarge(config.body_limit).to_string()),
+            };
+            let res = match converted {
+                Ok(converted) => {
+                    set_content_length(res.headers_mut(), &converted);
+                    res.set_body(converted).map_into_boxed_body()
+                }
+                Err(message) => HttpResponse::InternalServerError()
+                    .body(format!("failed to convert response: {}", message)),
+            };
+            Ok(ServiceResponse::new(req, res))
+        })
+    }
+}
+
+/// Extracts a `T` from a JSON request body, after converting its keys with the
+/// `Data<Converter<'static>>` registered on the app, like
+/// [`web::Json`](actix_web::web::Json) does without a conversion.
+///
+/// Unlike the middleware, two keys of an object that are converted to the same
+/// key reject the request instead of dropping one of the values. Bodies are
+/// limited to 2 MiB.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseNormalizedJson<T>(pub T);
+
+impl<T: DeserializeOwned + 'static> FromRequest for CaseNormalizedJson<T> {
+    type Error = CaseConvertError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, CaseConvertError>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let converter = req.app_data::<Data<Converter<'static>>>().cloned();
+        let payload = payload.take();
+
+        Box::pin(async move {
+            let converter = converter.ok_or(CaseConvertError::MissingConverter)?;
+            let body = read_payload(payload, DEFAULT_BODY_LIMIT).await?;
+            let value: Value = serde_json::from_slice(&body).map_err(CaseConvertError::Parse)?;
+            let (converted, dropped) = converter
+                .convert_value_with_drops(value)
+                .map_err(CaseConvertError::Convert)?;
+            if let Some(collision) = dropped
+                .iter()
+                .find(|dropped_key| matches!(dropped_key.reason, DropReason::Collision { .. }))
+            {
+                return Err(CaseConvertError::Collision(collision.clone()));
+            }
+            crate::typed::deserialize_value(converted, dropped)
+                .map(CaseNormalizedJson)
+                .map_err(CaseConvertError::Deserialize)
+        })
+    }
+}
+
+async fn read_payload(mut payload: Payload, limit: usize) -> Result<Bytes, CaseConvertError> {
+    let mut body = Vec::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(CaseConvertError::Payload)?;
+        if body.len() + chunk.len() > limit {
+            return Err(CaseConvertError::TooLarge(limit));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(Bytes::from(body))
+}
+
+fn convert_body(body: &[u8], converter: &Converter) -> Result<Bytes, CaseConvertError> {
+    let value: Value = serde_json::from_slice(body).map_err(CaseConvertError::Parse)?;
+    let converted = converter
+        .convert_value(value)
+        .map_err(CaseConvertError::Convert)?;
+    Ok(Bytes::from(converted.to_string()))
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    let content_type = match headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(content_type) => content_type,
+        None => return false,
+    };
+    let essence = content_type.split(';').next().unwrap_or("").trim();
+    let essence = essence.to_ascii_lowercase();
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+fn set_content_length(headers: &mut HeaderMap, body: &Bytes) {
+    headers.remove(header::TRANSFER_ENCODING);
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyCase;
+    use ::actix_web::{test, web, App};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Deserialize, Serialize)]
+    struct User {
+        user_name: String,
+        zip_code: u32,
+    }
+
+    async fn echo(user: web::Json<User>) -> web::Json<User> {
+        user
+    }
+
+    async fn echo_extracted(CaseNormalizedJson(user): CaseNormalizedJson<User>) -> web::Json<User> {
+        web::Json(user)
+    }
+
+    #[actix_web::test]
+    async fn middleware_converts_both_directions() {
+        let mut middleware = CaseConvertMiddleware::new(
+            Converter::new(KeyCase::Snake),
+            Converter::new(KeyCase::Camel),
+        );
+        middleware.with_body_limit(64);
+        let app = test::init_service(
+            App::new()
+                .wrap(middleware)
+                .route("/echo", web::post().to(echo)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_json(json!({"userName": "Ada", "zipCode": 1}))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(json!({"userName": "Ada", "zipCode": 1}), body);
+
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_json(json!({"userName": "Augusta Ada King, Countess of Lovelace", "zipCode": 1}))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, res.status());
+    }
+
+    #[actix_web::test]
+    async fn extractor_converts_requests() {
+        let app = test::init_service(
+            App::new()
+                .app_data(Data::new(Converter::new(KeyCase::Snake)))
+                .route("/echo", web::post().to(echo_extracted)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_json(json!({"UserName": "Ada", "zip-code": 1}))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(json!({"user_name": "Ada", "zip_code": 1}), body);
+
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_json(json!({"userName": "Ada", "user_name": "Bob", "zipCode": 1}))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(StatusCode::BAD_REQUEST, res.status());
+        let body = test::read_body(res).await;
+        assert!(body.starts_with(b"conflicting keys: "));
+    }
+}
@@ -0,0 +1,244 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error, JsonMap};
+use serde_json::Value;
+
+/// How [`Converter::convert_avro_schema`] renames fields and records.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AvroSchemaMode {
+    /// Replace the names.
+    #[default]
+    Rename,
+
+    /// Replace the names and add the original ones to `aliases`, so that
+    /// readers using the converted schema still resolve data written with the
+    /// original one.
+    RenameWithAliases,
+}
+
+impl Converter<'_> {
+    /// Converts the field and record names of an Avro schema written as JSON.
+    ///
+    /// Records are found inside unions, arrays, maps and field types. Only the
+    /// last part of a dotted record name is converted, and references to a
+    /// renamed record by the name it was written with are updated. Everything
+    /// else is kept: `namespace`, `default`, `doc`, primitive type names, and
+    /// the names and `symbols` of enums and fixed types. Options scoped by
+    /// pointer see the position of each name in the schema document, such as
+    /// `"/fields/0/userName"`.
+    pub fn convert_avro_schema(&self, schema: Value, mode: AvroSchemaMode) -> Result<Value, Error> {
+        let mut records = HashMap::new();
+        self.convert_avro_type(schema, &Path::Root, mode, &mut records)
+    }
+
+    /// Converts `schema`, recording the records renamed so far in `records`.
+    fn convert_avro_type(
+        &self,
+        schema: Value,
+        path: &Path,
+        mode: AvroSchemaMode,
+        records: &mut HashMap<String, String>,
+    ) -> Result<Value, Error> {
+        match schema {
+            Value::String(name) => Ok(Value::String(records.get(&name).cloned().unwrap_or(name))),
+            Value::Array(union) => union
+                .into_iter()
+                .enumerate()
+                .map(|(index, schema)| {
+                    self.convert_avro_type(schema, &path.index(index), mode, records)
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Value::Object(schema) => self
+                .convert_avro_object(schema, path, mode, records)
+                .map(Value::Object),
+            other => Ok(other),
+        }
+    }
+
+    fn convert_avro_object(
+        &self,
+        mut schema: JsonMap,
+        path: &Path,
+        mode: AvroSchemaMode,
+        records: &mut HashMap<String, String>,
+    ) -> Result<JsonMap, Error> {
+        let kind = schema.get("type").cloned();
+        match kind.as_ref().and_then(Value::as_str) {
+            Some("record") | Some("error") => {
+                if let Some(Value::String(name)) = schema.get("name").cloned() {
+                    let (namespace, simple_name) = match name.rfind('.') {
+                        Some(dot) => name.split_at(dot + 1),
+                        None => ("", name.as_str()),
+                    };
+                    let new_name = self.rename_avro_name(simple_name, path)?;
+                    if new_name != simple_name {
+                        let new_name = format!("{}{}", namespace, new_name);
+                        records.insert(name.clone(), new_name.clone());
+                        rename_in(&mut schema, name, new_name, mode);
+                    }
+                }
+
+                if let Some(Value::Array(fields)) = schema.remove("fields") {
+                    let fields_path = path.key("fields");
+                    let fields = fields
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, field)| match field {
+                            Value::Object(field) => self
+                                .convert_avro_field(field, &fields_path.index(index), mode, records)
+                                .map(Value::Object),
+                            other => Ok(other),
+                        })
+                        .collect::<Result<_, _>>()?;
+                    schema.insert("fields".to_owned(), Value::Array(fields));
+                }
+            }
+            Some("array") => self.convert_avro_member(&mut schema, "items", path, mode, records)?,
+            Some("map") => self.convert_avro_member(&mut schema, "values", path, mode, records)?,
+            Some(_) => {}
+            // A type written as a nested schema.
+            None => self.convert_avro_member(&mut schema, "type", path, mode, records)?,
+        }
+        Ok(schema)
+    }
+
+    fn convert_avro_field(
+        &self,
+        mut field: JsonMap,
+        path: &Path,
+        mode: AvroSchemaMode,
+        records: &mut HashMap<String, String>,
+    ) -> Result<JsonMap, Error> {
+        if let Some(Value::String(name)) = field.get("name").cloned() {
+            let new_name = self.rename_avro_name(&name, path)?;
+            if new_name != name {
+                rename_in(&mut field, name, new_name, mode);
+            }
+        }
+        self.convert_avro_member(&mut field, "type", path, mode, records)?;
+        Ok(field)
+    }
+
+    /// Converts the schema held in `member` of `object`, if there is one.
+    fn convert_avro_member(
+        &self,
+        object: &mut JsonMap,
+        member: &str,
+        path: &Path,
+        mode: AvroSchemaMode,
+        records: &mut HashMap<String, String>,
+    ) -> Result<(), Error> {
+        if let Some(schema) = object.remove(member) {
+            let schema = self.convert_avro_type(schema, &path.key(member), mode, records)?;
+            object.insert(member.to_owned(), schema);
+        }
+        Ok(())
+    }
+
+    /// The new name of a field or record, which is kept when the options
+    /// would drop it.
+    fn rename_avro_name(&self, name: &str, path: &Path) -> Result<String, Error> {
+        Ok(self
+            .rename_key(name, &path.key(name))?
+            .unwrap_or_else(|| name.to_owned()))
+    }
+}
+
+/// Sets the `name` of a field or record, adding the old name to its `aliases`
+/// when asked to.
+fn rename_in(object: &mut JsonMap, name: String, new_name: String, mode: AvroSchemaMode) {
+    object.insert("name".to_owned(), Value::String(new_name));
+    if mode == AvroSchemaMode::RenameWithAliases {
+        let aliases = object
+            .entry("aliases")
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(aliases) = aliases {
+            let name = Value::String(name);
+            if !aliases.contains(&name) {
+                aliases.push(name);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "type": "record",
+            "name": "com.example.user_created",
+            "namespace": "com.example",
+            "fields": [
+                {"name": "user_id", "type": "long", "aliases": ["id"]},
+                {"name": "account_type", "type": {
+                    "type": "enum", "name": "account_type", "symbols": ["FREE_TIER", "PAID_TIER"]
+                }, "default": "FREE_TIER"},
+                {"name": "home_address", "type": ["null", {
+                    "type": "record",
+                    "name": "postal_address",
+                    "fields": [
+                        {"name": "street_name", "type": "string", "doc": "street_name"},
+                        {"name": "zip_code", "type": {"type": "string", "logicalType": "uuid"}}
+                    ]
+                }], "default": null},
+                {"name": "past_addresses", "type": {"type": "array", "items": "postal_address"}},
+                {"name": "extra_attributes", "type": {"type": "map", "values": ["null", "string"]}}
+            ]
+        })
+    }
+
+    #[test]
+    fn renames_fields_and_records() {
+        let converted = Converter::new(KeyCase::Camel)
+            .convert_avro_schema(schema(), AvroSchemaMode::Rename)
+            .unwrap();
+
+        assert_eq!(
+            json!({
+                "type": "record",
+                "name": "com.example.userCreated",
+                "namespace": "com.example",
+                "fields": [
+                    {"name": "userId", "type": "long", "aliases": ["id"]},
+                    {"name": "accountType", "type": {
+                        "type": "enum", "name": "account_type", "symbols": ["FREE_TIER", "PAID_TIER"]
+                    }, "default": "FREE_TIER"},
+                    {"name": "homeAddress", "type": ["null", {
+                        "type": "record",
+                        "name": "postalAddress",
+                        "fields": [
+                            {"name": "streetName", "type": "string", "doc": "street_name"},
+                            {"name": "zipCode", "type": {"type": "string", "logicalType": "uuid"}}
+                        ]
+                    }], "default": null},
+                    {"name": "pastAddresses", "type": {"type": "array", "items": "postalAddress"}},
+                    {"name": "extraAttributes", "type": {"type": "map", "values": ["null", "string"]}}
+                ]
+            }),
+            converted
+        );
+    }
+
+    #[test]
+    fn adds_original_names_to_aliases() {
+        let converted = Converter::new(KeyCase::Camel)
+            .convert_avro_schema(schema(), AvroSchemaMode::RenameWithAliases)
+            .unwrap();
+
+        assert_eq!(json!(["com.example.user_created"]), converted["aliases"]);
+        assert_eq!(json!(["id", "user_id"]), converted["fields"][0]["aliases"]);
+        assert_eq!(None, converted["fields"][1]["type"].get("aliases"));
+        assert_eq!(
+            json!(["postal_address"]),
+            converted["fields"][2]["type"][1]["aliases"]
+        );
+        assert_eq!(
+            json!(["street_name"]),
+            converted["fields"][2]["type"][1]["fields"][0]["aliases"]
+        );
+    }
+}
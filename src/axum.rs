@@ -0,0 +1,215 @@
+//! Request and response bodies for `axum` handlers whose types don't follow
+//! the naming convention of the wire.
+//!
+//! Both take their [`Converter`] as an `Arc<Converter<'static>>`, which
+//! [`CaseNormalizedJson`] reads from the router state.
+
+use crate::prelude::*;
+use crate::{Converted, Converter, DropReason, DroppedKey, Error};
+use ::axum::body::Bytes;
+use ::axum::extract::rejection::BytesRejection;
+use ::axum::extract::{FromRef, FromRequest, Request};
+use ::axum::http::{header, StatusCode};
+use ::axum::response::{IntoResponse, Response};
+use core::fmt;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Extracts a `T` from a JSON request body, after converting its keys with the
+/// converter of the router state.
+///
+/// Unlike a plain conversion, two keys of an object that are converted to the
+/// same key reject the request instead of dropping one of the values.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaseNormalizedJson<T>(pub T);
+
+/// Why a [`CaseNormalizedJson`] couldn't be extracted.
+///
+/// Responds with `400 Bad Request` and a plain-text explanation, except for
+/// body read failures, which respond as `axum` does.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CaseNormalizedJsonRejection {
+    /// The body couldn't be read.
+    Body(BytesRejection),
+
+    /// The body isn't valid JSON.
+    Parse(serde_json::Error),
+
+    /// The converter rejected a key.
+    Convert(Error),
+
+    /// Two keys of an object were converted to the same key.
+    Collision(DroppedKey),
+
+    /// The converted document doesn't fit `T`.
+    Deserialize(Error),
+}
+
+impl fmt::Display for CaseNormalizedJsonRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CaseNormalizedJsonRejection::Body(err) => write!(f, "{}", err.body_text()),
+            CaseNormalizedJsonRejection::Parse(err) => write!(f, "invalid JSON body: {}", err),
+            CaseNormalizedJsonRejection::Convert(err) => {
+                write!(f, "failed to convert keys: {}", err)
+            }
+            CaseNormalizedJsonRejection::Collision(dropped) => {
+                write!(f, "conflicting keys: {}", dropped)
+            }
+            CaseNormalizedJsonRejection::Deserialize(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CaseNormalizedJsonRejection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CaseNormalizedJsonRejection::Body(err) => Some(err),
+            CaseNormalizedJsonRejection::Parse(err) => Some(err),
+            CaseNormalizedJsonRejection::Convert(err)
+            | CaseNormalizedJsonRejection::Deserialize(err) => Some(err),
+            CaseNormalizedJsonRejection::Collision(_) => None,
+        }
+    }
+}
+
+impl IntoResponse for CaseNormalizedJsonRejection {
+    fn into_response(self) -> Response {
+        match self {
+            CaseNormalizedJsonRejection::Body(err) => err.into_response(),
+            rejection => (StatusCode::BAD_REQUEST, rejection.to_string()).into_response(),
+        }
+    }
+}
+
+impl<T, S> FromRequest<S> for CaseNormalizedJson<T>
+where
+    T: DeserializeOwned,
+    Arc<Converter<'static>>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = CaseNormalizedJsonRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let converter = Arc::<Converter<'static>>::from_ref(state);
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(CaseNormalizedJsonRejection::Body)?;
+
+        let value: Value =
+            serde_json::from_slice(&body).map_err(CaseNormalizedJsonRejection::Parse)?;
+        let (converted, dropped) = converter
+            .convert_value_with_drops(value)
+            .map_err(CaseNormalizedJsonRejection::Convert)?;
+        if let Some(collision) = dropped
+            .iter()
+            .find(|dropped_key| matches!(dropped_key.reason, DropReason::Collision { .. }))
+        {
+            return Err(CaseNormalizedJsonRejection::Collision(collision.clone()));
+        }
+
+        crate::typed::deserialize_value(converted, dropped)
+            .map(CaseNormalizedJson)
+            .map_err(CaseNormalizedJsonRejection::Deserialize)
+    }
+}
+
+/// Responds with `value` as a JSON body, its keys converted with `converter`.
+///
+/// Responds with `500 Internal Server Error` if `value` fails to serialize or
+/// the converter rejects one of its keys.
+#[derive(Clone)]
+pub struct CaseConvertedJson<T> {
+    value: T,
+    converter: Arc<Converter<'static>>,
+}
+
+impl<T> CaseConvertedJson<T> {
+    /// Wraps `value`, to be converted with `converter`.
+    pub fn new(value: T, converter: Arc<Converter<'static>>) -> Self {
+        CaseConvertedJson { value, converter }
+    }
+}
+
+impl<T: Serialize> IntoResponse for CaseConvertedJson<T> {
+    fn into_response(self) -> Response {
+        match serde_json::to_vec(&Converted::new(&self.value, &self.converter)) {
+            Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+            Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyCase;
+    use ::axum::body::Body;
+    use ::axum::routing::post;
+    use ::axum::Router;
+    use http_body_util::BodyExt;
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    #[derive(Deserialize, Serialize)]
+    struct User {
+        user_name: String,
+        zip_code: u32,
+    }
+
+    // Reads snake_case through the state, and answers in camelCase.
+    async fn echo(CaseNormalizedJson(user): CaseNormalizedJson<User>) -> CaseConvertedJson<User> {
+        CaseConvertedJson::new(user, Arc::new(Converter::new(KeyCase::Camel)))
+    }
+
+    async fn post_json(body: &'static str) -> (StatusCode, String) {
+        let app = Router::new()
+            .route("/users", post(echo))
+            .with_state(Arc::new(Converter::new(KeyCase::Snake)));
+        let request = Request::post("/users")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn converts_requests_and_responses() {
+        assert_eq!(
+            (
+                StatusCode::OK,
+                r#"{"userName":"Ada","zipCode":1}"#.to_owned()
+            ),
+            post_json(r#"{"UserName": "Ada", "zip-code": 1}"#).await
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_requests() {
+        let (status, body) = post_json(r#"{"userName": "#).await;
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+        assert!(body.starts_with("invalid JSON body: "), "{}", body);
+
+        let (status, body) = post_json(r#"{"userName": "Ada", "user_name": "Bob"}"#).await;
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+        assert_eq!(
+            "conflicting keys: `userName` at `/userName` was overwritten by `user_name`, \
+             which was also converted to `user_name`",
+            body
+        );
+
+        let (status, body) = post_json(r#"{"userName": "Ada", "zipCode": "x"}"#).await;
+        assert_eq!(StatusCode::BAD_REQUEST, status);
+        assert!(
+            body.starts_with("failed to deserialize `/zip_code`: "),
+            "{}",
+            body
+        );
+    }
+}
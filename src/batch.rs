@@ -0,0 +1,214 @@
+use crate::prelude::*;
+use crate::{Converter, Error};
+use core::fmt;
+use serde_json::Value;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// What [`Converter::convert_batch_with_policy`] does when a document fails.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BatchPolicy {
+    /// Stop at the first failure.
+    #[default]
+    FailFast,
+
+    /// Convert every document, and report all the failures.
+    CollectErrors,
+}
+
+/// The documents of a batch that failed to convert.
+#[derive(Debug)]
+pub struct BatchError {
+    /// The index of each failed document in the input, and its error, by
+    /// index.
+    pub failures: Vec<(usize, Error)>,
+
+    /// With [`BatchPolicy::CollectErrors`], every document of the batch in
+    /// input order: converted, or `None` when it failed. Empty with
+    /// [`BatchPolicy::FailFast`].
+    pub converted: Vec<Option<Value>>,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.failures.first() {
+            Some((index, err)) => write!(f, "failed to convert document {}: {}", index, err)?,
+            None => write!(f, "failed to convert the batch")?,
+        }
+        if self.failures.len() > 1 {
+            write!(f, " (and {} more)", self.failures.len() - 1)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.failures
+            .first()
+            .map(|(_, err)| err as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl Converter<'_> {
+    /// Converts every document of `docs` as [`Converter::convert_value`]
+    /// does, returning them in input order, and stops at the first failure.
+    /// See [`Converter::convert_batch_with_policy`].
+    pub fn convert_batch(&self, docs: Vec<Value>) -> Result<Vec<Value>, BatchError> {
+        self.convert_batch_with_policy(docs, BatchPolicy::FailFast)
+    }
+
+    /// Converts every document of `docs` as [`Converter::convert_value`]
+    /// does, returning them in input order.
+    ///
+    /// The documents share a cache of converted keys. With the `parallel`
+    /// feature they are converted on the `rayon` thread pool, and with
+    /// [`BatchPolicy::FailFast`] the reported failure is the first one found,
+    /// which isn't always the one with the lowest index.
+    pub fn convert_batch_with_policy(
+        &self,
+        docs: Vec<Value>,
+        policy: BatchPolicy,
+    ) -> Result<Vec<Value>, BatchError> {
+        let cache = KeyCache::default();
+        let convert = |(index, doc): (usize, Value)| {
+            self.convert_root(doc, Some(&cache))
+                .map_err(|err| (index, err))
+        };
+
+        #[cfg(feature = "parallel")]
+        let docs = docs.into_par_iter().enumerate().map(convert);
+        #[cfg(not(feature = "parallel"))]
+        let docs = docs.into_iter().enumerate().map(convert);
+
+        match policy {
+            BatchPolicy::FailFast => docs
+                .collect::<Result<_, _>>()
+                .map_err(|failure| BatchError {
+                    failures: vec![failure],
+                    converted: Vec::new(),
+                }),
+            BatchPolicy::CollectErrors => {
+                let results: Vec<_> = docs.collect();
+                if results.iter().all(Result::is_ok) {
+                    return Ok(results.into_iter().filter_map(Result::ok).collect());
+                }
+                let mut failures = Vec::new();
+                let converted = results
+                    .into_iter()
+                    .map(|result| result.map_err(|failure| failures.push(failure)).ok())
+                    .collect();
+                Err(BatchError {
+                    failures,
+                    converted,
+                })
+            }
+        }
+    }
+}
+
+/// New keys by original key, shared by the documents of a batch.
+#[derive(Default)]
+pub(crate) struct KeyCache {
+    #[cfg(feature = "parallel")]
+    keys: std::sync::RwLock<HashMap<String, Option<String>>>,
+    #[cfg(not(feature = "parallel"))]
+    keys: core::cell::RefCell<HashMap<String, Option<String>>>,
+}
+
+impl KeyCache {
+    #[cfg(feature = "parallel")]
+    pub(crate) fn get(&self, key: &str) -> Option<Option<String>> {
+        let keys = self.keys.read().unwrap_or_else(|err| err.into_inner());
+        keys.get(key).cloned()
+    }
+
+    #[cfg(feature = "parallel")]
+    pub(crate) fn insert(&self, key: &str, new_key: Option<String>) {
+        let mut keys = self.keys.write().unwrap_or_else(|err| err.into_inner());
+        keys.insert(key.to_owned(), new_key);
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    pub(crate) fn get(&self, key: &str) -> Option<Option<String>> {
+        self.keys.borrow().get(key).cloned()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    pub(crate) fn insert(&self, key: &str, new_key: Option<String>) {
+        self.keys.borrow_mut().insert(key.to_owned(), new_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::{json, Value};
+
+    fn converter() -> Converter<'static> {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_empty_key_policy(EmptyKeyPolicy::Error);
+        converter
+    }
+
+    fn docs() -> Vec<Value> {
+        (0..100)
+            .map(|i| json!({"docIndex": i, "innerItem": {"unitPrice": i}}))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_order() {
+        let converter = converter();
+        let expected: Vec<_> = docs()
+            .into_iter()
+            .map(|doc| converter.convert_value(doc).unwrap())
+            .collect();
+        let converted = converter.convert_batch(docs()).unwrap();
+        assert_eq!(expected, converted);
+        assert_eq!(
+            json!({"doc_index": 42, "inner_item": {"unit_price": 42}}),
+            converted[42]
+        );
+    }
+
+    #[test]
+    fn fails_fast() {
+        let mut batch = docs();
+        batch[50] = json!({"okKey": {"": 1}});
+
+        let err = converter().convert_batch(batch).unwrap_err();
+        assert_eq!(1, err.failures.len());
+        assert_eq!(50, err.failures[0].0);
+        assert!(err.converted.is_empty());
+        assert_eq!(
+            "failed to convert document 50: empty key at `/okKey/`",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn collects_errors() {
+        let mut batch = docs();
+        batch[20] = json!({"": 1});
+        batch[70] = json!([{"": 2}]);
+
+        let err = converter()
+            .convert_batch_with_policy(batch, BatchPolicy::CollectErrors)
+            .unwrap_err();
+        let indices: Vec<_> = err.failures.iter().map(|(index, _)| *index).collect();
+        assert_eq!(vec![20, 70], indices);
+        assert_eq!(100, err.converted.len());
+        assert!(err.converted[20].is_none() && err.converted[70].is_none());
+        assert_eq!(
+            Some(json!({"doc_index": 21, "inner_item": {"unit_price": 21}})),
+            err.converted[21]
+        );
+        assert_eq!(
+            "failed to convert document 20: empty key at `/` (and 1 more)",
+            err.to_string()
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! Serves the conversion over HTTP, as described in the `server` module of
+//! the library.
+//!
+//! Configured through the environment: `JSON_CASE_LISTEN` is the address to
+//! listen on (`127.0.0.1:8080` by default), `JSON_CASE_BODY_LIMIT` the
+//! largest request body in bytes, and `JSON_CASE_CACHE_CAPACITY` how many
+//! converters built from specs are kept.
+
+use json_keys_case_changer::server::{router, ServerConfig};
+use std::env;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("json-case-server: {}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+async fn run() -> Result<(), String> {
+    let listen = env::var("JSON_CASE_LISTEN").unwrap_or_else(|_| "127.0.0.1:8080".to_owned());
+    let mut config = ServerConfig::default();
+    if let Some(body_limit) = number("JSON_CASE_BODY_LIMIT")? {
+        config.body_limit = body_limit;
+    }
+    if let Some(cache_capacity) = number("JSON_CASE_CACHE_CAPACITY")? {
+        config.cache_capacity = cache_capacity;
+    }
+
+    let listener = tokio::net::TcpListener::bind(&listen)
+        .await
+        .map_err(|err| format!("failed to listen on `{}`: {}", listen, err))?;
+    axum::serve(listener, router(config))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// The number in the environment variable `name`, if it is set.
+fn number<T: FromStr>(name: &str) -> Result<Option<T>, String> {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("`{}` is not a number: `{}`", name, value)),
+        Err(_) => Ok(None),
+    }
+}
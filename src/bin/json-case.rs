@@ -0,0 +1,186 @@
+//! Converts the keys of a JSON document read from a file or stdin, and writes
+//! it to stdout or a file. Files ending in `.gz` or `.zst` are decompressed
+//! and compressed with gzip or zstd.
+//!
+//! Exits with 0 on success, 1 when `--check` finds keys to convert, and 2 on
+//! usage, input and conversion errors, which are written to stderr.
+//!
+//! The binary is `json-case` rather than `json-keys-case-changer`, the name
+//! of the crate: it shipped under that name first, and `--case`, `--rename`,
+//! `--exclude` and `--depth` were added to it instead of to a second binary.
+
+use clap::Parser;
+use json_keys_case_changer::{Compression, Converter, Error, KeyCase};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "json-case",
+    version,
+    about = "Converts the keys of a JSON document"
+)]
+struct Args {
+    /// The case to convert keys to, such as `snake`, `camelCase` or `kebab-case`.
+    #[arg(long, visible_alias = "case")]
+    to: KeyCase,
+
+    /// A JSON file of manual renames, from original keys to new ones.
+    #[arg(long, value_name = "FILE")]
+    renames: Option<PathBuf>,
+
+    /// A manual rename, applied after those of `--renames`. May be repeated.
+    #[arg(long = "rename", value_name = "OLD=NEW", value_parser = parse_rename)]
+    rename_pairs: Vec<(String, String)>,
+
+    /// A key to keep as it is wherever it appears. May be repeated.
+    #[arg(long = "exclude", value_name = "KEY")]
+    exclude_keys: Vec<String>,
+
+    /// A JSON pointer to a key to keep as it is; `*` matches any key or index.
+    /// May be repeated.
+    #[arg(long = "exclude-pointer", value_name = "POINTER")]
+    exclude_pointers: Vec<String>,
+
+    /// Only convert the keys of the first N levels of objects.
+    #[arg(long, value_name = "N")]
+    depth: Option<usize>,
+
+    /// Indent the output.
+    #[arg(long)]
+    pretty: bool,
+
+    /// List the keys that would be converted, with their line and column,
+    /// instead of converting them, and exit with 1 if there are any.
+    #[arg(long)]
+    check: bool,
+
+    /// The output file; stdout is written when it is missing.
+    #[arg(long, short, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// How the input and output are compressed: `none`, `gzip` or `zstd`.
+    /// Defaults to what the extension of each file tells, and to `none` for
+    /// stdin and stdout.
+    #[arg(long)]
+    compression: Option<Compression>,
+
+    /// The compression level of the output.
+    #[arg(long, allow_negative_numbers = true)]
+    level: Option<i32>,
+
+    /// The input file; stdin is read when it is missing.
+    input: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("json-case: {}", message);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn run(args: Args) -> Result<ExitCode, String> {
+    let input = read_input(&args)?;
+
+    let mut converter = Converter::new(args.to);
+    if let Some(path) = &args.renames {
+        converter
+            .with_manual_renames_from_file(path)
+            .map_err(|err| format!("`{}`: {}", path.display(), err))?;
+    }
+    let mut spec = converter.to_spec();
+    spec.manual_renames
+        .extend(args.rename_pairs.iter().cloned());
+    spec.excluded_keys.extend(args.exclude_keys.iter().cloned());
+    spec.excluded_pointers
+        .extend(args.exclude_pointers.iter().cloned());
+    spec.max_depth = args.depth;
+    let converter = Converter::from_spec(spec).map_err(|err| err.to_string())?;
+
+    if args.check {
+        let changes = converter.check_str(&input).map_err(|err| match err {
+            Error::Parse(err) => format!("invalid JSON input: {}", err),
+            err => err.to_string(),
+        })?;
+        let mut stdout = io::stdout().lock();
+        for change in &changes {
+            writeln!(stdout, "{}", change).map_err(|err| err.to_string())?;
+        }
+        return Ok(if changes.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(1)
+        });
+    }
+
+    let value: Value =
+        serde_json::from_str(&input).map_err(|err| format!("invalid JSON input: {}", err))?;
+    let converted = converter
+        .convert_value(value)
+        .map_err(|err| err.to_string())?;
+    let output = if args.pretty {
+        serde_json::to_string_pretty(&converted)
+    } else {
+        serde_json::to_string(&converted)
+    }
+    .map_err(|err| err.to_string())?;
+    write_output(&args, &output)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse_rename(pair: &str) -> Result<(String, String), String> {
+    match pair.split_once('=') {
+        Some((old, new)) => Ok((old.to_owned(), new.to_owned())),
+        None => Err("expected `OLD=NEW`".to_owned()),
+    }
+}
+
+/// The compression of the file at `path`, or of stdin or stdout when there
+/// is none.
+fn compression(args: &Args, path: Option<&PathBuf>) -> Compression {
+    args.compression
+        .or_else(|| path.map(Compression::from_path))
+        .unwrap_or_default()
+}
+
+fn read_input(args: &Args) -> Result<String, String> {
+    let mut input = String::new();
+    let read = |reader: Box<dyn Read + '_>, input: &mut String| {
+        let mut reader = compression(args, args.input.as_ref()).reader(BufReader::new(reader))?;
+        reader.read_to_string(input).map_err(Error::Io)
+    };
+    match &args.input {
+        Some(path) => File::open(path)
+            .map_err(Error::Io)
+            .and_then(|file| read(Box::new(file), &mut input))
+            .map_err(|err| format!("failed to read `{}`: {}", path.display(), err))?,
+        None => read(Box::new(io::stdin()), &mut input)
+            .map_err(|err| format!("failed to read stdin: {}", err))?,
+    };
+    Ok(input)
+}
+
+fn write_output(args: &Args, output: &str) -> Result<(), String> {
+    let destination: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(
+            File::create(path)
+                .map_err(|err| format!("failed to create `{}`: {}", path.display(), err))?,
+        ),
+        None => Box::new(io::stdout().lock()),
+    };
+    let mut writer = compression(args, args.output.as_ref())
+        .writer(destination, args.level)
+        .map_err(|err| err.to_string())?;
+    writeln!(writer, "{}", output).map_err(|err| err.to_string())?;
+    writer
+        .finish()
+        .and_then(|mut destination| destination.flush().map_err(Error::Io))
+        .map_err(|err| err.to_string())
+}
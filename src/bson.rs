@@ -0,0 +1,78 @@
+use crate::path::Path;
+use crate::{Converter, Error};
+use bson::{Bson, Document};
+
+/// Converts the keys of a BSON document and of the documents inside it,
+/// including those in arrays. Values of every other type, such as ObjectIds,
+/// dates and Decimal128 numbers, are kept as they are.
+///
+/// Apply [`Preset::MongoDb`](crate::Preset::MongoDb) to keep operators such
+/// as `$set` and keys such as `_id`.
+pub fn convert_bson(doc: Document, converter: &Converter) -> Result<Document, Error> {
+    converter.convert_bson_document(doc, &Path::Root)
+}
+
+impl Converter<'_> {
+    fn convert_bson_document(&self, doc: Document, path: &Path) -> Result<Document, Error> {
+        let mut new_doc = Document::new();
+        for (key, value) in doc {
+            let key_path = path.key(&key);
+            if let Some(new_key) = self.rename_key(&key, &key_path)? {
+                new_doc.insert(new_key, self.convert_bson(value, &key_path)?);
+            }
+        }
+        Ok(new_doc)
+    }
+
+    fn convert_bson(&self, value: Bson, path: &Path) -> Result<Bson, Error> {
+        match value {
+            Bson::Document(doc) => self.convert_bson_document(doc, path).map(Bson::Document),
+            Bson::Array(array) => array
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| self.convert_bson(value, &path.index(index)))
+                .collect::<Result<_, _>>()
+                .map(Bson::Array),
+            value => Ok(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use ::bson::oid::ObjectId;
+    use ::bson::{doc, DateTime, Decimal128};
+
+    #[test]
+    fn keeps_bson_values() {
+        let id = ObjectId::new();
+        let now = DateTime::now();
+        let price: Decimal128 = "19.99".parse().unwrap();
+        let update = doc! {
+            "_id": id,
+            "$set": {
+                "lastLogin": now,
+                "unitPrice": price,
+                "pastOrders": [{"orderId": id, "placedAt": now}],
+            },
+            "$inc": {"loginCount": 1},
+        };
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_preset(Preset::MongoDb);
+
+        assert_eq!(
+            doc! {
+                "_id": id,
+                "$set": {
+                    "last_login": now,
+                    "unit_price": price,
+                    "past_orders": [{"order_id": id, "placed_at": now}],
+                },
+                "$inc": {"login_count": 1},
+            },
+            convert_bson(update, &converter).unwrap()
+        );
+    }
+}
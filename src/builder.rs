@@ -0,0 +1,137 @@
+use crate::{check_root, CaseChanger, Converter, Error, KeyCase, RenameBehavior, RenameMap};
+use serde_json::Value;
+
+/// Builds a [`CaseChanger`] in one expression, from
+/// [`CaseChanger::builder`]:
+///
+/// ```
+/// # use json_keys_case_changer::{CaseChanger, KeyCase};
+/// # use serde_json::json;
+/// let converted = CaseChanger::builder(json!({"ID": 1, "userName": "Ada"}), KeyCase::Snake)
+///     .manual_renames([("ID", "id")].iter().cloned().collect())
+///     .configure(|converter| converter.with_trim_keys(true))
+///     .convert();
+/// assert_eq!(json!({"id": 1, "user_name": "Ada"}), converted);
+/// ```
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct CaseChangerBuilder<'a> {
+    changer: CaseChanger<'a>,
+}
+
+impl<'a> CaseChangerBuilder<'a> {
+    /// Fails with [`Error::InvalidRoot`] when the input is neither an object
+    /// nor an array, as [`CaseChanger::new_strict`] does.
+    pub fn strict_root(self) -> Result<Self, Error> {
+        check_root(&self.changer.json_in)?;
+        Ok(self)
+    }
+
+    /// Sets the manual renames, as [`Converter::with_manual_renames`] does.
+    pub fn manual_renames(mut self, rename_list: RenameMap<'a>) -> Self {
+        self.changer.converter.with_manual_renames(rename_list);
+        self
+    }
+
+    /// Sets whether manual renames are looked up by key or by value, as
+    /// [`Converter::with_custom_rename_behavior`] does.
+    pub fn rename_behavior(mut self, rename_behavior: RenameBehavior) -> Self {
+        self.changer
+            .converter
+            .with_custom_rename_behavior(rename_behavior);
+        self
+    }
+
+    /// Sets any other option, through the `with_*` methods of the
+    /// [`Converter`].
+    pub fn configure(mut self, configure: impl FnOnce(&mut Converter<'a>)) -> Self {
+        configure(&mut self.changer.converter);
+        self
+    }
+
+    /// The changer, for conversions that don't consume it.
+    pub fn build(self) -> CaseChanger<'a> {
+        self.changer
+    }
+
+    /// Converts the input, as [`CaseChanger::into_converted`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the conversion fails, as described in
+    /// [`CaseChanger::convert`].
+    pub fn convert(self) -> Value {
+        self.changer.into_converted()
+    }
+
+    /// Converts the input, returning an error when a key is rejected by the
+    /// configured options.
+    pub fn try_convert(self) -> Result<Value, Error> {
+        self.changer.try_into_converted()
+    }
+}
+
+impl<'a> CaseChanger<'a> {
+    /// A builder over `json_obj`, converting to `new_case`. Like
+    /// [`CaseChanger::new`], it accepts scalars, which convert to themselves,
+    /// unless [`CaseChangerBuilder::strict_root`] is called.
+    pub fn builder(json_obj: Value, new_case: KeyCase) -> CaseChangerBuilder<'a> {
+        CaseChangerBuilder {
+            changer: CaseChanger {
+                json_in: json_obj,
+                converter: Converter::new(new_case),
+            },
+        }
+    }
+
+    /// Sets the manual renames of the changer.
+    #[deprecated(note = "use `CaseChanger::builder` and `CaseChangerBuilder::manual_renames`")]
+    pub fn with_manual_renames(&mut self, rename_list: RenameMap<'a>) {
+        self.converter.with_manual_renames(rename_list);
+    }
+
+    /// Sets whether the manual renames of the changer are looked up by key or
+    /// by value.
+    #[deprecated(note = "use `CaseChanger::builder` and `CaseChangerBuilder::rename_behavior`")]
+    pub fn with_custom_rename_behavior(&mut self, rename_behavior: RenameBehavior) {
+        self.converter.with_custom_rename_behavior(rename_behavior);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn builds_and_converts() {
+        let value = json!({"ID": 1, "userName": "Ada", "zipCode": 2});
+        let renames: RenameMap = [("ID", "identifier"), ("zip_code", "zipCode")]
+            .iter()
+            .cloned()
+            .collect();
+
+        let converted = CaseChanger::builder(value.clone(), KeyCase::Snake)
+            .manual_renames(renames.clone())
+            .rename_behavior(RenameBehavior::ByValue)
+            .convert();
+        assert_eq!(
+            json!({"id": 1, "user_name": "Ada", "zip_code": 2}),
+            converted
+        );
+
+        let mut changer = CaseChanger::builder(value, KeyCase::Kebab)
+            .manual_renames(renames)
+            .configure(|converter| converter.with_empty_key_policy(EmptyKeyPolicy::Error))
+            .build();
+        assert_eq!(
+            json!({"identifier": 1, "user-name": "Ada", "zip-code": 2}),
+            changer.convert()
+        );
+
+        let err = CaseChanger::builder(json!({"": 1}), KeyCase::Snake)
+            .configure(|converter| converter.with_empty_key_policy(EmptyKeyPolicy::Error))
+            .try_convert();
+        assert!(err.is_err());
+    }
+}
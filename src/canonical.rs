@@ -0,0 +1,226 @@
+//! JSON Canonicalization Scheme output, as specified by RFC 8785: no
+//! whitespace, object members sorted by the UTF-16 code units of their
+//! keys, numbers written as ECMAScript writes doubles, and strings escaped
+//! only where JSON requires it.
+
+use crate::prelude::*;
+use crate::Error;
+use core::fmt::Write;
+use serde_json::Value;
+
+/// Writes `value` in canonical form.
+pub(crate) fn to_canonical_string(value: &Value) -> Result<String, Error> {
+    let mut out = String::new();
+    write_value(value, &mut out)?;
+    Ok(out)
+}
+
+fn write_value(value: &Value, out: &mut String) -> Result<(), Error> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(number) => write_number(number, out)?,
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut members: Vec<_> = map.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_value(value, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// Escapes quotes, backslashes and control characters, the latter with the
+/// short forms JSON has for them or as lowercase `\u00xx`.
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Writes `number` as a double, the way ECMAScript's `Number::toString`
+/// does: integers below 10^21 and fractions down to 10^-6 in plain
+/// notation, everything else with an exponent, always with the fewest
+/// digits that read back to the same double.
+fn write_number(number: &serde_json::Number, out: &mut String) -> Result<(), Error> {
+    let n = match number.as_f64() {
+        Some(n) if n.is_finite() => n,
+        _ => {
+            return Err(Error::Serialize(serde::ser::Error::custom(format!(
+                "{} is not a finite double",
+                number
+            ))))
+        }
+    };
+    if n == 0.0 {
+        // Negative zero too.
+        out.push('0');
+        return Ok(());
+    }
+    if n < 0.0 {
+        out.push('-');
+    }
+
+    // Rust's `{:e}` also writes the shortest round-tripping digits, as
+    // `d.ddde-x`.
+    let scientific = format!("{:e}", n.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits: String = mantissa.chars().filter(|&c| c != '.').collect();
+    let k = digits.len() as i32;
+    // The position of the decimal point relative to the digits, as the
+    // specification names it.
+    let point = exponent.parse::<i32>().unwrap_or_default() + 1;
+
+    if k <= point && point <= 21 {
+        out.push_str(&digits);
+        out.extend(core::iter::repeat_n('0', (point - k) as usize));
+    } else if 0 < point && point <= 21 {
+        out.push_str(&digits[..point as usize]);
+        out.push('.');
+        out.push_str(&digits[point as usize..]);
+    } else if -6 < point && point <= 0 {
+        out.push_str("0.");
+        out.extend(core::iter::repeat_n('0', -point as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(
+            out,
+            "e{}{}",
+            if point > 0 { '+' } else { '-' },
+            (point - 1).abs()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn canonical(value: serde_json::Value) -> String {
+        super::to_canonical_string(&value).unwrap()
+    }
+
+    fn number(text: &str) -> String {
+        canonical(serde_json::from_str(text).unwrap())
+    }
+
+    /// The sample of RFC 8785, section 3.2.2, with its keys converted.
+    #[test]
+    fn rfc_sample() {
+        let input = r#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string_value": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literal_values": [null, true, false]
+        }"#;
+        let mut changer =
+            CaseChanger::new(serde_json::from_str(input).unwrap(), KeyCase::Camel).unwrap();
+        assert_eq!(
+            r#"{"literalValues":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"stringValue":"€$\u000f\nA'B\"\\\\\"/"}"#,
+            changer.convert_to_canonical_string().unwrap()
+        );
+    }
+
+    /// Members are sorted after conversion, by UTF-16 code units, so that a
+    /// key outside the Basic Multilingual Plane sorts before U+FB33 even
+    /// though its code point is higher.
+    #[test]
+    fn sorts_converted_keys_by_utf16() {
+        let input = json!({
+            "zip_code": 1,
+            "Address": 2,
+            "sorting": {
+                "\u{20ac}": "Euro Sign",
+                "\r": "Carriage Return",
+                "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+                "1": "One",
+                "\u{1f600}": "Emoji: Grinning Face",
+                "\u{80}": "Control",
+                "\u{f6}": "Latin Small Letter O With Diaeresis"
+            }
+        });
+        let mut changer = CaseChanger::new(input, KeyCase::Pascal).unwrap();
+        changer.with_dictionary_keys(["sorting"]);
+        assert_eq!(
+            "{\"Address\":2,\"Sorting\":{\"\\r\":\"Carriage Return\",\"1\":\"One\",\
+             \"\u{80}\":\"Control\",\"\u{f6}\":\"Latin Small Letter O With Diaeresis\",\
+             \"\u{20ac}\":\"Euro Sign\",\"\u{1f600}\":\"Emoji: Grinning Face\",\
+             \"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"},\"ZipCode\":1}",
+            changer.convert_to_canonical_string().unwrap()
+        );
+    }
+
+    /// Values of appendix B of RFC 8785 that JSON can express.
+    #[test]
+    fn numbers() {
+        for (text, expected) in [
+            ("0", "0"),
+            ("-0.0", "0"),
+            ("5e-324", "5e-324"),
+            ("-5e-324", "-5e-324"),
+            ("1.7976931348623157e308", "1.7976931348623157e+308"),
+            ("-1.7976931348623157e308", "-1.7976931348623157e+308"),
+            ("9007199254740992", "9007199254740992"),
+            ("-9007199254740992", "-9007199254740992"),
+            ("295147905179352830000", "295147905179352830000"),
+            ("9.999999999999997e22", "9.999999999999997e+22"),
+            ("1e23", "1e+23"),
+            ("1e21", "1e+21"),
+            ("999999999999999700000", "999999999999999700000"),
+            ("0.000001", "0.000001"),
+            ("0.0000001", "1e-7"),
+            ("1.5e-7", "1.5e-7"),
+            ("123.456", "123.456"),
+            ("18446744073709551615", "18446744073709552000"),
+        ] {
+            assert_eq!(expected, number(text), "{}", text);
+        }
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(
+            "\"\\u0000\\u001f\\b\\t\\n\\f\\r\\u000b\\\"\\\\/\u{7f}\"",
+            canonical(json!("\u{0}\u{1f}\u{8}\t\n\u{c}\r\u{b}\"\\/\u{7f}"))
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use crate::prelude::*;
+use crate::Error;
+#[cfg(feature = "std")]
+use convert_case::Case;
+use core::fmt;
+use core::str::FromStr;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// The case keys are converted to.
+///
+/// Examples show how `"user name ID"` is converted. Keys are split into words
+/// and joined as the `convert_case` crate does, which callers don't need to
+/// depend on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum KeyCase {
+    /// `USER NAME ID`
+    Upper,
+    /// `user name id`
+    Lower,
+    /// `User Name Id`
+    Title,
+    /// `uSER nAME iD`
+    Toggle,
+    /// `userNameId`
+    Camel,
+    /// `UserNameId`
+    Pascal,
+    /// `user_name_id`
+    Snake,
+    /// `USER_NAME_ID`
+    ScreamingSnake,
+    /// `user-name-id`
+    Kebab,
+    /// `USER-NAME-ID`
+    Cobol,
+    /// `User-Name-Id`
+    Train,
+    /// `usernameid`
+    Flat,
+    /// `USERNAMEID`
+    UpperFlat,
+    /// `uSeR nAmE iD`
+    Alternating,
+}
+
+/// Every case, with the name it is displayed as and the other names it is
+/// parsed from, normalized as by [`normalize`].
+const NAMES: &[(KeyCase, &str, &[&str])] = &[
+    (KeyCase::Upper, "UPPER CASE", &["upper"]),
+    (KeyCase::Lower, "lower case", &["lower"]),
+    (KeyCase::Title, "Title Case", &["title"]),
+    (KeyCase::Toggle, "tOGGLE cASE", &["toggle"]),
+    (KeyCase::Camel, "camelCase", &["camel", "lowercamel"]),
+    (KeyCase::Pascal, "PascalCase", &["pascal", "uppercamel"]),
+    (KeyCase::Snake, "snake_case", &["snake"]),
+    (
+        KeyCase::ScreamingSnake,
+        "SCREAMING_SNAKE_CASE",
+        &["screamingsnake", "uppersnake", "constant"],
+    ),
+    (KeyCase::Kebab, "kebab-case", &["kebab"]),
+    (KeyCase::Cobol, "COBOL-CASE", &["cobol", "screamingkebab"]),
+    (KeyCase::Train, "Train-Case", &["train"]),
+    (KeyCase::Flat, "flatcase", &["flat"]),
+    (KeyCase::UpperFlat, "UPPERFLATCASE", &["upperflat"]),
+    (KeyCase::Alternating, "aLtErNaTiNg CaSe", &["alternating"]),
+];
+
+impl KeyCase {
+    /// Every case, in the order they are listed in the docs.
+    pub fn all() -> &'static [KeyCase] {
+        &[
+            KeyCase::Upper,
+            KeyCase::Lower,
+            KeyCase::Title,
+            KeyCase::Toggle,
+            KeyCase::Camel,
+            KeyCase::Pascal,
+            KeyCase::Snake,
+            KeyCase::ScreamingSnake,
+            KeyCase::Kebab,
+            KeyCase::Cobol,
+            KeyCase::Train,
+            KeyCase::Flat,
+            KeyCase::UpperFlat,
+            KeyCase::Alternating,
+        ]
+    }
+
+    /// What separates the words of a key in the case, if anything.
+    pub(crate) fn separator(self) -> &'static str {
+        match self {
+            KeyCase::Snake | KeyCase::ScreamingSnake => "_",
+            KeyCase::Kebab | KeyCase::Cobol | KeyCase::Train => "-",
+            KeyCase::Upper
+            | KeyCase::Lower
+            | KeyCase::Title
+            | KeyCase::Toggle
+            | KeyCase::Alternating => " ",
+            KeyCase::Camel | KeyCase::Pascal | KeyCase::Flat | KeyCase::UpperFlat => "",
+        }
+    }
+
+    /// The name the case is displayed as, written in the case itself.
+    pub fn name(self) -> &'static str {
+        NAMES
+            .iter()
+            .find(|(case, _, _)| *case == self)
+            .map(|(_, name, _)| *name)
+            .expect("every case has a name")
+    }
+}
+
+/// Lowercases `name` and removes separators and a trailing "case", so that
+/// `"SCREAMING_SNAKE_CASE"`, `"screaming-snake"` and `"ScreamingSnake"` all
+/// become `"screamingsnake"`.
+fn normalize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| !"_- ".contains(*c))
+        .flat_map(char::to_lowercase)
+        .collect();
+    match name.strip_suffix("case") {
+        Some(rest) if !rest.is_empty() => rest.to_owned(),
+        _ => name,
+    }
+}
+
+/// Edit distance between `a` and `b`, counting insertions, deletions and
+/// substitutions.
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+impl fmt::Display for KeyCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the displayed names, such as `"kebab-case"`, and the variant
+/// names, such as `"kebab"` or `"Kebab"`. Case, separators and a trailing
+/// `"case"` are ignored when the name isn't a displayed one, so
+/// `"SNAKE_CASE"` is snake_case.
+impl FromStr for KeyCase {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if let Some((case, _, _)) = NAMES.iter().find(|(_, display, _)| *display == name) {
+            return Ok(*case);
+        }
+
+        let normalized = normalize(name);
+        let aliases = || {
+            NAMES
+                .iter()
+                .flat_map(|(case, _, aliases)| aliases.iter().map(move |alias| (*case, *alias)))
+        };
+        if let Some((case, _)) = aliases().find(|(_, alias)| *alias == normalized) {
+            return Ok(case);
+        }
+
+        let suggestion = aliases()
+            .map(|(case, alias)| (distance(&normalized, alias), case))
+            .filter(|(distance, _)| *distance <= 2)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, case)| case);
+        Err(Error::UnknownCase {
+            name: name.to_owned(),
+            suggestion,
+        })
+    }
+}
+
+/// Written as its displayed name.
+impl Serialize for KeyCase {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Read from any name [`FromStr`] accepts.
+impl<'de> Deserialize<'de> for KeyCase {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<KeyCase> for Case {
+    fn from(case: KeyCase) -> Self {
+        match case {
+            KeyCase::Upper => Case::Upper,
+            KeyCase::Lower => Case::Lower,
+            KeyCase::Title => Case::Title,
+            KeyCase::Toggle => Case::Toggle,
+            KeyCase::Camel => Case::Camel,
+            KeyCase::Pascal => Case::Pascal,
+            KeyCase::Snake => Case::Snake,
+            KeyCase::ScreamingSnake => Case::ScreamingSnake,
+            KeyCase::Kebab => Case::Kebab,
+            KeyCase::Cobol => Case::Cobol,
+            KeyCase::Train => Case::Train,
+            KeyCase::Flat => Case::Flat,
+            KeyCase::UpperFlat => Case::UpperFlat,
+            KeyCase::Alternating => Case::Alternating,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fast_case::to_case;
+    use crate::*;
+
+    #[test]
+    fn names_round_trip() {
+        for case in KeyCase::all() {
+            assert_eq!(Ok(*case), case.to_string().parse().map_err(drop));
+            assert_eq!(Ok(*case), format!("{:?}", case).parse().map_err(drop));
+            let lower = format!("{:?}", case).to_lowercase();
+            assert_eq!(Ok(*case), lower.parse::<KeyCase>().map_err(drop));
+        }
+
+        assert_eq!(Ok(KeyCase::Snake), "SNAKE_CASE".parse().map_err(drop));
+        assert_eq!(
+            Ok(KeyCase::ScreamingSnake),
+            "upper-snake".parse().map_err(drop)
+        );
+        assert_eq!(Ok(KeyCase::Upper), "UPPER CASE".parse().map_err(drop));
+    }
+
+    #[test]
+    fn typo_suggests_a_case() {
+        let err = "snak_case".parse::<KeyCase>().unwrap_err();
+        match &err {
+            Error::UnknownCase { name, suggestion } => {
+                assert_eq!("snak_case", name);
+                assert_eq!(Some(KeyCase::Snake), *suggestion);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+        assert!(err
+            .to_string()
+            .starts_with("unknown case `snak_case`, did you mean `snake_case`? Expected one of: "));
+        assert!(err.to_string().ends_with("`aLtErNaTiNg CaSe`"));
+
+        let err = "whatever".parse::<KeyCase>().unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("unknown case `whatever`, expected one of: `UPPER CASE`, "));
+    }
+
+    #[test]
+    fn documented_examples() {
+        let cases = [
+            (KeyCase::Upper, "USER NAME ID"),
+            (KeyCase::Lower, "user name id"),
+            (KeyCase::Title, "User Name Id"),
+            (KeyCase::Toggle, "uSER nAME iD"),
+            (KeyCase::Camel, "userNameId"),
+            (KeyCase::Pascal, "UserNameId"),
+            (KeyCase::Snake, "user_name_id"),
+            (KeyCase::ScreamingSnake, "USER_NAME_ID"),
+            (KeyCase::Kebab, "user-name-id"),
+            (KeyCase::Cobol, "USER-NAME-ID"),
+            (KeyCase::Train, "User-Name-Id"),
+            (KeyCase::Flat, "usernameid"),
+            (KeyCase::UpperFlat, "USERNAMEID"),
+            (KeyCase::Alternating, "uSeR nAmE iD"),
+        ];
+
+        for (case, expected) in cases.iter() {
+            assert_eq!(*expected, to_case("user name ID", *case), "{:?}", case);
+        }
+    }
+}
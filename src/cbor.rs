@@ -0,0 +1,100 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error};
+use ciborium::Value;
+
+/// Converts the keys of a CBOR document.
+///
+/// Byte strings, tags and every other value are kept as they are. Keys that
+/// aren't text are kept too, and the values under them are still converted.
+pub fn convert_cbor(input: &[u8], converter: &Converter) -> Result<Vec<u8>, Error> {
+    let value: Value = ciborium::from_reader(input).map_err(Error::CborParse)?;
+    let converted = converter.convert_cbor(value, &Path::Root)?;
+    let mut output = Vec::new();
+    ciborium::into_writer(&converted, &mut output).map_err(Error::CborWrite)?;
+    Ok(output)
+}
+
+impl Converter<'_> {
+    fn convert_cbor(&self, value: Value, path: &Path) -> Result<Value, Error> {
+        match value {
+            Value::Array(array) => array
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| self.convert_cbor(value, &path.index(index)))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Value::Map(map) => {
+                let mut new_map = Vec::with_capacity(map.len());
+                for (key, value) in map {
+                    match key {
+                        Value::Text(key) => {
+                            let key_path = path.key(&key);
+                            if let Some(new_key) = self.rename_key(&key, &key_path)? {
+                                let value = self.convert_cbor(value, &key_path)?;
+                                new_map.push((Value::Text(new_key), value));
+                            }
+                        }
+                        key => {
+                            let key_text = match &key {
+                                Value::Integer(integer) => i128::from(*integer).to_string(),
+                                _ => String::new(),
+                            };
+                            let value = self.convert_cbor(value, &path.key(&key_text))?;
+                            new_map.push((key, value));
+                        }
+                    }
+                }
+                Ok(Value::Map(new_map))
+            }
+            Value::Tag(tag, value) => {
+                Ok(Value::Tag(tag, Box::new(self.convert_cbor(*value, path)?)))
+            }
+            value => Ok(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use ciborium::Value;
+
+    #[test]
+    fn keeps_bytes_tags_and_non_text_keys() {
+        let text = |s: &str| Value::Text(s.to_owned());
+        let input = Value::Map(vec![
+            (text("fileName"), text("a.bin")),
+            (text("fileContents"), Value::Bytes(vec![0, 255])),
+            (
+                text("createdAt"),
+                Value::Tag(1, Box::new(Value::Integer(0.into()))),
+            ),
+            (
+                Value::Integer(404.into()),
+                Value::Map(vec![(text("errorMessage"), text("Not found"))]),
+            ),
+        ]);
+        let mut bytes = Vec::new();
+        ciborium::into_writer(&input, &mut bytes).unwrap();
+
+        let output = convert_cbor(&bytes, &Converter::new(KeyCase::Snake)).unwrap();
+        let output: Value = ciborium::from_reader(output.as_slice()).unwrap();
+
+        assert_eq!(
+            Value::Map(vec![
+                (text("file_name"), text("a.bin")),
+                (text("file_contents"), Value::Bytes(vec![0, 255])),
+                (
+                    text("created_at"),
+                    Value::Tag(1, Box::new(Value::Integer(0.into())))
+                ),
+                (
+                    Value::Integer(404.into()),
+                    Value::Map(vec![(text("error_message"), text("Not found"))]),
+                ),
+            ]),
+            output
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use crate::location::key_locations;
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error, KeyRule, Location};
+use core::fmt;
+use serde_json::Value;
+
+/// A key that a conversion would change, as found by
+/// [`Converter::check_value`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChange {
+    /// The key, as it appears in the input.
+    pub key: String,
+
+    /// JSON pointer, in the input, to the value stored under `key`.
+    pub path: String,
+
+    /// What the key would become, or `None` if its entry would be dropped.
+    pub new_key: Option<String>,
+
+    /// Whether a manual rename gives the new key.
+    pub manual_rename: bool,
+
+    /// The other keys of the same object that would become the same new key,
+    /// leaving the collision strategy to pick what is kept.
+    pub collides_with: Vec<String>,
+
+    /// Where the key is written, when the input was checked as text with
+    /// [`Converter::check_str`].
+    pub location: Option<Location>,
+}
+
+impl fmt::Display for KeyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location)?;
+        }
+        write!(f, "`{}` at `{}` ", self.key, self.path)?;
+        match &self.new_key {
+            Some(new_key) => write!(f, "would become `{}`", new_key)?,
+            None => write!(f, "would be dropped")?,
+        }
+        if self.manual_rename {
+            write!(f, " by a manual rename")?;
+        }
+        for (i, key) in self.collides_with.iter().enumerate() {
+            let sep = if i == 0 { ", colliding with" } else { "," };
+            write!(f, "{} `{}`", sep, key)?;
+        }
+        Ok(())
+    }
+}
+
+impl Converter<'_> {
+    /// Lists the keys of `value` that converting it would rename or drop, in
+    /// document order. An empty list means the document already follows the
+    /// configured options.
+    pub fn check_value(&self, value: &Value) -> Result<Vec<KeyChange>, Error> {
+        let mut changes = Vec::new();
+        self.check(value, &Path::Root, &mut changes)?;
+        Ok(changes)
+    }
+
+    /// Like [`Converter::check_value`], for the JSON text `input`: each
+    /// change also tells the line and column of its key, whether the text is
+    /// pretty-printed or not.
+    pub fn check_str(&self, input: &str) -> Result<Vec<KeyChange>, Error> {
+        let value: Value = serde_json::from_str(input).map_err(Error::Parse)?;
+        let mut changes = self.check_value(&value)?;
+        let locations = key_locations(input);
+        for change in &mut changes {
+            change.location = locations.get(&change.path).copied();
+        }
+        Ok(changes)
+    }
+
+    fn check(&self, value: &Value, path: &Path, changes: &mut Vec<KeyChange>) -> Result<(), Error> {
+        match value {
+            Value::Array(arr) => {
+                for (index, value) in arr.iter().enumerate() {
+                    self.check(value, &path.index(index), changes)?;
+                }
+            }
+            Value::Object(map) => {
+                let detected = self.is_detected_dictionary(map);
+                let mut new_keys = Vec::with_capacity(map.len());
+                for key in map.keys() {
+                    let mut rules = Vec::new();
+                    let new_key = if detected {
+                        Some(key.clone())
+                    } else {
+                        self.trace_key(key, &path.key(key), &mut rules)?
+                    };
+                    let manual = rules
+                        .iter()
+                        .any(|rule| matches!(rule, KeyRule::ManualRename { .. }));
+                    new_keys.push((new_key, manual));
+                }
+                let mut by_new_key: HashMap<&str, Vec<&String>> = HashMap::new();
+                for (key, (new_key, _)) in map.keys().zip(&new_keys) {
+                    if let Some(new_key) = new_key {
+                        by_new_key.entry(new_key).or_default().push(key);
+                    }
+                }
+                for ((key, value), (new_key, manual)) in map.iter().zip(&new_keys) {
+                    let key_path = path.key(key);
+                    if new_key.as_deref() != Some(key.as_str()) {
+                        let collides_with = new_key
+                            .as_deref()
+                            .and_then(|new_key| by_new_key.get(new_key))
+                            .into_iter()
+                            .flatten()
+                            .filter(|other| **other != key)
+                            .map(|other| (*other).clone())
+                            .collect();
+                        changes.push(KeyChange {
+                            key: key.clone(),
+                            path: key_path.to_pointer(),
+                            new_key: new_key.clone(),
+                            manual_rename: *manual,
+                            collides_with,
+                            location: None,
+                        });
+                    }
+                    self.check(value, &key_path, changes)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn lists_keys_that_would_change() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_empty_key_policy(EmptyKeyPolicy::Drop);
+
+        let value = json!({"user_name": "Ada", "homeAddress": {"zipCode": 1, "city": "x"}, "": 0});
+        let changes = converter.check_value(&value).unwrap();
+
+        assert_eq!(
+            vec![
+                "`homeAddress` at `/homeAddress` would become `home_address`",
+                "`zipCode` at `/homeAddress/zipCode` would become `zip_code`",
+                "`` at `/` would be dropped",
+            ],
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+        assert!(converter
+            .check_value(&converter.convert_value(value).unwrap())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn flags_risky_renames() {
+        let value = json!({"ID": 1, "userId": 2, "user_id": 3, "item": {"itemName": "x"}});
+        let mut changer = CaseChanger::builder(value, KeyCase::Snake)
+            .manual_renames([("ID", "id")].iter().cloned().collect())
+            .build();
+
+        assert_eq!(
+            vec![
+                "`ID` at `/ID` would become `id` by a manual rename",
+                "`userId` at `/userId` would become `user_id`, colliding with `user_id`",
+                "`itemName` at `/item/itemName` would become `item_name`",
+            ],
+            changer
+                .preview()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            json!({"id": 1, "user_id": 3, "item": {"item_name": "x"}}),
+            changer.convert()
+        );
+    }
+
+    const PRETTY: &str = r#"{
+  "user_name": "Ada",
+  "homeAddress": {
+    "zipCode": 1,
+    "city": "Zürich", "streetName": "x"
+  },
+  "items": [
+    {"sku": "a"},
+    {
+      "orderId": 3
+    }
+  ]
+}"#;
+
+    #[test]
+    fn locates_keys_in_pretty_text() {
+        let converter = Converter::new(KeyCase::Snake);
+        let changes = converter.check_str(PRETTY).unwrap();
+
+        assert_eq!(
+            vec![
+                "3:3: `homeAddress` at `/homeAddress` would become `home_address`",
+                "4:5: `zipCode` at `/homeAddress/zipCode` would become `zip_code`",
+                "5:23: `streetName` at `/homeAddress/streetName` would become `street_name`",
+                "10:7: `orderId` at `/items/1/orderId` would become `order_id`",
+            ],
+            changes.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some(PRETTY.find("\"streetName\"").unwrap()),
+            changes[2].location.map(|l| l.offset)
+        );
+    }
+
+    #[test]
+    fn locates_keys_in_minified_text() {
+        let input =
+            r#"{"a\"b":{"userId":1},"list":[{"x":[]},{"zipCode":"}"}],"ünïcode":{"fooBar":0}}"#;
+        let changes = Converter::new(KeyCase::Snake).check_str(input).unwrap();
+
+        let locations: Vec<_> = changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.location.unwrap()))
+            .collect();
+        let at = |key: &str| {
+            let offset = input.find(key).unwrap();
+            Location {
+                offset,
+                line: 1,
+                column: input[..offset].chars().count() + 1,
+            }
+        };
+        assert_eq!(
+            vec![
+                ("/a\"b/userId", at("\"userId\"")),
+                ("/list/1/zipCode", at("\"zipCode\"")),
+                ("/ünïcode/fooBar", at("\"fooBar\"")),
+            ],
+            locations
+        );
+        // `ü` and `ï` take two bytes each, but one column.
+        assert_eq!(locations[2].1.offset - 2, locations[2].1.column - 1);
+    }
+}
@@ -0,0 +1,278 @@
+use crate::converter::DropLog;
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{DropReason, Error, JsonMap, KeyCase, KeyCollision};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// What to do when keys of one object are converted to the same new key, as
+/// `myKey` and `my_key` are in snake case.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionStrategy {
+    /// Keep the value of the last entry, at the position of the first one.
+    #[default]
+    KeepLast,
+
+    /// Keep the value of the first entry.
+    KeepFirst,
+
+    /// Fail the conversion with [`Error::KeyCollision`], listing every
+    /// collision of the document.
+    Error,
+
+    /// Keep every value, in an array under the new key, in document order.
+    MergeArrays,
+
+    /// Keep every entry, numbering the new key of the later ones from 2 with
+    /// the separator of the case, as in `my_key_2` or `myKey2`.
+    SuffixNumber,
+}
+
+/// Inserts the converted entries of one object, resolving collisions with
+/// the converter's strategy.
+pub(crate) struct ObjectBuilder {
+    strategy: CollisionStrategy,
+    case: KeyCase,
+
+    /// Whether collisions are looked for at all: they aren't when the last
+    /// entry is kept and nobody is told.
+    tracked: bool,
+
+    /// The original key of the entry under each new key.
+    originals: HashMap<String, String>,
+
+    /// New keys whose value is already an array of merged values.
+    merged: Vec<String>,
+
+    /// The collisions found, under [`CollisionStrategy::Error`].
+    collisions: Vec<KeyCollision>,
+}
+
+impl ObjectBuilder {
+    pub(crate) fn new(strategy: CollisionStrategy, case: KeyCase, logged: bool) -> Self {
+        ObjectBuilder {
+            strategy,
+            case,
+            tracked: logged || strategy != CollisionStrategy::KeepLast,
+            originals: HashMap::new(),
+            merged: Vec::new(),
+            collisions: Vec::new(),
+        }
+    }
+
+    /// Inserts `value` under `new_key`, the new name of `key`, into `map`,
+    /// the object being rebuilt at `path`.
+    pub(crate) fn insert(
+        &mut self,
+        map: &mut JsonMap,
+        key: String,
+        new_key: String,
+        value: Value,
+        path: &Path,
+        log: &mut Option<DropLog>,
+    ) {
+        if !self.tracked {
+            // Keys that don't change keep their own string.
+            map.insert(if new_key == key { key } else { new_key }, value);
+            return;
+        }
+        let earlier = match self.originals.get(&new_key) {
+            Some(earlier) => earlier.clone(),
+            None => {
+                if let Some(log) = log {
+                    log.rename(&key, &path.key(&key), &new_key);
+                }
+                self.originals.insert(new_key.clone(), key);
+                map.insert(new_key, value);
+                return;
+            }
+        };
+
+        match self.strategy {
+            CollisionStrategy::KeepLast | CollisionStrategy::Error => {
+                if self.strategy == CollisionStrategy::Error {
+                    self.note_collision(path, &earlier, &key, &new_key);
+                }
+                if let Some(log) = log {
+                    let reason = DropReason::Collision {
+                        kept: key.clone(),
+                        new_key: new_key.clone(),
+                    };
+                    log.drop(&earlier, &path.key(&earlier), reason);
+                    log.unrename(&path.key(&earlier));
+                    log.rename(&key, &path.key(&key), &new_key);
+                }
+                self.originals.insert(new_key.clone(), key);
+                map.insert(new_key, value);
+            }
+            CollisionStrategy::KeepFirst => {
+                if let Some(log) = log {
+                    let reason = DropReason::Collision {
+                        kept: earlier,
+                        new_key,
+                    };
+                    log.drop(&key, &path.key(&key), reason);
+                }
+            }
+            CollisionStrategy::MergeArrays => {
+                let merged = self.merged.contains(&new_key);
+                if let Some(existing) = map.get_mut(&new_key) {
+                    match existing {
+                        Value::Array(values) if merged => values.push(value),
+                        existing => *existing = Value::Array(vec![existing.take(), value]),
+                    }
+                }
+                if let Some(log) = log {
+                    log.rename(&key, &path.key(&key), &new_key);
+                }
+                if !merged {
+                    self.merged.push(new_key);
+                }
+            }
+            CollisionStrategy::SuffixNumber => {
+                let separator = self.case.separator();
+                let numbered = (2..)
+                    .map(|n| format!("{}{}{}", new_key, separator, n))
+                    .find(|numbered| !self.originals.contains_key(numbered))
+                    .unwrap_or(new_key);
+                if let Some(log) = log {
+                    log.rename(&key, &path.key(&key), &numbered);
+                }
+                self.originals.insert(numbered.clone(), key);
+                map.insert(numbered, value);
+            }
+        }
+    }
+
+    /// Notes that `key` has the same new key as `earlier`, in the object at
+    /// `path`.
+    fn note_collision(&mut self, path: &Path, earlier: &str, key: &str, new_key: &str) {
+        match self
+            .collisions
+            .iter_mut()
+            .find(|collision| collision.new_key == new_key)
+        {
+            Some(collision) => collision.keys.push(key.to_owned()),
+            None => self.collisions.push(KeyCollision {
+                path: path.to_pointer(),
+                keys: vec![earlier.to_owned(), key.to_owned()],
+                new_key: new_key.to_owned(),
+            }),
+        }
+    }
+
+    /// Hands the collisions found over to `log`, to be reported once the
+    /// whole document is converted, or fails right away without one.
+    pub(crate) fn finish(self, log: &mut Option<DropLog>) -> Result<(), Error> {
+        if self.collisions.is_empty() {
+            return Ok(());
+        }
+        match log {
+            Some(log) => {
+                log.collisions.extend(self.collisions);
+                Ok(())
+            }
+            None => Err(Error::KeyCollision {
+                collisions: self.collisions,
+            }),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn document() -> Value {
+        json!({
+            "myKey": 1,
+            "items": [{"userId": "a", "user_id": "b", "UserID": "c"}],
+            "my_key": 2
+        })
+    }
+
+    fn convert(strategy: CollisionStrategy) -> Result<Value, Error> {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_collision_strategy(strategy);
+        converter.convert_value(document())
+    }
+
+    #[test]
+    fn keeps_one_value() {
+        assert_eq!(
+            json!({"my_key": 2, "items": [{"user_id": "c"}]}),
+            convert(CollisionStrategy::KeepLast).unwrap()
+        );
+        assert_eq!(
+            json!({"my_key": 1, "items": [{"user_id": "a"}]}),
+            convert(CollisionStrategy::KeepFirst).unwrap()
+        );
+    }
+
+    #[test]
+    fn keeps_every_value() {
+        assert_eq!(
+            json!({"my_key": [1, 2], "items": [{"user_id": ["a", "b", "c"]}]}),
+            convert(CollisionStrategy::MergeArrays).unwrap()
+        );
+        assert_eq!(
+            json!({
+                "my_key": 1,
+                "items": [{"user_id": "a", "user_id_2": "b", "user_id_3": "c"}],
+                "my_key_2": 2
+            }),
+            convert(CollisionStrategy::SuffixNumber).unwrap()
+        );
+
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_collision_strategy(CollisionStrategy::SuffixNumber);
+        assert_eq!(
+            json!({"myKey": 1, "myKey2": 2}),
+            converter
+                .convert_value(json!({"my_key": 1, "MyKey": 2}))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn lists_every_collision() {
+        match convert(CollisionStrategy::Error) {
+            Err(Error::KeyCollision { collisions }) => assert_eq!(
+                vec![
+                    KeyCollision {
+                        path: "/items/0".to_owned(),
+                        keys: vec![
+                            "userId".to_owned(),
+                            "user_id".to_owned(),
+                            "UserID".to_owned()
+                        ],
+                        new_key: "user_id".to_owned(),
+                    },
+                    KeyCollision {
+                        path: String::new(),
+                        keys: vec!["myKey".to_owned(), "my_key".to_owned()],
+                        new_key: "my_key".to_owned(),
+                    },
+                ],
+                collisions
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut changer = CaseChanger::new(json!({"aB": 1, "a_b": 2}), KeyCase::Snake).unwrap();
+        changer.with_collision_strategy(CollisionStrategy::Error);
+        assert_eq!(
+            "keys collide after conversion: `aB`, `a_b` in the root object to `a_b`",
+            changer.try_convert().unwrap_err().to_string()
+        );
+        let mut changer =
+            CaseChanger::new(json!({"items": [{"aB": 1, "a_b": 2}]}), KeyCase::Snake).unwrap();
+        changer.with_collision_strategy(CollisionStrategy::Error);
+        assert_eq!(
+            "keys collide after conversion: `aB`, `a_b` at `/items/0` to `a_b`",
+            changer.try_convert().unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! The renaming algorithms of `serde_derive`, for
+//! [`Compatibility::SerdeRenameAll`].
+
+use crate::prelude::*;
+use crate::KeyCase;
+use serde::{Deserialize, Serialize};
+
+/// Whose rules keys are converted by. See
+/// [`Converter::with_compatibility`](crate::Converter::with_compatibility).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Compatibility {
+    /// Split keys into words as `convert_case` does, and join them in the
+    /// case.
+    #[default]
+    ConvertCase,
+
+    /// Rename keys exactly as `#[serde(rename_all = "...")]` renames the
+    /// fields and variants of a Rust type. A key without uppercase letters
+    /// is taken as a field name, which serde expects in snake case, and any
+    /// other key as a variant name, which serde expects in Pascal case.
+    ///
+    /// The cases serde has are camel, Pascal, snake, screaming snake, kebab
+    /// and screaming kebab ([`KeyCase::Cobol`]), with `lowercase` and
+    /// `UPPERCASE` being [`KeyCase::Flat`] and [`KeyCase::UpperFlat`]. Keys
+    /// are converted to other cases as with [`Compatibility::ConvertCase`].
+    SerdeRenameAll,
+
+    /// Split keys into words and join them as lodash 4's `camelCase`,
+    /// `snakeCase` and `kebabCase` do, for keys shared with JavaScript code
+    /// that converts them with lodash. Other cases are converted as with
+    /// [`Compatibility::ConvertCase`].
+    Lodash,
+}
+
+/// Renames `key` as `#[serde(rename_all)]` would for `case`, or returns
+/// `None` when serde has no such case.
+pub(crate) fn serde_rename_all(key: &str, case: KeyCase) -> Option<String> {
+    if key.chars().any(char::is_uppercase) {
+        rename_variant(key, case)
+    } else {
+        rename_field(key, case)
+    }
+}
+
+/// `RenameRule::apply_to_field` of `serde_derive`.
+fn rename_field(field: &str, case: KeyCase) -> Option<String> {
+    Some(match case {
+        KeyCase::Flat | KeyCase::Snake => field.to_owned(),
+        KeyCase::UpperFlat | KeyCase::ScreamingSnake => field.to_ascii_uppercase(),
+        KeyCase::Pascal => pascal_field(field),
+        KeyCase::Camel => lowercase_first(&pascal_field(field)),
+        KeyCase::Kebab => field.replace('_', "-"),
+        KeyCase::Cobol => field.to_ascii_uppercase().replace('_', "-"),
+        _ => return None,
+    })
+}
+
+/// `RenameRule::apply_to_variant` of `serde_derive`.
+fn rename_variant(variant: &str, case: KeyCase) -> Option<String> {
+    Some(match case {
+        KeyCase::Pascal => variant.to_owned(),
+        KeyCase::Flat => variant.to_ascii_lowercase(),
+        KeyCase::UpperFlat => variant.to_ascii_uppercase(),
+        KeyCase::Camel => lowercase_first(variant),
+        KeyCase::Snake => snake_variant(variant),
+        KeyCase::ScreamingSnake => snake_variant(variant).to_ascii_uppercase(),
+        KeyCase::Kebab => snake_variant(variant).replace('_', "-"),
+        KeyCase::Cobol => snake_variant(variant)
+            .to_ascii_uppercase()
+            .replace('_', "-"),
+        _ => return None,
+    })
+}
+
+/// Drops underscores and capitalizes the letter after each one, and the
+/// first letter.
+fn pascal_field(field: &str) -> String {
+    let mut pascal = String::with_capacity(field.len());
+    let mut capitalize = true;
+    for c in field.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            pascal.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            pascal.push(c);
+        }
+    }
+    pascal
+}
+
+/// Puts an underscore before every uppercase letter but the first, and
+/// lowercases them.
+fn snake_variant(variant: &str) -> String {
+    let mut snake = String::with_capacity(variant.len() + 4);
+    for (i, c) in variant.char_indices() {
+        if i > 0 && c.is_uppercase() {
+            snake.push('_');
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// serde lowercases the first byte, which it knows to be ASCII in a Rust
+/// identifier; other first characters are left as they are.
+fn lowercase_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => {
+            let mut lowered = String::with_capacity(name.len());
+            lowered.push(first.to_ascii_lowercase());
+            lowered.push_str(chars.as_str());
+            lowered
+        }
+        None => String::new(),
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde::Serialize;
+
+    /// Declares, for each `rename_all` rule, a struct whose fields are the
+    /// field names and an enum whose variants are the variant names, and
+    /// lists those names.
+    macro_rules! corpus {
+        (fields: $fields:tt, variants: $variants:tt, rules: [$($rule:literal => $module:ident),*]) => {
+            corpus!(@names $fields $variants);
+            $(corpus!(@rule $rule $module $fields $variants);)*
+        };
+        (@names [$($field:ident),*] [$($variant:ident),*]) => {
+            const FIELDS: &[&str] = &[$(stringify!($field)),*];
+            const VARIANTS: &[&str] = &[$(stringify!($variant)),*];
+        };
+        (@rule $rule:literal $module:ident [$($field:ident),*] [$($variant:ident),*]) => {
+            #[allow(non_camel_case_types, non_snake_case, dead_code, clippy::upper_case_acronyms)]
+            mod $module {
+                use super::*;
+
+                #[derive(Default, Serialize)]
+                #[serde(rename_all = $rule)]
+                pub struct Fields {
+                    $(pub $field: u8),*
+                }
+
+                #[derive(Serialize)]
+                #[serde(rename_all = $rule)]
+                pub enum Variants {
+                    $($variant),*
+                }
+
+                pub fn variants() -> Vec<Variants> {
+                    vec![$(Variants::$variant),*]
+                }
+            }
+        };
+    }
+
+    corpus! {
+        fields: [
+            user_id, address_line1, http_server2, v2_api, ipv4_address, a1b2c3, x,
+            _private, __double_leading, trailing_, double__inner, sha256_sum,
+            oauth2_token, e_tag, x_y_z, n0, already9_9, café_au_lait
+        ],
+        variants: [
+            UserId, HTTPServer, XMLHttpRequest, Ipv4Address, IPv6, A1B2C3, X,
+            V2Api, SHA256Sum, ABC, OAuth2Token, ETag, Http2Push
+        ],
+        rules: [
+            "camelCase" => camel,
+            "PascalCase" => pascal,
+            "snake_case" => snake,
+            "SCREAMING_SNAKE_CASE" => screaming_snake,
+            "kebab-case" => kebab,
+            "SCREAMING-KEBAB-CASE" => screaming_kebab,
+            "lowercase" => lower,
+            "UPPERCASE" => upper
+        ]
+    }
+
+    fn converted(names: &[&str], case: KeyCase) -> Vec<String> {
+        let mut converter = Converter::new(case);
+        converter.with_compatibility(Compatibility::SerdeRenameAll);
+        converter
+            .convert_names(names.iter().copied())
+            .into_iter()
+            .map(|mapping| mapping.new_name.unwrap().unwrap())
+            .collect()
+    }
+
+    fn serialized_fields(fields: impl Serialize) -> Vec<String> {
+        match serde_json::to_value(fields).unwrap() {
+            serde_json::Value::Object(map) => map.keys().cloned().collect(),
+            other => panic!("not an object: {}", other),
+        }
+    }
+
+    fn serialized_variants(variants: impl Serialize) -> Vec<String> {
+        serde_json::from_value(serde_json::to_value(variants).unwrap()).unwrap()
+    }
+
+    /// Compares the new keys with what serde itself names the fields and
+    /// variants of the corpus, for every rule.
+    #[test]
+    fn matches_serde_derive() {
+        let rules = [
+            (
+                KeyCase::Camel,
+                serialized_fields(camel::Fields::default()),
+                serialized_variants(camel::variants()),
+            ),
+            (
+                KeyCase::Pascal,
+                serialized_fields(pascal::Fields::default()),
+                serialized_variants(pascal::variants()),
+            ),
+            (
+                KeyCase::Snake,
+                serialized_fields(snake::Fields::default()),
+                serialized_variants(snake::variants()),
+            ),
+            (
+                KeyCase::ScreamingSnake,
+                serialized_fields(screaming_snake::Fields::default()),
+                serialized_variants(screaming_snake::variants()),
+            ),
+            (
+                KeyCase::Kebab,
+                serialized_fields(kebab::Fields::default()),
+                serialized_variants(kebab::variants()),
+            ),
+            (
+                KeyCase::Cobol,
+                serialized_fields(screaming_kebab::Fields::default()),
+                serialized_variants(screaming_kebab::variants()),
+            ),
+            (
+                KeyCase::Flat,
+                serialized_fields(lower::Fields::default()),
+                serialized_variants(lower::variants()),
+            ),
+            (
+                KeyCase::UpperFlat,
+                serialized_fields(upper::Fields::default()),
+                serialized_variants(upper::variants()),
+            ),
+        ];
+        for (case, fields, variants) in rules {
+            assert_eq!(fields, converted(FIELDS, case), "fields to {}", case);
+            assert_eq!(variants, converted(VARIANTS, case), "variants to {}", case);
+        }
+    }
+
+    #[test]
+    fn differs_from_convert_case() {
+        let value = serde_json::json!({"address_line1": 1, "HTTPServer": 2, "_private": 3});
+        let mut converter = Converter::new(KeyCase::Snake);
+        assert_eq!(
+            serde_json::json!({"address_line_1": 1, "http_server": 2, "private": 3}),
+            converter.convert_value(value.clone()).unwrap()
+        );
+
+        converter.with_compatibility(Compatibility::SerdeRenameAll);
+        assert_eq!(
+            serde_json::json!({"address_line1": 1, "h_t_t_p_server": 2, "_private": 3}),
+            converter.convert_value(value).unwrap()
+        );
+
+        // Cases serde doesn't have are converted as usual.
+        let mut converter = Converter::new(KeyCase::Train);
+        converter.with_compatibility(Compatibility::SerdeRenameAll);
+        assert_eq!(
+            serde_json::json!({"Address-Line-1": 1}),
+            converter
+                .convert_value(serde_json::json!({"address_line1": 1}))
+                .unwrap()
+        );
+    }
+}
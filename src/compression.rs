@@ -0,0 +1,234 @@
+use crate::{Converter, Error};
+use core::fmt;
+use core::str::FromStr;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// How a document is compressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// A writer compressing what is written to it, made by
+/// [`Compression::writer`]. It must be finished with
+/// [`CompressedWriter::finish`] for the output to be complete.
+pub struct CompressedWriter<W: Write> {
+    encoder: Encoder<W>,
+}
+
+enum Encoder<W: Write> {
+    None(W),
+    Gzip(flate2::write::GzEncoder<W>),
+    Zstd(zstd::Encoder<'static, W>),
+}
+
+impl Compression {
+    /// The compression of a file, from its extension: `.gz` for gzip, `.zst`
+    /// for zstd and none for anything else.
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            _ => Compression::None,
+        }
+    }
+
+    /// Wraps `input`, decompressing what is read from it as it goes.
+    /// Concatenated gzip members are read one after the other.
+    pub fn reader<'r>(self, input: impl BufRead + 'r) -> Result<Box<dyn Read + 'r>, Error> {
+        Ok(match self {
+            Compression::None => Box::new(input),
+            Compression::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(input)),
+            Compression::Zstd => Box::new(zstd::Decoder::with_buffer(input).map_err(Error::Io)?),
+        })
+    }
+
+    /// Wraps `output`, compressing what is written to it at `level`, or at
+    /// the default level of the format. Gzip levels go from 0 to 9, and zstd
+    /// levels from 1 to 22.
+    pub fn writer<W: Write>(
+        self,
+        output: W,
+        level: Option<i32>,
+    ) -> Result<CompressedWriter<W>, Error> {
+        let encoder = match self {
+            Compression::None => Encoder::None(output),
+            Compression::Gzip => {
+                let level = match level {
+                    None => flate2::Compression::default(),
+                    Some(level @ 0..=9) => flate2::Compression::new(level as u32),
+                    Some(level) => {
+                        return Err(Error::Io(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("gzip levels go from 0 to 9, not {}", level),
+                        )))
+                    }
+                };
+                Encoder::Gzip(flate2::write::GzEncoder::new(output, level))
+            }
+            Compression::Zstd => Encoder::Zstd(
+                zstd::Encoder::new(output, level.unwrap_or(zstd::DEFAULT_COMPRESSION_LEVEL))
+                    .map_err(Error::Io)?,
+            ),
+        };
+        Ok(CompressedWriter { encoder })
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        })
+    }
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+
+    /// Parses `none`, `gzip` or `zstd`.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "none" => Ok(Compression::None),
+            "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
+            _ => Err(Error::InvalidOption {
+                option: "compression",
+                message: format!("expected `none`, `gzip` or `zstd`, found `{}`", s),
+            }),
+        }
+    }
+}
+
+impl<W: Write> CompressedWriter<W> {
+    /// Writes what is left of the compressed stream and returns the writer
+    /// it was written to.
+    pub fn finish(self) -> Result<W, Error> {
+        match self.encoder {
+            Encoder::None(output) => Ok(output),
+            Encoder::Gzip(encoder) => encoder.finish().map_err(Error::Io),
+            Encoder::Zstd(encoder) => encoder.finish().map_err(Error::Io),
+        }
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            Encoder::None(output) => output.write(buf),
+            Encoder::Gzip(encoder) => encoder.write(buf),
+            Encoder::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            Encoder::None(output) => output.flush(),
+            Encoder::Gzip(encoder) => encoder.flush(),
+            Encoder::Zstd(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl Converter<'_> {
+    /// Converts the JSON document in the file at `input` into a new file at
+    /// `output`, streaming it with [`Converter::convert_reader`]. Each file
+    /// is compressed as its extension tells, see [`Compression::from_path`],
+    /// and `output` is compressed at `level`, see [`Compression::writer`].
+    pub fn convert_file(
+        &self,
+        input: impl AsRef<Path>,
+        output: impl AsRef<Path>,
+        level: Option<i32>,
+    ) -> Result<(), Error> {
+        let (input, output) = (input.as_ref(), output.as_ref());
+        let reader = BufReader::new(File::open(input).map_err(Error::Io)?);
+        let reader = Compression::from_path(input).reader(reader)?;
+        let writer = BufWriter::new(File::create(output).map_err(Error::Io)?);
+        let mut writer = Compression::from_path(output).writer(writer, level)?;
+        self.convert_reader(reader, &mut writer)?;
+        writer.finish()?.flush().map_err(Error::Io)
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use std::io::{BufReader, Read};
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", name]
+            .iter()
+            .collect()
+    }
+
+    fn read(path: &std::path::Path) -> String {
+        let file = BufReader::new(std::fs::File::open(path).unwrap());
+        let mut text = String::new();
+        Compression::from_path(path)
+            .reader(file)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn round_trips_compressed_files() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let converter = Converter::new(KeyCase::Snake);
+
+        let plain = dir.join(format!("user-{}.json", id));
+        converter
+            .convert_file(fixture("user.json"), &plain, None)
+            .unwrap();
+        let expected = std::fs::read_to_string(&plain).unwrap();
+        std::fs::remove_file(&plain).unwrap();
+        assert_eq!(
+            r#"{"user_name":"Ada","home_address":{"zip_code":"1"},"id":7}"#,
+            expected
+        );
+
+        for (input, output, level) in [
+            ("user.json.gz", format!("user-{}.json.gz", id), Some(9)),
+            ("user.json.zst", format!("user-{}.json.zst", id), None),
+        ] {
+            let output = dir.join(output);
+            converter
+                .convert_file(fixture(input), &output, level)
+                .unwrap();
+            let converted = read(&output);
+            std::fs::remove_file(&output).unwrap();
+            assert_eq!(expected, converted, "{}", input);
+        }
+    }
+
+    #[test]
+    fn explicit_compression() {
+        let mut compressed = Compression::Zstd.writer(Vec::new(), Some(3)).unwrap();
+        Converter::new(KeyCase::Camel)
+            .convert_reader(&br#"{"user_name": [{"zip_code": 1}]}"#[..], &mut compressed)
+            .unwrap();
+        let compressed = compressed.finish().unwrap();
+
+        let mut text = String::new();
+        Compression::Zstd
+            .reader(compressed.as_slice())
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(r#"{"userName":[{"zipCode":1}]}"#, text);
+
+        assert!(Compression::Gzip.writer(Vec::new(), Some(10)).is_err());
+        assert_eq!(Ok(Compression::Gzip), "gzip".parse().map_err(|_| ()));
+    }
+}
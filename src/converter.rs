@@ -0,0 +1,2733 @@
+use crate::batch::KeyCache;
+use crate::collision::ObjectBuilder;
+use crate::guide::Guided;
+#[cfg(feature = "jsonpath")]
+use crate::jsonpath::{JsonPaths, Selection};
+use crate::path::{escape_pointer_token, Path};
+use crate::prelude::*;
+use crate::preview::Trace;
+use crate::scope::ScopeMatcher;
+use crate::transform::ValueTransform;
+#[cfg(feature = "normalization")]
+use crate::NormalizationForm;
+use crate::{
+    AffixSpec, CaseRuleSpec, ChainPolicy, CollisionStrategy, Compatibility, ConversionSpec,
+    DictionaryHeuristic, DropReason, DroppedKey, EmptyKeyPolicy, Error, GuardPolicy,
+    IdentifierStyle, JsonMap, KeyCase, KeyCollision, KeyContext, KeyOrder, KeyRename, KeyRule,
+    KeyScope, KeyValidator, KeyViolation, LengthPolicy, MaxKeyLengthSpec, PathRule, PathRuleSpec,
+    Preset, PresetRules, RedactionRule, RenameBehavior, RenameMap, RenameReport, UndeclaredKeys,
+    VariantRenamesSpec,
+};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use serde_json::Value;
+
+/// The conversion settings, independent of any particular document.
+///
+/// A [`CaseChanger`] owns one of these next to its input and dereferences to
+/// it, so every `with_*` option below is also available on it.
+///
+/// [`Converter::to_spec`] gives a snapshot of every option, for logging or
+/// for building the same converter again.
+#[derive(Clone, Debug)]
+pub struct Converter<'a> {
+    /// The case to convert.
+    case: KeyCase,
+
+    /// Map of list of manual renames.
+    manual_renames: Renames<'a>,
+
+    /// Rename either by key or by value.
+    rename_behavior: RenameBehavior,
+
+    /// What to do when a rename gives a key that is renamed again.
+    chain_policy: ChainPolicy,
+
+    /// Manual renames used instead of `manual_renames` for a given case, in
+    /// multi-target conversions.
+    case_renames: Vec<(KeyCase, Renames<'a>)>,
+
+    /// Trim whitespace around keys before anything else looks at them.
+    trim_keys: bool,
+
+    /// What to do with empty keys.
+    empty_key_policy: EmptyKeyPolicy,
+
+    /// What to do with keys that can pollute object prototypes.
+    prototype_pollution_guard: Option<GuardPolicy>,
+
+    /// What to do with keys of one object converted to the same new key.
+    collision_strategy: CollisionStrategy,
+
+    /// The order of the entries of converted objects.
+    key_order: KeyOrder,
+
+    /// Sanitize the new keys into identifiers of this language.
+    identifier_style: Option<IdentifierStyle>,
+
+    /// Prefixes removed from keys before renaming them.
+    strip_prefixes: Vec<String>,
+
+    /// Only strip a prefix when a new word starts right after it.
+    strip_prefix_boundary: bool,
+
+    /// Longest new key allowed, in bytes, and what to do with longer ones.
+    max_key_length: Option<(usize, LengthPolicy)>,
+
+    /// Text prepended to the new keys in scope.
+    key_prefix: Option<(String, ScopeMatcher)>,
+
+    /// Text appended to the new keys in scope.
+    key_suffix: Option<(String, ScopeMatcher)>,
+
+    /// Keys whose object value holds data keys that must not be renamed.
+    dictionary_keys: Vec<String>,
+
+    /// Tells dictionaries apart from records by looking at their keys.
+    dictionary_detection: Option<DictionaryHeuristic>,
+
+    /// Keys whose array value holds `[key, value]` or `{"key", "value"}` rows
+    /// whose keys are renamed.
+    pair_array_keys: Vec<String>,
+
+    /// Keys whose string value holds a JSON document whose keys are
+    /// converted.
+    embedded_json_fields: Vec<String>,
+
+    /// Keys starting with one of these are kept as they are.
+    skip_prefixes: Vec<String>,
+
+    /// Keys equal to one of these are kept as they are.
+    excluded_keys: Vec<String>,
+
+    /// Keys at these pointers are kept as they are.
+    excluded_pointers: Vec<ScopeMatcher>,
+
+    /// When set, only keys inside the values these select are converted.
+    path_rules: Vec<PathRule>,
+
+    /// When set, keys enclosed by this many objects or more are kept as
+    /// they are.
+    max_depth: Option<usize>,
+
+    /// Cases the keys at some paths are converted to instead, the first
+    /// match winning.
+    case_rules: Vec<PathRule>,
+
+    /// Cases the keys at some depths are converted to instead.
+    depth_cases: BTreeMap<usize, KeyCase>,
+
+    /// When set, the only original keys converted to the case; the others
+    /// are kept as they are unless manually renamed.
+    allowed_keys: Option<Vec<String>>,
+
+    /// Find and replace rules applied to keys before they are converted to
+    /// the case, in order.
+    #[cfg(feature = "regex")]
+    regex_renames: Vec<(regex::Regex, String)>,
+
+    /// Find and replace rules applied to keys after they are converted to
+    /// the case, in order.
+    #[cfg(feature = "regex")]
+    regex_renames_after_case: Vec<(regex::Regex, String)>,
+
+    /// JSON Schema telling apart property names, which are converted, from
+    /// the keys of maps, which are kept.
+    schema_guide: Option<Value>,
+
+    /// What to do with keys the schema guide doesn't declare.
+    undeclared_keys: UndeclaredKeys,
+
+    /// Manual renames merged over `manual_renames` inside objects whose
+    /// discriminator key (first) holds the variant (second).
+    variant_renames: Vec<(String, String, Renames<'a>)>,
+
+    /// Checks run against every new key.
+    validators: Vec<KeyValidator>,
+
+    /// Fail conversions on the first key a validator rejects.
+    strict_validation: bool,
+
+    /// Rules replacing the values of sensitive keys, the first match winning.
+    redactions: Vec<RedactionRule>,
+
+    /// Functions applied to the converted values of some entries.
+    value_transforms: Vec<ValueTransform>,
+
+    /// Whose rules keys are converted by.
+    compatibility: Compatibility,
+
+    /// JSONPath selectors of the subtrees whose keys are converted, and of
+    /// those whose keys are kept.
+    #[cfg(feature = "jsonpath")]
+    jsonpaths: JsonPaths,
+
+    /// Where `jsonpaths` matched in the document being converted.
+    #[cfg(feature = "jsonpath")]
+    jsonpath_selection: Option<Selection>,
+
+    /// Normalization form keys are brought to before anything else looks at
+    /// them.
+    #[cfg(feature = "normalization")]
+    unicode_normalization: Option<NormalizationForm>,
+
+    /// Value of the `label` label on recorded metrics.
+    #[cfg(feature = "metrics")]
+    metrics_label: Option<String>,
+}
+
+impl<'a> Converter<'a> {
+    pub fn new(new_case: KeyCase) -> Self {
+        Self {
+            case: new_case,
+            manual_renames: Renames::default(),
+            rename_behavior: RenameBehavior::default(),
+            chain_policy: ChainPolicy::default(),
+            case_renames: Vec::new(),
+            trim_keys: false,
+            empty_key_policy: EmptyKeyPolicy::default(),
+            prototype_pollution_guard: None,
+            collision_strategy: CollisionStrategy::default(),
+            key_order: KeyOrder::default(),
+            identifier_style: None,
+            strip_prefixes: Vec::new(),
+            strip_prefix_boundary: false,
+            max_key_length: None,
+            key_prefix: None,
+            key_suffix: None,
+            dictionary_keys: Vec::new(),
+            dictionary_detection: None,
+            pair_array_keys: Vec::new(),
+            embedded_json_fields: Vec::new(),
+            skip_prefixes: Vec::new(),
+            excluded_keys: Vec::new(),
+            excluded_pointers: Vec::new(),
+            path_rules: Vec::new(),
+            max_depth: None,
+            case_rules: Vec::new(),
+            depth_cases: BTreeMap::new(),
+            allowed_keys: None,
+            #[cfg(feature = "regex")]
+            regex_renames: Vec::new(),
+            #[cfg(feature = "regex")]
+            regex_renames_after_case: Vec::new(),
+            schema_guide: None,
+            undeclared_keys: UndeclaredKeys::default(),
+            variant_renames: Vec::new(),
+            validators: Vec::new(),
+            strict_validation: false,
+            redactions: Vec::new(),
+            value_transforms: Vec::new(),
+            compatibility: Compatibility::default(),
+            #[cfg(feature = "jsonpath")]
+            jsonpaths: JsonPaths::default(),
+            #[cfg(feature = "jsonpath")]
+            jsonpath_selection: None,
+            #[cfg(feature = "normalization")]
+            unicode_normalization: None,
+            #[cfg(feature = "metrics")]
+            metrics_label: None,
+        }
+    }
+
+    /// The case keys are converted to.
+    pub fn case(&self) -> KeyCase {
+        self.case
+    }
+
+    pub fn rename_behavior(&self) -> RenameBehavior {
+        self.rename_behavior
+    }
+
+    /// The manual renames, from the original key to the new one, in no
+    /// particular order.
+    pub fn manual_renames(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.manual_renames
+            .iter()
+            .map(|(from, to)| (from.as_ref(), to.as_ref()))
+    }
+
+    pub fn with_manual_renames(&mut self, rename_list: RenameMap<'a>) {
+        self.manual_renames = borrow_renames(rename_list);
+    }
+
+    /// Sets the manual renames from a JSON mapping table, either an object
+    /// (`{"oldName": "new_name"}`) or an array of pairs
+    /// (`[["oldName", "new_name"]]`), in which a later pair replaces an earlier
+    /// one with the same first name.
+    pub fn with_manual_renames_from_value(&mut self, rename_list: &Value) -> Result<(), Error> {
+        self.manual_renames = own_renames(renames_from_value(rename_list)?);
+        Ok(())
+    }
+
+    /// Sets the manual renames from a JSON file, in any of the forms accepted
+    /// by [`Converter::with_manual_renames_from_value`].
+    #[cfg(feature = "std")]
+    pub fn with_manual_renames_from_file(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), Error> {
+        let text = std::fs::read_to_string(path).map_err(Error::Io)?;
+        let rename_list = serde_json::from_str(&text).map_err(Error::Parse)?;
+        self.with_manual_renames_from_value(&rename_list)
+    }
+
+    pub fn with_custom_rename_behavior(&mut self, rename_behavior: RenameBehavior) {
+        self.rename_behavior = rename_behavior;
+    }
+
+    /// Sets what happens when a manual rename gives a key that the same table
+    /// renames again. Chains are followed within one table: variant renames
+    /// and manual renames are each looked at on their own.
+    pub fn with_chain_policy(&mut self, chain_policy: ChainPolicy) {
+        self.chain_policy = chain_policy;
+    }
+
+    /// Overrides the manual renames for `case` when converting with
+    /// [`CaseChanger::convert_multi`]. Other cases keep the list set by
+    /// [`Converter::with_manual_renames`].
+    pub fn with_case_renames(&mut self, case: KeyCase, rename_list: RenameMap<'a>) {
+        self.set_case_renames(case, borrow_renames(rename_list));
+    }
+
+    fn set_case_renames(&mut self, case: KeyCase, rename_list: Renames<'a>) {
+        match self.case_renames.iter_mut().find(|(c, _)| *c == case) {
+            Some((_, renames)) => *renames = rename_list,
+            None => self.case_renames.push((case, rename_list)),
+        }
+    }
+
+    /// Merges `rename_list` over the manual renames inside objects whose
+    /// `discriminator_key` holds the string `variant`, such as
+    /// `"type": "orderCreated"`, and inside everything nested in them, down to
+    /// objects that hold a variant of their own. Replaces the list previously
+    /// set for the same discriminator and variant.
+    ///
+    /// The discriminator is matched against the original keys and values.
+    /// Only conversions of [`Value`]s look at variants; the serde adapters,
+    /// the other formats and multi-target conversions use the manual renames
+    /// alone.
+    pub fn with_variant_renames(
+        &mut self,
+        discriminator_key: &str,
+        variant: &str,
+        rename_list: RenameMap<'a>,
+    ) {
+        self.set_variant_renames(discriminator_key, variant, borrow_renames(rename_list));
+    }
+
+    fn set_variant_renames(
+        &mut self,
+        discriminator_key: &str,
+        variant: &str,
+        rename_list: Renames<'a>,
+    ) {
+        match self
+            .variant_renames
+            .iter_mut()
+            .find(|(d, v, _)| d == discriminator_key && v == variant)
+        {
+            Some((_, _, renames)) => *renames = rename_list,
+            None => self.variant_renames.push((
+                discriminator_key.to_owned(),
+                variant.to_owned(),
+                rename_list,
+            )),
+        }
+    }
+
+    /// Labels the metrics recorded for this converter with `label`, under
+    /// the `label` key. Without a label, metrics have no labels.
+    ///
+    /// Each successful [`Converter::convert_value`] records, through the
+    /// `metrics` facade, the counters `jkcc_keys_converted_total` (keys whose
+    /// name changed), `jkcc_keys_unchanged_total`, `jkcc_collisions_total`
+    /// and `jkcc_values_redacted_total`, and the histogram
+    /// `jkcc_convert_duration_seconds`. Other conversions,
+    /// such as the serde adapters and the other formats, record nothing.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics_label(&mut self, label: impl Into<String>) {
+        self.metrics_label = Some(label.into());
+    }
+
+    /// Converts keys by the rules of `compatibility` rather than those of
+    /// `convert_case`. With [`Compatibility::SerdeRenameAll`], new keys are
+    /// the names `#[serde(rename_all)]` gives fields and variants, so that
+    /// they match what a Rust type renamed that way expects. With
+    /// [`Compatibility::Lodash`], they are what lodash's `camelCase`,
+    /// `snakeCase` and `kebabCase` return. Manual renames still apply.
+    pub fn with_compatibility(&mut self, compatibility: Compatibility) {
+        self.compatibility = compatibility;
+    }
+
+    /// Brings every key to the normalization `form` before it is trimmed,
+    /// filtered, matched against the manual renames and converted, so that
+    /// keys written in different forms, such as `é` composed or as `e` and a
+    /// combining accent, become the same key. When both forms are in one
+    /// object, they collide like any other keys converted to the same name.
+    /// Accents are kept.
+    ///
+    /// Manual renames, allowed keys and prefixes are compared with the
+    /// normalized keys, so they should be written in `form`. Keys kept as
+    /// they are, such as those of dictionaries, are not normalized.
+    #[cfg(feature = "normalization")]
+    pub fn with_unicode_normalization(&mut self, form: NormalizationForm) {
+        self.unicode_normalization = Some(form);
+    }
+
+    /// Trims leading and trailing Unicode whitespace from every key before it
+    /// is matched against the manual renames and converted. Whitespace inside
+    /// the key is left to the case conversion.
+    pub fn with_trim_keys(&mut self, trim_keys: bool) {
+        self.trim_keys = trim_keys;
+    }
+
+    /// Sets what happens to keys that are empty, or become empty after
+    /// trimming. Defaults to [`EmptyKeyPolicy::Preserve`].
+    pub fn with_empty_key_policy(&mut self, empty_key_policy: EmptyKeyPolicy) {
+        self.empty_key_policy = empty_key_policy;
+    }
+
+    /// Guards against the keys `__proto__`, `constructor` and `prototype`,
+    /// whether they are in the input or come out of the conversion, such as
+    /// `Constructor` converted to camel case. Every key is checked, at any
+    /// depth, including keys that are kept as they are by other options.
+    pub fn with_prototype_pollution_guard(&mut self, policy: GuardPolicy) {
+        self.prototype_pollution_guard = Some(policy);
+    }
+
+    /// Sets what happens when keys of one object are converted to the same
+    /// new key. By default, the last entry is kept.
+    ///
+    /// The strategy applies to conversions of [`Value`]s, in place or not,
+    /// single or multi-target. [`Converter::convert_value_to_entries`] keeps
+    /// every member whatever the strategy, and the serde adapters keep the
+    /// last one.
+    pub fn with_collision_strategy(&mut self, collision_strategy: CollisionStrategy) {
+        self.collision_strategy = collision_strategy;
+    }
+
+    /// Sets the order of the entries of converted objects, as far as
+    /// `serde_json` keeps one, see [`KeyOrder`]. By default, objects keep the
+    /// order of the input.
+    ///
+    /// The order applies to single-target conversions of [`Value`]s, in
+    /// place or not. The other conversions keep the order of the input.
+    pub fn with_key_order(&mut self, key_order: KeyOrder) {
+        self.key_order = key_order;
+    }
+
+    /// Makes every new key a valid identifier of `identifier_style`, after
+    /// manual renames, case conversion and affixes. See [`IdentifierStyle`] for the
+    /// exact mapping. Keys kept by [`EmptyKeyPolicy::Preserve`] are left alone.
+    pub fn with_safe_identifiers(&mut self, identifier_style: IdentifierStyle) {
+        self.identifier_style = Some(identifier_style);
+    }
+
+    /// Limits new keys to `max` bytes, after every other step including
+    /// [`Converter::with_safe_identifiers`]. Longer keys are handled by
+    /// `policy`; when shortening makes two keys of an object equal, the last
+    /// one is kept, as with any other collision. Keys kept as they are, such
+    /// as excluded ones, are left alone.
+    pub fn with_max_key_length(&mut self, max: usize, policy: LengthPolicy) {
+        self.max_key_length = Some((max, policy));
+    }
+
+    /// Removes the first of `prefixes` that a key starts with, before the key
+    /// is matched against the manual renames and converted, so that
+    /// `"strUserName"` becomes `user_name` rather than `str_user_name`.
+    ///
+    /// A key made only of a prefix is kept whole.
+    pub fn with_strip_prefixes<I, S>(&mut self, prefixes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.strip_prefixes = prefixes.into_iter().map(Into::into).collect();
+    }
+
+    /// When enabled, a prefix is only stripped if it ends with a separator
+    /// (`_`, `-` or a space) or is followed by an uppercase letter or a
+    /// separator, so that the prefix `"str"` leaves `"string"` alone.
+    pub fn with_strip_prefix_boundary(&mut self, strip_prefix_boundary: bool) {
+        self.strip_prefix_boundary = strip_prefix_boundary;
+    }
+
+    /// Prepends `prefix` to the keys in `scope`, after manual renames and case
+    /// conversion, replacing any previous prefix. Scopes are evaluated against
+    /// the input document.
+    pub fn with_key_prefix(&mut self, prefix: impl Into<String>, scope: KeyScope) {
+        self.key_prefix = Some((prefix.into(), ScopeMatcher::new(scope)));
+    }
+
+    /// Appends `suffix` to the keys in `scope`, after manual renames and case
+    /// conversion, replacing any previous suffix. Scopes are evaluated against
+    /// the input document.
+    pub fn with_key_suffix(&mut self, suffix: impl Into<String>, scope: KeyScope) {
+        self.key_suffix = Some((suffix.into(), ScopeMatcher::new(scope)));
+    }
+
+    /// Marks keys whose value is a dictionary: when the value stored under one
+    /// of these (original) keys is an object, its own keys are data, such as
+    /// IDs or user-defined names, and are kept exactly as they are. The key
+    /// itself and the values inside the dictionary are converted as usual.
+    pub fn with_dictionary_keys<I, S>(&mut self, dictionary_keys: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.dictionary_keys = dictionary_keys.into_iter().map(Into::into).collect();
+    }
+
+    /// Keeps the keys of objects that `heuristic` finds to be dictionaries,
+    /// such as objects keyed by UUIDs or dates, exactly as they are. Unlike
+    /// [`Converter::with_dictionary_keys`], each object is judged by its own
+    /// keys wherever it appears; the values inside it are converted as usual.
+    ///
+    /// Conversions of [`Value`]s, checks and preflights apply it, and
+    /// [`Converter::preflight`] lists the objects it fired on. The serde
+    /// adapters and the other formats see one key at a time and don't.
+    pub fn with_dictionary_detection(&mut self, heuristic: DictionaryHeuristic) {
+        self.dictionary_detection = Some(heuristic);
+    }
+
+    /// Marks keys whose value is a map encoded as an array of rows, either
+    /// `[key, value]` pairs or `{"key": key, "value": value}` objects: when
+    /// the value stored under one of these (original) keys is an array, the
+    /// key of each row is renamed like an object key and its value converted
+    /// as usual. Rows of any other shape are kept exactly as they are.
+    ///
+    /// Only conversions of [`Value`]s look at rows; the serde adapters and
+    /// multi-target conversions treat them as plain arrays.
+    pub fn with_pair_array_keys<I, S>(&mut self, pair_array_keys: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.pair_array_keys = pair_array_keys.into_iter().map(Into::into).collect();
+    }
+
+    /// Marks keys whose value is a JSON document embedded in a string, such
+    /// as `"payload": "{\"innerKey\": 1}"`: when the value stored under one
+    /// of these (original) keys is a string holding an object or an array,
+    /// its keys are converted as if it were nested there, and it is written
+    /// back as compact JSON. Other strings, including those that aren't
+    /// valid JSON, are kept exactly as they are.
+    ///
+    /// Only conversions of [`Value`]s look into strings; the serde adapters
+    /// and multi-target conversions treat them as plain strings.
+    pub fn with_embedded_json_fields<I, S>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.embedded_json_fields = fields.into_iter().map(Into::into).collect();
+    }
+
+    /// Keeps keys starting with one of `prefixes` exactly as they are, such as
+    /// `"$set"` with the prefix `"$"`. Their values are converted as usual.
+    pub fn with_skip_prefixes<I, S>(&mut self, prefixes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.skip_prefixes = prefixes.into_iter().map(Into::into).collect();
+    }
+
+    /// Keeps the keys at `pointers` exactly as they are. Pointers are written
+    /// like [`KeyScope::Pointer`], so `*` stands for any single key or index.
+    /// The values under those keys are converted as usual.
+    pub fn with_excluded_pointers<I, S>(&mut self, pointers: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.excluded_pointers = pointers
+            .into_iter()
+            .map(|pointer| ScopeMatcher::new(KeyScope::Pointer(pointer.into())))
+            .collect();
+    }
+
+    /// Keeps the keys equal to one of `keys` exactly as they are, wherever
+    /// they appear, such as `"X-Request-ID"` or `"en_US"`. The values under
+    /// those keys are converted as usual.
+    pub fn with_excluded_keys<I, S>(&mut self, keys: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.excluded_keys = keys.into_iter().map(Into::into).collect();
+    }
+
+    /// Keeps the keys at `paths` exactly as they are, in addition to the
+    /// excluded pointers already set. Paths are written like the patterns of
+    /// [`PathRule::new`], as JSON pointers or dotted paths such as
+    /// `"items[*].Raw"`, and are exported as pointers by
+    /// [`Converter::to_spec`].
+    pub fn with_excluded_paths<I, S>(&mut self, paths: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for path in paths {
+            let tokens = crate::path_rule::parse_path(path.as_ref(), "excluded_paths")?;
+            let pointer: String = tokens
+                .iter()
+                .map(|token| format!("/{}", escape_pointer_token(token)))
+                .collect();
+            self.excluded_pointers
+                .push(ScopeMatcher::new(KeyScope::Pointer(pointer)));
+        }
+        Ok(())
+    }
+
+    /// Only converts the keys inside the values the `rules` select, and below
+    /// them, and keeps every other key exactly as it is. A rule with a case
+    /// of its own converts its keys to that case, and the innermost rule
+    /// enclosing a key decides it.
+    ///
+    /// ```
+    /// # use json_keys_case_changer::{Converter, KeyCase, PathRule};
+    /// # use serde_json::json;
+    /// let mut converter = Converter::new(KeyCase::Snake);
+    /// converter.with_path_rules(vec![PathRule::new("/data/attributes")?]);
+    /// assert_eq!(
+    ///     json!({"data": {"attributes": {"first_name": "Ada"}}, "meta": {"pageSize": 1}}),
+    ///     converter.convert_value(json!({
+    ///         "data": {"attributes": {"firstName": "Ada"}},
+    ///         "meta": {"pageSize": 1}
+    ///     }))?
+    /// );
+    /// # Ok::<(), json_keys_case_changer::Error>(())
+    /// ```
+    pub fn with_path_rules<I>(&mut self, rules: I)
+    where
+        I: IntoIterator<Item = PathRule>,
+    {
+        self.path_rules = rules.into_iter().collect();
+    }
+
+    /// Only converts the keys of the first `levels` levels of objects, and
+    /// keeps deeper keys exactly as they are: with 1, only the keys of the
+    /// outermost objects are converted. Levels are counted like
+    /// [`KeyRef::depth`](crate::KeyRef::depth), so arrays don't add one.
+    ///
+    /// Conversions of [`Value`]s don't descend below the maximum depth at
+    /// all, unless redaction rules, value transforms, validators or the
+    /// prototype pollution guard need to look at the deeper entries.
+    pub fn with_max_depth(&mut self, levels: usize) {
+        self.max_depth = Some(levels);
+    }
+
+    /// Converts the keys at `path` to `case` rather than to the case of the
+    /// converter. The path is written like the patterns of
+    /// [`PathRule::new`], and names the keys themselves, so `"config.*"`
+    /// matches every key of the `config` object, but not the keys nested in
+    /// them.
+    ///
+    /// Case rules take precedence over the cases set by depth and by path
+    /// rules, and the first rule added that matches a key wins. They apply
+    /// to every target of a multi-target conversion.
+    pub fn with_case_rule(&mut self, path: &str, case: KeyCase) -> Result<(), Error> {
+        let rule = PathRule::parse(path, "case_rules")?;
+        self.case_rules.push(rule.with_case(case));
+        Ok(())
+    }
+
+    /// Converts the keys at `depth`, counted like
+    /// [`KeyRef::depth`](crate::KeyRef::depth), to `case` rather than to the
+    /// case of the converter. A case rule matching a key takes precedence.
+    pub fn with_depth_case(&mut self, depth: usize, case: KeyCase) {
+        self.depth_cases.insert(depth, case);
+    }
+
+    /// Only converts keys named in `keys` (by their original name), wherever
+    /// they appear, and keeps every other key exactly as it is. Manual
+    /// renames still apply to any key, and values are converted as usual
+    /// under every key.
+    ///
+    /// An empty list converts no key at all, rather than every key.
+    pub fn with_allowed_keys<I, S>(&mut self, keys: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_keys = Some(keys.into_iter().map(Into::into).collect());
+    }
+
+    /// Rewrites keys with `renames` before converting them to the case. Each
+    /// regex replaces every match in turn, with a replacement that may refer
+    /// to capture groups as in [`regex::Regex::replace_all`], so
+    /// `(Regex::new("^legacy_")?, "")` strips a prefix and
+    /// `(Regex::new("V[0-9]+$")?, "")` turns `fooBarV2` into `foo_bar` in
+    /// snake case.
+    ///
+    /// Manual renames take precedence, and keys kept as they are by other
+    /// options aren't rewritten. Like validators, regex renames are not part
+    /// of [`ConversionSpec`].
+    #[cfg(feature = "regex")]
+    pub fn with_regex_renames(&mut self, renames: Vec<(regex::Regex, String)>) {
+        self.regex_renames = renames;
+    }
+
+    /// Rewrites keys with `renames` after converting them to the case, as
+    /// [`Converter::with_regex_renames`] does before.
+    #[cfg(feature = "regex")]
+    pub fn with_regex_renames_after_case(&mut self, renames: Vec<(regex::Regex, String)>) {
+        self.regex_renames_after_case = renames;
+    }
+
+    /// Keeps converting keys only inside the values `expr` selects, and
+    /// below them, adding to the include selectors already set. Keys
+    /// outside every selected value are kept as they are.
+    ///
+    /// Selectors are JSONPath expressions (RFC 9535), such as
+    /// `$.orders[*].items[?@.type=='custom'].attributes`, evaluated once
+    /// against each input document before it is converted. Only conversions
+    /// of [`Value`]s apply them; checks, previews and the serde adapters
+    /// don't see whole documents and ignore them.
+    #[cfg(feature = "jsonpath")]
+    pub fn with_include_jsonpath(&mut self, expr: &str) -> Result<(), Error> {
+        let path = crate::jsonpath::parse("include_jsonpaths", expr)?;
+        self.jsonpaths.include.push((expr.to_owned(), path));
+        Ok(())
+    }
+
+    /// Keeps the keys inside the values `expr` selects, and below them,
+    /// exactly as they are, such as `$..metadata` for every `metadata`
+    /// object at any depth. The selected value's own key is converted as
+    /// usual. Exclusions win over inclusions; see
+    /// [`Converter::with_include_jsonpath`] for how selectors are evaluated.
+    #[cfg(feature = "jsonpath")]
+    pub fn with_exclude_jsonpath(&mut self, expr: &str) -> Result<(), Error> {
+        let path = crate::jsonpath::parse("exclude_jsonpaths", expr)?;
+        self.jsonpaths.exclude.push((expr.to_owned(), path));
+        Ok(())
+    }
+
+    /// Walks documents along with the JSON Schema `schema`, and only converts
+    /// the keys declared in `properties`. Keys of maps, matched by
+    /// `patternProperties` or by an `additionalProperties` schema, are kept
+    /// as they are, while the values under them are still guided by those
+    /// schemas. Other keys are handled as set with
+    /// [`Converter::with_undeclared_keys`].
+    ///
+    /// Local `$ref`s, `items`, `prefixItems` and the branches of `allOf`,
+    /// `anyOf` and `oneOf` are followed; a key declared by any branch counts
+    /// as declared. Patterns aren't matched, so any `patternProperties`
+    /// makes an object a map.
+    pub fn with_schema_guide(&mut self, schema: &Value) {
+        self.schema_guide = Some(schema.clone());
+    }
+
+    /// Sets what happens to keys that the schema guide doesn't declare.
+    /// Defaults to [`UndeclaredKeys::Convert`].
+    pub fn with_undeclared_keys(&mut self, undeclared_keys: UndeclaredKeys) {
+        self.undeclared_keys = undeclared_keys;
+    }
+
+    /// Runs `validators` against every new key, after every other step, so
+    /// that keys coming from manual renames, affixes and kept keys are
+    /// checked too. Keys of objects found by the dictionary detection are
+    /// not.
+    ///
+    /// [`Converter::preflight`] reports every key a validator rejects, and
+    /// with [`Converter::with_strict_validation`], conversions fail on the
+    /// first one. Validators are not part of [`ConversionSpec`], since
+    /// custom ones can't be saved.
+    pub fn with_validators(&mut self, validators: Vec<KeyValidator>) {
+        self.validators = validators;
+    }
+
+    /// When enabled, a key rejected by a validator fails the conversion with
+    /// [`Error::InvalidKey`].
+    pub fn with_strict_validation(&mut self, strict_validation: bool) {
+        self.strict_validation = strict_validation;
+    }
+
+    /// Adds `rule` to the redaction rules. The value of every key a rule
+    /// matches, by its name in the input, is replaced as the rule says
+    /// instead of being converted, whether the key itself is renamed, kept
+    /// or excluded. When several rules match a key, the first one added
+    /// wins.
+    ///
+    /// [`Converter::preflight`] lists the redacted values. Like validators,
+    /// redaction rules are not part of [`ConversionSpec`].
+    pub fn with_redaction(&mut self, rule: RedactionRule) {
+        self.redactions.push(rule);
+    }
+
+    /// Applies `transform` to the value of every entry whose original key is
+    /// `key_or_pointer`, or, when it starts with `/`, of every entry at that
+    /// JSON pointer, written as for [`KeyScope::Pointer`]. The function gets
+    /// the value once its own keys are converted, and what it returns is
+    /// inserted under the new key. An `Err` fails the conversion with
+    /// [`Error::ValueTransform`].
+    ///
+    /// Transforms run in the order they were added, each on the result of
+    /// the previous one, on conversions of [`Value`]s only. Redacted values
+    /// are not transformed. Like validators, transforms are not part of
+    /// [`ConversionSpec`].
+    pub fn with_value_transform(
+        &mut self,
+        key_or_pointer: &str,
+        transform: impl Fn(Value) -> Result<Value, String> + Send + Sync + 'static,
+    ) {
+        self.value_transforms
+            .push(ValueTransform::new(key_or_pointer, Arc::new(transform)));
+    }
+
+    /// Adds the rules of `preset` to the skip prefixes, dictionary keys and
+    /// excluded pointers already set. See [`Preset`] for what each one sets.
+    pub fn with_preset(&mut self, preset: Preset) {
+        self.with_preset_rules(preset.rules());
+    }
+
+    /// Adds `rules` to the skip prefixes, dictionary keys and excluded
+    /// pointers already set, for presets whose rules were adjusted first.
+    pub fn with_preset_rules(&mut self, rules: PresetRules) {
+        let PresetRules {
+            skip_prefixes,
+            dictionary_keys,
+            excluded_pointers,
+        } = rules;
+        self.skip_prefixes.extend(skip_prefixes);
+        self.dictionary_keys.extend(dictionary_keys);
+        self.excluded_pointers.extend(
+            excluded_pointers
+                .into_iter()
+                .map(|pointer| ScopeMatcher::new(KeyScope::Pointer(pointer))),
+        );
+    }
+
+    /// Builds a converter from a spec, typically loaded from a configuration
+    /// file, after checking the options serde can't with
+    /// [`ConversionSpec::validate`].
+    pub fn from_spec(spec: ConversionSpec) -> Result<Converter<'static>, Error> {
+        spec.validate()
+            .map_err(|diagnostics| Error::InvalidConfig { diagnostics })?;
+
+        let mut converter = Converter::new(spec.case);
+        converter.manual_renames = own_renames(spec.manual_renames);
+        converter.with_custom_rename_behavior(spec.rename_behavior);
+        converter.with_chain_policy(spec.chain_policy);
+        for (case, rename_list) in spec.case_renames {
+            converter.set_case_renames(case, own_renames(rename_list));
+        }
+        converter.with_trim_keys(spec.trim_keys);
+        converter.with_empty_key_policy(spec.empty_key_policy);
+        converter.prototype_pollution_guard = spec.prototype_pollution_guard;
+        converter.with_collision_strategy(spec.collision_strategy);
+        converter.with_key_order(spec.key_order);
+        converter.identifier_style = spec.identifier_style;
+        converter.with_strip_prefixes(spec.strip_prefixes);
+        converter.with_strip_prefix_boundary(spec.strip_prefix_boundary);
+        if let Some(MaxKeyLengthSpec { max, policy }) = spec.max_key_length {
+            converter.with_max_key_length(max, policy);
+        }
+        if let Some(AffixSpec { text, scope }) = spec.key_prefix {
+            converter.with_key_prefix(text, scope);
+        }
+        if let Some(AffixSpec { text, scope }) = spec.key_suffix {
+            converter.with_key_suffix(text, scope);
+        }
+        converter.with_dictionary_keys(spec.dictionary_keys);
+        converter.dictionary_detection = spec.dictionary_detection;
+        converter.with_pair_array_keys(spec.pair_array_keys);
+        converter.with_embedded_json_fields(spec.embedded_json_fields);
+        converter.with_skip_prefixes(spec.skip_prefixes);
+        converter.with_excluded_keys(spec.excluded_keys);
+        converter.with_excluded_pointers(spec.excluded_pointers);
+        converter.max_depth = spec.max_depth;
+        for CaseRuleSpec { path, case } in &spec.case_rules {
+            converter.with_case_rule(path, *case)?;
+        }
+        converter.depth_cases = spec.depth_cases;
+        converter.path_rules = spec
+            .path_rules
+            .iter()
+            .map(|PathRuleSpec { path, case }| {
+                let rule = PathRule::new(path)?;
+                Ok(match case {
+                    Some(case) => rule.with_case(*case),
+                    None => rule,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        converter.allowed_keys = spec.allowed_keys;
+        converter.schema_guide = spec.schema_guide;
+        converter.with_undeclared_keys(spec.undeclared_keys);
+        converter.with_compatibility(spec.compatibility);
+        #[cfg(feature = "jsonpath")]
+        {
+            for expr in &spec.include_jsonpaths {
+                converter.with_include_jsonpath(expr)?;
+            }
+            for expr in &spec.exclude_jsonpaths {
+                converter.with_exclude_jsonpath(expr)?;
+            }
+        }
+        #[cfg(feature = "normalization")]
+        {
+            converter.unicode_normalization = spec.unicode_normalization;
+        }
+        for VariantRenamesSpec {
+            discriminator_key,
+            variant,
+            manual_renames,
+        } in spec.variant_renames
+        {
+            converter.set_variant_renames(
+                &discriminator_key,
+                &variant,
+                own_renames(manual_renames),
+            );
+        }
+        Ok(converter)
+    }
+
+    /// Exports the options of the converter, so that they can be saved and
+    /// loaded back with [`Converter::from_spec`].
+    pub fn to_spec(&self) -> ConversionSpec {
+        let affix = |affix: &Option<(String, ScopeMatcher)>| {
+            affix.as_ref().map(|(text, scope)| AffixSpec {
+                text: text.clone(),
+                scope: scope.scope().clone(),
+            })
+        };
+
+        let mut spec = ConversionSpec::new(self.case);
+        spec.manual_renames = export_renames(&self.manual_renames);
+        spec.rename_behavior = self.rename_behavior;
+        spec.chain_policy = self.chain_policy;
+        spec.case_renames = self
+            .case_renames
+            .iter()
+            .map(|(case, rename_list)| (*case, export_renames(rename_list)))
+            .collect();
+        spec.trim_keys = self.trim_keys;
+        spec.empty_key_policy = self.empty_key_policy;
+        spec.prototype_pollution_guard = self.prototype_pollution_guard;
+        spec.collision_strategy = self.collision_strategy;
+        spec.key_order = self.key_order;
+        spec.identifier_style = self.identifier_style;
+        spec.strip_prefixes = self.strip_prefixes.clone();
+        spec.strip_prefix_boundary = self.strip_prefix_boundary;
+        spec.max_key_length = self
+            .max_key_length
+            .map(|(max, policy)| MaxKeyLengthSpec { max, policy });
+        spec.key_prefix = affix(&self.key_prefix);
+        spec.key_suffix = affix(&self.key_suffix);
+        spec.dictionary_keys = self.dictionary_keys.clone();
+        spec.dictionary_detection = self.dictionary_detection.clone();
+        spec.pair_array_keys = self.pair_array_keys.clone();
+        spec.embedded_json_fields = self.embedded_json_fields.clone();
+        spec.skip_prefixes = self.skip_prefixes.clone();
+        spec.excluded_pointers = self
+            .excluded_pointers
+            .iter()
+            .filter_map(|matcher| match matcher.scope() {
+                KeyScope::Pointer(pointer) => Some(pointer.clone()),
+                _ => None,
+            })
+            .collect();
+        spec.excluded_keys = self.excluded_keys.clone();
+        spec.path_rules = self
+            .path_rules
+            .iter()
+            .map(|rule| PathRuleSpec {
+                path: rule.pattern().to_owned(),
+                case: rule.case(),
+            })
+            .collect();
+        spec.max_depth = self.max_depth;
+        spec.case_rules = self
+            .case_rules
+            .iter()
+            .filter_map(|rule| {
+                rule.case().map(|case| CaseRuleSpec {
+                    path: rule.pattern().to_owned(),
+                    case,
+                })
+            })
+            .collect();
+        spec.depth_cases = self.depth_cases.clone();
+        spec.allowed_keys = self.allowed_keys.clone();
+        spec.schema_guide = self.schema_guide.clone();
+        spec.undeclared_keys = self.undeclared_keys;
+        spec.compatibility = self.compatibility;
+        #[cfg(feature = "jsonpath")]
+        {
+            let exprs =
+                |paths: &[(String, _)]| paths.iter().map(|(expr, _)| expr.clone()).collect();
+            spec.include_jsonpaths = exprs(&self.jsonpaths.include);
+            spec.exclude_jsonpaths = exprs(&self.jsonpaths.exclude);
+        }
+        #[cfg(feature = "normalization")]
+        {
+            spec.unicode_normalization = self.unicode_normalization;
+        }
+        spec.variant_renames = self
+            .variant_renames
+            .iter()
+            .map(
+                |(discriminator_key, variant, rename_list)| VariantRenamesSpec {
+                    discriminator_key: discriminator_key.clone(),
+                    variant: variant.clone(),
+                    manual_renames: export_renames(rename_list),
+                },
+            )
+            .collect();
+        spec
+    }
+
+    /// Converts the keys of `value`, returning an error when a key is rejected
+    /// by the configured options.
+    ///
+    /// Objects are rebuilt in input order, so they keep it with the
+    /// `preserve_order` feature and come out sorted by new key without it,
+    /// unless [`Converter::with_key_order`] asks for another order.
+    ///
+    /// With the `metrics` feature, each call is recorded as described in
+    /// [`Converter::with_metrics_label`].
+    pub fn convert_value(&self, value: Value) -> Result<Value, Error> {
+        self.convert_root(value, None)
+    }
+
+    /// Converts the keys of `map`, as [`Converter::convert_value`] converts
+    /// an object, for callers already holding a [`JsonMap`].
+    pub fn convert_object(&self, map: JsonMap) -> Result<JsonMap, Error> {
+        match self.convert_root(Value::Object(map), None)? {
+            Value::Object(map) => Ok(map),
+            _ => unreachable!("objects are converted to objects"),
+        }
+    }
+
+    /// Like [`Converter::convert_value`], also returning the entries that
+    /// were left out of the output.
+    pub(crate) fn convert_value_with_drops(
+        &self,
+        value: Value,
+    ) -> Result<(Value, Vec<DroppedKey>), Error> {
+        self.convert_root_with_drops(value, None)
+    }
+
+    /// Like [`Converter::convert_value`], looking up and storing new keys in
+    /// `cache` when they don't depend on where they are.
+    pub(crate) fn convert_root(
+        &self,
+        value: Value,
+        cache: Option<&KeyCache>,
+    ) -> Result<Value, Error> {
+        #[cfg(feature = "jsonpath")]
+        if let Some(selected) = self.select_jsonpaths(&value) {
+            return selected.convert_root(value, cache);
+        }
+        if cfg!(feature = "metrics") || self.collision_strategy == CollisionStrategy::Error {
+            return self
+                .convert_root_with_drops(value, cache)
+                .map(|(converted, _)| converted);
+        }
+        self.internal_convert(value, &Path::Root, None, cache, &mut None)
+    }
+
+    fn convert_root_with_drops(
+        &self,
+        value: Value,
+        cache: Option<&KeyCache>,
+    ) -> Result<(Value, Vec<DroppedKey>), Error> {
+        let (converted, log) = self.convert_root_with_log(value, cache, DropLog::default())?;
+        Ok((converted, log.dropped))
+    }
+
+    /// Converts the keys of `value`, also reporting which keys were renamed
+    /// and where they ended up, and which entries were left out. See
+    /// [`RenameReport::original_path`] to point errors about the converted
+    /// document back to the input.
+    ///
+    /// Keys of pair arrays and of embedded JSON documents are not reported.
+    pub fn convert_with_report(&self, value: Value) -> Result<(Value, RenameReport), Error> {
+        let log = DropLog {
+            renamed: Some(Vec::new()),
+            ..DropLog::default()
+        };
+        let (converted, log) = self.convert_root_with_log(value, None, log)?;
+        let report = RenameReport {
+            renamed: log.renamed.unwrap_or_default(),
+            dropped: log.dropped,
+        };
+        Ok((converted, report))
+    }
+
+    fn convert_root_with_log(
+        &self,
+        value: Value,
+        cache: Option<&KeyCache>,
+        log: DropLog,
+    ) -> Result<(Value, DropLog), Error> {
+        #[cfg(feature = "jsonpath")]
+        if let Some(selected) = self.select_jsonpaths(&value) {
+            return selected.convert_root_with_log(value, cache, log);
+        }
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+        let mut log = Some(log);
+        let converted = self.internal_convert(value, &Path::Root, None, cache, &mut log)?;
+        let log = log.unwrap_or_default();
+        if !log.collisions.is_empty() {
+            return Err(Error::KeyCollision {
+                collisions: log.collisions,
+            });
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::record(
+            self.metrics_label.as_deref(),
+            &log.counts,
+            &log.dropped,
+            started.elapsed(),
+        );
+        Ok((converted, log))
+    }
+
+    /// A copy of the converter with the JSONPath selectors evaluated against
+    /// `value`, when there are selectors and they weren't evaluated yet.
+    #[cfg(feature = "jsonpath")]
+    pub(crate) fn select_jsonpaths(&self, value: &Value) -> Option<Converter<'a>> {
+        if self.jsonpaths.is_empty() || self.jsonpath_selection.is_some() {
+            return None;
+        }
+        let mut selected = self.clone();
+        selected.jsonpath_selection = Some(self.jsonpaths.select(value));
+        Some(selected)
+    }
+
+    /// Decides the new name of the key at `path`, for traversals that don't
+    /// go through a [`Value`].
+    pub(crate) fn rename_key(&self, key: &str, path: &Path) -> Result<Option<String>, Error> {
+        self.convert_key(key, self.case, &self.manual_renames, None, path, &mut ())
+    }
+
+    /// Like [`Converter::rename_key`], for a key of an object inside `variant`.
+    pub(crate) fn rename_variant_key(
+        &self,
+        key: &str,
+        variant: Option<&Renames>,
+        path: &Path,
+    ) -> Result<Option<String>, Error> {
+        self.convert_key(key, self.case, &self.manual_renames, variant, path, &mut ())
+    }
+
+    /// Like [`Converter::rename_key`], noting the rules that have a say in
+    /// `trace`.
+    pub(crate) fn trace_key(
+        &self,
+        key: &str,
+        path: &Path,
+        trace: &mut Vec<KeyRule>,
+    ) -> Result<Option<String>, Error> {
+        self.convert_key(key, self.case, &self.manual_renames, None, path, trace)
+    }
+
+    /// The variant renames in effect inside `object`, when it holds one of the
+    /// variants set with [`Converter::with_variant_renames`].
+    pub(crate) fn object_variant(&self, object: &JsonMap) -> Option<&Renames<'a>> {
+        self.variant_renames
+            .iter()
+            .find(|(discriminator_key, variant, _)| {
+                object.get(discriminator_key).and_then(Value::as_str) == Some(variant.as_str())
+            })
+            .map(|(_, _, renames)| renames)
+    }
+
+    /// Converts the keys of `value` as if it were found at `path`.
+    pub(crate) fn convert_value_at(&self, value: Value, path: &Path) -> Result<Value, Error> {
+        self.internal_convert(value, path, None, None, &mut None)
+    }
+
+    /// Converts the rows of the map-encoded array at `path`.
+    pub(crate) fn convert_pair_rows_at(
+        &self,
+        rows: Vec<Value>,
+        path: &Path,
+        variant: Option<&Renames>,
+    ) -> Result<Vec<Value>, Error> {
+        self.convert_pair_rows(rows, path, variant, None, &mut None)
+    }
+
+    pub(crate) fn convert_multi_value(
+        &self,
+        value: &Value,
+        cases: &[KeyCase],
+    ) -> Result<HashMap<KeyCase, Value>, Error> {
+        #[cfg(feature = "jsonpath")]
+        if let Some(selected) = self.select_jsonpaths(value) {
+            return selected.convert_multi_value(value, cases);
+        }
+        let targets: Vec<(KeyCase, &Renames)> = cases
+            .iter()
+            .map(|&case| {
+                let renames = self
+                    .case_renames
+                    .iter()
+                    .find(|(c, _)| *c == case)
+                    .map_or(&self.manual_renames, |(_, renames)| renames);
+                (case, renames)
+            })
+            .collect();
+
+        let json_out =
+            self.internal_convert_multi(value, &targets, &Path::Root, None, &mut HashMap::new())?;
+
+        Ok(cases.iter().copied().zip(json_out).collect())
+    }
+
+    fn internal_convert(
+        &self,
+        actual_json: Value,
+        path: &Path,
+        variant: Option<&Renames>,
+        cache: Option<&KeyCache>,
+        log: &mut Option<DropLog>,
+    ) -> Result<Value, Error> {
+        match actual_json {
+            Value::Array(arr) => arr
+                .into_iter()
+                .enumerate()
+                .map(|(index, deep_value)| {
+                    DropLog::within(log, &index.to_string(), |log| {
+                        self.internal_convert(deep_value, &path.index(index), variant, cache, log)
+                    })
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Value::Object(actual_json) => self
+                .internal_convert_map(actual_json, path, variant, cache, log)
+                .map(Value::Object),
+            value => Ok(value),
+        }
+    }
+
+    fn internal_convert_map(
+        &self,
+        actual_json: JsonMap,
+        path: &Path,
+        variant: Option<&Renames>,
+        cache: Option<&KeyCache>,
+        log: &mut Option<DropLog>,
+    ) -> Result<JsonMap, Error> {
+        let variant = self.object_variant(&actual_json).or(variant);
+        let detected = self.is_detected_dictionary(&actual_json);
+        let actual_json = self.order_input(actual_json);
+        let mut new_json = JsonMap::new();
+        let mut builder = ObjectBuilder::new(self.collision_strategy, self.case, log.is_some());
+        for (key, value) in actual_json {
+            let key_path = path.key(&key);
+            let new_key = if detected {
+                self.guard_key(&key, Some(key.clone()), &key_path)?
+            } else {
+                self.cached_convert_key(&key, variant, cache, &key_path)?
+            };
+            let new_key = match new_key {
+                Some(new_key) => new_key,
+                None => {
+                    if let Some(log) = log {
+                        log.drop(&key, &key_path, self.drop_reason(&key));
+                    }
+                    continue;
+                }
+            };
+            #[cfg(feature = "metrics")]
+            if let Some(log) = log {
+                log.counts.count(&key, &new_key);
+            }
+            let new_value = match self.redaction(&key) {
+                Some(rule) => {
+                    #[cfg(feature = "metrics")]
+                    if let Some(log) = log {
+                        log.counts.count_redaction();
+                    }
+                    rule.redact(&value)
+                }
+                None if log.is_none() && self.keeps_whole(&key_path) => value,
+                None => {
+                    let new_value = DropLog::within(log, &new_key, |log| match value {
+                        Value::Array(rows) if self.is_pair_array_key(&key) => self
+                            .convert_pair_rows(rows, &key_path, variant, cache, log)
+                            .map(Value::Array),
+                        Value::String(text) if self.is_embedded_json_field(&key) => {
+                            self.convert_embedded_json(text, &key_path)
+                        }
+                        value => self.internal_convert(value, &key_path, variant, cache, log),
+                    })?;
+                    self.transform_value(&key, &key_path, new_value)?
+                }
+            };
+
+            builder.insert(&mut new_json, key, new_key, new_value, path, log);
+        }
+
+        builder.finish(log)?;
+        Ok(self.order_output(new_json))
+    }
+
+    /// Converts the rows of a map encoded as an array, found at `path`. Row
+    /// keys are matched against the options as if the array were an object.
+    fn convert_pair_rows(
+        &self,
+        rows: Vec<Value>,
+        path: &Path,
+        variant: Option<&Renames>,
+        cache: Option<&KeyCache>,
+        log: &mut Option<DropLog>,
+    ) -> Result<Vec<Value>, Error> {
+        /// Where a row keeps its value, to put the converted one back.
+        enum Row {
+            Pair,
+            Entry(JsonMap),
+        }
+
+        let mut new_rows = Vec::with_capacity(rows.len());
+        for (index, row) in rows.into_iter().enumerate() {
+            let (key, value, row) = match row {
+                Value::Array(mut pair) if pair.len() == 2 && pair[0].is_string() => {
+                    let value = pair[1].take();
+                    (pair[0].take(), value, Row::Pair)
+                }
+                Value::Object(mut entry)
+                    if entry.len() == 2
+                        && entry.get("key").is_some_and(Value::is_string)
+                        && entry.contains_key("value") =>
+                {
+                    let key = entry["key"].take();
+                    (key, entry["value"].take(), Row::Entry(entry))
+                }
+                row => {
+                    new_rows.push(row);
+                    continue;
+                }
+            };
+            let key = key.as_str().unwrap_or_default();
+
+            let key_path = path.key(key);
+            let new_key = match self.cached_convert_key(key, variant, cache, &key_path)? {
+                Some(new_key) => new_key,
+                None => {
+                    if let Some(log) = log {
+                        log.drop(key, &path.index(index), self.drop_reason(key));
+                    }
+                    continue;
+                }
+            };
+            #[cfg(feature = "metrics")]
+            if let Some(log) = log {
+                log.counts.count(key, &new_key);
+            }
+            let token = match row {
+                Row::Pair => "1",
+                Row::Entry(_) => "value",
+            };
+            let new_index = new_rows.len().to_string();
+            let value = match self.redaction(key) {
+                Some(rule) => {
+                    #[cfg(feature = "metrics")]
+                    if let Some(log) = log {
+                        log.counts.count_redaction();
+                    }
+                    rule.redact(&value)
+                }
+                None => {
+                    let value = DropLog::within(log, &new_index, |log| {
+                        DropLog::within(log, token, |log| {
+                            self.internal_convert(value, &key_path, variant, cache, log)
+                        })
+                    })?;
+                    self.transform_value(key, &key_path, value)?
+                }
+            };
+
+            new_rows.push(match row {
+                Row::Pair => Value::Array(vec![Value::String(new_key), value]),
+                Row::Entry(mut entry) => {
+                    entry.insert("key".to_owned(), Value::String(new_key));
+                    entry.insert("value".to_owned(), value);
+                    Value::Object(entry)
+                }
+            });
+        }
+        Ok(new_rows)
+    }
+
+    /// Converts `actual_json` once per target, walking it only once. It goes
+    /// through the same steps as [`Converter::internal_convert`], so that
+    /// each output is what converting to its case alone would give.
+    fn internal_convert_multi<'v>(
+        &self,
+        actual_json: &'v Value,
+        targets: &[(KeyCase, &Renames)],
+        path: &Path,
+        variant: Option<&Renames>,
+        key_cache: &mut HashMap<&'v str, Vec<Option<String>>>,
+    ) -> Result<Vec<Value>, Error> {
+        match actual_json {
+            Value::Array(arr) => {
+                let mut deep_arrs = vec![Vec::with_capacity(arr.len()); targets.len()];
+                for (index, deep_value) in arr.iter().enumerate() {
+                    let new_values = self.internal_convert_multi(
+                        deep_value,
+                        targets,
+                        &path.index(index),
+                        variant,
+                        key_cache,
+                    )?;
+                    for (deep_arr, new_value) in deep_arrs.iter_mut().zip(new_values) {
+                        deep_arr.push(new_value);
+                    }
+                }
+
+                Ok(deep_arrs.into_iter().map(Value::Array).collect())
+            }
+            Value::Object(actual_json) => Ok(self
+                .internal_convert_multi_map(actual_json, targets, path, variant, key_cache)?
+                .into_iter()
+                .map(Value::Object)
+                .collect()),
+            value => Ok(vec![value.clone(); targets.len()]),
+        }
+    }
+
+    fn internal_convert_multi_map<'v>(
+        &self,
+        actual_json: &'v JsonMap,
+        targets: &[(KeyCase, &Renames)],
+        path: &Path,
+        variant: Option<&Renames>,
+        key_cache: &mut HashMap<&'v str, Vec<Option<String>>>,
+    ) -> Result<Vec<JsonMap>, Error> {
+        let variant = self.object_variant(actual_json).or(variant);
+        let detected = self.is_detected_dictionary(actual_json);
+        let mut entries: Vec<_> = actual_json.iter().collect();
+        if self.key_order == KeyOrder::SortByOriginalKey {
+            entries.sort_by_key(|(key, _)| *key);
+        }
+        let mut new_jsons = vec![JsonMap::new(); targets.len()];
+        let mut builders: Vec<_> = targets
+            .iter()
+            .map(|(case, _)| ObjectBuilder::new(self.collision_strategy, *case, false))
+            .collect();
+        for (key, value) in entries {
+            let key_path = path.key(key);
+            let new_values = match self.redaction(key) {
+                Some(rule) => vec![rule.redact(value); targets.len()],
+                None => {
+                    let new_values = match value {
+                        Value::Array(rows) if self.is_pair_array_key(key) => self
+                            .convert_multi_pair_rows(rows, targets, &key_path, variant, key_cache)?
+                            .into_iter()
+                            .map(Value::Array)
+                            .collect(),
+                        Value::String(text) if self.is_embedded_json_field(key) => {
+                            self.convert_multi_embedded_json(text, targets, &key_path)?
+                        }
+                        value => self.internal_convert_multi(
+                            value, targets, &key_path, variant, key_cache,
+                        )?,
+                    };
+                    new_values
+                        .into_iter()
+                        .map(|new_value| self.transform_value(key, &key_path, new_value))
+                        .collect::<Result<_, _>>()?
+                }
+            };
+            let uncached_keys;
+            let new_keys = if detected {
+                let new_key = self.guard_key(key, Some(key.clone()), &key_path)?;
+                uncached_keys = vec![new_key; targets.len()];
+                &uncached_keys
+            } else if variant.is_some() || self.keys_depend_on_path() {
+                uncached_keys = self.convert_multi_key(key, targets, variant, &key_path)?;
+                &uncached_keys
+            } else {
+                if !key_cache.contains_key(key.as_str()) {
+                    let new_keys = self.convert_multi_key(key, targets, None, &key_path)?;
+                    key_cache.insert(key, new_keys);
+                }
+                &key_cache[key.as_str()]
+            };
+
+            for (((new_json, builder), new_key), new_value) in new_jsons
+                .iter_mut()
+                .zip(&mut builders)
+                .zip(new_keys)
+                .zip(new_values)
+            {
+                if let Some(new_key) = new_key {
+                    let (key, new_key) = (key.clone(), new_key.clone());
+                    builder.insert(new_json, key, new_key, new_value, path, &mut None);
+                }
+            }
+        }
+        for builder in builders {
+            builder.finish(&mut None)?;
+        }
+
+        Ok(new_jsons
+            .into_iter()
+            .map(|new_json| self.order_output(new_json))
+            .collect())
+    }
+
+    /// The new name of `key` for each target.
+    fn convert_multi_key(
+        &self,
+        key: &str,
+        targets: &[(KeyCase, &Renames)],
+        variant: Option<&Renames>,
+        path: &Path,
+    ) -> Result<Vec<Option<String>>, Error> {
+        targets
+            .iter()
+            .map(|(case, manual_renames)| {
+                self.convert_key(key, *case, manual_renames, variant, path, &mut ())
+            })
+            .collect()
+    }
+
+    /// Like [`Converter::convert_pair_rows`], once per target.
+    fn convert_multi_pair_rows<'v>(
+        &self,
+        rows: &'v [Value],
+        targets: &[(KeyCase, &Renames)],
+        path: &Path,
+        variant: Option<&Renames>,
+        key_cache: &mut HashMap<&'v str, Vec<Option<String>>>,
+    ) -> Result<Vec<Vec<Value>>, Error> {
+        let mut new_rows = vec![Vec::with_capacity(rows.len()); targets.len()];
+        for row in rows {
+            let (key, value, entry) = match row {
+                Value::Array(pair) if pair.len() == 2 && pair[0].is_string() => {
+                    (&pair[0], &pair[1], None)
+                }
+                Value::Object(entry)
+                    if entry.len() == 2
+                        && entry.get("key").is_some_and(Value::is_string)
+                        && entry.contains_key("value") =>
+                {
+                    (&entry["key"], &entry["value"], Some(entry))
+                }
+                row => {
+                    for new_rows in &mut new_rows {
+                        new_rows.push(row.clone());
+                    }
+                    continue;
+                }
+            };
+            let key = key.as_str().unwrap_or_default();
+
+            let key_path = path.key(key);
+            let new_keys = self.convert_multi_key(key, targets, variant, &key_path)?;
+            let new_values: Vec<Value> = match self.redaction(key) {
+                Some(rule) => vec![rule.redact(value); targets.len()],
+                None => self
+                    .internal_convert_multi(value, targets, &key_path, variant, key_cache)?
+                    .into_iter()
+                    .map(|new_value| self.transform_value(key, &key_path, new_value))
+                    .collect::<Result<_, _>>()?,
+            };
+
+            for ((new_rows, new_key), new_value) in
+                new_rows.iter_mut().zip(new_keys).zip(new_values)
+            {
+                let new_key = match new_key {
+                    Some(new_key) => Value::String(new_key),
+                    None => continue,
+                };
+                new_rows.push(match entry {
+                    None => Value::Array(vec![new_key, new_value]),
+                    Some(entry) => {
+                        // Rebuilt in the order of the original entry.
+                        let mut new_entry: JsonMap =
+                            entry.keys().map(|k| (k.clone(), Value::Null)).collect();
+                        new_entry["key"] = new_key;
+                        new_entry["value"] = new_value;
+                        Value::Object(new_entry)
+                    }
+                });
+            }
+        }
+        Ok(new_rows)
+    }
+
+    /// Like [`Converter::convert_embedded_json`], once per target.
+    fn convert_multi_embedded_json(
+        &self,
+        text: &str,
+        targets: &[(KeyCase, &Renames)],
+        path: &Path,
+    ) -> Result<Vec<Value>, Error> {
+        match serde_json::from_str(text) {
+            Ok(document @ Value::Object(_)) | Ok(document @ Value::Array(_)) => Ok(self
+                .internal_convert_multi(&document, targets, path, None, &mut HashMap::new())?
+                .into_iter()
+                .map(|converted| Value::String(converted.to_string()))
+                .collect()),
+            _ => Ok(vec![Value::String(text.to_owned()); targets.len()]),
+        }
+    }
+
+    /// Decides the new name of `key`, or `None` when its entry must be dropped.
+    /// The `variant` renames, if any, take precedence over `manual_renames`.
+    /// Every rule that has a say is noted in `trace`.
+    fn convert_key(
+        &self,
+        original_key: &str,
+        case: KeyCase,
+        manual_renames: &Renames,
+        variant: Option<&Renames>,
+        path: &Path,
+        trace: &mut impl Trace,
+    ) -> Result<Option<String>, Error> {
+        let new_key = self.decide_key(original_key, case, manual_renames, variant, path, trace)?;
+        let new_key = self.guard_key(original_key, new_key, path)?;
+        if let (true, Some(new_key)) = (self.strict_validation, &new_key) {
+            if let Some(violation) = self.key_violations(original_key, new_key, path).next() {
+                return Err(Error::InvalidKey {
+                    path: violation.path,
+                    key: violation.key,
+                    message: violation.message,
+                });
+            }
+        }
+        Ok(new_key)
+    }
+
+    /// Applies the prototype pollution guard to `new_key`, the new name of
+    /// `original_key` at `path`.
+    pub(crate) fn guard_key(
+        &self,
+        original_key: &str,
+        new_key: Option<String>,
+        path: &Path,
+    ) -> Result<Option<String>, Error> {
+        let policy = match (self.prototype_pollution_guard, &new_key) {
+            (Some(policy), Some(_)) => policy,
+            _ => return Ok(new_key),
+        };
+        let dangerous = if is_prototype_key(original_key) {
+            Some(original_key.to_owned())
+        } else {
+            new_key.clone().filter(|new_key| is_prototype_key(new_key))
+        };
+        match (dangerous, policy) {
+            (None, _) => Ok(new_key),
+            (Some(key), GuardPolicy::Reject) => Err(Error::PrototypePollution {
+                path: path.to_pointer(),
+                key,
+            }),
+            (Some(_), GuardPolicy::Drop) => Ok(None),
+            (Some(_), GuardPolicy::Prefix) => Ok(new_key.map(|mut new_key| {
+                if is_prototype_key(&new_key) {
+                    new_key.push('_');
+                }
+                new_key
+            })),
+        }
+    }
+
+    /// Why the entry with the original key `key` was left out of the output:
+    /// the key is empty, or the prototype pollution guard dropped it.
+    fn drop_reason(&self, key: &str) -> DropReason {
+        let normalized = self.normalize(key);
+        if self.trim_keys && normalized.trim().is_empty() || normalized.is_empty() {
+            DropReason::EmptyKey
+        } else {
+            DropReason::PrototypePollution
+        }
+    }
+
+    /// The keys the validators reject among `new_key`, the new name of
+    /// `original_key` at `path`.
+    pub(crate) fn key_violations<'s>(
+        &'s self,
+        original_key: &'s str,
+        new_key: &'s str,
+        path: &Path,
+    ) -> impl Iterator<Item = KeyViolation> + 's {
+        let pointer = if self.validators.is_empty() {
+            String::new()
+        } else {
+            path.to_pointer()
+        };
+        self.validators.iter().filter_map(move |validator| {
+            let context = KeyContext {
+                original_key,
+                path: &pointer,
+            };
+            validator
+                .validate(new_key, &context)
+                .err()
+                .map(|message| KeyViolation {
+                    path: pointer.clone(),
+                    key: new_key.to_owned(),
+                    message,
+                })
+        })
+    }
+
+    /// The steps of [`Converter::convert_key`] before validation.
+    fn decide_key(
+        &self,
+        original_key: &str,
+        case: KeyCase,
+        manual_renames: &Renames,
+        variant: Option<&Renames>,
+        path: &Path,
+        trace: &mut impl Trace,
+    ) -> Result<Option<String>, Error> {
+        let normalized = self.normalize(original_key);
+        let key = match self.key_step(&normalized, path) {
+            KeyStep::Keep(kept) => {
+                trace.note(|| match kept {
+                    Kept::Dictionary => KeyRule::Dictionary,
+                    Kept::SkipPrefix(prefix) => KeyRule::SkipPrefix(prefix.to_owned()),
+                    Kept::ExcludedKey => KeyRule::ExcludedKey,
+                    Kept::Excluded(matcher) => match matcher.scope() {
+                        KeyScope::Pointer(pointer) => KeyRule::ExcludedPointer(pointer.clone()),
+                        _ => KeyRule::ExcludedPointer(String::new()),
+                    },
+                    Kept::MapKey => KeyRule::SchemaMapKey,
+                    Kept::Undeclared => KeyRule::SchemaUndeclared,
+                    #[cfg(feature = "jsonpath")]
+                    Kept::ExcludedJsonPath => KeyRule::ExcludedJsonPath,
+                    #[cfg(feature = "jsonpath")]
+                    Kept::NotIncludedJsonPath => KeyRule::NotIncludedJsonPath,
+                    Kept::OutsidePathRules => KeyRule::OutsidePathRules,
+                    Kept::BelowMaxDepth => KeyRule::BelowMaxDepth,
+                });
+                return Ok(Some(original_key.to_owned()));
+            }
+            KeyStep::Empty => {
+                trace.note(|| KeyRule::EmptyKey(self.empty_key_policy));
+                return match self.empty_key_policy {
+                    EmptyKeyPolicy::Preserve => Ok(Some(original_key.to_owned())),
+                    EmptyKeyPolicy::Drop => Ok(None),
+                    EmptyKeyPolicy::Error => Err(Error::EmptyKey {
+                        path: path.to_pointer(),
+                    }),
+                };
+            }
+            KeyStep::Rename(key) => key,
+        };
+        let case = self.case_override(path).unwrap_or(case);
+        let trimmed = || {
+            if self.trim_keys {
+                normalized.trim()
+            } else {
+                &normalized
+            }
+        };
+        trace.note_if(|| trimmed() != normalized, || KeyRule::Trimmed);
+        trace.note_if(
+            || trimmed() != key,
+            || {
+                let trimmed = trimmed();
+                KeyRule::StrippedPrefix(trimmed[..trimmed.len() - key.len()].to_owned())
+            },
+        );
+
+        let manual_case = match variant {
+            Some(renames) => self.manual_rename(key, renames, path)?,
+            None => None,
+        };
+        let manual_case = match manual_case {
+            Some(k) => Some(k),
+            None => self.manual_rename(key, manual_renames, path)?,
+        };
+        let mut new_key = match manual_case {
+            Some(k) => {
+                trace.note(|| KeyRule::ManualRename {
+                    from: key.to_owned(),
+                    to: k.to_owned(),
+                });
+                k.to_owned()
+            }
+            None if !self.is_allowed(trimmed()) => {
+                trace.note(|| KeyRule::NotAllowed);
+                return Ok(Some(original_key.to_owned()));
+            }
+            #[cfg(feature = "regex")]
+            None => {
+                let key = regex_rename(key, &self.regex_renames, trace);
+                trace.note(|| KeyRule::Case(case));
+                let new_key = self.to_case(&key, case);
+                regex_rename(&new_key, &self.regex_renames_after_case, trace).into_owned()
+            }
+            #[cfg(not(feature = "regex"))]
+            None => {
+                trace.note(|| KeyRule::Case(case));
+                self.to_case(key, case)
+            }
+        };
+
+        if let Some((prefix, scope)) = &self.key_prefix {
+            if scope.matches(path) {
+                trace.note(|| KeyRule::KeyPrefix(prefix.clone()));
+                new_key.insert_str(0, prefix);
+            }
+        }
+        if let Some((suffix, scope)) = &self.key_suffix {
+            if scope.matches(path) {
+                trace.note(|| KeyRule::KeySuffix(suffix.clone()));
+                new_key.push_str(suffix);
+            }
+        }
+
+        if let Some(identifier_style) = self.identifier_style {
+            let sanitized = identifier_style.sanitize(&new_key);
+            trace.note_if(
+                || sanitized != new_key,
+                || KeyRule::SafeIdentifier(identifier_style),
+            );
+            new_key = sanitized;
+        }
+
+        match self.max_key_length {
+            Some((max, policy)) => {
+                trace.note_if(
+                    || new_key.len() > max,
+                    || KeyRule::MaxKeyLength(max, policy),
+                );
+                match policy.apply(new_key, max) {
+                    Ok(new_key) => Ok(Some(new_key)),
+                    Err(key) => Err(Error::KeyTooLong {
+                        path: path.to_pointer(),
+                        key,
+                        max,
+                    }),
+                }
+            }
+            None => Ok(Some(new_key)),
+        }
+    }
+
+    /// Like [`Converter::convert_key`] with the target case and manual
+    /// renames, going through `cache` when the new key only depends on the
+    /// key itself.
+    fn cached_convert_key(
+        &self,
+        key: &str,
+        variant: Option<&Renames>,
+        cache: Option<&KeyCache>,
+        path: &Path,
+    ) -> Result<Option<String>, Error> {
+        let cache = match cache {
+            Some(cache) if variant.is_none() && !self.keys_depend_on_path() => cache,
+            _ => {
+                return self.convert_key(
+                    key,
+                    self.case,
+                    &self.manual_renames,
+                    variant,
+                    path,
+                    &mut (),
+                )
+            }
+        };
+        if let Some(new_key) = cache.get(key) {
+            return Ok(new_key);
+        }
+        let new_key =
+            self.convert_key(key, self.case, &self.manual_renames, variant, path, &mut ())?;
+        cache.insert(key, new_key.clone());
+        Ok(new_key)
+    }
+
+    /// The first steps of [`Converter::convert_key`]: whether the key is kept
+    /// as it is, is empty, or what is left of it to rename.
+    fn key_step<'s, 'k>(&'s self, original_key: &'k str, path: &Path) -> KeyStep<'s, 'k> {
+        if let Path::Key(parent, _) = path {
+            if self.is_dictionary(parent) {
+                return KeyStep::Keep(Kept::Dictionary);
+            }
+        }
+        let key = if self.trim_keys {
+            original_key.trim()
+        } else {
+            original_key
+        };
+        if let Some(prefix) = self
+            .skip_prefixes
+            .iter()
+            .find(|prefix| key.starts_with(prefix.as_str()))
+        {
+            return KeyStep::Keep(Kept::SkipPrefix(prefix));
+        }
+        if self.excluded_keys.iter().any(|excluded| excluded == key) {
+            return KeyStep::Keep(Kept::ExcludedKey);
+        }
+        if let Some(excluded) = self
+            .excluded_pointers
+            .iter()
+            .find(|excluded| excluded.matches(path))
+        {
+            return KeyStep::Keep(Kept::Excluded(excluded));
+        }
+        #[cfg(feature = "jsonpath")]
+        if let Some(selection) = &self.jsonpath_selection {
+            if selection.is_excluded(path) {
+                return KeyStep::Keep(Kept::ExcludedJsonPath);
+            }
+            if selection.is_not_included(path) {
+                return KeyStep::Keep(Kept::NotIncludedJsonPath);
+            }
+        }
+        if self.max_depth.is_some_and(|levels| path.depth() >= levels) {
+            return KeyStep::Keep(Kept::BelowMaxDepth);
+        }
+        if !self.path_rules.is_empty() && self.path_rule(path).is_none() {
+            return KeyStep::Keep(Kept::OutsidePathRules);
+        }
+        if let Some(schema) = &self.schema_guide {
+            match crate::guide::classify(schema, path) {
+                Guided::Declared => {}
+                Guided::MapKey => return KeyStep::Keep(Kept::MapKey),
+                Guided::Undeclared => {
+                    if self.undeclared_keys == UndeclaredKeys::Preserve {
+                        return KeyStep::Keep(Kept::Undeclared);
+                    }
+                }
+            }
+        }
+
+        if key.is_empty() {
+            return KeyStep::Empty;
+        }
+
+        KeyStep::Rename(self.strip_prefix(key))
+    }
+
+    /// The entry of the manual renames that the key at `path` goes through,
+    /// named by its original key. Keys renamed by `variant` go through none.
+    pub(crate) fn manual_rename_entry(
+        &self,
+        original_key: &str,
+        variant: Option<&Renames>,
+        path: &Path,
+    ) -> Option<&str> {
+        let normalized = self.normalize(original_key);
+        let key = match self.key_step(&normalized, path) {
+            KeyStep::Rename(key) => key,
+            _ => return None,
+        };
+        if variant
+            .and_then(|renames| {
+                Converter::determine_manual_case(key, renames, self.rename_behavior)
+            })
+            .is_some()
+        {
+            return None;
+        }
+        match self.rename_behavior {
+            RenameBehavior::ByKey => self
+                .manual_renames
+                .get_key_value(key)
+                .map(|(from, _)| from.as_ref()),
+            RenameBehavior::ByValue => self
+                .manual_renames
+                .iter()
+                .find(|(_, to)| *to == key)
+                .map(|(from, _)| from.as_ref()),
+        }
+    }
+
+    /// Whether the value of the key at `path` can be kept whole, every key
+    /// inside it being below the maximum depth and no option looking at its
+    /// entries.
+    fn keeps_whole(&self, path: &Path) -> bool {
+        self.max_depth
+            .is_some_and(|levels| path.depth() + 1 >= levels)
+            && self.redactions.is_empty()
+            && self.value_transforms.is_empty()
+            && self.validators.is_empty()
+            && self.prototype_pollution_guard.is_none()
+            && self.key_order == KeyOrder::PreserveInput
+    }
+
+    /// The case the key at `path` is converted to instead of the target
+    /// case, if any: that of the first case rule matching it, of its depth,
+    /// or of the innermost path rule enclosing it.
+    fn case_override(&self, path: &Path) -> Option<KeyCase> {
+        if let Some(rule) = self.case_rules.iter().find(|rule| rule.selects(path)) {
+            return rule.case();
+        }
+        if let Some(case) = self.depth_cases.get(&path.depth()) {
+            return Some(*case);
+        }
+        self.path_rule(path).and_then(PathRule::case)
+    }
+
+    /// The innermost path rule enclosing the key at `path`.
+    fn path_rule(&self, path: &Path) -> Option<&PathRule> {
+        let mut enclosing = path.parent();
+        while let Some(parent) = enclosing {
+            if let Some(rule) = self.path_rules.iter().find(|rule| rule.selects(parent)) {
+                return Some(rule);
+            }
+            enclosing = parent.parent();
+        }
+        None
+    }
+
+    /// Sorts the entries of an object by original key, before they are
+    /// converted, when the key order asks for it.
+    pub(crate) fn order_input(&self, map: JsonMap) -> JsonMap {
+        match self.key_order {
+            KeyOrder::SortByOriginalKey => sorted_by_key(map),
+            _ => map,
+        }
+    }
+
+    /// Sorts the entries of a converted object by new key when the key
+    /// order asks for it.
+    pub(crate) fn order_output(&self, map: JsonMap) -> JsonMap {
+        match self.key_order {
+            KeyOrder::SortAlphabetically => sorted_by_key(map),
+            _ => map,
+        }
+    }
+
+    pub(crate) fn collision_strategy(&self) -> CollisionStrategy {
+        self.collision_strategy
+    }
+
+    pub(crate) fn excluded_pointers(&self) -> &[ScopeMatcher] {
+        &self.excluded_pointers
+    }
+
+    /// The redaction rule for the original key `key`, if one matches it.
+    pub(crate) fn redaction(&self, key: &str) -> Option<&RedactionRule> {
+        self.redactions.iter().find(|rule| rule.matches(key))
+    }
+
+    /// Whether a value transform matches the entry with the original key
+    /// `key`, at `path`.
+    pub(crate) fn is_transformed(&self, key: &str, path: &Path) -> bool {
+        self.value_transforms
+            .iter()
+            .any(|transform| transform.matches(key, path))
+    }
+
+    /// Runs the value transforms matching the entry with the original key
+    /// `key`, at `path`, on its converted value.
+    pub(crate) fn transform_value(
+        &self,
+        key: &str,
+        path: &Path,
+        value: Value,
+    ) -> Result<Value, Error> {
+        self.value_transforms
+            .iter()
+            .filter(|transform| transform.matches(key, path))
+            .try_fold(value, |value, transform| transform.apply(value, path))
+    }
+
+    pub(crate) fn is_pair_array_key(&self, key: &str) -> bool {
+        self.pair_array_keys.iter().any(|k| k == key)
+    }
+
+    pub(crate) fn is_embedded_json_field(&self, key: &str) -> bool {
+        self.embedded_json_fields.iter().any(|k| k == key)
+    }
+
+    /// Converts the keys of the JSON document in `text`, the value at `path`,
+    /// or gives `text` back when it holds no object or array.
+    pub(crate) fn convert_embedded_json(&self, text: String, path: &Path) -> Result<Value, Error> {
+        match serde_json::from_str(&text) {
+            Ok(document @ Value::Object(_)) | Ok(document @ Value::Array(_)) => {
+                let converted = self.convert_value_at(document, path)?;
+                Ok(Value::String(converted.to_string()))
+            }
+            _ => Ok(Value::String(text)),
+        }
+    }
+
+    /// Whether the same key may be converted differently depending on where it
+    /// appears, which rules out caching conversions by key.
+    fn keys_depend_on_path(&self) -> bool {
+        #[cfg(feature = "jsonpath")]
+        if !self.jsonpaths.is_empty() {
+            return true;
+        }
+        let scoped = |affix: &Option<(String, ScopeMatcher)>| {
+            affix.as_ref().is_some_and(|(_, scope)| !scope.is_all())
+        };
+        scoped(&self.key_prefix)
+            || scoped(&self.key_suffix)
+            || !self.dictionary_keys.is_empty()
+            || !self.excluded_pointers.is_empty()
+            || !self.path_rules.is_empty()
+            || self.max_depth.is_some()
+            || !self.case_rules.is_empty()
+            || !self.depth_cases.is_empty()
+            || self.schema_guide.is_some()
+    }
+
+    /// Converts `key` to `case` by the rules of the compatibility mode.
+    fn to_case(&self, key: &str, case: KeyCase) -> String {
+        match self.compatibility {
+            Compatibility::SerdeRenameAll => crate::compatibility::serde_rename_all(key, case),
+            Compatibility::Lodash => crate::lodash::lodash_case(key, case),
+            Compatibility::ConvertCase => None,
+        }
+        .unwrap_or_else(|| crate::fast_case::to_case(key, case))
+    }
+
+    /// `key` in the normalization form of the converter, if it has one.
+    fn normalize<'k>(&self, key: &'k str) -> Cow<'k, str> {
+        #[cfg(feature = "normalization")]
+        if let Some(form) = self.unicode_normalization {
+            return form.normalize(key);
+        }
+        Cow::Borrowed(key)
+    }
+
+    /// Whether the allowed keys, if any, let `key`, the original key once
+    /// trimmed, be converted.
+    fn is_allowed(&self, key: &str) -> bool {
+        self.allowed_keys
+            .as_ref()
+            .is_none_or(|keys| keys.iter().any(|k| k == key))
+    }
+
+    /// Whether the dictionary detection finds `object` to be a dictionary.
+    pub(crate) fn is_detected_dictionary(&self, object: &JsonMap) -> bool {
+        self.dictionary_detection
+            .as_ref()
+            .is_some_and(|heuristic| heuristic.detects(object))
+    }
+
+    /// Whether the object at `path` is a dictionary whose keys are kept.
+    fn is_dictionary(&self, path: &Path) -> bool {
+        match path {
+            Path::Key(_, key) => self.dictionary_keys.iter().any(|k| k == key),
+            _ => false,
+        }
+    }
+
+    fn strip_prefix<'k>(&self, key: &'k str) -> &'k str {
+        let is_separator = |c: char| "_- ".contains(c);
+
+        self.strip_prefixes
+            .iter()
+            .filter_map(|prefix| {
+                let rest = key.strip_prefix(prefix.as_str())?;
+                let at_boundary = prefix.ends_with(is_separator)
+                    || rest.starts_with(|c: char| c.is_uppercase() || is_separator(c));
+                if rest.is_empty() || (self.strip_prefix_boundary && !at_boundary) {
+                    None
+                } else {
+                    Some(rest)
+                }
+            })
+            .next()
+            .unwrap_or(key)
+    }
+
+    /// The new name `renames` gives `key`, following chains of renames as
+    /// the chain policy says.
+    fn manual_rename<'b>(
+        &self,
+        key: &str,
+        renames: &'b Renames,
+        path: &Path,
+    ) -> Result<Option<&'b str>, Error> {
+        let mut new_key = match Converter::determine_manual_case(key, renames, self.rename_behavior)
+        {
+            Some(new_key) => new_key,
+            None => return Ok(None),
+        };
+        if self.chain_policy == ChainPolicy::Single {
+            return Ok(Some(new_key));
+        }
+        let mut chain = vec![key, new_key];
+        while let Some(next) =
+            Converter::determine_manual_case(new_key, renames, self.rename_behavior)
+                .filter(|&next| next != new_key)
+        {
+            let cycle = chain.contains(&next);
+            chain.push(next);
+            let chain = chain.iter().map(|&k| k.to_owned()).collect();
+            if cycle {
+                return Err(Error::RenameCycle {
+                    path: path.to_pointer(),
+                    cycle: chain,
+                });
+            }
+            if self.chain_policy == ChainPolicy::Error {
+                return Err(Error::RenameChain {
+                    path: path.to_pointer(),
+                    chain,
+                });
+            }
+            new_key = next;
+        }
+        Ok(Some(new_key))
+    }
+
+    fn determine_manual_case<'b>(
+        key: &str,
+        manual_renames: &'b Renames,
+        rename_behavior: RenameBehavior,
+    ) -> Option<&'b str> {
+        match rename_behavior {
+            RenameBehavior::ByKey => manual_renames.get(key).map(|found| found.as_ref()),
+            RenameBehavior::ByValue => manual_renames
+                .iter()
+                .find(|(_, rename_value)| *rename_value == key)
+                .map(|(key, _)| key.as_ref()),
+        }
+    }
+}
+
+/// What [`Converter::key_step`] decided.
+enum KeyStep<'s, 'k> {
+    /// The key is kept exactly as it is.
+    Keep(Kept<'s>),
+
+    /// The key is empty, or became empty after trimming.
+    Empty,
+
+    /// The key, trimmed and without its prefix, goes through the manual
+    /// renames and the case conversion.
+    Rename(&'k str),
+}
+
+/// Which option keeps a key as it is.
+enum Kept<'s> {
+    Dictionary,
+    SkipPrefix(&'s str),
+    ExcludedKey,
+    Excluded(&'s ScopeMatcher),
+    MapKey,
+    Undeclared,
+    #[cfg(feature = "jsonpath")]
+    ExcludedJsonPath,
+    #[cfg(feature = "jsonpath")]
+    NotIncludedJsonPath,
+    OutsidePathRules,
+    BelowMaxDepth,
+}
+
+/// Manual renames, borrowed when set from a [`RenameMap`] and owned when
+/// loaded from a [`ConversionSpec`].
+pub(crate) type Renames<'a> = HashMap<Cow<'a, str>, Cow<'a, str>>;
+
+/// Whether `key` names a property JavaScript objects inherit, through which
+/// a merge or assignment can reach their prototype.
+fn is_prototype_key(key: &str) -> bool {
+    matches!(key, "__proto__" | "constructor" | "prototype")
+}
+
+/// `map` with its entries sorted by key, for maps that keep their order.
+fn sorted_by_key(map: JsonMap) -> JsonMap {
+    let mut entries: Vec<_> = map.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    entries.into_iter().collect()
+}
+
+/// Applies each of `renames` to `key` in turn.
+#[cfg(feature = "regex")]
+fn regex_rename<'k>(
+    key: &'k str,
+    renames: &[(regex::Regex, String)],
+    trace: &mut impl Trace,
+) -> Cow<'k, str> {
+    let mut key = Cow::Borrowed(key);
+    for (regex, replacement) in renames {
+        let renamed = match regex.replace_all(&key, replacement.as_str()) {
+            Cow::Owned(renamed) => renamed,
+            Cow::Borrowed(_) => continue,
+        };
+        trace.note(|| KeyRule::RegexRename(regex.as_str().to_owned()));
+        key = Cow::Owned(renamed);
+    }
+    key
+}
+
+fn borrow_renames(rename_list: RenameMap) -> Renames {
+    rename_list
+        .into_iter()
+        .map(|(from, to)| (Cow::Borrowed(from), Cow::Borrowed(to)))
+        .collect()
+}
+
+fn own_renames(rename_list: BTreeMap<String, String>) -> Renames<'static> {
+    rename_list
+        .into_iter()
+        .map(|(from, to)| (Cow::Owned(from), Cow::Owned(to)))
+        .collect()
+}
+
+fn renames_from_value(rename_list: &Value) -> Result<BTreeMap<String, String>, Error> {
+    let invalid = |path: String, message: &str| Error::InvalidRenames {
+        path,
+        message: message.to_owned(),
+    };
+    let name = |value: &Value, path: String| match value {
+        Value::String(name) => Ok(name.clone()),
+        _ => Err(invalid(path, "expected a string")),
+    };
+
+    match rename_list {
+        Value::Object(map) => map
+            .iter()
+            .map(|(from, to)| {
+                let path = Path::Root.key(from).to_pointer();
+                Ok((from.clone(), name(to, path)?))
+            })
+            .collect(),
+        Value::Array(pairs) => pairs
+            .iter()
+            .enumerate()
+            .map(|(index, pair)| match pair.as_array().map(Vec::as_slice) {
+                Some([from, to]) => Ok((
+                    name(from, format!("/{}/0", index))?,
+                    name(to, format!("/{}/1", index))?,
+                )),
+                _ => Err(invalid(format!("/{}", index), "expected a pair of strings")),
+            })
+            .collect(),
+        _ => Err(invalid(
+            String::new(),
+            "expected an object or an array of pairs",
+        )),
+    }
+}
+
+fn export_renames(rename_list: &Renames) -> BTreeMap<String, String> {
+    rename_list
+        .iter()
+        .map(|(from, to)| (from.clone().into_owned(), to.clone().into_owned()))
+        .collect()
+}
+
+/// Entries left out while converting, and where the traversal is in the
+/// converted document.
+#[derive(Default)]
+pub(crate) struct DropLog {
+    dropped: Vec<DroppedKey>,
+
+    /// Collisions found under [`CollisionStrategy::Error`], reported once
+    /// the whole document is converted.
+    pub(crate) collisions: Vec<KeyCollision>,
+
+    /// JSON pointer, in the converted document, to the value being converted.
+    pointer: String,
+
+    /// The keys renamed, when they are reported.
+    renamed: Option<Vec<KeyRename>>,
+
+    #[cfg(feature = "metrics")]
+    counts: crate::metrics::KeyCounts,
+}
+
+impl DropLog {
+    /// Runs `f` one level below the current value, under `token`.
+    fn within<T>(
+        log: &mut Option<DropLog>,
+        token: &str,
+        f: impl FnOnce(&mut Option<DropLog>) -> T,
+    ) -> T {
+        let len = match log {
+            Some(log) => {
+                let len = log.pointer.len();
+                log.pointer.push('/');
+                log.pointer.push_str(&escape_pointer_token(token));
+                len
+            }
+            None => return f(log),
+        };
+        let result = f(log);
+        if let Some(log) = log {
+            log.pointer.truncate(len);
+        }
+        result
+    }
+
+    pub(crate) fn drop(&mut self, key: &str, path: &Path, reason: DropReason) {
+        self.dropped.push(DroppedKey {
+            key: key.to_owned(),
+            path: path.to_pointer(),
+            parent: self.pointer.clone(),
+            reason,
+        });
+    }
+
+    /// Notes that `key`, at `path`, was stored under `new_key` in the current
+    /// object, when renames are reported and the key changed.
+    pub(crate) fn rename(&mut self, key: &str, path: &Path, new_key: &str) {
+        if key == new_key {
+            return;
+        }
+        if let Some(renamed) = &mut self.renamed {
+            renamed.push(KeyRename {
+                key: key.to_owned(),
+                path: path.to_pointer(),
+                new_key: new_key.to_owned(),
+                new_path: format!("{}/{}", self.pointer, escape_pointer_token(new_key)),
+            });
+        }
+    }
+
+    /// Forgets the rename of the entry at `path`, which a later entry
+    /// replaced.
+    pub(crate) fn unrename(&mut self, path: &Path) {
+        if let Some(renamed) = &mut self.renamed {
+            let pointer = path.to_pointer();
+            renamed.retain(|rename| rename.path != pointer);
+        }
+    }
+}
+
+/// What the serde adapters carry around while walking: the converter, and
+/// where they are in the document.
+#[derive(Clone, Copy)]
+pub(crate) struct Context<'p> {
+    pub(crate) converter: &'p Converter<'p>,
+    pub(crate) path: &'p Path<'p>,
+}
+
+impl<'p> Context<'p> {
+    pub(crate) fn new(converter: &'p Converter<'p>) -> Self {
+        Context {
+            converter,
+            path: &Path::Root,
+        }
+    }
+
+    pub(crate) fn at(self, path: &'p Path<'p>) -> Context<'p> {
+        Context {
+            converter: self.converter,
+            path,
+        }
+    }
+}
+
+pub(crate) fn expect_converted<T>(result: Result<T, Error>) -> T {
+    result.unwrap_or_else(|err| panic!("conversion failed: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn pair_arrays() {
+        let value = json!({
+            "attributes": [
+                ["firstName", {"nickName": "Ada"}],
+                ["lastName", "Lovelace"],
+                ["tooMany", 1, 2],
+                [3, 4],
+                "plainValue"
+            ],
+            "properties": [
+                {"key": "birthYear", "value": 1815},
+                {"key": "homeTown", "value": {"cityName": "London"}},
+                {"key": "noValue"},
+                {"key": "extraField", "value": 0, "otherField": 1}
+            ],
+            "otherPairs": [["keptAs", "is"]]
+        });
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_pair_array_keys(vec!["attributes", "properties"]);
+
+        assert_eq!(
+            json!({
+                "attributes": [
+                    ["first_name", {"nick_name": "Ada"}],
+                    ["last_name", "Lovelace"],
+                    ["tooMany", 1, 2],
+                    [3, 4],
+                    "plainValue"
+                ],
+                "properties": [
+                    {"key": "birth_year", "value": 1815},
+                    {"key": "home_town", "value": {"city_name": "London"}},
+                    {"key": "noValue"},
+                    {"key": "extraField", "value": 0, "otherField": 1}
+                ],
+                "other_pairs": [["keptAs", "is"]]
+            }),
+            converter.convert_value(value).unwrap()
+        );
+    }
+
+    #[test]
+    fn embedded_json_fields() {
+        let value = json!({
+            "eventType": "created",
+            "payload": r#"{"innerKey": 1, "nestedList": [{"itemId": 2}]}"#,
+            "events": [
+                {"payload": "[{\"eventId\": 3}]"},
+                {"payload": "not {json"},
+                {"payload": " 42 "}
+            ],
+            "rawText": "{\"keptAs\": 1}"
+        });
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_embedded_json_fields(vec!["payload"]);
+        converter.with_excluded_pointers(vec!["/payload/nestedList"]);
+
+        let expected = json!({
+            "event_type": "created",
+            "payload": r#"{"inner_key":1,"nestedList":[{"item_id":2}]}"#,
+            "events": [
+                {"payload": "[{\"event_id\":3}]"},
+                {"payload": "not {json"},
+                {"payload": " 42 "}
+            ],
+            "raw_text": "{\"keptAs\": 1}"
+        });
+        assert_eq!(expected, converter.convert_value(value.clone()).unwrap());
+
+        let mut in_place = value;
+        converter.convert_in_place(&mut in_place).unwrap();
+        assert_eq!(expected, in_place);
+    }
+
+    #[test]
+    fn prototype_pollution_guard() {
+        let value = json!({"items": [{"id": 1, "__proto__": {"isAdmin": true}}]});
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_prototype_pollution_guard(GuardPolicy::Reject);
+        match converter.convert_value(value.clone()) {
+            Err(Error::PrototypePollution { path, key }) => {
+                assert_eq!("/items/0/__proto__", path);
+                assert_eq!("__proto__", key);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        converter.with_prototype_pollution_guard(GuardPolicy::Drop);
+        let (converted, dropped) = converter.convert_value_with_drops(value).unwrap();
+        assert_eq!(json!({"items": [{"id": 1}]}), converted);
+        assert_eq!(DropReason::PrototypePollution, dropped[0].reason);
+
+        // Keys kept by other options are guarded too, and so are keys the
+        // conversion turns into dangerous ones.
+        converter.with_prototype_pollution_guard(GuardPolicy::Prefix);
+        converter.with_skip_prefixes(vec!["__"]);
+        assert_eq!(
+            json!({"__proto___": {"constructor_": 1, "prototype_": 2, "protoType": 3}}),
+            converter
+                .convert_value(json!({
+                    "__proto__": {"Constructor": 1, "PROTOTYPE": 2, "proto_type": 3}
+                }))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn variant_renames() {
+        let value = json!([
+            {"type": "orderCreated", "ID": 1, "Total": 9, "lineItem": {"ID": 2}},
+            {"type": "userUpdated", "ID": 3, "Total": 1, "address": {"type": "postal", "ID": 4}},
+            {"ID": 5, "Total": 0}
+        ]);
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("ID", "id")].iter().cloned().collect());
+        let order_renames: RenameMap = [("ID", "order_id"), ("Total", "amount")]
+            .iter()
+            .cloned()
+            .collect();
+        converter.with_variant_renames("type", "orderCreated", order_renames);
+        converter.with_variant_renames(
+            "type",
+            "userUpdated",
+            [("ID", "user_id")].iter().cloned().collect(),
+        );
+        converter.with_variant_renames("type", "postal", RenameMap::new());
+
+        assert_eq!(
+            json!([
+                {"type": "orderCreated", "order_id": 1, "amount": 9, "line_item": {"order_id": 2}},
+                {"type": "userUpdated", "user_id": 3, "total": 1, "address": {"type": "postal", "id": 4}},
+                {"id": 5, "total": 0}
+            ]),
+            converter.convert_value(value).unwrap()
+        );
+    }
+
+    #[test]
+    fn allowed_keys() {
+        let value = json!({
+            "userName": "Ada",
+            "createdAt": 1,
+            "orderItems": [{"userName": "Bob", "unitPrice": 2}],
+            "shippingAddress": {"createdAt": 3, "zipCode": "1000"},
+            "ID": 4
+        });
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_allowed_keys(vec!["userName", "createdAt", "orderItems"]);
+        converter.with_manual_renames([("ID", "id")].iter().cloned().collect());
+
+        let expected = json!({
+            "user_name": "Ada",
+            "created_at": 1,
+            "order_items": [{"user_name": "Bob", "unitPrice": 2}],
+            "shippingAddress": {"created_at": 3, "zipCode": "1000"},
+            "id": 4
+        });
+        assert_eq!(expected, converter.convert_value(value.clone()).unwrap());
+
+        let from_spec = Converter::from_spec(converter.to_spec()).unwrap();
+        assert_eq!(expected, from_spec.convert_value(value).unwrap());
+    }
+
+    #[test]
+    fn excluded_keys_and_paths() {
+        let value = json!({
+            "headers": {"X-Request-ID": "r", "ContentType": "json"},
+            "labels": {"en_US": "Hello", "de_DE": "Hallo"},
+            "items": [{"RawValue": 1, "unitPrice": 2}]
+        });
+
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_excluded_keys(vec!["X-Request-ID", "en_US"]);
+        converter
+            .with_excluded_paths(vec!["items[*].RawValue", "/labels/de_DE"])
+            .unwrap();
+
+        let expected = json!({
+            "headers": {"X-Request-ID": "r", "contentType": "json"},
+            "labels": {"en_US": "Hello", "de_DE": "Hallo"},
+            "items": [{"RawValue": 1, "unitPrice": 2}]
+        });
+        assert_eq!(expected, converter.convert_value(value.clone()).unwrap());
+        assert_eq!(
+            vec!["/items/*/RawValue".to_owned(), "/labels/de_DE".to_owned()],
+            converter.to_spec().excluded_pointers
+        );
+
+        let from_spec = Converter::from_spec(converter.to_spec()).unwrap();
+        assert_eq!(expected, from_spec.convert_value(value).unwrap());
+        assert!(converter.with_excluded_paths(vec!["items[x]"]).is_err());
+    }
+
+    #[test]
+    fn excluded_keys_after_trimming() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_trim_keys(true);
+        converter.with_excluded_keys(vec!["userName"]);
+        converter.with_skip_prefixes(vec!["__"]);
+
+        assert_eq!(
+            json!({" userName ": 1, " __typeName": 2, "zip_code": 3}),
+            converter
+                .convert_value(json!({" userName ": 1, " __typeName": 2, " zipCode ": 3}))
+                .unwrap()
+        );
+        assert_eq!(
+            vec![KeyRule::ExcludedKey],
+            converter.preview_key(" userName ", None).rules
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_renames() {
+        let value = json!({"legacy_userName": "Ada", "fooBarV2": 1, "ID": 2});
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("ID", "id")].iter().cloned().collect());
+        converter.with_regex_renames(vec![
+            (regex::Regex::new("^legacy_").unwrap(), String::new()),
+            (regex::Regex::new("V[0-9]+$").unwrap(), String::new()),
+        ]);
+        converter.with_regex_renames_after_case(vec![(
+            regex::Regex::new("^user_(.*)$").unwrap(),
+            "${1}_of_user".to_owned(),
+        )]);
+
+        assert_eq!(
+            json!({"name_of_user": "Ada", "foo_bar": 1, "id": 2}),
+            converter.convert_value(value).unwrap()
+        );
+        assert_eq!(
+            vec![
+                KeyRule::RegexRename("^legacy_".to_owned()),
+                KeyRule::Case(KeyCase::Snake),
+                KeyRule::RegexRename("^user_(.*)$".to_owned()),
+            ],
+            converter.preview_key("legacy_userName", None).rules
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_max_depth(2);
+
+        assert_eq!(
+            json!({"user_name": "Ada", "past_orders": [{"order_id": 1, "line_items": {"unitPrice": 2}}]}),
+            converter
+                .convert_value(json!({
+                    "userName": "Ada",
+                    "pastOrders": [{"orderId": 1, "lineItems": {"unitPrice": 2}}]
+                }))
+                .unwrap()
+        );
+        converter.with_prototype_pollution_guard(GuardPolicy::Drop);
+        assert_eq!(
+            json!({"user_name": "Ada", "meta": {"tags": {"Raw": 1}}}),
+            converter
+                .convert_value(json!({
+                    "userName": "Ada",
+                    "meta": {"tags": {"Raw": 1, "__proto__": {}}}
+                }))
+                .unwrap()
+        );
+        assert_eq!(
+            vec![KeyRule::BelowMaxDepth],
+            converter.preview_key("lineItems", Some("/a/b")).rules
+        );
+    }
+
+    #[test]
+    fn allowed_keys_after_trimming() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_trim_keys(true);
+        converter.with_allowed_keys(vec!["userName"]);
+
+        assert_eq!(
+            json!({"user_name": 2, "zipCode ": 3}),
+            converter
+                .convert_value(json!({" userName": 1, "userName": 2, "zipCode ": 3}))
+                .unwrap()
+        );
+        assert_eq!(
+            vec![KeyRule::Trimmed, KeyRule::Case(KeyCase::Snake)],
+            converter.preview_key(" userName", None).rules
+        );
+    }
+
+    #[test]
+    fn empty_allowed_keys() {
+        let value = json!({"userName": "Ada", "homeAddress": {"zipCode": "1000"}});
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_allowed_keys(Vec::<String>::new());
+
+        assert_eq!(value, converter.convert_value(value.clone()).unwrap());
+        assert_eq!(
+            vec![KeyRule::NotAllowed],
+            converter.preview_key("userName", None).rules
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use crate::converter::Renames;
+use crate::path::Path;
+use crate::prelude::*;
+use crate::Converter;
+use alloc::collections::BTreeMap;
+use serde::Serialize;
+use serde_json::Value;
+
+/// What [`analyze_corpus`] found over a set of documents. It serializes to
+/// JSON, for dashboards.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct CorpusReport {
+    /// How many documents were analyzed.
+    pub documents: usize,
+
+    /// Every distinct original key, sorted.
+    pub keys: Vec<KeyStats>,
+
+    /// Keys of one object converted to the same new key, of which only the
+    /// last would be kept, in document order.
+    pub collisions: Vec<CorpusCollision>,
+
+    /// New keys reached from differently written original keys, spread over
+    /// more than one document, such as `userId` in one and `user_id` in
+    /// another, sorted by new key. Variants only found together in one
+    /// document are left to `collisions`.
+    pub casing_variants: Vec<CasingVariants>,
+}
+
+/// How an original key appears in a corpus.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KeyStats {
+    pub key: String,
+
+    /// How many entries have the key, over every document.
+    pub occurrences: usize,
+
+    /// How many documents have the key.
+    pub documents: usize,
+
+    /// Every distinct new key it is converted to, `None` standing for
+    /// entries that would be dropped or rejected. More than one means the
+    /// conversion depends on where the key is.
+    pub targets: Vec<Option<String>>,
+
+    /// JSON pointer to the first entry with the key, in the document at
+    /// `sample_document`.
+    pub sample_path: String,
+    pub sample_document: usize,
+}
+
+/// Keys of one object of a corpus that are converted to the same new key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CorpusCollision {
+    /// The index of the document, in the order they were given.
+    pub document: usize,
+
+    /// JSON pointer to the object.
+    pub path: String,
+
+    /// The original keys, in document order.
+    pub keys: Vec<String>,
+
+    /// The key they are all converted to.
+    pub new_key: String,
+}
+
+/// Original keys of a corpus that are written differently but converted to
+/// the same new key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CasingVariants {
+    pub new_key: String,
+
+    /// Each original key, sorted, with the indices of the documents it
+    /// appears in.
+    pub variants: Vec<(String, Vec<usize>)>,
+}
+
+impl KeyStats {
+    /// Whether the key is converted the same way everywhere.
+    pub fn is_consistent(&self) -> bool {
+        self.targets.len() <= 1
+    }
+}
+
+/// Scans `values` without converting them, to learn every key they hold,
+/// how often it appears, what it would be converted to, and where `config`
+/// would make keys collide or bring differently written keys together.
+pub fn analyze_corpus(values: impl Iterator<Item = Value>, config: &Converter) -> CorpusReport {
+    let mut corpus = Corpus {
+        converter: config,
+        document: 0,
+        keys: BTreeMap::new(),
+    };
+    let mut report = CorpusReport::default();
+    for (document, value) in values.enumerate() {
+        corpus.document = document;
+        corpus.value(&value, &Path::Root, None);
+        report
+            .collisions
+            .extend(
+                config
+                    .preflight(&value)
+                    .collisions
+                    .into_iter()
+                    .map(|collision| CorpusCollision {
+                        document,
+                        path: collision.path,
+                        keys: collision.keys,
+                        new_key: collision.new_key,
+                    }),
+            );
+        report.documents += 1;
+    }
+
+    let mut by_target: BTreeMap<String, Vec<(String, Vec<usize>)>> = BTreeMap::new();
+    for (key, seen) in corpus.keys {
+        for target in seen.targets.iter().flatten() {
+            by_target
+                .entry(target.clone())
+                .or_default()
+                .push((key.clone(), seen.documents.clone()));
+        }
+        report.keys.push(KeyStats {
+            key,
+            occurrences: seen.occurrences,
+            documents: seen.documents.len(),
+            targets: seen.targets,
+            sample_path: seen.sample_path,
+            sample_document: seen.documents[0],
+        });
+    }
+    report.casing_variants = by_target
+        .into_iter()
+        .filter(|(_, variants)| {
+            let mut documents: Vec<usize> = variants
+                .iter()
+                .flat_map(|(_, documents)| documents.iter().copied())
+                .collect();
+            documents.sort_unstable();
+            documents.dedup();
+            variants.len() > 1 && documents.len() > 1
+        })
+        .map(|(new_key, variants)| CasingVariants { new_key, variants })
+        .collect();
+    report
+}
+
+/// What is known of a key so far.
+struct Seen {
+    occurrences: usize,
+
+    /// The indices of the documents holding the key, without duplicates.
+    documents: Vec<usize>,
+    targets: Vec<Option<String>>,
+    sample_path: String,
+}
+
+struct Corpus<'c> {
+    converter: &'c Converter<'c>,
+    document: usize,
+    keys: BTreeMap<String, Seen>,
+}
+
+impl<'c> Corpus<'c> {
+    fn value(&mut self, value: &Value, path: &Path, variant: Option<&'c Renames<'c>>) {
+        match value {
+            Value::Array(arr) => {
+                for (index, value) in arr.iter().enumerate() {
+                    self.value(value, &path.index(index), variant);
+                }
+            }
+            Value::Object(map) => {
+                let variant = self.converter.object_variant(map).or(variant);
+                let detected = self.converter.is_detected_dictionary(map);
+                for (key, value) in map {
+                    let key_path = path.key(key);
+                    let target = if detected {
+                        Some(key.clone())
+                    } else {
+                        self.converter
+                            .rename_variant_key(key, variant, &key_path)
+                            .unwrap_or(None)
+                    };
+                    let document = self.document;
+                    let seen = self.keys.entry(key.clone()).or_insert_with(|| Seen {
+                        occurrences: 0,
+                        documents: Vec::new(),
+                        targets: Vec::new(),
+                        sample_path: key_path.to_pointer(),
+                    });
+                    seen.occurrences += 1;
+                    if seen.documents.last() != Some(&document) {
+                        seen.documents.push(document);
+                    }
+                    if !seen.targets.contains(&target) {
+                        seen.targets.push(target);
+                    }
+                    self.value(value, &key_path, variant);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn finds_collisions_and_casing_variants() {
+        let documents = vec![
+            json!({"userId": 1, "orderItems": [{"unitPrice": 2}, {"unitPrice": 3}]}),
+            json!({"user_id": 4, "tags": ["a"]}),
+            json!({"meta": {"UserName": "a", "userName": "b"}}),
+        ];
+        let report = analyze_corpus(documents.into_iter(), &Converter::new(KeyCase::Snake));
+
+        assert_eq!(3, report.documents);
+        assert_eq!(
+            vec![CorpusCollision {
+                document: 2,
+                path: "/meta".to_owned(),
+                keys: vec!["UserName".to_owned(), "userName".to_owned()],
+                new_key: "user_name".to_owned(),
+            }],
+            report.collisions
+        );
+        assert_eq!(
+            vec![CasingVariants {
+                new_key: "user_id".to_owned(),
+                variants: vec![
+                    ("userId".to_owned(), vec![0]),
+                    ("user_id".to_owned(), vec![1]),
+                ],
+            }],
+            report.casing_variants
+        );
+
+        let unit_price = report
+            .keys
+            .iter()
+            .find(|stats| stats.key == "unitPrice")
+            .unwrap();
+        assert_eq!(2, unit_price.occurrences);
+        assert_eq!(1, unit_price.documents);
+        assert_eq!(vec![Some("unit_price".to_owned())], unit_price.targets);
+        assert_eq!("/orderItems/0/unitPrice", unit_price.sample_path);
+        assert!(report.keys.iter().all(KeyStats::is_consistent));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json!(2), json["collisions"][0]["document"]);
+    }
+}
@@ -0,0 +1,667 @@
+use crate::converter::Context;
+use crate::prelude::*;
+use crate::Converter;
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+
+/// A [`Deserializer`] renaming map keys with a [`Converter`] while they stream
+/// past, so that a `T` can be read from camelCase input without building a
+/// [`Value`](serde_json::Value) first:
+///
+/// ```
+/// # use json_keys_case_changer::{CaseChangingDeserializer, Converter};
+/// # use json_keys_case_changer::KeyCase;
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct User {
+///     user_name: String,
+/// }
+///
+/// let converter = Converter::new(KeyCase::Snake);
+/// let mut de = serde_json::Deserializer::from_str(r#"{"userName": "Ada"}"#);
+/// let user = User::deserialize(CaseChangingDeserializer::new(&mut de, &converter)).unwrap();
+/// de.end().unwrap();
+///
+/// assert_eq!("Ada", user.user_name);
+/// ```
+///
+/// Maps nested anywhere, including inside sequences and enum variants, are
+/// renamed. Enum variant names are not, since formats hand them over as
+/// variants rather than map keys. Non-string keys are passed through as they
+/// are. Entries whose key is dropped by the converter are skipped.
+pub struct CaseChangingDeserializer<'c, D> {
+    inner: D,
+    converter: &'c Converter<'c>,
+}
+
+impl<'c, D> CaseChangingDeserializer<'c, D> {
+    pub fn new(inner: D, converter: &'c Converter<'c>) -> Self {
+        CaseChangingDeserializer { inner, converter }
+    }
+}
+
+struct Wrap<'p, D> {
+    inner: D,
+    cx: Context<'p>,
+}
+
+struct Visit<'p, V> {
+    visitor: V,
+    cx: Context<'p>,
+}
+
+struct Seed<'p, S> {
+    seed: S,
+    cx: Context<'p>,
+}
+
+struct Seq<'p, A> {
+    inner: A,
+    cx: Context<'p>,
+    index: usize,
+}
+
+struct Map<'p, A> {
+    inner: A,
+    cx: Context<'p>,
+    key: Option<String>,
+}
+
+struct Enum<'p, A> {
+    inner: A,
+    cx: Context<'p>,
+}
+
+struct Variant<'p, A> {
+    inner: A,
+    cx: Context<'p>,
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.wrap().$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+macro_rules! forward_deserialize_wrapped {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.inner.$method($($arg,)* Visit { visitor, cx: self.cx })
+            }
+        )*
+    };
+}
+
+macro_rules! deserialize_methods {
+    ($forward:ident) => {
+        $forward! {
+            deserialize_any();
+            deserialize_bool();
+            deserialize_i8();
+            deserialize_i16();
+            deserialize_i32();
+            deserialize_i64();
+            deserialize_i128();
+            deserialize_u8();
+            deserialize_u16();
+            deserialize_u32();
+            deserialize_u64();
+            deserialize_u128();
+            deserialize_f32();
+            deserialize_f64();
+            deserialize_char();
+            deserialize_str();
+            deserialize_string();
+            deserialize_bytes();
+            deserialize_byte_buf();
+            deserialize_option();
+            deserialize_unit();
+            deserialize_unit_struct(name: &'static str);
+            deserialize_newtype_struct(name: &'static str);
+            deserialize_seq();
+            deserialize_tuple(len: usize);
+            deserialize_tuple_struct(name: &'static str, len: usize);
+            deserialize_map();
+            deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+            deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+            deserialize_identifier();
+            deserialize_ignored_any();
+        }
+    };
+}
+
+impl<'c, D> CaseChangingDeserializer<'c, D> {
+    fn wrap(self) -> Wrap<'c, D> {
+        Wrap {
+            inner: self.inner,
+            cx: Context::new(self.converter),
+        }
+    }
+}
+
+impl<'de, 'c, D: Deserializer<'de>> Deserializer<'de> for CaseChangingDeserializer<'c, D> {
+    type Error = D::Error;
+
+    deserialize_methods!(forward_deserialize);
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+impl<'de, 'p, D: Deserializer<'de>> Deserializer<'de> for Wrap<'p, D> {
+    type Error = D::Error;
+
+    deserialize_methods!(forward_deserialize_wrapped);
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                self.visitor.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, 'p, V: Visitor<'de>> Visitor<'de> for Visit<'p, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.visitor.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.visitor.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, inner: D) -> Result<Self::Value, D::Error> {
+        self.visitor.visit_some(Wrap { inner, cx: self.cx })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, inner: D) -> Result<Self::Value, D::Error> {
+        self.visitor
+            .visit_newtype_struct(Wrap { inner, cx: self.cx })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, inner: A) -> Result<Self::Value, A::Error> {
+        self.visitor.visit_seq(Seq {
+            inner,
+            cx: self.cx,
+            index: 0,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, inner: A) -> Result<Self::Value, A::Error> {
+        self.visitor.visit_map(Map {
+            inner,
+            cx: self.cx,
+            key: None,
+        })
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, inner: A) -> Result<Self::Value, A::Error> {
+        self.visitor.visit_enum(Enum { inner, cx: self.cx })
+    }
+}
+
+impl<'de, 'p, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Seed<'p, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, inner: D) -> Result<Self::Value, D::Error> {
+        self.seed.deserialize(Wrap { inner, cx: self.cx })
+    }
+}
+
+impl<'de, 'p, A: SeqAccess<'de>> SeqAccess<'de> for Seq<'p, A> {
+    type Error = A::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let path = self.cx.path.index(self.index);
+        self.index += 1;
+        self.inner.next_element_seed(Seed {
+            seed,
+            cx: self.cx.at(&path),
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, 'p, A: MapAccess<'de>> MapAccess<'de> for Map<'p, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        loop {
+            let key = match self.inner.next_key_seed(KeyCapture)? {
+                Some(key) => key,
+                None => return Ok(None),
+            };
+
+            match key {
+                CapturedKey::Str(original) => {
+                    let path = self.cx.path.key(&original);
+                    let renamed = self
+                        .cx
+                        .converter
+                        .rename_key(&original, &path)
+                        .map_err(de::Error::custom)?;
+
+                    match renamed {
+                        Some(new_key) => {
+                            let value = seed.deserialize(KeyDeserializer::new(new_key))?;
+                            self.key = Some(original);
+                            return Ok(Some(value));
+                        }
+                        None => {
+                            self.inner.next_value::<de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                other => {
+                    self.key = Some(other.to_string());
+                    return other.deserialize_into(seed).map(Some);
+                }
+            }
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let key = self.key.take().unwrap_or_default();
+        let path = self.cx.path.key(&key);
+        self.inner.next_value_seed(Seed {
+            seed,
+            cx: self.cx.at(&path),
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, 'p, A: EnumAccess<'de>> EnumAccess<'de> for Enum<'p, A> {
+    type Error = A::Error;
+    type Variant = Variant<'p, A::Variant>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let (value, inner) = self.inner.variant_seed(seed)?;
+        Ok((value, Variant { inner, cx: self.cx }))
+    }
+}
+
+impl<'de, 'p, A: VariantAccess<'de>> VariantAccess<'de> for Variant<'p, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        self.inner.newtype_variant_seed(Seed { seed, cx: self.cx })
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.tuple_variant(
+            len,
+            Visit {
+                visitor,
+                cx: self.cx,
+            },
+        )
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.struct_variant(
+            fields,
+            Visit {
+                visitor,
+                cx: self.cx,
+            },
+        )
+    }
+}
+
+/// A map key, read before deciding how to rename it.
+enum CapturedKey {
+    Str(String),
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bytes(Vec<u8>),
+}
+
+impl CapturedKey {
+    fn deserialize_into<'de, K, E>(self, seed: K) -> Result<K::Value, E>
+    where
+        K: DeserializeSeed<'de>,
+        E: de::Error,
+    {
+        match self {
+            CapturedKey::Str(key) => seed.deserialize(KeyDeserializer::new(key)),
+            CapturedKey::Bool(key) => seed.deserialize(key.into_deserializer()),
+            CapturedKey::I64(key) => seed.deserialize(key.into_deserializer()),
+            CapturedKey::U64(key) => seed.deserialize(key.into_deserializer()),
+            CapturedKey::F64(key) => seed.deserialize(key.into_deserializer()),
+            CapturedKey::Bytes(key) => {
+                seed.deserialize(de::value::BytesDeserializer::<E>::new(&key))
+            }
+        }
+    }
+}
+
+impl fmt::Display for CapturedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CapturedKey::Str(key) => f.write_str(key),
+            CapturedKey::Bool(key) => write!(f, "{}", key),
+            CapturedKey::I64(key) => write!(f, "{}", key),
+            CapturedKey::U64(key) => write!(f, "{}", key),
+            CapturedKey::F64(key) => write!(f, "{}", key),
+            CapturedKey::Bytes(key) => write!(f, "{}", String::from_utf8_lossy(key)),
+        }
+    }
+}
+
+struct KeyCapture;
+
+impl<'de> DeserializeSeed<'de> for KeyCapture {
+    type Value = CapturedKey;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeyCapture {
+    type Value = CapturedKey;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map key")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(CapturedKey::Str(v.to_owned()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(CapturedKey::Str(v))
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<Self::Value, E> {
+        Ok(CapturedKey::Str(v.to_string()))
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(CapturedKey::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(CapturedKey::I64(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(CapturedKey::U64(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(CapturedKey::F64(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(CapturedKey::Bytes(v.to_vec()))
+    }
+}
+
+/// Hands a renamed key to the seed. Like `serde_json`'s own map keys, it
+/// parses numbers and booleans out of the string when the seed asks for them,
+/// so maps keyed by integers keep working.
+struct KeyDeserializer<E> {
+    key: String,
+    error: PhantomData<E>,
+}
+
+impl<E> KeyDeserializer<E> {
+    fn new(key: String) -> Self {
+        KeyDeserializer {
+            key,
+            error: PhantomData,
+        }
+    }
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.key.parse() {
+                    Ok(parsed) => visitor.$visit(parsed),
+                    Err(_) => visitor.visit_string(self.key),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de, E: de::Error> Deserializer<'de> for KeyDeserializer<E> {
+    type Error = E;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_string(self.key)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.key
+            .into_deserializer()
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::HashMap;
+    use crate::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Settings {
+        user_name: String,
+        home_address: Address,
+        feature_flags: HashMap<String, Flag>,
+        recent_orders: Vec<Order>,
+        nick_name: Option<String>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Address {
+        street_name: String,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Flag {
+        is_enabled: bool,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Order {
+        order_id: u32,
+    }
+
+    #[test]
+    fn camel_string_into_snake_struct() {
+        let input = r#"{
+            "userName": "Ada",
+            "homeAddress": {"streetName": "Main"},
+            "featureFlags": {"darkMode": {"isEnabled": true}, "betaAPI": {"isEnabled": false}},
+            "recentOrders": [{"orderId": 1}, {"orderId": 2}],
+            "nickName": null
+        }"#;
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_dictionary_keys(vec!["featureFlags"]);
+
+        let mut de = serde_json::Deserializer::from_str(input);
+        let settings =
+            Settings::deserialize(CaseChangingDeserializer::new(&mut de, &converter)).unwrap();
+        de.end().unwrap();
+
+        let feature_flags = vec![
+            ("darkMode".to_owned(), Flag { is_enabled: true }),
+            ("betaAPI".to_owned(), Flag { is_enabled: false }),
+        ];
+        let expected = Settings {
+            user_name: "Ada".to_owned(),
+            home_address: Address {
+                street_name: "Main".to_owned(),
+            },
+            feature_flags: feature_flags.into_iter().collect(),
+            recent_orders: vec![Order { order_id: 1 }, Order { order_id: 2 }],
+            nick_name: None,
+        };
+        assert_eq!(expected, settings);
+    }
+
+    #[test]
+    fn matches_value_conversion() {
+        let input = r#"{"someKey": [{"innerKey": {"deepKey": 1}}], "idMap": {"aB": {"cD": 2}}}"#;
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_dictionary_keys(vec!["idMap"]);
+
+        let mut de = serde_json::Deserializer::from_str(input);
+        let streamed =
+            serde_json::Value::deserialize(CaseChangingDeserializer::new(&mut de, &converter))
+                .unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(input).unwrap();
+        let expected = serde_json::json!({
+            "some_key": [{"inner_key": {"deep_key": 1}}],
+            "id_map": {"aB": {"c_d": 2}}
+        });
+        assert_eq!(expected, converter.convert_value(value).unwrap());
+        assert_eq!(expected, streamed);
+    }
+
+    #[test]
+    fn integer_keys() {
+        let converter = Converter::new(KeyCase::Snake);
+
+        let mut de = serde_json::Deserializer::from_str(r#"{"1": "a", "20": "b"}"#);
+        let map =
+            HashMap::<u32, String>::deserialize(CaseChangingDeserializer::new(&mut de, &converter))
+                .unwrap();
+
+        assert_eq!(Some(&"b".to_owned()), map.get(&20));
+    }
+}
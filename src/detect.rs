@@ -0,0 +1,107 @@
+use crate::fast_case::to_case;
+use crate::keys::keys;
+use crate::{CaseChanger, KeyCase};
+use alloc::collections::BTreeMap;
+use serde_json::Value;
+
+/// How the keys of a document are written, as found by
+/// [`CaseChanger::detect_cases`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DetectedCases {
+    /// How many keys were looked at. Keys without a letter, such as `""` or
+    /// `"42"`, are in every case and left out.
+    pub keys: usize,
+
+    /// How many of those keys are already in each case, for every case at
+    /// least one key is in. A key of one lowercase word, such as `id`, is in
+    /// camelCase, snake_case, kebab-case and a few others at once.
+    pub matches: BTreeMap<KeyCase, usize>,
+}
+
+impl DetectedCases {
+    /// Whether every key is already in `case`, so that converting to it
+    /// would leave the keys as they are.
+    pub fn is_consistently(&self, case: KeyCase) -> bool {
+        self.matches.get(&case).copied().unwrap_or(0) == self.keys
+    }
+
+    /// The case every key is in, when there is exactly one.
+    pub fn case(&self) -> Option<KeyCase> {
+        let mut cases = self
+            .matches
+            .iter()
+            .filter(|(_, matches)| **matches == self.keys)
+            .map(|(case, _)| *case);
+        match (cases.next(), cases.next()) {
+            (Some(case), None) if self.keys > 0 => Some(case),
+            _ => None,
+        }
+    }
+}
+
+impl CaseChanger<'_> {
+    /// The case every key of `value` is in, or `None` when the keys mix
+    /// cases, when they fit several cases equally well (as keys made of one
+    /// lowercase word do), or when there are none.
+    pub fn detect_case(value: &Value) -> Option<KeyCase> {
+        CaseChanger::detect_cases(value).case()
+    }
+
+    /// Counts, over every key of `value`, how many are already in each case.
+    pub fn detect_cases(value: &Value) -> DetectedCases {
+        let mut detected = DetectedCases::default();
+        for found in keys(value) {
+            if !found.key.chars().any(char::is_alphabetic) {
+                continue;
+            }
+            detected.keys += 1;
+            for case in KeyCase::all() {
+                if to_case(found.key, *case) == found.key {
+                    *detected.matches.entry(*case).or_insert(0) += 1;
+                }
+            }
+        }
+        detected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn detects_a_consistent_case() {
+        let camel = json!({"userName": "Ada", "id": 1, "lineItems": [{"unitPrice": 2, "42": 0}]});
+        assert_eq!(Some(KeyCase::Camel), CaseChanger::detect_case(&camel));
+
+        let detected = CaseChanger::detect_cases(&camel);
+        assert_eq!(4, detected.keys);
+        assert!(detected.is_consistently(KeyCase::Camel));
+        assert!(!detected.is_consistently(KeyCase::Snake));
+        assert_eq!(Some(&1), detected.matches.get(&KeyCase::Snake));
+
+        assert_eq!(
+            Some(KeyCase::Snake),
+            CaseChanger::detect_case(&json!({"user_name": {"zip_code": 1}}))
+        );
+        assert_eq!(
+            Some(KeyCase::ScreamingSnake),
+            CaseChanger::detect_case(&json!({"USER_NAME": 1}))
+        );
+    }
+
+    #[test]
+    fn finds_no_case_when_ambiguous() {
+        assert_eq!(
+            None,
+            CaseChanger::detect_case(&json!({"userName": 1, "zip_code": 2}))
+        );
+        assert_eq!(None, CaseChanger::detect_case(&json!({"id": 1, "name": 2})));
+        assert_eq!(None, CaseChanger::detect_case(&json!([1, {"": 2}])));
+
+        let detected = CaseChanger::detect_cases(&json!({"id": 1}));
+        assert!(detected.is_consistently(KeyCase::Camel));
+        assert!(detected.is_consistently(KeyCase::Snake));
+    }
+}
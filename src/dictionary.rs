@@ -0,0 +1,166 @@
+use crate::prelude::*;
+use crate::JsonMap;
+use serde::{Deserialize, Serialize};
+
+/// Decides from the keys of an object alone that it is a dictionary, whose
+/// keys are data rather than field names, as set with
+/// [`Converter::with_dictionary_detection`](crate::Converter::with_dictionary_detection).
+///
+/// An object is a dictionary when more than `threshold` of its keys, as a
+/// fraction between 0 and 1, are matched by one of the `detectors`. Empty
+/// objects never are.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DictionaryHeuristic {
+    pub threshold: f32,
+    pub detectors: Vec<KeyDetector>,
+}
+
+// Thresholds are plain fractions; a NaN one would never fire anyway.
+impl Eq for DictionaryHeuristic {}
+
+/// A kind of key that looks like data, for [`DictionaryHeuristic`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyDetector {
+    /// A UUID in its hyphenated form, such as
+    /// `"67e55044-10b1-426f-9247-bb680e5fe0c8"`, in either case.
+    Uuid,
+
+    /// A date such as `"2024-03-01"`, possibly followed by a time after a
+    /// `T` or a space.
+    Date,
+
+    /// A number such as `"42"` or `"-1.5"`, without exponent.
+    Numeric,
+
+    /// A key longer than this many characters.
+    LongerThan(usize),
+}
+
+impl DictionaryHeuristic {
+    /// Whether the keys of `object` make it a dictionary.
+    pub(crate) fn detects(&self, object: &JsonMap) -> bool {
+        if object.is_empty() {
+            return false;
+        }
+        let matched = object
+            .keys()
+            .filter(|key| self.detectors.iter().any(|detector| detector.matches(key)))
+            .count();
+        matched as f32 / object.len() as f32 > self.threshold
+    }
+}
+
+impl KeyDetector {
+    fn matches(self, key: &str) -> bool {
+        match self {
+            KeyDetector::Uuid => is_uuid(key),
+            KeyDetector::Date => is_date(key),
+            KeyDetector::Numeric => is_numeric(key),
+            KeyDetector::LongerThan(max) => key.chars().count() > max,
+        }
+    }
+}
+
+fn is_uuid(key: &str) -> bool {
+    key.len() == 36
+        && key.bytes().enumerate().all(|(i, byte)| match i {
+            8 | 13 | 18 | 23 => byte == b'-',
+            _ => byte.is_ascii_hexdigit(),
+        })
+}
+
+fn is_date(key: &str) -> bool {
+    let bytes = key.as_bytes();
+    let is_digits = |range: core::ops::Range<usize>| bytes[range].iter().all(u8::is_ascii_digit);
+    bytes.len() >= 10
+        && is_digits(0..4)
+        && bytes[4] == b'-'
+        && is_digits(5..7)
+        && bytes[7] == b'-'
+        && is_digits(8..10)
+        && matches!(bytes.get(10), None | Some(b'T') | Some(b' '))
+}
+
+fn is_numeric(key: &str) -> bool {
+    let digits = key.strip_prefix('-').unwrap_or(key);
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (digits, None),
+    };
+    let all_digits = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    all_digits(whole) && fraction.is_none_or(all_digits)
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn converter(threshold: f32) -> Converter<'static> {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_dictionary_detection(DictionaryHeuristic {
+            threshold,
+            detectors: vec![KeyDetector::Uuid, KeyDetector::Date, KeyDetector::Numeric],
+        });
+        converter
+    }
+
+    #[test]
+    fn keeps_uuid_keys() {
+        let document = json!({
+            "usersById": {
+                "67e55044-10b1-426f-9247-bb680e5fe0c8": {"displayName": "Ada"},
+                "6F9619FF-8B86-D011-B42D-00C04FC964FF": {"displayName": "Grace"}
+            },
+            "totalsByDay": {"2024-03-01": 4, "2024-03-02T00:00:00Z": 5}
+        });
+        let converter = converter(0.5);
+
+        assert_eq!(
+            json!({
+                "users_by_id": {
+                    "67e55044-10b1-426f-9247-bb680e5fe0c8": {"display_name": "Ada"},
+                    "6F9619FF-8B86-D011-B42D-00C04FC964FF": {"display_name": "Grace"}
+                },
+                "totals_by_day": {"2024-03-01": 4, "2024-03-02T00:00:00Z": 5}
+            }),
+            converter.convert_value(document.clone()).unwrap()
+        );
+        assert_eq!(
+            vec!["/usersById", "/totalsByDay"],
+            converter.preflight(&document).detected_dictionaries
+        );
+    }
+
+    #[test]
+    fn converts_records() {
+        let document = json!({"userId": 1, "lastLogin": "2024-03-01", "scoreHistory": [1, 2]});
+        let converter = converter(0.5);
+
+        assert_eq!(
+            json!({"user_id": 1, "last_login": "2024-03-01", "score_history": [1, 2]}),
+            converter.convert_value(document.clone()).unwrap()
+        );
+        assert!(converter.preflight(&document).is_empty());
+    }
+
+    #[test]
+    fn fires_only_above_threshold() {
+        // Two keys of four look like data: exactly half.
+        let document = json!({"10": 1, "2024-03-01": 2, "pageSize": 3, "nextPage": 4});
+
+        assert_eq!(
+            json!({"10": 1, "2024_03_01": 2, "page_size": 3, "next_page": 4}),
+            converter(0.5).convert_value(document.clone()).unwrap()
+        );
+        assert_eq!(
+            document,
+            converter(0.49).convert_value(document.clone()).unwrap()
+        );
+
+        assert!(converter(0.49).check_value(&document).unwrap().is_empty());
+        assert_eq!(3, converter(0.5).check_value(&document).unwrap().len());
+    }
+}
@@ -0,0 +1,298 @@
+use crate::converter::expect_converted;
+use crate::prelude::*;
+use crate::Converter;
+use core::fmt::Write;
+use serde_json::Value;
+
+/// How [`Converter::render_diff`] shows what a conversion changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffStyle {
+    /// A unified diff of the pretty-printed documents, with three lines of
+    /// context.
+    Unified,
+
+    /// One `path: oldKey -> new_key` line per converted key, sorted by path.
+    Keys,
+}
+
+/// Lines of context around the changes of a unified diff.
+const CONTEXT: usize = 3;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+impl Converter<'_> {
+    /// Renders the changes from `original` to `converted`, the result of
+    /// converting it, for review. Nothing is rendered when nothing changed.
+    ///
+    /// [`DiffStyle::Keys`] lists what [`Converter::check_value`] reports for
+    /// `original`, so it matches what the conversion does; paths are those
+    /// of the original document.
+    ///
+    /// # Panics
+    ///
+    /// With [`DiffStyle::Keys`], panics if the options reject a key of
+    /// `original`, in which case it couldn't have been converted.
+    pub fn render_diff(&self, original: &Value, converted: &Value, style: DiffStyle) -> String {
+        self.render(original, converted, style, false)
+    }
+
+    /// Like [`Converter::render_diff`], coloring removed lines and old keys
+    /// in red, and added lines and new keys in green, for terminals.
+    pub fn render_colored_diff(
+        &self,
+        original: &Value,
+        converted: &Value,
+        style: DiffStyle,
+    ) -> String {
+        self.render(original, converted, style, true)
+    }
+
+    fn render(&self, original: &Value, converted: &Value, style: DiffStyle, color: bool) -> String {
+        let paint = |color_code: &'static str| if color { color_code } else { "" };
+        let reset = paint(RESET);
+        let mut out = String::new();
+
+        match style {
+            DiffStyle::Keys => {
+                let mut changes = expect_converted(self.check_value(original));
+                changes.sort_by(|a, b| a.path.cmp(&b.path));
+                for change in changes {
+                    let _ = write!(
+                        out,
+                        "{}: {}{}{} -> ",
+                        change.path,
+                        paint(RED),
+                        change.key,
+                        reset
+                    );
+                    let _ = match change.new_key {
+                        Some(new_key) => writeln!(out, "{}{}{}", paint(GREEN), new_key, reset),
+                        None => writeln!(out, "(dropped)"),
+                    };
+                }
+            }
+            DiffStyle::Unified => {
+                let old = pretty(original);
+                let new = pretty(converted);
+                let old: Vec<&str> = old.lines().collect();
+                let new: Vec<&str> = new.lines().collect();
+                let edits = diff_lines(&old, &new);
+                if edits.iter().all(|edit| matches!(edit, Edit::Equal(..))) {
+                    return out;
+                }
+
+                out.push_str("--- original\n+++ converted\n");
+                for hunk in hunks(&edits) {
+                    let (old_start, new_start) = positions(&edits[..hunk.start]);
+                    let hunk = &edits[hunk];
+                    let old_count = hunk
+                        .iter()
+                        .filter(|e| !matches!(e, Edit::Insert(_)))
+                        .count();
+                    let new_count = hunk
+                        .iter()
+                        .filter(|e| !matches!(e, Edit::Delete(_)))
+                        .count();
+                    let _ = writeln!(
+                        out,
+                        "{}@@ -{} +{} @@{}",
+                        paint(CYAN),
+                        range(old_start, old_count),
+                        range(new_start, new_count),
+                        reset
+                    );
+                    for edit in hunk {
+                        let _ = match *edit {
+                            Edit::Equal(i, _) => writeln!(out, " {}", old[i]),
+                            Edit::Delete(i) => writeln!(out, "{}-{}{}", paint(RED), old[i], reset),
+                            Edit::Insert(j) => {
+                                writeln!(out, "{}+{}{}", paint(GREEN), new[j], reset)
+                            }
+                        };
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// A step from the old lines to the new ones, with their indices.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// The shortest edit script from `old` to `new`, with Myers' algorithm.
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let index = |k: isize| (k + max + 1) as usize;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert(prev_y as usize));
+            } else {
+                edits.push(Edit::Delete(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+/// The ranges of `edits` shown as hunks: every change, with up to
+/// [`CONTEXT`] equal lines around it, merging hunks that would overlap.
+fn hunks(edits: &[Edit]) -> Vec<core::ops::Range<usize>> {
+    let mut hunks: Vec<core::ops::Range<usize>> = Vec::new();
+    for (at, edit) in edits.iter().enumerate() {
+        if matches!(edit, Edit::Equal(..)) {
+            continue;
+        }
+        let start = at.saturating_sub(CONTEXT);
+        let end = (at + 1 + CONTEXT).min(edits.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+    hunks
+}
+
+/// How many old and new lines come before `edits`.
+fn positions(edits: &[Edit]) -> (usize, usize) {
+    edits.iter().fold((0, 0), |(old, new), edit| match edit {
+        Edit::Equal(..) => (old + 1, new + 1),
+        Edit::Delete(_) => (old + 1, new),
+        Edit::Insert(_) => (old, new + 1),
+    })
+}
+
+/// A `start,count` range of a hunk header, where `start` is 1-based, or the
+/// line before an empty range.
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, count),
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn fixture() -> serde_json::Value {
+        json!({
+            "id": 7,
+            "userName": "Ada",
+            "tags": ["a", "b", "c", "d"],
+            "settings": {"theme": "dark", "fontSize": 12},
+            "zipCode": "1000"
+        })
+    }
+
+    #[test]
+    fn renders_unified_diff() {
+        let converter = Converter::new(KeyCase::Snake);
+        let converted = converter.convert_value(fixture()).unwrap();
+
+        assert_eq!(
+            "--- original\n\
+             +++ converted\n\
+             @@ -1,6 +1,6 @@\n \
+             {\n   \
+             \"id\": 7,\n\
+             -  \"userName\": \"Ada\",\n\
+             +  \"user_name\": \"Ada\",\n   \
+             \"tags\": [\n     \
+             \"a\",\n     \
+             \"b\",\n\
+             @@ -9,7 +9,7 @@\n   \
+             ],\n   \
+             \"settings\": {\n     \
+             \"theme\": \"dark\",\n\
+             -    \"fontSize\": 12\n\
+             +    \"font_size\": 12\n   \
+             },\n\
+             -  \"zipCode\": \"1000\"\n\
+             +  \"zip_code\": \"1000\"\n \
+             }\n",
+            converter.render_diff(&fixture(), &converted, DiffStyle::Unified)
+        );
+        assert_eq!(
+            "",
+            converter.render_diff(&converted, &converted, DiffStyle::Unified)
+        );
+    }
+
+    #[test]
+    fn renders_key_changes() {
+        let converter = Converter::new(KeyCase::Snake);
+        let converted = converter.convert_value(fixture()).unwrap();
+
+        assert_eq!(
+            "/settings/fontSize: fontSize -> font_size\n\
+             /userName: userName -> user_name\n\
+             /zipCode: zipCode -> zip_code\n",
+            converter.render_diff(&fixture(), &converted, DiffStyle::Keys)
+        );
+        assert_eq!(
+            "/settings/fontSize: \x1b[31mfontSize\x1b[0m -> \x1b[32mfont_size\x1b[0m\n\
+             /userName: \x1b[31muserName\x1b[0m -> \x1b[32muser_name\x1b[0m\n\
+             /zipCode: \x1b[31mzipCode\x1b[0m -> \x1b[32mzip_code\x1b[0m\n",
+            converter.render_colored_diff(&fixture(), &converted, DiffStyle::Keys)
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{Converter, Error};
+use serde_json::Value;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// What [`Converter::convert_dir`] converts, and where it writes.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct DirOptions {
+    /// The directory to walk.
+    pub input: PathBuf,
+
+    /// Which files to convert, as a glob matched against their path
+    /// relative to `input`, with `/` as separator. Defaults to
+    /// `**/*.json`.
+    pub glob: String,
+
+    pub output: DirOutput,
+
+    /// Indent the converted files.
+    pub pretty: bool,
+
+    /// Convert files on the `rayon` thread pool. Only has an effect with
+    /// the `parallel` feature.
+    pub parallel: bool,
+
+    /// Stop at the first file that fails, instead of going on with the
+    /// others.
+    pub fail_fast: bool,
+}
+
+/// Where [`Converter::convert_dir`] writes converted files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DirOutput {
+    /// Into this directory, at the same relative path as in the input. Files
+    /// whose keys don't change are written too.
+    Dir(PathBuf),
+
+    /// Over the input files, through a temporary file renamed over each
+    /// one, so that a file is never left half-written. Files whose keys
+    /// don't change are left alone. With `backup`, each file is first copied
+    /// next to itself with `.bak` appended to its name.
+    InPlace { backup: bool },
+}
+
+/// What [`Converter::convert_dir`] did, with paths as found under
+/// [`DirOptions::input`], each list sorted.
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct DirReport {
+    /// Files whose keys were converted.
+    pub converted: Vec<PathBuf>,
+
+    /// Files whose keys were already in the target case.
+    pub unchanged: Vec<PathBuf>,
+
+    /// Symbolic links matching the glob, which are neither followed nor
+    /// converted.
+    pub skipped: Vec<PathBuf>,
+
+    /// Files that couldn't be read, parsed, converted or written.
+    pub errors: Vec<(PathBuf, Error)>,
+}
+
+impl DirOptions {
+    /// Options converting the `**/*.json` files under `input` into `output`,
+    /// one file at a time, going on past failures.
+    pub fn new(input: impl Into<PathBuf>, output: DirOutput) -> Self {
+        DirOptions {
+            input: input.into(),
+            glob: "**/*.json".to_owned(),
+            output,
+            pretty: false,
+            parallel: false,
+            fail_fast: false,
+        }
+    }
+}
+
+impl DirReport {
+    /// Whether every file was converted or left unchanged.
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl Converter<'_> {
+    /// Converts every file under `options.input` matching `options.glob`,
+    /// and reports what became of each one.
+    ///
+    /// Directories are walked in file name order, without following
+    /// symbolic links; links are listed as skipped. Paths don't need to be
+    /// UTF-8: they are matched as the platform stores them and reported as
+    /// they are. A failing file is listed with its error, and the other
+    /// files are still converted unless `options.fail_fast` is set, in which
+    /// case files not converted yet are left out of the report.
+    ///
+    /// Only an invalid glob, or an `input` that can't be walked, fails the
+    /// whole call.
+    pub fn convert_dir(&self, options: DirOptions) -> Result<DirReport, Error> {
+        let glob = globset::GlobBuilder::new(&options.glob)
+            .literal_separator(true)
+            .build()
+            .map_err(|err| Error::InvalidOption {
+                option: "glob",
+                message: err.to_string(),
+            })?
+            .compile_matcher();
+
+        let mut report = DirReport::default();
+        let mut files = Vec::new();
+        for entry in walkdir::WalkDir::new(&options.input).sort_by_file_name() {
+            let entry = entry.map_err(|err| Error::Io(err.into()))?;
+            let relative = entry
+                .path()
+                .strip_prefix(&options.input)
+                .unwrap_or(entry.path());
+            if !glob.is_match(relative) {
+                continue;
+            }
+            if entry.path_is_symlink() {
+                report.skipped.push(entry.into_path());
+            } else if entry.file_type().is_file() {
+                files.push(entry.into_path());
+            }
+        }
+
+        let failed = AtomicBool::new(false);
+        let convert = |path: PathBuf| {
+            if options.fail_fast && failed.load(Ordering::Relaxed) {
+                return None;
+            }
+            let outcome = self.convert_dir_file(&path, &options);
+            if outcome.is_err() {
+                failed.store(true, Ordering::Relaxed);
+            }
+            Some((path, outcome))
+        };
+
+        #[cfg(feature = "parallel")]
+        let outcomes: Vec<_> = if options.parallel {
+            files.into_par_iter().map(convert).collect()
+        } else {
+            files.into_iter().map(convert).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let outcomes: Vec<_> = files.into_iter().map(convert).collect();
+
+        for (path, outcome) in outcomes.into_iter().flatten() {
+            match outcome {
+                Ok(true) => report.converted.push(path),
+                Ok(false) => report.unchanged.push(path),
+                Err(err) => report.errors.push((path, err)),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Converts the file at `path`, returning whether its keys changed.
+    fn convert_dir_file(&self, path: &Path, options: &DirOptions) -> Result<bool, Error> {
+        let text = fs::read_to_string(path).map_err(Error::Io)?;
+        let value: Value = serde_json::from_str(&text).map_err(Error::Parse)?;
+        let converted = self.convert_value(value.clone())?;
+        let changed = converted != value;
+
+        let mut output = if options.pretty {
+            serde_json::to_string_pretty(&converted)
+        } else {
+            serde_json::to_string(&converted)
+        }
+        .map_err(Error::Serialize)?;
+        if text.ends_with('\n') {
+            output.push('\n');
+        }
+
+        match &options.output {
+            DirOutput::Dir(root) => {
+                let relative = path.strip_prefix(&options.input).unwrap_or(path);
+                let target = root.join(relative);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent).map_err(Error::Io)?;
+                }
+                write_atomically(&target, &output)?;
+            }
+            DirOutput::InPlace { .. } if !changed => {}
+            DirOutput::InPlace { backup } => {
+                if *backup {
+                    fs::copy(path, with_suffix(path, ".bak")).map_err(Error::Io)?;
+                }
+                write_atomically(path, &output)?;
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Writes `contents` to a temporary file next to `path`, then renames it
+/// over `path`.
+fn write_atomically(path: &Path, contents: &str) -> Result<(), Error> {
+    let temporary = with_suffix(path, &format!(".{}.tmp", std::process::id()));
+    fs::write(&temporary, contents)
+        .and_then(|()| fs::rename(&temporary, path))
+        .map_err(|err| {
+            let _ = fs::remove_file(&temporary);
+            Error::Io(err)
+        })
+}
+
+/// `path` with `suffix` appended to its file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
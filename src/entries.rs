@@ -0,0 +1,231 @@
+use crate::converter::{expect_converted, Renames};
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{CaseChanger, Converter, Error};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::{Number, Value};
+
+/// A converted document whose objects are lists of members, so that members
+/// sharing a key, such as two keys converted to the same new key, are all
+/// kept, in document order.
+///
+/// It serializes as JSON with every member written, duplicates included.
+/// Converting it into a [`Value`] keeps, of members sharing a key, the value
+/// of the last one at the position of the first one, which is what
+/// [`Converter::convert_value`] itself does.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonEntries {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<JsonEntries>),
+    Object(Vec<(String, JsonEntries)>),
+}
+
+impl JsonEntries {
+    /// Whether an object of the tree has a key more than once.
+    pub fn has_duplicates(&self) -> bool {
+        match self {
+            JsonEntries::Array(items) => items.iter().any(JsonEntries::has_duplicates),
+            JsonEntries::Object(members) => {
+                members
+                    .iter()
+                    .enumerate()
+                    .any(|(i, (key, _))| members[..i].iter().any(|(earlier, _)| earlier == key))
+                    || members.iter().any(|(_, value)| value.has_duplicates())
+            }
+            _ => false,
+        }
+    }
+}
+
+impl From<Value> for JsonEntries {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => JsonEntries::Null,
+            Value::Bool(b) => JsonEntries::Bool(b),
+            Value::Number(n) => JsonEntries::Number(n),
+            Value::String(s) => JsonEntries::String(s),
+            Value::Array(items) => {
+                JsonEntries::Array(items.into_iter().map(JsonEntries::from).collect())
+            }
+            Value::Object(map) => JsonEntries::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, JsonEntries::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<JsonEntries> for Value {
+    fn from(entries: JsonEntries) -> Self {
+        match entries {
+            JsonEntries::Null => Value::Null,
+            JsonEntries::Bool(b) => Value::Bool(b),
+            JsonEntries::Number(n) => Value::Number(n),
+            JsonEntries::String(s) => Value::String(s),
+            JsonEntries::Array(items) => Value::Array(items.into_iter().map(Value::from).collect()),
+            JsonEntries::Object(members) => Value::Object(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Serialize for JsonEntries {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonEntries::Null => serializer.serialize_unit(),
+            JsonEntries::Bool(b) => serializer.serialize_bool(*b),
+            JsonEntries::Number(n) => n.serialize(serializer),
+            JsonEntries::String(s) => serializer.serialize_str(s),
+            JsonEntries::Array(items) => serializer.collect_seq(items),
+            JsonEntries::Object(members) => {
+                let mut map = serializer.serialize_map(Some(members.len()))?;
+                for (key, value) in members {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl Converter<'_> {
+    /// Converts the keys of `value` like [`Converter::convert_value`], but
+    /// keeps every member of an object whose key collides with another one
+    /// after conversion. Values of map-encoded arrays (see
+    /// [`Converter::with_pair_array_keys`]) are converted as usual.
+    pub fn convert_value_to_entries(&self, value: Value) -> Result<JsonEntries, Error> {
+        #[cfg(feature = "jsonpath")]
+        if let Some(selected) = self.select_jsonpaths(&value) {
+            return selected.convert_value_to_entries(value);
+        }
+        self.entries(value, &Path::Root, None)
+    }
+
+    fn entries(
+        &self,
+        value: Value,
+        path: &Path,
+        variant: Option<&Renames>,
+    ) -> Result<JsonEntries, Error> {
+        let object = match value {
+            Value::Array(items) => {
+                return items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, item)| self.entries(item, &path.index(index), variant))
+                    .collect::<Result<_, _>>()
+                    .map(JsonEntries::Array);
+            }
+            Value::Object(object) => object,
+            value => return Ok(value.into()),
+        };
+
+        let variant = self.object_variant(&object).or(variant);
+        let detected = self.is_detected_dictionary(&object);
+        let mut members = Vec::with_capacity(object.len());
+        for (key, value) in object {
+            let key_path = path.key(&key);
+            let new_key = if detected {
+                self.guard_key(&key, Some(key.clone()), &key_path)?
+            } else {
+                self.rename_variant_key(&key, variant, &key_path)?
+            };
+            let new_key = match new_key {
+                Some(new_key) => new_key,
+                None => continue,
+            };
+            let new_value = match (self.redaction(&key), value) {
+                (Some(rule), value) => rule.redact(&value).into(),
+                (None, Value::Array(rows)) if self.is_pair_array_key(&key) => self
+                    .convert_pair_rows_at(rows, &key_path, variant)
+                    .map(|rows| Value::Array(rows).into())?,
+                (None, value) => self.entries(value, &key_path, variant)?,
+            };
+            let new_value =
+                if self.redaction(&key).is_none() && self.is_transformed(&key, &key_path) {
+                    self.transform_value(&key, &key_path, new_value.into())?
+                        .into()
+                } else {
+                    new_value
+                };
+            members.push((new_key, new_value));
+        }
+        Ok(JsonEntries::Object(members))
+    }
+}
+
+impl CaseChanger<'_> {
+    /// Converts the input into a [`JsonEntries`] tree, keeping every member
+    /// of colliding keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the conversion fails, as described in
+    /// [`CaseChanger::convert`].
+    pub fn convert_to_entries(&mut self) -> JsonEntries {
+        expect_converted(
+            self.converter
+                .convert_value_to_entries(self.json_in.clone()),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_colliding_members() {
+        let mut changer = CaseChanger::new(
+            json!({"user_id": 1, "nested": [{"userId": 2, "UserID": 3}], "USER_ID": 4}),
+            KeyCase::Snake,
+        )
+        .unwrap();
+        let entries = changer.convert_to_entries();
+        assert_eq!(
+            JsonEntries::Object(vec![
+                ("user_id".to_owned(), JsonEntries::Number(1.into())),
+                (
+                    "nested".to_owned(),
+                    JsonEntries::Array(vec![JsonEntries::Object(vec![
+                        ("user_id".to_owned(), JsonEntries::Number(2.into())),
+                        ("user_id".to_owned(), JsonEntries::Number(3.into())),
+                    ])])
+                ),
+                ("user_id".to_owned(), JsonEntries::Number(4.into())),
+            ]),
+            entries
+        );
+        assert!(entries.has_duplicates());
+        assert_eq!(
+            r#"{"user_id":1,"nested":[{"user_id":2,"user_id":3}],"user_id":4}"#,
+            serde_json::to_string(&entries).unwrap()
+        );
+
+        // Back to a value, the last member wins, as in a plain conversion.
+        assert_eq!(changer.convert(), Value::from(entries));
+    }
+
+    #[test]
+    fn round_trips_without_duplicates() {
+        let value = json!({"firstName": "Ada", "tags": ["a", {"isAdmin": true}], "age": null});
+        let entries = Converter::new(KeyCase::Snake)
+            .convert_value_to_entries(value)
+            .unwrap();
+        assert!(!entries.has_duplicates());
+
+        let converted =
+            json!({"first_name": "Ada", "tags": ["a", {"is_admin": true}], "age": null});
+        assert_eq!(JsonEntries::from(converted.clone()), entries);
+        assert_eq!(converted, Value::from(entries));
+    }
+}
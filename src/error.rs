@@ -0,0 +1,405 @@
+use crate::prelude::*;
+use crate::{ConfigDiagnostic, DroppedKey, KeyCase, KeyCollision};
+use core::fmt;
+use serde_json::Value;
+
+/// Errors returned by fallible conversions, and by the other fallible
+/// methods of [`CaseChanger`](crate::CaseChanger) and [`Converter`](crate::Converter).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A key was empty, or became empty after trimming, while the empty key
+    /// policy is [`EmptyKeyPolicy::Error`](crate::EmptyKeyPolicy::Error).
+    EmptyKey {
+        /// JSON pointer to the entry holding the empty key.
+        path: String,
+    },
+
+    /// A new key is longer than the maximum set with
+    /// [`Converter::with_max_key_length`](crate::Converter::with_max_key_length),
+    /// under [`LengthPolicy::Error`](crate::LengthPolicy::Error).
+    KeyTooLong {
+        /// JSON pointer to the entry, with its original key.
+        path: String,
+
+        /// The new key.
+        key: String,
+        max: usize,
+    },
+
+    /// A validator rejected a new key, under
+    /// [`Converter::with_strict_validation`](crate::Converter::with_strict_validation).
+    InvalidKey {
+        /// JSON pointer to the entry, with its original key.
+        path: String,
+
+        /// The new key.
+        key: String,
+
+        /// What is wrong with it, as told by the validator.
+        message: String,
+    },
+
+    /// A key appeared twice in an object of a JSON text, or two of its keys
+    /// would be converted to the same new key, as checked by
+    /// [`Converter::parse_strict`](crate::Converter::parse_strict).
+    DuplicateKey {
+        /// JSON pointer to the later entry, with its original key.
+        path: String,
+        key: String,
+    },
+
+    /// A manual rename gives a key that the same table renames again, under
+    /// [`ChainPolicy::Error`](crate::ChainPolicy::Error).
+    RenameChain {
+        /// JSON pointer to the entry, with its original key.
+        path: String,
+
+        /// The original key, then each key it would be renamed to in turn.
+        chain: Vec<String>,
+    },
+
+    /// Following the manual renames of a key comes back to a key already
+    /// seen, unless renames aren't chained with
+    /// [`ChainPolicy::Single`](crate::ChainPolicy::Single).
+    RenameCycle {
+        /// JSON pointer to the entry, with its original key.
+        path: String,
+
+        /// The original key, then each key it would be renamed to in turn,
+        /// ending with the repeated one.
+        cycle: Vec<String>,
+    },
+
+    /// Keys of one object were converted to the same new key, under
+    /// [`CollisionStrategy::Error`](crate::CollisionStrategy::Error).
+    KeyCollision {
+        /// Every collision of the document, in the order the objects were
+        /// finished: inner objects come before the objects holding them.
+        collisions: Vec<KeyCollision>,
+    },
+
+    /// The document is not of the kind the method needs at its root.
+    InvalidRoot {
+        /// What was needed, such as `"an object"`.
+        expected: &'static str,
+
+        /// What the root is, such as `"an array"`.
+        found: &'static str,
+    },
+
+    /// A key is, or would be converted to, `__proto__`, `constructor` or
+    /// `prototype`, under [`GuardPolicy::Reject`](crate::GuardPolicy::Reject).
+    PrototypePollution {
+        /// JSON pointer to the entry, with its original key.
+        path: String,
+
+        /// The dangerous key, original or new.
+        key: String,
+    },
+
+    /// A function set with
+    /// [`Converter::with_value_transform`](crate::Converter::with_value_transform)
+    /// rejected a value.
+    ValueTransform {
+        /// JSON pointer to the entry, with its original key.
+        path: String,
+
+        /// What is wrong with the value, as told by the function.
+        message: String,
+    },
+
+    /// The input of a typed conversion could not be serialized to JSON.
+    Serialize(serde_json::Error),
+
+    /// The converted document could not be deserialized into the target type.
+    Deserialize {
+        /// JSON pointer, in the converted document, to the value that failed.
+        path: String,
+        source: serde_json::Error,
+
+        /// Entries the conversion left out of the object at `path`, or of its
+        /// parent, which may be why deserialization failed.
+        dropped: Vec<DroppedKey>,
+    },
+
+    /// A string didn't name any [`KeyCase`].
+    UnknownCase {
+        /// The string that was parsed.
+        name: String,
+
+        /// The case that was probably meant, if the name is close to one.
+        suggestion: Option<KeyCase>,
+    },
+
+    /// A table of manual renames is malformed.
+    InvalidRenames {
+        /// JSON pointer, in the table, to the offending value.
+        path: String,
+        message: String,
+    },
+
+    /// A JSON Patch document is malformed.
+    InvalidPatch {
+        /// JSON pointer, in the patch, to the offending value.
+        path: String,
+        message: String,
+    },
+
+    /// A file could not be read.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+
+    /// A file is not valid JSON.
+    Parse(serde_json::Error),
+
+    /// A YAML document could not be parsed or written.
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Error),
+
+    /// A TOML document could not be parsed.
+    #[cfg(feature = "toml")]
+    TomlParse(toml::de::Error),
+
+    /// A converted TOML document could not be written.
+    #[cfg(feature = "toml")]
+    TomlWrite(toml::ser::Error),
+
+    /// A MessagePack document could not be read, converted or written.
+    #[cfg(feature = "msgpack")]
+    MsgPack(rmp_serde::encode::Error),
+
+    /// A CBOR document could not be parsed.
+    #[cfg(feature = "cbor")]
+    CborParse(ciborium::de::Error<std::io::Error>),
+
+    /// A converted CBOR document could not be written.
+    #[cfg(feature = "cbor")]
+    CborWrite(ciborium::ser::Error<std::io::Error>),
+
+    /// A JSON5 document could not be parsed.
+    #[cfg(feature = "json5")]
+    Json5(json5::Error),
+
+    /// A body marked as base64-encoded could not be decoded.
+    #[cfg(feature = "lambda")]
+    Base64(base64::DecodeError),
+
+    /// An HTTP request failed, or its response couldn't be read.
+    #[cfg(feature = "reqwest")]
+    Http(reqwest::Error),
+
+    /// An option of a [`ConversionSpec`](crate::ConversionSpec) has a value
+    /// that can't be used.
+    InvalidOption {
+        /// The name of the option in the spec.
+        option: &'static str,
+        message: String,
+    },
+
+    /// A [`ConversionSpec`](crate::ConversionSpec) has options that can't be
+    /// used, every one of them reported.
+    InvalidConfig { diagnostics: Vec<ConfigDiagnostic> },
+
+    /// A [`Registry`](crate::Registry) was set a second time.
+    #[cfg(feature = "std")]
+    AlreadySet,
+
+    /// A [`Registry`](crate::Registry) was used before being set.
+    #[cfg(feature = "std")]
+    NotSet,
+}
+
+impl Error {
+    /// An [`Error::InvalidRoot`] for `root`, when `expected` was needed.
+    pub(crate) fn invalid_root(expected: &'static str, root: &Value) -> Self {
+        let found = match root {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Number(_) => "a number",
+            Value::String(_) => "a string",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object",
+        };
+        Error::InvalidRoot { expected, found }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::EmptyKey { path } => write!(f, "empty key at `{}`", path),
+            Error::KeyTooLong { path, key, max } => write!(
+                f,
+                "key `{}` at `{}` is longer than {} bytes",
+                key, path, max
+            ),
+            Error::InvalidKey { path, key, message } => {
+                write!(f, "key `{}` at `{}` {}", key, path, message)
+            }
+            Error::DuplicateKey { path, key } => {
+                write!(f, "duplicate key `{}` at `{}`", key, path)
+            }
+            Error::RenameChain { path, chain } => write!(
+                f,
+                "key at `{}` is renamed more than once: `{}`",
+                path,
+                chain.join("` -> `")
+            ),
+            Error::RenameCycle { path, cycle } => write!(
+                f,
+                "renames of the key at `{}` form a cycle: `{}`",
+                path,
+                cycle.join("` -> `")
+            ),
+            Error::KeyCollision { collisions } => {
+                write!(f, "keys collide after conversion: ")?;
+                for (i, collision) in collisions.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "`{}` ", collision.keys.join("`, `"))?;
+                    if collision.path.is_empty() {
+                        write!(f, "in the root object")?;
+                    } else {
+                        write!(f, "at `{}`", collision.path)?;
+                    }
+                    write!(f, " to `{}`", collision.new_key)?;
+                }
+                Ok(())
+            }
+            Error::InvalidRoot { expected, found } => {
+                write!(f, "expected {} at the root, found {}", expected, found)
+            }
+            Error::PrototypePollution { path, key } => write!(
+                f,
+                "the key at `{}` is rejected by the prototype pollution guard: `{}`",
+                path, key
+            ),
+            Error::ValueTransform { path, message } => {
+                write!(
+                    f,
+                    "failed to transform the value at `{}`: {}",
+                    path, message
+                )
+            }
+            Error::Serialize(err) => write!(f, "failed to serialize input: {}", err),
+            Error::Deserialize {
+                path,
+                source,
+                dropped,
+            } => {
+                write!(f, "failed to deserialize `{}`: {}", path, source)?;
+                for (i, dropped_key) in dropped.iter().enumerate() {
+                    let sep = if i == 0 { " (note: " } else { "; " };
+                    write!(f, "{}{}", sep, dropped_key)?;
+                }
+                if !dropped.is_empty() {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Error::InvalidRenames { path, message } => {
+                write!(f, "invalid manual renames at `{}`: {}", path, message)
+            }
+            Error::InvalidPatch { path, message } => {
+                write!(f, "invalid JSON Patch at `{}`: {}", path, message)
+            }
+            #[cfg(feature = "std")]
+            Error::Io(err) => write!(f, "failed to read file: {}", err),
+            Error::Parse(err) => write!(f, "failed to parse JSON: {}", err),
+            #[cfg(feature = "json5")]
+            Error::Json5(err) => write!(f, "invalid JSON5: {}", err),
+            #[cfg(feature = "lambda")]
+            Error::Base64(err) => write!(f, "invalid base64 body: {}", err),
+            #[cfg(feature = "yaml")]
+            Error::Yaml(err) => write!(f, "invalid YAML: {}", err),
+            #[cfg(feature = "toml")]
+            Error::TomlParse(err) => write!(f, "invalid TOML: {}", err),
+            #[cfg(feature = "toml")]
+            Error::TomlWrite(err) => write!(f, "failed to write TOML: {}", err),
+            #[cfg(feature = "msgpack")]
+            Error::MsgPack(err) => write!(f, "failed to convert MessagePack: {}", err),
+            #[cfg(feature = "cbor")]
+            Error::CborParse(err) => write!(f, "invalid CBOR: {}", err),
+            #[cfg(feature = "cbor")]
+            Error::CborWrite(err) => write!(f, "failed to write CBOR: {}", err),
+            #[cfg(feature = "reqwest")]
+            Error::Http(err) => write!(f, "HTTP request failed: {}", err),
+            Error::InvalidOption { option, message } => {
+                write!(f, "invalid `{}`: {}", option, message)
+            }
+            Error::InvalidConfig { diagnostics } => {
+                write!(f, "invalid configuration")?;
+                for (i, diagnostic) in diagnostics.iter().enumerate() {
+                    let sep = if i == 0 { ": " } else { "; " };
+                    write!(f, "{}{}", sep, diagnostic)?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "std")]
+            Error::AlreadySet => write!(f, "the converters are already set"),
+            #[cfg(feature = "std")]
+            Error::NotSet => write!(f, "the converters are not set"),
+            Error::UnknownCase { name, suggestion } => {
+                write!(f, "unknown case `{}`, ", name)?;
+                match suggestion {
+                    Some(case) => write!(f, "did you mean `{}`? Expected one of: ", case)?,
+                    None => write!(f, "expected one of: ")?,
+                }
+                for (i, case) in KeyCase::all().iter().enumerate() {
+                    let sep = if i == 0 { "" } else { ", " };
+                    write!(f, "{}`{}`", sep, case)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::EmptyKey { .. }
+            | Error::KeyTooLong { .. }
+            | Error::InvalidKey { .. }
+            | Error::DuplicateKey { .. }
+            | Error::RenameChain { .. }
+            | Error::RenameCycle { .. }
+            | Error::KeyCollision { .. }
+            | Error::InvalidRoot { .. }
+            | Error::PrototypePollution { .. }
+            | Error::ValueTransform { .. }
+            | Error::UnknownCase { .. }
+            | Error::InvalidOption { .. }
+            | Error::InvalidConfig { .. }
+            | Error::InvalidRenames { .. }
+            | Error::InvalidPatch { .. }
+            | Error::AlreadySet
+            | Error::NotSet => None,
+            Error::Io(err) => Some(err),
+            #[cfg(feature = "json5")]
+            Error::Json5(err) => Some(err),
+            #[cfg(feature = "lambda")]
+            Error::Base64(err) => Some(err),
+            #[cfg(feature = "yaml")]
+            Error::Yaml(err) => Some(err),
+            #[cfg(feature = "toml")]
+            Error::TomlParse(err) => Some(err),
+            #[cfg(feature = "toml")]
+            Error::TomlWrite(err) => Some(err),
+            #[cfg(feature = "msgpack")]
+            Error::MsgPack(err) => Some(err),
+            #[cfg(feature = "cbor")]
+            Error::CborParse(err) => Some(err),
+            #[cfg(feature = "cbor")]
+            Error::CborWrite(err) => Some(err),
+            #[cfg(feature = "reqwest")]
+            Error::Http(err) => Some(err),
+            Error::Serialize(err) | Error::Parse(err) | Error::Deserialize { source: err, .. } => {
+                Some(err)
+            }
+        }
+    }
+}
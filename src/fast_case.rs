@@ -0,0 +1,334 @@
+//! Case conversion of keys without going through `convert_case`, which
+//! links `std` and allocates a string per word.
+//!
+//! Keys are split into words as `convert_case` 0.4 splits them: on
+//! underscores, hyphens and spaces, between a lowercase and an uppercase
+//! letter, between a digit and a letter either way, and before the last
+//! capital of an acronym followed by a lowercase letter, as in `HTTPServer`.
+//! Unlike `convert_case`, which mixes up character and byte offsets, keys
+//! with non-ASCII letters are split on characters.
+
+use crate::prelude::*;
+use crate::KeyCase;
+
+/// How the letters of a word are written.
+#[derive(Clone, Copy)]
+enum Word {
+    Lower,
+    Upper,
+    Capitalized,
+    Toggled,
+}
+
+/// Converts `key` to `case`, taking the fast path when it applies. No option
+/// of a converter changes how keys are split into words, so it gives the
+/// same result as `convert_case` for every configuration; an option that
+/// did would have to bypass it.
+pub(crate) fn to_case(key: &str, case: KeyCase) -> String {
+    ascii_to_case(key, case).unwrap_or_else(|| words_to_case(key, case))
+}
+
+/// Converts `key` to `case` word by word, for any key and case.
+fn words_to_case(key: &str, case: KeyCase) -> String {
+    let (separator, first, rest) = match case {
+        KeyCase::Upper => (" ", Word::Upper, Word::Upper),
+        KeyCase::Lower => (" ", Word::Lower, Word::Lower),
+        KeyCase::Title => (" ", Word::Capitalized, Word::Capitalized),
+        KeyCase::Toggle => (" ", Word::Toggled, Word::Toggled),
+        KeyCase::Camel => ("", Word::Lower, Word::Capitalized),
+        KeyCase::Pascal => ("", Word::Capitalized, Word::Capitalized),
+        KeyCase::Snake => ("_", Word::Lower, Word::Lower),
+        KeyCase::ScreamingSnake => ("_", Word::Upper, Word::Upper),
+        KeyCase::Kebab => ("-", Word::Lower, Word::Lower),
+        KeyCase::Cobol => ("-", Word::Upper, Word::Upper),
+        KeyCase::Train => ("-", Word::Capitalized, Word::Capitalized),
+        KeyCase::Flat => ("", Word::Lower, Word::Lower),
+        KeyCase::UpperFlat => ("", Word::Upper, Word::Upper),
+        KeyCase::Alternating => return alternating(key),
+    };
+
+    let mut new_key = String::with_capacity(key.len() + 4);
+    for (i, word) in words(key).enumerate() {
+        let style = if i == 0 {
+            first
+        } else {
+            new_key.push_str(separator);
+            rest
+        };
+        match style {
+            Word::Lower => new_key.push_str(&word.to_lowercase()),
+            Word::Upper => new_key.push_str(&word.to_uppercase()),
+            Word::Capitalized | Word::Toggled => {
+                let mut chars = word.chars();
+                if let Some(c) = chars.next() {
+                    if let Word::Capitalized = style {
+                        new_key.extend(c.to_uppercase());
+                        new_key.push_str(&chars.as_str().to_lowercase());
+                    } else {
+                        new_key.extend(c.to_lowercase());
+                        new_key.push_str(&chars.as_str().to_uppercase());
+                    }
+                }
+            }
+        }
+    }
+    new_key
+}
+
+/// `key` in alternating case: words joined by spaces, with letters
+/// alternately lowercase and uppercase across words, starting lowercase.
+fn alternating(key: &str) -> String {
+    let mut new_key = String::with_capacity(key.len() + 4);
+    let mut upper = false;
+    for (i, word) in words(key).enumerate() {
+        if i > 0 {
+            new_key.push(' ');
+        }
+        for c in word.chars() {
+            if !c.is_uppercase() && !c.is_lowercase() {
+                new_key.push(c);
+            } else if upper {
+                new_key.extend(c.to_uppercase());
+            } else {
+                new_key.extend(c.to_lowercase());
+            }
+            upper ^= c.is_uppercase() || c.is_lowercase();
+        }
+    }
+    new_key
+}
+
+/// The words of `key`, split on separators and within each run of other
+/// characters.
+fn words(key: &str) -> impl Iterator<Item = &str> {
+    key.split(['-', '_', ' '])
+        .flat_map(split_camel)
+        .filter(|word| !word.is_empty())
+}
+
+/// Splits `segment`, which holds no separator, where a new word starts.
+fn split_camel(segment: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = segment.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = 0;
+    for i in 1..chars.len() {
+        let ((offset, at), before) = (chars[i], chars[i - 1].1);
+        let ends_acronym = chars.get(i + 1).is_some_and(|(_, after)| {
+            before.is_uppercase() && at.is_uppercase() && after.is_lowercase()
+        });
+        if ends_acronym || starts_word(before, at) {
+            words.push(&segment[start..offset]);
+            start = offset;
+        }
+    }
+    words.push(&segment[start..]);
+    words
+}
+
+/// Whether `at` starts a new word after `before`, without looking further.
+fn starts_word(before: char, at: char) -> bool {
+    let other = |c: char| !c.is_ascii_digit() && !c.is_ascii_punctuation();
+    (before.is_lowercase() && at.is_uppercase())
+        || (before.is_ascii_digit() && other(at))
+        || (other(before) && at.is_ascii_digit())
+}
+
+/// Converts `key` to `case` in one pass, or returns `None` when the key or
+/// the case is not one the fast path handles: only keys made of ASCII
+/// letters, digits and underscores, converted to snake, screaming snake,
+/// camel or Pascal case.
+pub(crate) fn ascii_to_case(key: &str, case: KeyCase) -> Option<String> {
+    let (separator, first, rest) = match case {
+        KeyCase::Snake => (Some('_'), Word::Lower, Word::Lower),
+        KeyCase::ScreamingSnake => (Some('_'), Word::Upper, Word::Upper),
+        KeyCase::Camel => (None, Word::Lower, Word::Capitalized),
+        KeyCase::Pascal => (None, Word::Capitalized, Word::Capitalized),
+        _ => return None,
+    };
+    if !key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_') {
+        return None;
+    }
+
+    let mut new_key = String::with_capacity(key.len() + 4);
+    let mut style = first;
+    for segment in key.split('_').filter(|segment| !segment.is_empty()) {
+        let bytes = segment.as_bytes();
+        let mut start = 0;
+        for end in 1..=bytes.len() {
+            if end < bytes.len() && !is_boundary(bytes, end) {
+                continue;
+            }
+            if let (Some(separator), false) = (separator, new_key.is_empty()) {
+                new_key.push(separator);
+            }
+            push_word(&mut new_key, &segment[start..end], style);
+            style = rest;
+            start = end;
+        }
+    }
+    Some(new_key)
+}
+
+/// Whether a new word starts at `index` of `word`, which holds ASCII
+/// letters and digits only.
+fn is_boundary(word: &[u8], index: usize) -> bool {
+    let (before, at) = (word[index - 1], word[index]);
+    let ends_acronym = word.get(index + 1).is_some_and(|after| {
+        before.is_ascii_uppercase() && at.is_ascii_uppercase() && after.is_ascii_lowercase()
+    });
+    ends_acronym
+        || (before.is_ascii_lowercase() && at.is_ascii_uppercase())
+        || (before.is_ascii_digit() != at.is_ascii_digit())
+}
+
+fn push_word(new_key: &mut String, word: &str, style: Word) {
+    for (i, c) in word.chars().enumerate() {
+        new_key.push(match style {
+            Word::Upper => c.to_ascii_uppercase(),
+            Word::Capitalized if i == 0 => c.to_ascii_uppercase(),
+            Word::Toggled if i > 0 => c.to_ascii_uppercase(),
+            Word::Lower | Word::Capitalized | Word::Toggled => c.to_ascii_lowercase(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ascii_to_case, to_case};
+    use crate::*;
+    #[cfg(feature = "std")]
+    use convert_case::Casing;
+
+    #[cfg(feature = "std")]
+    const CASES: &[KeyCase] = &[
+        KeyCase::Snake,
+        KeyCase::ScreamingSnake,
+        KeyCase::Camel,
+        KeyCase::Pascal,
+    ];
+
+    #[cfg(feature = "std")]
+    fn assert_same(key: &str) {
+        for &case in CASES {
+            assert_eq!(
+                Some(key.to_case(case.into())),
+                ascii_to_case(key, case),
+                "{:?} to {}",
+                key,
+                case
+            );
+        }
+    }
+
+    /// Every key of up to six characters taken from letters of both cases,
+    /// digits and underscores, which covers every pair and triple of
+    /// character classes the word splitting looks at.
+    #[test]
+    #[cfg(feature = "std")]
+    fn matches_convert_case_on_short_keys() {
+        const ALPHABET: &[u8] = b"aZb_Y09";
+        let mut key = Vec::new();
+        fn extend(key: &mut Vec<u8>, len: usize) {
+            assert_same(core::str::from_utf8(key).unwrap());
+            if key.len() == len {
+                return;
+            }
+            for &c in ALPHABET {
+                key.push(c);
+                extend(key, len);
+                key.pop();
+            }
+        }
+        extend(&mut key, 6);
+    }
+
+    /// Longer keys drawn at random from every character the fast path
+    /// accepts, and from realistic field names.
+    #[test]
+    #[cfg(feature = "std")]
+    fn matches_convert_case_on_long_keys() {
+        const ALPHABET: &[u8] = b"abcxyzABCXYZ0123456789________";
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..20_000 {
+            let len = (next() % 32) as usize;
+            let key: String = (0..len)
+                .map(|_| ALPHABET[(next() % ALPHABET.len() as u64) as usize] as char)
+                .collect();
+            assert_same(&key);
+        }
+
+        for key in &[
+            "userId",
+            "user_id",
+            "UserID",
+            "HTTPServerError",
+            "XMLHttpRequest",
+            "ipv4Address",
+            "IPv6",
+            "oauth2Token",
+            "SHA256Sum",
+            "__private__field__",
+            "ABC",
+            "a1b2C3",
+        ] {
+            assert_same(key);
+        }
+    }
+
+    /// Every key of up to five characters taken from letters, digits,
+    /// separators and punctuation, converted to every case.
+    #[test]
+    #[cfg(feature = "std")]
+    fn matches_convert_case_in_every_case() {
+        const ALPHABET: &[u8] = b"aZY9_- .";
+        let mut key = Vec::new();
+        fn extend(key: &mut Vec<u8>, len: usize) {
+            let text = core::str::from_utf8(key).unwrap();
+            for &case in KeyCase::all() {
+                assert_eq!(
+                    text.to_case(case.into()),
+                    to_case(text, case),
+                    "{:?} to {}",
+                    text,
+                    case
+                );
+            }
+            if key.len() == len {
+                return;
+            }
+            for &c in ALPHABET {
+                key.push(c);
+                extend(key, len);
+                key.pop();
+            }
+        }
+        extend(&mut key, 5);
+    }
+
+    #[test]
+    fn splits_non_ascii_keys_on_characters() {
+        assert_eq!("naïve_key", to_case("naïveKey", KeyCase::Snake));
+        assert_eq!("aé_b", to_case("aéB", KeyCase::Snake));
+        assert_eq!(
+            "über-http-server",
+            to_case("ÜberHTTPServer", KeyCase::Kebab)
+        );
+        assert_eq!("Straße Nr 2", to_case("straße_nr2", KeyCase::Title));
+        assert_eq!("ä B ä B", to_case("ä-b Ä b", KeyCase::Alternating));
+    }
+
+    #[test]
+    fn falls_back_on_other_keys_and_cases() {
+        assert_eq!(None, ascii_to_case("user-id", KeyCase::Snake));
+        assert_eq!(None, ascii_to_case("user id", KeyCase::Camel));
+        assert_eq!(None, ascii_to_case("naïve", KeyCase::Snake));
+        assert_eq!(None, ascii_to_case("userId", KeyCase::Kebab));
+        assert_eq!("user-id", super::to_case("userId", KeyCase::Kebab));
+    }
+}
@@ -0,0 +1,214 @@
+//! A C API over [`Converter`], for embedding conversions in other languages.
+//!
+//! Strings cross the boundary as NUL-terminated UTF-8. Strings returned by
+//! the library must be released with [`jkcc_string_free`], and converters with
+//! [`jkcc_converter_free`]. No function lets a panic unwind into the caller.
+
+use crate::prelude::*;
+use crate::{ConversionSpec, Converter, Error};
+use core::ptr;
+use serde_json::Value;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+
+/// A converter built by [`jkcc_converter_new`].
+pub struct JkccConverter(Converter<'static>);
+
+/// What a call returned.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JkccStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer was null.
+    NullArgument = 1,
+    /// An input string was not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// The input document was not valid JSON.
+    InvalidJson = 3,
+    /// A key was rejected by the converter's options.
+    ConversionFailed = 4,
+    /// The library panicked; this is a bug.
+    Panic = 5,
+}
+
+/// Builds a converter from a [`ConversionSpec`] written as JSON, such as
+/// `{"case": "snake_case"}`. Returns null if `spec_json` is null, or is not a
+/// valid spec.
+///
+/// # Safety
+///
+/// `spec_json` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn jkcc_converter_new(spec_json: *const c_char) -> *mut JkccConverter {
+    let result = panic::catch_unwind(|| {
+        let spec = read_str(spec_json).ok()?;
+        let spec: ConversionSpec = serde_json::from_str(spec).ok()?;
+        Converter::from_spec(spec).ok()
+    });
+    match result {
+        Ok(Some(converter)) => Box::into_raw(Box::new(JkccConverter(converter))),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Converts the keys of the JSON document `json`.
+///
+/// On success, stores the converted document in `*out`. Otherwise stores a
+/// message explaining the failure in `*err`, if `err` is not null. Whatever is
+/// stored must be released with [`jkcc_string_free`].
+///
+/// # Safety
+///
+/// `converter` must be null or come from [`jkcc_converter_new`] and not have
+/// been freed. `json` must be null or point to a NUL-terminated string, and
+/// `out` and `err` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn jkcc_convert(
+    converter: *const JkccConverter,
+    json: *const c_char,
+    out: *mut *mut c_char,
+    err: *mut *mut c_char,
+) -> JkccStatus {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        if converter.is_null() || out.is_null() {
+            return Err((JkccStatus::NullArgument, "null argument".to_owned()));
+        }
+        let json = read_str(json)?;
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| (JkccStatus::InvalidJson, Error::Parse(e).to_string()))?;
+        let converted = (*converter)
+            .0
+            .convert_value(value)
+            .map_err(|e| (JkccStatus::ConversionFailed, e.to_string()))?;
+        Ok(converted.to_string())
+    }));
+
+    let (status, message) = match result {
+        Ok(Ok(converted)) => {
+            *out = into_c_string(converted);
+            return JkccStatus::Ok;
+        }
+        Ok(Err(failure)) => failure,
+        Err(_) => (JkccStatus::Panic, "panicked while converting".to_owned()),
+    };
+    if !err.is_null() {
+        *err = into_c_string(message);
+    }
+    status
+}
+
+/// Releases a string returned by the library. Does nothing if `s` is null.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by the library that has not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn jkcc_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Releases a converter. Does nothing if `converter` is null.
+///
+/// # Safety
+///
+/// `converter` must be null or come from [`jkcc_converter_new`] and not have
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn jkcc_converter_free(converter: *mut JkccConverter) {
+    if !converter.is_null() {
+        drop(Box::from_raw(converter));
+    }
+}
+
+unsafe fn read_str<'s>(s: *const c_char) -> Result<&'s str, (JkccStatus, String)> {
+    if s.is_null() {
+        return Err((JkccStatus::NullArgument, "null argument".to_owned()));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|e| (JkccStatus::InvalidUtf8, format!("invalid UTF-8: {}", e)))
+}
+
+/// Hands `s` over to the caller. JSON and error messages never contain NUL
+/// bytes, but if one does, it is cut there.
+fn into_c_string(s: String) -> *mut c_char {
+    let s = match CString::new(s) {
+        Ok(s) => s,
+        Err(e) => {
+            let end = e.nul_position();
+            let mut bytes = e.into_vec();
+            bytes.truncate(end);
+            CString::new(bytes).expect("cut at the first NUL")
+        }
+    };
+    s.into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    unsafe fn take(s: *mut c_char) -> String {
+        let owned = CStr::from_ptr(s).to_str().unwrap().to_owned();
+        jkcc_string_free(s);
+        owned
+    }
+
+    #[test]
+    fn converts_through_the_c_abi() {
+        unsafe {
+            let converter = jkcc_converter_new(c(r#"{"case": "snake_case"}"#).as_ptr());
+            assert!(!converter.is_null());
+
+            let mut out = ptr::null_mut();
+            let mut err = ptr::null_mut();
+            let status = jkcc_convert(
+                converter,
+                c(r#"{"userName": {"zipCode": 1}}"#).as_ptr(),
+                &mut out,
+                &mut err,
+            );
+            assert_eq!(JkccStatus::Ok, status);
+            assert!(err.is_null());
+            assert_eq!(r#"{"user_name":{"zip_code":1}}"#, take(out));
+
+            jkcc_converter_free(converter);
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        unsafe {
+            assert!(jkcc_converter_new(c(r#"{"case": "snek"}"#).as_ptr()).is_null());
+            assert!(jkcc_converter_new(ptr::null()).is_null());
+
+            let spec = c(r#"{"case": "camel", "empty_key_policy": "error"}"#);
+            let converter = jkcc_converter_new(spec.as_ptr());
+            let mut out = ptr::null_mut();
+            let mut err = ptr::null_mut();
+
+            let status = jkcc_convert(converter, c(r#"{"a": "#).as_ptr(), &mut out, &mut err);
+            assert_eq!(JkccStatus::InvalidJson, status);
+            assert!(out.is_null());
+            assert!(take(err).starts_with("failed to parse JSON: "));
+
+            let status = jkcc_convert(converter, c(r#"{"": 1}"#).as_ptr(), &mut out, &mut err);
+            assert_eq!(JkccStatus::ConversionFailed, status);
+            assert_eq!("empty key at `/`", take(err));
+
+            let status = jkcc_convert(converter, ptr::null(), &mut out, ptr::null_mut());
+            assert_eq!(JkccStatus::NullArgument, status);
+
+            jkcc_converter_free(converter);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error, KeyCase};
+use ::figment::value::{Dict, Map, Value};
+use ::figment::{Metadata, Profile, Provider};
+
+/// A `figment` [`Provider`] converting the keys of the data of another
+/// provider, before it is merged.
+///
+/// Profile names are kept, and so are the tags of values, so errors still
+/// point to where a value came from. Options scoped by pointer see each
+/// profile as a document of its own.
+///
+/// ```
+/// # use json_keys_case_changer::{CaseNormalized, KeyCase};
+/// use figment::providers::{Format, Toml};
+/// use figment::Figment;
+///
+/// #[derive(serde::Deserialize)]
+/// struct Config {
+///     max_connections: u32,
+/// }
+///
+/// let toml = Toml::string("maxConnections = 8");
+/// let config: Config = Figment::new()
+///     .merge(CaseNormalized::new(toml, KeyCase::Snake))
+///     .extract()
+///     .unwrap();
+/// assert_eq!(8, config.max_connections);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CaseNormalized<'c, P> {
+    inner: P,
+    converter: Converter<'c>,
+}
+
+impl<P: Provider> CaseNormalized<'static, P> {
+    /// Converts the keys of `inner` to `case`.
+    pub fn new(inner: P, case: KeyCase) -> Self {
+        CaseNormalized::with_converter(inner, Converter::new(case))
+    }
+}
+
+impl<'c, P: Provider> CaseNormalized<'c, P> {
+    /// Converts the keys of `inner` with the options of `converter`, such as
+    /// manual renames and dictionary keys.
+    pub fn with_converter(inner: P, converter: Converter<'c>) -> Self {
+        CaseNormalized { inner, converter }
+    }
+}
+
+impl<P: Provider> Provider for CaseNormalized<'_, P> {
+    fn metadata(&self) -> Metadata {
+        self.inner.metadata()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, ::figment::Error> {
+        self.inner
+            .data()?
+            .into_iter()
+            .map(|(profile, dict)| {
+                let dict = self.converter.convert_figment_dict(dict, &Path::Root)?;
+                Ok((profile, dict))
+            })
+            .collect::<Result<_, Error>>()
+            .map_err(|err| ::figment::Error::from(err.to_string()))
+    }
+
+    fn profile(&self) -> Option<Profile> {
+        self.inner.profile()
+    }
+}
+
+impl Converter<'_> {
+    fn convert_figment_dict(&self, dict: Dict, path: &Path) -> Result<Dict, Error> {
+        let mut new_dict = Dict::new();
+        for (key, value) in dict {
+            let key_path = path.key(&key);
+            if let Some(new_key) = self.rename_key(&key, &key_path)? {
+                new_dict.insert(new_key, self.convert_figment(value, &key_path)?);
+            }
+        }
+        Ok(new_dict)
+    }
+
+    fn convert_figment(&self, value: Value, path: &Path) -> Result<Value, Error> {
+        match value {
+            Value::Dict(tag, dict) => self
+                .convert_figment_dict(dict, path)
+                .map(|dict| Value::Dict(tag, dict)),
+            Value::Array(tag, array) => array
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| self.convert_figment(value, &path.index(index)))
+                .collect::<Result<_, _>>()
+                .map(|array| Value::Array(tag, array)),
+            value => Ok(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use ::figment::providers::{Data, Format, Toml};
+    use ::figment::Figment;
+    use serde::Deserialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct ServiceConfig {
+        service_name: String,
+        max_connections: u32,
+        http_server: HttpServer,
+        env_overrides: BTreeMap<String, String>,
+        upstream_hosts: Vec<UpstreamHost>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct HttpServer {
+        bind_address: String,
+        request_timeout_secs: u64,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct UpstreamHost {
+        host_name: String,
+        port_number: u16,
+    }
+
+    fn fixture() -> Data<Toml> {
+        let path: std::path::PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "tests",
+            "fixtures",
+            "service.toml",
+        ]
+        .iter()
+        .collect();
+        Toml::file(path)
+    }
+
+    #[test]
+    fn loads_camel_case_config() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_dictionary_keys(vec!["envOverrides"]);
+
+        let config: ServiceConfig = Figment::new()
+            .merge(CaseNormalized::with_converter(fixture(), converter))
+            .extract()
+            .unwrap();
+
+        assert_eq!(
+            ServiceConfig {
+                service_name: "billing".to_owned(),
+                max_connections: 32,
+                http_server: HttpServer {
+                    bind_address: "0.0.0.0".to_owned(),
+                    request_timeout_secs: 30,
+                },
+                env_overrides: [("RUST_LOG", "debug"), ("featureFlags", "beta")]
+                    .iter()
+                    .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect(),
+                upstream_hosts: vec![UpstreamHost {
+                    host_name: "ledger".to_owned(),
+                    port_number: 8080,
+                }],
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn keeps_value_sources() {
+        let figment = Figment::new().merge(CaseNormalized::new(fixture(), KeyCase::Snake));
+        let err = figment.extract_inner::<u32>("service_name").unwrap_err();
+        let source = err.metadata.and_then(|metadata| metadata.source);
+        assert!(source.unwrap().to_string().ends_with("service.toml"));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{Converter, Error};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// The converters of a [`Registry`]: one for documents going out, one for
+/// documents coming in.
+pub struct WireConverters {
+    /// Converts keys to the case used on the wire.
+    pub to_wire: Converter<'static>,
+
+    /// Converts keys from the case used on the wire.
+    pub from_wire: Converter<'static>,
+}
+
+/// A pair of converters that is set once and then shared, so that call sites
+/// don't need to be handed a [`Converter`].
+///
+/// The process-wide one is used through [`set_global`], [`to_wire`] and
+/// [`from_wire`]; other instances behave the same.
+pub struct Registry {
+    converters: OnceLock<WireConverters>,
+}
+
+impl Registry {
+    pub const fn new() -> Self {
+        Registry {
+            converters: OnceLock::new(),
+        }
+    }
+
+    /// Sets the converters, failing with [`Error::AlreadySet`] if they were
+    /// set before.
+    pub fn set(&self, converters: WireConverters) -> Result<(), Error> {
+        self.converters
+            .set(converters)
+            .map_err(|_| Error::AlreadySet)
+    }
+
+    pub fn get(&self) -> Option<&WireConverters> {
+        self.converters.get()
+    }
+
+    /// Converts `value` with the `to_wire` converter, failing with
+    /// [`Error::NotSet`] before the converters are set.
+    pub fn to_wire(&self, value: Value) -> Result<Value, Error> {
+        self.get()
+            .ok_or(Error::NotSet)?
+            .to_wire
+            .convert_value(value)
+    }
+
+    /// Converts `value` with the `from_wire` converter, failing with
+    /// [`Error::NotSet`] before the converters are set.
+    pub fn from_wire(&self, value: Value) -> Result<Value, Error> {
+        self.get()
+            .ok_or(Error::NotSet)?
+            .from_wire
+            .convert_value(value)
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+static GLOBAL: Registry = Registry::new();
+
+/// Sets the process-wide converters, typically once at startup. Fails with
+/// [`Error::AlreadySet`] if they were set before.
+pub fn set_global(converters: WireConverters) -> Result<(), Error> {
+    GLOBAL.set(converters)
+}
+
+/// The process-wide converters, if they are set.
+pub fn global() -> Option<&'static WireConverters> {
+    GLOBAL.get()
+}
+
+/// Converts `value` with the process-wide `to_wire` converter.
+pub fn to_wire(value: Value) -> Result<Value, Error> {
+    GLOBAL.to_wire(value)
+}
+
+/// Converts `value` with the process-wide `from_wire` converter.
+pub fn from_wire(value: Value) -> Result<Value, Error> {
+    GLOBAL.from_wire(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn converters() -> WireConverters {
+        WireConverters {
+            to_wire: Converter::new(KeyCase::Camel),
+            from_wire: Converter::new(KeyCase::Snake),
+        }
+    }
+
+    #[test]
+    fn sets_once() {
+        let registry = Registry::new();
+        assert!(matches!(
+            registry.to_wire(json!({"user_name": 1})),
+            Err(Error::NotSet)
+        ));
+
+        registry.set(converters()).unwrap();
+        assert!(matches!(registry.set(converters()), Err(Error::AlreadySet)));
+        assert_eq!(
+            json!({"userName": 1}),
+            registry.to_wire(json!({"user_name": 1})).unwrap()
+        );
+        assert_eq!(
+            json!({"user_name": 1}),
+            registry.from_wire(json!({"userName": 1})).unwrap()
+        );
+    }
+
+    #[test]
+    fn concurrent_reads() {
+        let registry = Registry::new();
+        registry.set(converters()).unwrap();
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let registry = &registry;
+                scope.spawn(move || {
+                    for _ in 0..100 {
+                        assert_eq!(
+                            json!({"itemId": i}),
+                            registry.to_wire(json!({"item_id": i})).unwrap()
+                        );
+                    }
+                });
+            }
+        });
+    }
+}
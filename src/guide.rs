@@ -0,0 +1,223 @@
+use crate::path::Path;
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// What happens to keys that a schema guide, set with
+/// [`Converter::with_schema_guide`](crate::Converter::with_schema_guide),
+/// doesn't declare.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UndeclaredKeys {
+    /// Convert them as if there were no guide.
+    #[default]
+    Convert,
+
+    /// Keep them as they are.
+    Preserve,
+}
+
+/// What a schema guide says about a key.
+pub(crate) enum Guided {
+    /// A property declared in `properties`.
+    Declared,
+
+    /// A key of a map, matched by `patternProperties` or by an
+    /// `additionalProperties` schema.
+    MapKey,
+
+    /// A key the schema doesn't know.
+    Undeclared,
+}
+
+/// How many `$ref`s and combinators are followed from one schema, so that
+/// recursive schemas end.
+const MAX_EXPANSION_DEPTH: usize = 32;
+
+/// Finds what `schema` says about the key at `path`, by following the path
+/// from the root of the schema.
+pub(crate) fn classify(schema: &Value, path: &Path) -> Guided {
+    let (parent, key) = match path {
+        Path::Key(parent, key) => (parent, key),
+        _ => return Guided::Undeclared,
+    };
+    let holders = match candidates(schema, parent) {
+        Some(holders) => holders,
+        None => return Guided::Undeclared,
+    };
+
+    if holders.iter().any(|holder| property(holder, key).is_some()) {
+        Guided::Declared
+    } else if holders.iter().any(|holder| !map_values(holder).is_empty()) {
+        Guided::MapKey
+    } else {
+        Guided::Undeclared
+    }
+}
+
+/// The schemas that may describe the value at `path`, or `None` when the
+/// path leaves what the schema describes.
+fn candidates<'s>(root: &'s Value, path: &Path) -> Option<Vec<&'s Value>> {
+    let subschemas: Vec<&Value> = match path {
+        Path::Root => vec![root],
+        Path::Key(parent, key) => {
+            let holders = candidates(root, parent)?;
+            let declared: Vec<_> = holders
+                .iter()
+                .filter_map(|holder| property(holder, key))
+                .collect();
+            if declared.is_empty() {
+                holders
+                    .iter()
+                    .flat_map(|holder| map_values(holder))
+                    .collect()
+            } else {
+                declared
+            }
+        }
+        Path::Index(parent, index) => candidates(root, parent)?
+            .iter()
+            .filter_map(|holder| item(holder, *index))
+            .collect(),
+    };
+
+    let mut expanded = Vec::new();
+    for subschema in subschemas {
+        expand(root, subschema, 0, &mut expanded);
+    }
+    if expanded.is_empty() {
+        None
+    } else {
+        Some(expanded)
+    }
+}
+
+/// Adds `schema` to `expanded`, along with what its local `$ref` and its
+/// `allOf`, `anyOf` and `oneOf` branches point to.
+fn expand<'s>(root: &'s Value, schema: &'s Value, depth: usize, expanded: &mut Vec<&'s Value>) {
+    if depth > MAX_EXPANSION_DEPTH || !schema.is_object() {
+        return;
+    }
+    expanded.push(schema);
+
+    if let Some(target) = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer))
+    {
+        expand(root, target, depth + 1, expanded);
+    }
+    for combinator in ["allOf", "anyOf", "oneOf"].iter() {
+        if let Some(branches) = schema.get(combinator).and_then(Value::as_array) {
+            for branch in branches {
+                expand(root, branch, depth + 1, expanded);
+            }
+        }
+    }
+}
+
+fn property<'s>(schema: &'s Value, key: &str) -> Option<&'s Value> {
+    schema.get("properties")?.get(key)
+}
+
+/// The schemas of the values of a map: every `patternProperties` schema,
+/// since patterns aren't matched, and an `additionalProperties` schema.
+fn map_values(schema: &Value) -> Vec<&Value> {
+    let patterns = schema
+        .get("patternProperties")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|patterns| patterns.values());
+    let additional = schema
+        .get("additionalProperties")
+        .filter(|additional| additional.is_object());
+    patterns.chain(additional).collect()
+}
+
+fn item(schema: &Value, index: usize) -> Option<&Value> {
+    match (schema.get("prefixItems"), schema.get("items")) {
+        (Some(Value::Array(prefix)), _) if index < prefix.len() => Some(&prefix[index]),
+        (_, Some(Value::Array(items))) => {
+            items.get(index).or_else(|| schema.get("additionalItems"))
+        }
+        (_, Some(items)) => Some(items),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "$defs": {
+                "address": {
+                    "type": "object",
+                    "properties": {"streetName": {"type": "string"}, "zipCode": {}}
+                }
+            },
+            "type": "object",
+            "properties": {
+                "userName": {"type": "string"},
+                "homeAddress": {"$ref": "#/$defs/address"},
+                "settings": {
+                    "type": "object",
+                    "additionalProperties": {
+                        "type": "object",
+                        "properties": {"isEnabled": {"type": "boolean"}}
+                    }
+                },
+                "contacts": {
+                    "type": "array",
+                    "items": {
+                        "oneOf": [
+                            {"properties": {"phoneNumber": {}}},
+                            {"properties": {"emailAddress": {}}}
+                        ]
+                    }
+                }
+            }
+        })
+    }
+
+    fn document() -> serde_json::Value {
+        json!({
+            "userName": "Ada",
+            "homeAddress": {"streetName": "x", "zipCode": 1, "extraInfo": 2},
+            "settings": {"darkMode": {"isEnabled": true}, "fontSize": {"isEnabled": false}},
+            "contacts": [{"phoneNumber": "1"}, {"emailAddress": "a@b"}],
+            "undeclaredKey": {"innerKey": 0}
+        })
+    }
+
+    #[test]
+    fn converts_declared_keys() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_schema_guide(&schema());
+        assert_eq!(
+            json!({
+                "user_name": "Ada",
+                "home_address": {"street_name": "x", "zip_code": 1, "extra_info": 2},
+                "settings": {"darkMode": {"is_enabled": true}, "fontSize": {"is_enabled": false}},
+                "contacts": [{"phone_number": "1"}, {"email_address": "a@b"}],
+                "undeclared_key": {"inner_key": 0}
+            }),
+            converter.convert_value(document()).unwrap()
+        );
+
+        converter.with_undeclared_keys(UndeclaredKeys::Preserve);
+        assert_eq!(
+            json!({
+                "user_name": "Ada",
+                "home_address": {"street_name": "x", "zip_code": 1, "extraInfo": 2},
+                "settings": {"darkMode": {"is_enabled": true}, "fontSize": {"is_enabled": false}},
+                "contacts": [{"phone_number": "1"}, {"email_address": "a@b"}],
+                "undeclaredKey": {"innerKey": 0}
+            }),
+            converter.convert_value(document()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Language whose identifier rules keys are sanitized for, see
+/// [`CaseChanger::with_safe_identifiers`](crate::CaseChanger::with_safe_identifiers).
+///
+/// Keys are rewritten to match `[A-Za-z_][A-Za-z0-9_]*`, in this order:
+///
+/// 1. every character outside `[A-Za-z0-9_]` (including non-ASCII letters) is
+///    replaced by one `_`;
+/// 2. a key starting with a digit is prefixed with `_`;
+/// 3. a key equal to a reserved word of the language gets a trailing `_`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentifierStyle {
+    /// Rust keywords, including the reserved ones (`type` becomes `type_`).
+    Rust,
+
+    /// JavaScript reserved words, including literals and strict mode ones
+    /// (`class` becomes `class_`).
+    JavaScript,
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+    "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+const JAVASCRIPT_KEYWORDS: &[&str] = &[
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+impl IdentifierStyle {
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            IdentifierStyle::Rust => RUST_KEYWORDS,
+            IdentifierStyle::JavaScript => JAVASCRIPT_KEYWORDS,
+        }
+    }
+
+    pub(crate) fn sanitize(self, key: &str) -> String {
+        let mut identifier: String = key
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+
+        if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+            identifier.insert(0, '_');
+        }
+        if self.keywords().contains(&identifier.as_str()) {
+            identifier.push('_');
+        }
+
+        identifier
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn sanitize_rules() {
+        assert_eq!("_1st_place", IdentifierStyle::Rust.sanitize("1st_place"));
+        assert_eq!("foo_bar_", IdentifierStyle::Rust.sanitize("foo-bar!"));
+        assert_eq!("total_", IdentifierStyle::Rust.sanitize("total%"));
+        assert_eq!("caf_", IdentifierStyle::Rust.sanitize("café"));
+        assert_eq!("type_", IdentifierStyle::Rust.sanitize("type"));
+        assert_eq!("type", IdentifierStyle::JavaScript.sanitize("type"));
+        assert_eq!("class_", IdentifierStyle::JavaScript.sanitize("class"));
+    }
+
+    #[test]
+    fn safe_identifiers() {
+        let value = json!({"1stPlace": 1, "foo-bar!": 2, "type": 3, "a!": 4, "a?": 5});
+
+        let mut case_changer = CaseChanger::new(value, KeyCase::Snake).unwrap();
+        case_changer.with_safe_identifiers(IdentifierStyle::Rust);
+
+        // `a!` and `a?` both become `a_`; as with any other collision, the
+        // entry converted last wins.
+        let expected = json!({"_1_st_place": 1, "foo_bar_": 2, "type_": 3, "a_": 5});
+        assert_eq!(expected, case_changer.convert());
+    }
+}
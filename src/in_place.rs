@@ -0,0 +1,367 @@
+use crate::collision::ObjectBuilder;
+use crate::converter::{expect_converted, Renames};
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{CaseChanger, CollisionStrategy, Converter, Error, JsonMap, KeyCollision};
+use core::mem;
+use serde_json::Value;
+
+impl Converter<'_> {
+    /// Converts the keys of `value` where it is, with the same result as
+    /// [`Converter::convert_value`] but without building a second document:
+    /// each object is emptied and refilled with its own entries, so values
+    /// are moved rather than copied, and keys that don't change keep their
+    /// string.
+    ///
+    /// When a key is rejected, the object holding it gets its remaining
+    /// entries back unconverted, and `value` is left partly converted. Under
+    /// [`CollisionStrategy::Error`], the keys of each object are converted
+    /// before any entry is moved, so an object whose keys collide is left
+    /// as it was. A value whose transform fails is kept as the transform got
+    /// it.
+    pub fn convert_in_place(&self, value: &mut Value) -> Result<(), Error> {
+        #[cfg(feature = "jsonpath")]
+        if let Some(selected) = self.select_jsonpaths(value) {
+            return selected.convert_in_place(value);
+        }
+        self.convert_value_in_place(value, &Path::Root, None)
+    }
+
+    /// Converts the keys of `map` where it is, as
+    /// [`Converter::convert_in_place`] converts an object.
+    pub fn convert_object_in_place(&self, map: &mut JsonMap) -> Result<(), Error> {
+        let mut value = Value::Object(mem::take(map));
+        let result = self.convert_in_place(&mut value);
+        if let Value::Object(converted) = value {
+            *map = converted;
+        }
+        result
+    }
+
+    fn convert_value_in_place(
+        &self,
+        value: &mut Value,
+        path: &Path,
+        variant: Option<&Renames>,
+    ) -> Result<(), Error> {
+        match value {
+            Value::Array(arr) => {
+                for (index, value) in arr.iter_mut().enumerate() {
+                    self.convert_value_in_place(value, &path.index(index), variant)?;
+                }
+                Ok(())
+            }
+            Value::Object(map) => self.convert_map_in_place(map, path, variant),
+            _ => Ok(()),
+        }
+    }
+
+    fn convert_map_in_place(
+        &self,
+        map: &mut JsonMap,
+        path: &Path,
+        variant: Option<&Renames>,
+    ) -> Result<(), Error> {
+        let variant = self.object_variant(map).or(variant);
+        let detected = self.is_detected_dictionary(map);
+        let mut builder = ObjectBuilder::new(self.collision_strategy(), self.case(), false);
+        let ordered = self.order_input(mem::take(map));
+        // Colliding entries would overwrite each other before the collision
+        // is reported, so they are looked for first.
+        let mut checked_keys = match self.collision_strategy() {
+            CollisionStrategy::Error => {
+                match self.colliding_keys(&ordered, path, variant, detected) {
+                    Ok(new_keys) => Some(new_keys.into_iter()),
+                    Err(err) => {
+                        *map = ordered;
+                        return Err(err);
+                    }
+                }
+            }
+            _ => None,
+        };
+        let mut entries = ordered.into_iter();
+        while let Some((key, mut value)) = entries.next() {
+            let key_path = path.key(&key);
+            let new_key = match &mut checked_keys {
+                Some(new_keys) => Ok(new_keys.next().expect("a new key for every entry")),
+                None => self.in_place_key(&key, &key_path, variant, detected),
+            };
+            let result = new_key.and_then(|new_key| {
+                match self.redaction(&key) {
+                    Some(rule) => value = rule.redact(&value),
+                    None => {
+                        match &mut value {
+                            Value::Array(rows) if self.is_pair_array_key(&key) => {
+                                self.convert_rows_in_place(rows, &key_path, variant)?
+                            }
+                            Value::String(text) if self.is_embedded_json_field(&key) => {
+                                let text = mem::take(text);
+                                value = self.convert_embedded_json(text, &key_path)?;
+                            }
+                            value => self.convert_value_in_place(value, &key_path, variant)?,
+                        }
+                        self.transform_in_place(&key, &key_path, &mut value)?;
+                    }
+                }
+                Ok(new_key)
+            });
+            match result {
+                Ok(Some(new_key)) => builder.insert(map, key, new_key, value, path, &mut None),
+                Ok(None) => {}
+                Err(err) => {
+                    map.insert(key, value);
+                    map.extend(entries);
+                    return Err(err);
+                }
+            }
+        }
+        builder.finish(&mut None)?;
+        *map = self.order_output(mem::take(map));
+        Ok(())
+    }
+
+    /// The new name of the key at `path`, in an object that is a detected
+    /// dictionary or not.
+    fn in_place_key(
+        &self,
+        key: &str,
+        path: &Path,
+        variant: Option<&Renames>,
+        detected: bool,
+    ) -> Result<Option<String>, Error> {
+        if detected {
+            self.guard_key(key, Some(key.to_owned()), path)
+        } else {
+            self.rename_variant_key(key, variant, path)
+        }
+    }
+
+    /// The new name of every key of `map`, the object at `path`, or an
+    /// [`Error::KeyCollision`] listing the keys that would collide.
+    fn colliding_keys(
+        &self,
+        map: &JsonMap,
+        path: &Path,
+        variant: Option<&Renames>,
+        detected: bool,
+    ) -> Result<Vec<Option<String>>, Error> {
+        let mut new_keys = Vec::with_capacity(map.len());
+        let mut collisions: Vec<KeyCollision> = Vec::new();
+        let mut originals: HashMap<String, &str> = HashMap::new();
+        for key in map.keys() {
+            let new_key = self.in_place_key(key, &path.key(key), variant, detected)?;
+            if let Some(new_key) = &new_key {
+                if let Some(earlier) = originals.insert(new_key.clone(), key) {
+                    match collisions.iter_mut().find(|c| c.new_key == *new_key) {
+                        Some(collision) => collision.keys.push(key.clone()),
+                        None => collisions.push(KeyCollision {
+                            path: path.to_pointer(),
+                            keys: vec![earlier.to_owned(), key.clone()],
+                            new_key: new_key.clone(),
+                        }),
+                    }
+                }
+            }
+            new_keys.push(new_key);
+        }
+        if collisions.is_empty() {
+            Ok(new_keys)
+        } else {
+            Err(Error::KeyCollision { collisions })
+        }
+    }
+
+    /// Converts the rows of a map encoded as an array, as
+    /// [`Converter::with_pair_array_keys`] describes.
+    fn convert_rows_in_place(
+        &self,
+        rows: &mut Vec<Value>,
+        path: &Path,
+        variant: Option<&Renames>,
+    ) -> Result<(), Error> {
+        let mut index = 0;
+        while index < rows.len() {
+            let (key, value) = match row_parts(&mut rows[index]) {
+                Some(parts) => parts,
+                None => {
+                    index += 1;
+                    continue;
+                }
+            };
+
+            let key_path = path.key(key);
+            match self.rename_variant_key(key, variant, &key_path)? {
+                Some(new_key) => {
+                    match self.redaction(key) {
+                        Some(rule) => *value = rule.redact(value),
+                        None => {
+                            self.convert_value_in_place(value, &key_path, variant)?;
+                            self.transform_in_place(key, &key_path, value)?;
+                        }
+                    }
+                    if new_key != *key {
+                        *key = new_key;
+                    }
+                    index += 1;
+                }
+                None => {
+                    rows.remove(index);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the value transforms matching the entry with the original key
+    /// `key` on its converted value, which is left as it was when one fails.
+    /// Transforms take their value, so it is copied for them.
+    fn transform_in_place(&self, key: &str, path: &Path, value: &mut Value) -> Result<(), Error> {
+        if self.is_transformed(key, path) {
+            *value = self.transform_value(key, path, value.clone())?;
+        }
+        Ok(())
+    }
+}
+
+impl CaseChanger<'_> {
+    /// Converts the input, consuming the changer so that the input is not
+    /// cloned first: values are moved into the converted document, so
+    /// converting a large document doesn't hold two copies of it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the conversion fails, as described in
+    /// [`CaseChanger::convert`].
+    pub fn into_converted(self) -> Value {
+        expect_converted(self.try_into_converted())
+    }
+
+    /// Like [`CaseChanger::into_converted`], returning an error when a key
+    /// is rejected by the configured options.
+    pub fn try_into_converted(self) -> Result<Value, Error> {
+        self.converter.convert_value(self.json_in)
+    }
+}
+
+/// The key and value of a `[key, value]` or `{"key": key, "value": value}`
+/// row, or `None` for rows of any other shape.
+fn row_parts(row: &mut Value) -> Option<(&mut String, &mut Value)> {
+    match row {
+        Value::Array(pair) => match pair.as_mut_slice() {
+            [Value::String(key), value] => Some((key, value)),
+            _ => None,
+        },
+        Value::Object(entry) if entry.len() == 2 => {
+            let (mut key, mut value) = (None, None);
+            for (name, field) in entry.iter_mut() {
+                match (name.as_str(), field) {
+                    ("key", Value::String(field)) => key = Some(field),
+                    ("value", field) => value = Some(field),
+                    _ => {}
+                }
+            }
+            Some((key?, value?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::{json, Value};
+
+    /// A document with a few thousand objects at various depths.
+    fn fixture() -> Value {
+        let orders: Vec<_> = (0..500)
+            .map(|i| {
+                json!({
+                    "orderId": i,
+                    "customerInfo": {"firstName": "Ada", "lastName": "Lovelace", "$Ref": i},
+                    "lineItems": [
+                        {"productId": i, "unitPrice": 1.5, "Tags": ["keepThis", {"tagName": "x"}]},
+                        {"productId": i + 1, "unitPrice": 2.5, "Tags": []}
+                    ],
+                    "attributes": [["giftWrap", true], {"key": "deliveryNote", "value": {"noteText": "x"}}]
+                })
+            })
+            .collect();
+        json!({"pageInfo": {"pageSize": 500}, "Orders": orders})
+    }
+
+    #[test]
+    fn matches_convert_value() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_skip_prefixes(vec!["$"]);
+        converter.with_pair_array_keys(vec!["attributes"]);
+        converter.with_manual_renames([("Tags", "labels")].iter().cloned().collect());
+        converter.with_redaction(RedactionRule::exact("lastName", Redaction::Hash));
+        converter.with_value_transform("/pageInfo/pageSize", |size| Ok(json!([size])));
+
+        let expected = converter.convert_value(fixture()).unwrap();
+        let mut value = fixture();
+        converter.convert_in_place(&mut value).unwrap();
+        assert_eq!(expected, value);
+        assert_eq!(
+            json!({"key": "delivery_note", "value": {"note_text": "x"}}),
+            value["orders"][0]["attributes"][1]
+        );
+    }
+
+    #[test]
+    fn converts_into_the_output() {
+        let mut changer = CaseChanger::new(fixture(), KeyCase::Kebab).unwrap();
+        let expected = changer.convert();
+        assert_eq!(expected, changer.into_converted());
+
+        let mut changer = CaseChanger::new(json!({"": 1}), KeyCase::Kebab).unwrap();
+        changer.with_empty_key_policy(EmptyKeyPolicy::Error);
+        assert!(changer.try_into_converted().is_err());
+    }
+
+    #[test]
+    fn keeps_colliding_entries() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_collision_strategy(CollisionStrategy::Error);
+
+        let mut value = json!({"outerKey": 0, "inner": {"aB": 1, "a_b": 2}});
+        match converter.convert_in_place(&mut value) {
+            Err(Error::KeyCollision { collisions }) => {
+                assert_eq!("/inner", collisions[0].path);
+                assert_eq!("a_b", collisions[0].new_key);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(json!({"aB": 1, "a_b": 2}), value["inner"]);
+    }
+
+    #[test]
+    fn keeps_values_whose_transform_fails() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_value_transform("pageSize", |_| Err("not a page size".to_owned()));
+
+        let mut value = json!({"pageInfo": {"pageSize": 50}});
+        assert!(matches!(
+            converter.convert_in_place(&mut value),
+            Err(Error::ValueTransform { .. })
+        ));
+        assert_eq!(json!({"pageSize": 50}), value["pageInfo"]);
+
+        converter.with_pair_array_keys(vec!["pairs"]);
+        let mut value = json!({"pairs": [["pageSize", 20]]});
+        assert!(converter.convert_in_place(&mut value).is_err());
+        assert_eq!(json!([["pageSize", 20]]), value["pairs"]);
+    }
+
+    #[test]
+    fn keeps_unconverted_entries_on_error() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_empty_key_policy(EmptyKeyPolicy::Error);
+
+        let mut value = json!({"firstKey": 1, "inner": {"": 2}, "lastKey": 3});
+        assert!(converter.convert_in_place(&mut value).is_err());
+        assert_eq!(3, value.as_object().unwrap().len());
+        assert_eq!(json!({"": 2}), value["inner"]);
+    }
+}
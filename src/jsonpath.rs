@@ -0,0 +1,226 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::Error;
+use alloc::collections::BTreeMap;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+
+/// The JSONPath selectors of a converter, parsed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct JsonPaths {
+    pub(crate) include: Vec<(String, JsonPath)>,
+    pub(crate) exclude: Vec<(String, JsonPath)>,
+}
+
+/// Where the selectors matched in one document: the keys of the matched
+/// values and of everything below them are selected.
+#[derive(Clone, Debug)]
+pub(crate) struct Selection {
+    /// `None` when there are no include selectors, so that every key is
+    /// included.
+    included: Option<Node>,
+    excluded: Node,
+}
+
+/// The locations of the matched values, as a tree of their tokens.
+#[derive(Clone, Debug, Default)]
+struct Node {
+    matched: bool,
+    keys: BTreeMap<String, Node>,
+    indices: BTreeMap<usize, Node>,
+}
+
+enum Walk<'n> {
+    /// The path is at or below a matched value.
+    Inside,
+    At(&'n Node),
+    Outside,
+}
+
+/// Parses `expr`, reporting a syntax error against `option`.
+pub(crate) fn parse(option: &'static str, expr: &str) -> Result<JsonPath, Error> {
+    JsonPath::parse(expr).map_err(|err| Error::InvalidOption {
+        option,
+        message: format!("`{}` is not a JSONPath expression: {}", expr, err),
+    })
+}
+
+impl JsonPaths {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Evaluates every selector against `value` once.
+    pub(crate) fn select(&self, value: &Value) -> Selection {
+        let select = |paths: &[(String, JsonPath)]| {
+            let mut root = Node::default();
+            for (_, path) in paths {
+                for location in path.query_located(value).locations() {
+                    let mut node = &mut root;
+                    for element in location.iter() {
+                        node = match element.as_name() {
+                            Some(name) => node.keys.entry(name.to_owned()).or_default(),
+                            None => node
+                                .indices
+                                .entry(element.as_index().unwrap_or_default())
+                                .or_default(),
+                        };
+                    }
+                    node.matched = true;
+                }
+            }
+            root
+        };
+        Selection {
+            included: if self.include.is_empty() {
+                None
+            } else {
+                Some(select(&self.include))
+            },
+            excluded: select(&self.exclude),
+        }
+    }
+}
+
+impl Selection {
+    /// Whether the key at `path` is left out by the include selectors.
+    pub(crate) fn is_not_included(&self, path: &Path) -> bool {
+        match (&self.included, path) {
+            (Some(included), Path::Key(parent, _)) => !included.contains(parent),
+            _ => false,
+        }
+    }
+
+    /// Whether the key at `path` is selected by an exclude selector.
+    pub(crate) fn is_excluded(&self, path: &Path) -> bool {
+        match path {
+            Path::Key(parent, _) => self.excluded.contains(parent),
+            _ => false,
+        }
+    }
+}
+
+impl Node {
+    /// Whether the value at `path` is a matched value or below one.
+    fn contains(&self, path: &Path) -> bool {
+        matches!(self.walk(path), Walk::Inside)
+    }
+
+    fn walk(&self, path: &Path) -> Walk<'_> {
+        let child = match path {
+            Path::Root if self.matched => return Walk::Inside,
+            Path::Root => return Walk::At(self),
+            Path::Key(parent, key) => match self.walk(parent) {
+                Walk::At(node) => node.keys.get(*key),
+                walk => return walk,
+            },
+            Path::Index(parent, index) => match self.walk(parent) {
+                Walk::At(node) => node.indices.get(index),
+                walk => return walk,
+            },
+        };
+        match child {
+            Some(child) if child.matched => Walk::Inside,
+            Some(child) => Walk::At(child),
+            None => Walk::Outside,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn excludes_at_any_depth() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_exclude_jsonpath("$..metadata").unwrap();
+
+        let converted = converter
+            .convert_value(json!({
+                "orderId": 1,
+                "metadata": {"sourceSystem": "crm", "rawTags": {"keepMe": true}},
+                "lineItems": [{"unitPrice": 2, "metadata": {"addedBy": "ada"}}]
+            }))
+            .unwrap();
+        assert_eq!(
+            json!({
+                "order_id": 1,
+                "metadata": {"sourceSystem": "crm", "rawTags": {"keepMe": true}},
+                "line_items": [{"unit_price": 2, "metadata": {"addedBy": "ada"}}]
+            }),
+            converted
+        );
+    }
+
+    #[test]
+    fn excludes_in_place() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_exclude_jsonpath("$..metadata").unwrap();
+
+        let mut value = json!({"orderId": 1, "metadata": {"keepMe": 2}});
+        converter.convert_in_place(&mut value).unwrap();
+        assert_eq!(json!({"order_id": 1, "metadata": {"keepMe": 2}}), value);
+
+        let mut map = json!({"lineItems": [{"metadata": {"keepMe": 2}}]})
+            .as_object()
+            .unwrap()
+            .clone();
+        converter.convert_object_in_place(&mut map).unwrap();
+        assert_eq!(
+            json!({"line_items": [{"metadata": {"keepMe": 2}}]}),
+            Value::Object(map)
+        );
+    }
+
+    #[test]
+    fn includes_filtered_array_items() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter
+            .with_include_jsonpath("$.orders[*].items[?(@.type=='custom')].attributes")
+            .unwrap();
+
+        let converted = converter
+            .convert_value(json!({
+                "orderCount": 1,
+                "orders": [{"items": [
+                    {"type": "custom", "attributes": {"fontSize": 12, "inkColor": {"hexCode": "#000"}}},
+                    {"type": "stock", "attributes": {"fontSize": 10}}
+                ]}]
+            }))
+            .unwrap();
+        assert_eq!(
+            json!({
+                "orderCount": 1,
+                "orders": [{"items": [
+                    {"type": "custom", "attributes": {"font_size": 12, "ink_color": {"hex_code": "#000"}}},
+                    {"type": "stock", "attributes": {"fontSize": 10}}
+                ]}]
+            }),
+            converted
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        let err = converter.with_exclude_jsonpath("$..[").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::InvalidOption {
+                option: "exclude_jsonpaths",
+                ..
+            }
+        ));
+
+        let mut spec = ConversionSpec::new(KeyCase::Snake);
+        spec.include_jsonpaths = vec!["orders".to_owned()];
+        assert!(Converter::from_spec(spec).is_err());
+
+        let mut spec = ConversionSpec::new(KeyCase::Snake);
+        spec.exclude_jsonpaths = vec!["$..metadata".to_owned()];
+        let converter = Converter::from_spec(spec.clone()).unwrap();
+        assert_eq!(spec, converter.to_spec());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::path::escape_pointer_token;
+use crate::prelude::*;
+use core::slice;
+use serde_json::{map, Value};
+
+/// A key found while walking a JSON document with [`keys`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyRef<'v> {
+    /// The key, as it appears in the document.
+    pub key: &'v str,
+
+    /// JSON pointer (RFC 6901) to the value stored under `key`.
+    pub path: String,
+
+    /// How many objects enclose the object holding `key`. Keys of the
+    /// outermost objects (including objects directly inside a root array)
+    /// have depth 0; arrays don't add depth.
+    pub depth: usize,
+}
+
+/// Iterates over every key in `value`, in document order, descending into
+/// nested objects and arrays.
+pub fn keys(value: &Value) -> Keys<'_> {
+    let mut keys = Keys { stack: Vec::new() };
+    keys.push(value, String::new(), 0);
+    keys
+}
+
+/// Iterator returned by [`keys`].
+pub struct Keys<'v> {
+    stack: Vec<Frame<'v>>,
+}
+
+enum Frame<'v> {
+    Object(map::Iter<'v>, String, usize),
+    Array(core::iter::Enumerate<slice::Iter<'v, Value>>, String, usize),
+}
+
+impl<'v> Keys<'v> {
+    fn push(&mut self, value: &'v Value, path: String, depth: usize) {
+        match value {
+            Value::Object(map) => self.stack.push(Frame::Object(map.iter(), path, depth)),
+            Value::Array(arr) => self
+                .stack
+                .push(Frame::Array(arr.iter().enumerate(), path, depth)),
+            _ => {}
+        }
+    }
+}
+
+impl<'v> Iterator for Keys<'v> {
+    type Item = KeyRef<'v>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.stack.last_mut()? {
+                Frame::Object(iter, parent, depth) => match iter.next() {
+                    Some((key, value)) => {
+                        let depth = *depth;
+                        let path = format!("{}/{}", parent, escape_pointer_token(key));
+                        self.push(value, path.clone(), depth + 1);
+                        return Some(KeyRef { key, path, depth });
+                    }
+                    None => {
+                        self.stack.pop();
+                    }
+                },
+                Frame::Array(iter, parent, depth) => match iter.next() {
+                    Some((index, value)) => {
+                        let depth = *depth;
+                        let path = format!("{}/{}", parent, index);
+                        self.push(value, path, depth);
+                    }
+                    None => {
+                        self.stack.pop();
+                    }
+                },
+            }
+        }
+    }
+}
+
+// Document order is only kept with `preserve_order`.
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn nested_document_order() {
+        let value = json!({
+            "userName": "a",
+            "orders": [{"orderId": 1}, {"orderId": 2, "a/b": {"c~d": true}}],
+            "meta": {"createdAt": 0}
+        });
+
+        let found: Vec<(&str, String, usize)> = keys(&value)
+            .map(|key_ref| (key_ref.key, key_ref.path, key_ref.depth))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("userName", "/userName".to_owned(), 0),
+                ("orders", "/orders".to_owned(), 0),
+                ("orderId", "/orders/0/orderId".to_owned(), 1),
+                ("orderId", "/orders/1/orderId".to_owned(), 1),
+                ("a/b", "/orders/1/a~1b".to_owned(), 1),
+                ("c~d", "/orders/1/a~1b/c~0d".to_owned(), 2),
+                ("meta", "/meta".to_owned(), 0),
+                ("createdAt", "/meta/createdAt".to_owned(), 1),
+            ],
+            found
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{Converter, Error};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::Value;
+
+/// Converts the keys of the JSON payload of an API Gateway proxy `event`,
+/// which sits as text in its `body`, decoding it first when
+/// `isBase64Encoded` is set. The converted payload is written back as text,
+/// encoded the same way.
+///
+/// The envelope is left alone: `httpMethod`, `requestContext` and the other
+/// keys of the event, and the keys of `headers`, `queryStringParameters` and
+/// `pathParameters`, which are data. Events without a body, or with an
+/// empty or `null` one, are returned as they are.
+pub fn normalize_apigw_event(mut event: Value, converter: &Converter) -> Result<Value, Error> {
+    let is_base64 = is_base64_encoded(&event);
+    let body = match event.get_mut("body") {
+        Some(Value::String(body)) if !body.is_empty() => body,
+        _ => return Ok(event),
+    };
+
+    let payload: Value = if is_base64 {
+        let bytes = BASE64.decode(body.as_bytes()).map_err(Error::Base64)?;
+        serde_json::from_slice(&bytes)
+    } else {
+        serde_json::from_str(body)
+    }
+    .map_err(Error::Parse)?;
+    *body = encode_body(converter.convert_value(payload)?, is_base64)?;
+    Ok(event)
+}
+
+/// Builds the response of a proxy integration from `response`, whose `body`
+/// holds the payload as a JSON value rather than as text: its keys are
+/// converted, and it is written as text, base64-encoded when
+/// `isBase64Encoded` is set. `statusCode`, `headers` and the rest of the
+/// response are kept, and responses without a `body` are returned as they
+/// are.
+pub fn finish_apigw_response(mut response: Value, converter: &Converter) -> Result<Value, Error> {
+    let is_base64 = is_base64_encoded(&response);
+    if let Some(body) = response.get_mut("body") {
+        let payload = converter.convert_value(body.take())?;
+        *body = Value::String(encode_body(payload, is_base64)?);
+    }
+    Ok(response)
+}
+
+fn is_base64_encoded(envelope: &Value) -> bool {
+    envelope
+        .get("isBase64Encoded")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn encode_body(payload: Value, is_base64: bool) -> Result<String, Error> {
+    let text = serde_json::to_string(&payload).map_err(Error::Serialize)?;
+    Ok(if is_base64 { BASE64.encode(text) } else { text })
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use super::BASE64;
+    use crate::*;
+    use base64::Engine;
+    use serde_json::json;
+
+    fn event(body: serde_json::Value, is_base64: bool) -> serde_json::Value {
+        json!({
+            "httpMethod": "POST",
+            "path": "/orders",
+            "headers": {"Content-Type": "application/json", "X-Request-Id": "r1"},
+            "queryStringParameters": {"dryRun": "true"},
+            "pathParameters": {"orderId": "7"},
+            "requestContext": {"requestId": "r1"},
+            "isBase64Encoded": is_base64,
+            "body": body
+        })
+    }
+
+    #[test]
+    fn converts_plain_body() {
+        let converter = Converter::new(KeyCase::Snake);
+        let event = event(json!(r#"{"orderItems": [{"unitPrice": 2}]}"#), false);
+
+        let normalized = normalize_apigw_event(event.clone(), &converter).unwrap();
+
+        let mut expected = event;
+        expected["body"] = json!(r#"{"order_items":[{"unit_price":2}]}"#);
+        assert_eq!(expected, normalized);
+    }
+
+    #[test]
+    fn converts_base64_body() {
+        let converter = Converter::new(KeyCase::Snake);
+        let body = BASE64.encode(r#"{"orderItems": []}"#);
+        let event = event(json!(body), true);
+
+        let normalized = normalize_apigw_event(event, &converter).unwrap();
+        let body = BASE64.decode(normalized["body"].as_str().unwrap()).unwrap();
+        assert_eq!(br#"{"order_items":[]}"#, body.as_slice());
+
+        let invalid = json!({"isBase64Encoded": true, "body": "not base64!"});
+        assert!(matches!(
+            normalize_apigw_event(invalid, &converter),
+            Err(Error::Base64(_))
+        ));
+    }
+
+    #[test]
+    fn keeps_event_without_body() {
+        let converter = Converter::new(KeyCase::Snake);
+        for body in [json!(null), json!("")].iter() {
+            let event = event(body.clone(), false);
+            assert_eq!(
+                event,
+                normalize_apigw_event(event.clone(), &converter).unwrap()
+            );
+        }
+        let event = json!({"httpMethod": "GET", "headers": {"X-Request-Id": "r1"}});
+        assert_eq!(
+            event,
+            normalize_apigw_event(event.clone(), &converter).unwrap()
+        );
+    }
+
+    #[test]
+    fn builds_response() {
+        let converter = Converter::new(KeyCase::Camel);
+        let response = json!({
+            "statusCode": 201,
+            "headers": {"Content-Type": "application/json"},
+            "body": {"order_id": 7}
+        });
+
+        assert_eq!(
+            json!({
+                "statusCode": 201,
+                "headers": {"Content-Type": "application/json"},
+                "body": r#"{"orderId":7}"#
+            }),
+            finish_apigw_response(response, &converter).unwrap()
+        );
+
+        let response = json!({"statusCode": 200, "isBase64Encoded": true, "body": {"order_id": 7}});
+        let response = finish_apigw_response(response, &converter).unwrap();
+        assert_eq!(json!(BASE64.encode(r#"{"orderId":7}"#)), response["body"]);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What to do with new keys longer than the maximum set with
+/// [`Converter::with_max_key_length`](crate::Converter::with_max_key_length).
+/// Lengths are counted in bytes of UTF-8, and keys are only ever cut between
+/// characters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LengthPolicy {
+    /// Fail the conversion with [`Error::KeyTooLong`](crate::Error::KeyTooLong).
+    Error,
+
+    /// Cut the key to the maximum length, at the last word boundary (a
+    /// separator, or an uppercase letter starting a word) if there is one.
+    Truncate,
+
+    /// Cut the key to leave room for `_` and 8 hex digits of a hash of the
+    /// whole key, so that keys sharing a long prefix stay apart. The hash
+    /// (32-bit FNV-1a) is the same on every run and platform.
+    TruncateWithHash,
+}
+
+const HASH_LEN: usize = 8;
+
+impl LengthPolicy {
+    /// Shortens `key` to at most `max` bytes, or gives it back as an error
+    /// when it is too long and the policy is [`LengthPolicy::Error`].
+    pub(crate) fn apply(self, key: String, max: usize) -> Result<String, String> {
+        if key.len() <= max {
+            return Ok(key);
+        }
+        match self {
+            LengthPolicy::Error => Err(key),
+            LengthPolicy::Truncate => Ok(truncate(&key, max).to_owned()),
+            LengthPolicy::TruncateWithHash => {
+                let hash = format!("{:08x}", fnv1a(&key));
+                if max <= HASH_LEN {
+                    return Ok(hash[..max].to_owned());
+                }
+                let head = truncate(&key, max - HASH_LEN - 1);
+                Ok(format!("{}_{}", head, hash))
+            }
+        }
+    }
+}
+
+/// The longest start of `key` of at most `max` bytes, ending at a word
+/// boundary when there is one.
+fn truncate(key: &str, max: usize) -> &str {
+    let mut end = max;
+    while !key.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = &key[..end];
+
+    let is_separator = |c: char| "_-. ".contains(c);
+    let boundary = head
+        .char_indices()
+        .skip(1)
+        .filter(|&(_, c)| is_separator(c) || c.is_uppercase())
+        .map(|(i, _)| i)
+        .chain(
+            key[end..]
+                .starts_with(|c: char| is_separator(c) || c.is_uppercase())
+                .then_some(end),
+        )
+        .last();
+    let head = match boundary {
+        Some(boundary) => &head[..boundary],
+        None => head,
+    };
+    let trimmed = head.trim_end_matches(is_separator);
+    if trimmed.is_empty() {
+        head
+    } else {
+        trimmed
+    }
+}
+
+fn fnv1a(key: &str) -> u32 {
+    key.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn converter(max: usize, policy: LengthPolicy) -> Converter<'static> {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_max_key_length(max, policy);
+        converter
+    }
+
+    #[test]
+    fn error() {
+        let converter = converter(10, LengthPolicy::Error);
+        assert_eq!(
+            json!({"short_key": 1}),
+            converter.convert_value(json!({"shortKey": 1})).unwrap()
+        );
+        match converter.convert_value(json!({"inner": {"veryLongKeyName": 1}})) {
+            Err(err @ Error::KeyTooLong { .. }) => assert_eq!(
+                "key `very_long_key_name` at `/inner/veryLongKeyName` is longer than 10 bytes",
+                err.to_string()
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn truncate() {
+        let converter = converter(12, LengthPolicy::Truncate);
+        assert_eq!(
+            json!({"customer": 1, "abcdefghijkl": 2, "order_total": 3}),
+            converter
+                .convert_value(json!({
+                    "customerAccountNumber": 1,
+                    "abcdefghijklmnop": 2,
+                    "orderTotal": 3
+                }))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn truncation_collisions_are_reported() {
+        let converter = converter(8, LengthPolicy::Truncate);
+        let (converted, dropped) = converter
+            .convert_value_with_drops(json!({"deliveryAddress": 1, "deliveryDate": 2}))
+            .unwrap();
+        assert_eq!(json!({"delivery": 2}), converted);
+        assert_eq!(
+            vec![DroppedKey {
+                key: "deliveryAddress".to_owned(),
+                path: "/deliveryAddress".to_owned(),
+                parent: String::new(),
+                reason: DropReason::Collision {
+                    kept: "deliveryDate".to_owned(),
+                    new_key: "delivery".to_owned(),
+                },
+            }],
+            dropped
+        );
+    }
+
+    #[test]
+    fn truncate_with_hash() {
+        let converter = converter(17, LengthPolicy::TruncateWithHash);
+        let converted = converter
+            .convert_value(json!({"deliveryAddressLine": 1, "deliveryAddressCity": 2}))
+            .unwrap();
+        // The hashes are fixed, so that keys stay the same across runs.
+        assert_eq!(
+            json!({"delivery_35e7e481": 1, "delivery_e437fcc4": 2}),
+            converted
+        );
+    }
+
+    #[test]
+    fn multi_byte_keys() {
+        let converter = converter(6, LengthPolicy::Truncate);
+        // The second `é` takes bytes 6 and 7, so the cut falls before it.
+        assert_eq!(
+            json!({"café": 1}),
+            converter.convert_value(json!({"caféééé": 1})).unwrap()
+        );
+    }
+}
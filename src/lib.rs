@@ -1,179 +1,453 @@
-use convert_case::{Case, Casing};
+// Without the `std` feature, only targets without an operating system build
+// as `no_std`: on the others, the `cdylib` needs `std` for its allocator and
+// panic handler.
+#![cfg_attr(all(not(feature = "std"), target_os = "none"), no_std)]
+
+extern crate alloc;
+
+use crate::prelude::*;
+use core::ops::{Deref, DerefMut};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::HashMap;
+
+#[cfg(feature = "actix")]
+pub mod actix;
+mod avro;
+#[cfg(feature = "axum")]
+pub mod axum;
+mod batch;
+#[cfg(feature = "bson")]
+mod bson;
+mod builder;
+mod canonical;
+mod case;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod check;
+mod collision;
+mod compatibility;
+#[cfg(feature = "compression")]
+mod compression;
+mod converter;
+mod corpus;
+mod de;
+mod detect;
+mod dictionary;
+mod diff;
+#[cfg(feature = "dir")]
+mod dir;
+mod entries;
+mod error;
+mod fast_case;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "figment")]
+mod figment;
+#[cfg(feature = "std")]
+mod global;
+mod guide;
+mod identifier;
+mod in_place;
+#[cfg(feature = "jsonpath")]
+mod jsonpath;
+mod keys;
+#[cfg(feature = "lambda")]
+mod lambda;
+mod length;
+mod location;
+mod lodash;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod names;
+#[cfg(feature = "normalization")]
+mod normalization;
+mod openapi;
+mod patch;
+mod path;
+mod path_rule;
+mod plan;
+mod preflight;
+mod prelude;
+mod preserving;
+mod preset;
+mod preview;
+#[cfg(feature = "python")]
+pub mod python;
+mod redaction;
+mod rename_dictionary;
+mod report;
+#[cfg(feature = "reqwest")]
+mod reqwest;
+mod roundtrip;
+mod schema;
+mod scope;
+mod ser;
+pub mod serde_with;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "simd-json")]
+mod simd;
+#[cfg(feature = "test-utils")]
+mod snapshot;
+mod spec;
+#[cfg(feature = "sqlx")]
+pub mod sqlx;
+#[cfg(feature = "test-utils")]
+mod strategies;
+mod strict;
+#[cfg(feature = "toml")]
+mod toml;
+#[cfg(feature = "tower")]
+pub mod tower;
+mod transcode;
+mod transform;
+mod typed;
+mod validator;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+#[cfg(feature = "bson")]
+pub use crate::bson::convert_bson;
+#[cfg(feature = "figment")]
+pub use crate::figment::CaseNormalized;
+#[cfg(feature = "lambda")]
+pub use crate::lambda::{finish_apigw_response, normalize_apigw_event};
+#[cfg(feature = "reqwest")]
+pub use crate::reqwest::{RequestBuilderExt, ResponseExt};
+#[cfg(feature = "toml")]
+pub use crate::toml::convert_toml_str;
+pub use avro::AvroSchemaMode;
+pub use batch::{BatchError, BatchPolicy};
+pub use builder::CaseChangerBuilder;
+pub use case::KeyCase;
+#[cfg(feature = "cbor")]
+pub use cbor::convert_cbor;
+pub use check::KeyChange;
+pub use collision::CollisionStrategy;
+pub use compatibility::Compatibility;
+#[cfg(feature = "compression")]
+pub use compression::{CompressedWriter, Compression};
+use converter::expect_converted;
+pub use converter::Converter;
+pub use corpus::{analyze_corpus, CasingVariants, CorpusCollision, CorpusReport, KeyStats};
+pub use de::CaseChangingDeserializer;
+pub use detect::DetectedCases;
+pub use dictionary::{DictionaryHeuristic, KeyDetector};
+pub use diff::DiffStyle;
+#[cfg(feature = "dir")]
+pub use dir::{DirOptions, DirOutput, DirReport};
+pub use entries::JsonEntries;
+pub use error::Error;
+#[cfg(feature = "std")]
+pub use global::{from_wire, global, set_global, to_wire, Registry, WireConverters};
+pub use guide::UndeclaredKeys;
+pub use identifier::IdentifierStyle;
+pub use keys::{keys, KeyRef, Keys};
+pub use length::LengthPolicy;
+pub use location::Location;
+pub use names::NameMapping;
+#[cfg(feature = "normalization")]
+pub use normalization::NormalizationForm;
+pub use openapi::OpenApiMode;
+pub use path_rule::PathRule;
+pub use plan::{
+    apply_plan, apply_plan_with_warnings, ConversionPlan, PlanError, PlanOperation, UncoveredKeys,
+    PLAN_VERSION,
+};
+pub use preflight::{KeyCollision, PreflightReport};
+pub use preset::{Preset, PresetRules};
+pub use preview::{KeyPreview, KeyRule};
+pub use redaction::{Redaction, RedactionRule};
+pub use rename_dictionary::{RenameConflict, RenameDictionary};
+pub use report::{DropReason, DroppedKey, KeyRename, RenameReport};
+pub use roundtrip::{verify_roundtrip, verify_roundtrip_keys, RoundtripViolation};
+pub use scope::KeyScope;
+pub use ser::Converted;
+#[cfg(feature = "test-utils")]
+pub use snapshot::{normalize_for_snapshot, normalize_for_snapshot_with};
+pub use spec::{
+    AffixSpec, CaseRuleSpec, ConfigDiagnostic, ConversionSpec, DiagnosticCode, MaxKeyLengthSpec,
+    PathRuleSpec, VariantRenamesSpec,
+};
+#[cfg(feature = "test-utils")]
+pub use strategies::{arb_json_with_case, arb_key_in_case};
+pub use strict::{DuplicateCheck, DuplicateKey};
+#[cfg(feature = "msgpack")]
+pub use transcode::convert_msgpack;
+#[cfg(feature = "std")]
+pub use transcode::convert_reader_to_writer;
+pub use transcode::convert_with_format;
+pub use typed::{convert_typed, convert_typed_keys};
+pub use validator::{KeyCheck, KeyContext, KeyValidator, KeyViolation, StyleGuide};
+#[cfg(feature = "yaml")]
+pub use yaml::convert_yaml_str;
 
 pub type JsonMap = Map<String, Value>;
+/// Manual renames, from the original key to the new one. Without the `std`
+/// feature this is a `BTreeMap`.
 pub type RenameMap<'a> = HashMap<&'a str, &'a str>;
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum RenameBehavior {
+    #[default]
     ByKey,
     ByValue,
 }
 
-impl Default for RenameBehavior {
-    fn default() -> Self {
-        Self::ByKey
-    }
+/// What to do when the new key a manual rename gives is itself renamed by
+/// the same table, as with `a` → `b` and `b` → `c`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainPolicy {
+    /// Fail the conversion of a key that starts a chain with
+    /// [`Error::RenameChain`], or a cycle with [`Error::RenameCycle`].
+    #[default]
+    Error,
+
+    /// Rename each key once: `a` becomes `b`, whatever `b` becomes. Keys are
+    /// only ever looked up among the original keys, so a swap such as
+    /// `a` → `b` and `b` → `a` exchanges the two keys.
+    Single,
+
+    /// Follow the chain to its end: `a` becomes `c`. Cycles still fail with
+    /// [`Error::RenameCycle`].
+    Resolve,
+}
+
+/// What to do with the keys `__proto__`, `constructor` and `prototype`, which
+/// can pollute object prototypes once a JavaScript program reads the output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardPolicy {
+    /// Fail the conversion with [`Error::PrototypePollution`].
+    Reject,
+
+    /// Leave the entry out of the output.
+    Drop,
+
+    /// Keep the entry under its new key with `_` appended, such as
+    /// `__proto___`.
+    Prefix,
+}
+
+/// The order of the entries of converted objects.
+///
+/// Objects can only keep an order when `serde_json` keeps one, as it does
+/// with this crate's `preserve_order` feature or `serde_json/preserve_order`
+/// enabled anywhere in the build. Otherwise they are always sorted by new
+/// key, whatever the option.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyOrder {
+    /// The order of the input.
+    #[default]
+    PreserveInput,
+
+    /// Sorted by new key, comparing their bytes.
+    SortAlphabetically,
+
+    /// Sorted by original key, comparing their bytes. Entries are converted
+    /// in that order, so it also decides which entry comes last when keys
+    /// collide.
+    SortByOriginalKey,
+}
+
+/// What to do with keys that are empty, or become empty after trimming.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyKeyPolicy {
+    /// Keep the entry under its original key.
+    #[default]
+    Preserve,
+
+    /// Leave the entry out of the output.
+    Drop,
+
+    /// Fail the conversion with [`Error::EmptyKey`].
+    Error,
+}
+
+/// Converts the keys of `json_map` to `new_case`, without wrapping it into a
+/// [`Value`] first.
+pub fn convert_map_keys(json_map: JsonMap, new_case: KeyCase) -> JsonMap {
+    expect_converted(Converter::new(new_case).convert_object(json_map))
 }
 
+#[derive(Clone, Debug)]
 pub struct CaseChanger<'a> {
     /// The input JSON.
     json_in: Value,
 
-    /// The case to convert.
-    case: Case,
-
-    /// Map of list of manual renames.
-    manual_renames: RenameMap<'a>,
-
-    /// Rename either by key or by value.
-    rename_behavior: RenameBehavior,
+    /// How to convert it.
+    converter: Converter<'a>,
 }
 
 impl<'a> CaseChanger<'a> {
-    pub fn new(json_obj: serde_json::Value, new_case: Case) -> Result<Self, ()> {
+    /// Builds a changer over `json_obj`, converting to `new_case`. Any value
+    /// is accepted, and scalars convert to themselves; use
+    /// [`CaseChanger::new_strict`] to reject them.
+    pub fn new(json_obj: serde_json::Value, new_case: KeyCase) -> Result<Self, Error> {
         Ok(Self {
             json_in: json_obj,
-            case: new_case,
-            manual_renames: RenameMap::default(),
-            rename_behavior: RenameBehavior::default(),
+            converter: Converter::new(new_case),
         })
     }
 
-    pub fn with_manual_renames(&mut self, rename_list: RenameMap<'a>) {
-        self.manual_renames = rename_list;
+    /// Like [`CaseChanger::new`], but fails with [`Error::InvalidRoot`] when
+    /// `json_obj` is neither an object nor an array, as a scalar has no key
+    /// to convert. [`CaseChangerBuilder::strict_root`] checks the same.
+    pub fn new_strict(json_obj: serde_json::Value, new_case: KeyCase) -> Result<Self, Error> {
+        check_root(&json_obj)?;
+        CaseChanger::new(json_obj, new_case)
+    }
+
+    /// Builds a changer over a document written in JSON5, which also covers
+    /// JSONC: comments, trailing commas, unquoted keys and single-quoted
+    /// strings are accepted. Comments are not kept, and the output is plain
+    /// JSON. Parse errors carry the line and column where parsing stopped.
+    #[cfg(feature = "json5")]
+    pub fn from_json5_str(input: &str, new_case: KeyCase) -> Result<Self, Error> {
+        let json_in = json5::from_str(input).map_err(Error::Json5)?;
+        CaseChanger::new(json_in, new_case)
     }
 
-    pub fn with_custom_rename_behavior(&mut self, rename_behavior: RenameBehavior) {
-        self.rename_behavior = rename_behavior;
+    /// Builds a changer over a JSON object, for callers already holding a
+    /// [`JsonMap`]. Pair it with [`CaseChanger::convert_map`] to get a map back,
+    /// or convert maps directly with [`Converter::convert_object`] and
+    /// [`Converter::convert_object_in_place`], which a changer also has.
+    pub fn new_from_map(json_map: JsonMap, new_case: KeyCase) -> Result<Self, Error> {
+        CaseChanger::new(Value::Object(json_map), new_case)
     }
 
+    /// Converts the input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the conversion fails, which can only happen with options that
+    /// turn offending keys into errors (such as [`EmptyKeyPolicy::Error`]).
+    /// Use [`CaseChanger::try_convert`] to handle those errors instead.
     pub fn convert(&mut self) -> Value {
-        let json_out = CaseChanger::internal_convert(
-            self.json_in.clone(),
-            self.case,
-            &self.manual_renames,
-            self.rename_behavior,
-        );
-
-        json_out
-    }
-
-    fn internal_convert(
-        actual_json: Value,
-        case: Case,
-        manual_renames: &RenameMap,
-        rename_behavior: RenameBehavior,
-    ) -> Value {
-        match actual_json {
-            Value::Array(arr) => {
-                let mut deep_arr: Vec<Value> = Vec::new();
-
-                for deep_value in arr {
-                    deep_arr.push(CaseChanger::internal_convert(
-                        deep_value,
-                        case,
-                        manual_renames,
-                        rename_behavior,
-                    ));
-                }
-
-                Value::Array(deep_arr)
-            }
-            Value::Object(actual_json) => {
-                let mut new_json = JsonMap::new();
-                for (key, value) in actual_json.iter() {
-                    match (key, value) {
-                        (key, Value::Object(elem)) => {
-                            let inner_obj = CaseChanger::internal_convert(
-                                Value::Object(elem.clone()),
-                                case,
-                                manual_renames,
-                                rename_behavior,
-                            );
-
-                            let manual_case = CaseChanger::determine_manual_case(
-                                key,
-                                &manual_renames,
-                                rename_behavior,
-                            );
-                            match manual_case {
-                                Some(k) => new_json.insert(k.to_owned(), inner_obj),
-                                None => new_json.insert(key.to_case(case), inner_obj),
-                            };
-                        }
-                        (key, Value::Array(elem)) => {
-                            let mut inner_arr: Vec<Value> = Vec::new();
-
-                            for obj in elem.iter() {
-                                let inner_obj = CaseChanger::internal_convert(
-                                    obj.clone(),
-                                    case,
-                                    manual_renames,
-                                    rename_behavior,
-                                );
-
-                                inner_arr.push(inner_obj);
-                            }
-                            let manual_case = CaseChanger::determine_manual_case(
-                                key,
-                                &manual_renames,
-                                rename_behavior,
-                            );
-                            match manual_case {
-                                Some(k) => new_json.insert(k.to_owned(), Value::Array(inner_arr)),
-                                None => new_json.insert(key.to_case(case), Value::Array(inner_arr)),
-                            };
-                        }
-                        (key, value) => {
-                            let manual_case = CaseChanger::determine_manual_case(
-                                key,
-                                &manual_renames,
-                                rename_behavior,
-                            );
-                            match manual_case {
-                                Some(k) => new_json.insert(k.to_owned(), value.clone()),
-                                None => new_json.insert(key.to_case(case), value.clone()),
-                            };
-                        }
-                    }
-                }
-
-                Value::Object(new_json)
-            }
-            value => value.clone(),
-        }
+        expect_converted(self.try_convert())
     }
 
-    fn determine_manual_case<'b>(
-        key: &'a str,
-        manual_renames: &'b RenameMap,
-        rename_behavior: RenameBehavior,
-    ) -> Option<&'b str> {
-        match rename_behavior {
-            RenameBehavior::ByKey => manual_renames
-                .get(key)
-                .and_then(|found| Some(found.to_owned())),
-            RenameBehavior::ByValue => manual_renames
-                .iter()
-                .find(|(_, rename_value)| **rename_value == key)
-                .and_then(|(key, _)| Some(key.to_owned())),
+    /// Converts the input, returning an error when a key is rejected by the
+    /// configured options.
+    ///
+    /// The input is cloned first, so that the changer can convert it again;
+    /// [`CaseChanger::try_into_converted`] converts it without the copy.
+    pub fn try_convert(&mut self) -> Result<Value, Error> {
+        self.converter.convert_value(self.json_in.clone())
+    }
+
+    /// Lists the keys of the input that converting it would rename or drop,
+    /// without converting it, as [`Converter::check_value`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the conversion would fail, as described in
+    /// [`CaseChanger::convert`]. Use [`Converter::check_value`] to handle
+    /// those errors instead.
+    pub fn preview(&self) -> Vec<KeyChange> {
+        expect_converted(self.converter.check_value(&self.json_in))
+    }
+
+    /// Converts the input and writes it in the canonical form of RFC 8785
+    /// (JSON Canonicalization Scheme), ready to be hashed or signed: without
+    /// whitespace, with object members sorted by the UTF-16 code units of
+    /// their new keys, and with numbers written as ECMAScript writes doubles,
+    /// so integers beyond 2^53 lose precision. The order of the input, and
+    /// the `preserve_order` feature, have no effect on it.
+    pub fn convert_to_canonical_string(&mut self) -> Result<String, Error> {
+        canonical::to_canonical_string(&self.try_convert()?)
+    }
+
+    /// Same as [`CaseChanger::try_convert`], but returns the converted object
+    /// as a [`JsonMap`]. Fails with [`Error::InvalidRoot`] when the input is
+    /// not an object, which can only happen when the changer was built with
+    /// [`CaseChanger::new`] from an array or a scalar.
+    pub fn convert_map(&mut self) -> Result<JsonMap, Error> {
+        match &self.json_in {
+            Value::Object(map) => self.converter.convert_object(map.clone()),
+            root => Err(Error::invalid_root("an object", root)),
         }
     }
+
+    /// Converts the input and deserializes it into a `T`, consuming the changer
+    /// so that neither the input nor the converted document is cloned.
+    ///
+    /// Deserialization errors carry the JSON pointer of the value that failed,
+    /// in the converted document.
+    pub fn convert_into<T: DeserializeOwned>(self) -> Result<T, Error> {
+        let (converted, dropped) = self.converter.convert_value_with_drops(self.json_in)?;
+        typed::deserialize_value(converted, dropped)
+    }
+
+    /// Converts the input to every case in `cases` walking it only once, and
+    /// returns the output of each case, by case. Each output is what
+    /// converting to its case alone would give, with the manual renames set
+    /// for that case with [`Converter::with_case_renames`], if any. Like
+    /// [`RenameMap`], the map is a `BTreeMap` without the `std` feature.
+    ///
+    /// Each original key is converted once per case and the result is reused
+    /// wherever that key appears again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the conversion fails, as described in [`CaseChanger::convert`].
+    /// Use [`CaseChanger::try_convert_multi`] to handle those errors instead.
+    pub fn convert_multi(&mut self, cases: &[KeyCase]) -> HashMap<KeyCase, Value> {
+        expect_converted(self.try_convert_multi(cases))
+    }
+
+    /// Like [`CaseChanger::convert_multi`], returning an error when a key is
+    /// rejected by the configured options.
+    pub fn try_convert_multi(
+        &mut self,
+        cases: &[KeyCase],
+    ) -> Result<HashMap<KeyCase, Value>, Error> {
+        self.converter.convert_multi_value(&self.json_in, cases)
+    }
+}
+
+impl<'a> Deref for CaseChanger<'a> {
+    type Target = Converter<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.converter
+    }
+}
+
+impl<'a> DerefMut for CaseChanger<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.converter
+    }
+}
+
+/// Fails with [`Error::InvalidRoot`] when `root` is neither an object nor an
+/// array, for changers built strictly.
+pub(crate) fn check_root(root: &Value) -> Result<(), Error> {
+    if root.is_object() || root.is_array() {
+        Ok(())
+    } else {
+        Err(Error::invalid_root("an object or an array", root))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use serde_json::json;
     use crate::*;
+    use serde_json::json;
 
     #[test]
     fn root_array() {
         let value = json!([{"myCamel": 1}, {"myCamel": 2}]);
         let expected = json!([{"my_camel": 1}, {"my_camel": 2}]);
 
-        let case_changed = CaseChanger::new(value, Case::Snake).unwrap().convert();
+        let case_changed = CaseChanger::new(value, KeyCase::Snake).unwrap().convert();
 
         assert_eq!(expected, case_changed);
     }
@@ -186,8 +460,568 @@ mod tests {
         // modifications.
         let expected = json!({"an_array": ["ObjectOne", "ObjectTwo"]});
 
-        let case_changed = CaseChanger::new(value, Case::Snake).unwrap().convert();
+        let case_changed = CaseChanger::new(value, KeyCase::Snake).unwrap().convert();
 
         assert_eq!(expected, case_changed);
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[cfg(feature = "preserve_order")]
+    fn keeps_key_order() {
+        let value = json!({"b": 1, "aCamel": {"zKey": 2, "yKey": 3}});
+
+        let case_changed = CaseChanger::new(value, KeyCase::Snake).unwrap().convert();
+
+        assert_eq!(
+            r#"{"b":1,"a_camel":{"z_key":2,"y_key":3}}"#,
+            serde_json::to_string(&case_changed).unwrap()
+        );
+    }
+
+    fn ordered(key_order: KeyOrder, in_place: bool) -> String {
+        let mut value = json!({"Zeta": 1, "beta": {"yKey": 2, "X": 3}, "Alpha": 4});
+        let mut converter = Converter::new(KeyCase::Kebab);
+        converter.with_key_order(key_order);
+        if in_place {
+            converter.convert_in_place(&mut value).unwrap();
+        } else {
+            value = converter.convert_value(value).unwrap();
+        }
+        serde_json::to_string(&value).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "preserve_order")]
+    fn key_orders() {
+        for &in_place in &[false, true] {
+            assert_eq!(
+                r#"{"zeta":1,"beta":{"y-key":2,"x":3},"alpha":4}"#,
+                ordered(KeyOrder::PreserveInput, in_place)
+            );
+            assert_eq!(
+                r#"{"alpha":4,"beta":{"x":3,"y-key":2},"zeta":1}"#,
+                ordered(KeyOrder::SortAlphabetically, in_place)
+            );
+            // Upper case letters sort first.
+            assert_eq!(
+                r#"{"alpha":4,"zeta":1,"beta":{"x":3,"y-key":2}}"#,
+                ordered(KeyOrder::SortByOriginalKey, in_place)
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "preserve_order"))]
+    fn key_orders_without_preserve_order() {
+        // Maps are sorted by new key whatever the option.
+        for &key_order in &[
+            KeyOrder::PreserveInput,
+            KeyOrder::SortAlphabetically,
+            KeyOrder::SortByOriginalKey,
+        ] {
+            for &in_place in &[false, true] {
+                assert_eq!(
+                    r#"{"alpha":4,"beta":{"x":3,"y-key":2},"zeta":1}"#,
+                    ordered(key_order, in_place)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn key_order_below_max_depth() {
+        let value = json!({"bKey": {"zZ": 1, "aA": 2}, "aKey": 0});
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_max_depth(1);
+        converter.with_key_order(KeyOrder::SortAlphabetically);
+
+        let expected = r#"{"a_key":0,"b_key":{"aA":2,"zZ":1}}"#;
+        let converted = converter.convert_value(value.clone()).unwrap();
+        assert_eq!(expected, serde_json::to_string(&converted).unwrap());
+        let (reported, _) = converter.convert_with_report(value.clone()).unwrap();
+        assert_eq!(expected, serde_json::to_string(&reported).unwrap());
+        let mut in_place = value;
+        converter.convert_in_place(&mut in_place).unwrap();
+        assert_eq!(expected, serde_json::to_string(&in_place).unwrap());
+    }
+
+    #[test]
+    fn empty_map() {
+        let case_changed = CaseChanger::new_from_map(JsonMap::new(), KeyCase::Snake)
+            .unwrap()
+            .convert_map()
+            .unwrap();
+
+        assert!(case_changed.is_empty());
+        assert!(convert_map_keys(JsonMap::new(), KeyCase::Snake).is_empty());
+    }
+
+    #[test]
+    fn rejects_scalar_roots_when_strict() {
+        for root in &[json!(null), json!(true), json!(1), json!("aKey")] {
+            let mut changer = CaseChanger::new(root.clone(), KeyCase::Snake).unwrap();
+            assert_eq!(*root, changer.convert());
+            assert!(matches!(
+                CaseChanger::new_strict(root.clone(), KeyCase::Snake),
+                Err(Error::InvalidRoot { .. })
+            ));
+            assert!(matches!(
+                CaseChanger::builder(root.clone(), KeyCase::Snake).strict_root(),
+                Err(Error::InvalidRoot { .. })
+            ));
+        }
+        assert_eq!(
+            "expected an object or an array at the root, found a string",
+            CaseChanger::new_strict(json!("aKey"), KeyCase::Snake)
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(CaseChanger::new_strict(json!([]), KeyCase::Snake).is_ok());
+        assert!(CaseChanger::builder(json!({}), KeyCase::Snake)
+            .strict_root()
+            .is_ok());
+    }
+
+    #[test]
+    fn convert_map_of_an_array() {
+        let mut changer = CaseChanger::new(json!([{"aKey": 1}]), KeyCase::Snake).unwrap();
+        assert_eq!(
+            "expected an object at the root, found an array",
+            changer.convert_map().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn convert_objects_directly() {
+        let mut map = JsonMap::new();
+        map.insert("userName".to_owned(), json!("Ada"));
+        map.insert("homeAddress".to_owned(), json!({"zipCode": "1000"}));
+
+        let changer = CaseChanger::new_from_map(JsonMap::new(), KeyCase::Snake).unwrap();
+        let converted = changer.convert_object(map.clone()).unwrap();
+        assert_eq!(
+            json!({"user_name": "Ada", "home_address": {"zip_code": "1000"}}),
+            Value::Object(converted.clone())
+        );
+
+        changer.convert_object_in_place(&mut map).unwrap();
+        assert_eq!(converted, map);
+    }
+
+    #[test]
+    fn map_with_array_of_objects() {
+        let value = json!({"someItems": [{"itemId": 1}, {"itemId": 2}], "totalCount": 2});
+        let map = value.as_object().unwrap().clone();
+
+        let wrapped = CaseChanger::new(value, KeyCase::Snake).unwrap().convert();
+        let expected = json!({"some_items": [{"item_id": 1}, {"item_id": 2}], "total_count": 2});
+        assert_eq!(expected, wrapped);
+
+        let case_changed = CaseChanger::new_from_map(map.clone(), KeyCase::Snake)
+            .unwrap()
+            .convert_map()
+            .unwrap();
+        assert_eq!(wrapped, Value::Object(case_changed));
+
+        assert_eq!(
+            wrapped,
+            Value::Object(convert_map_keys(map, KeyCase::Snake))
+        );
+    }
+
+    #[test]
+    fn clone_and_inspect() {
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_manual_renames([("ID", "identifier")].iter().cloned().collect());
+        converter.with_custom_rename_behavior(RenameBehavior::ByValue);
+        converter.with_skip_prefixes(vec!["$"]);
+
+        let copy = converter.clone();
+        assert_eq!(KeyCase::Camel, copy.case());
+        assert_eq!(RenameBehavior::ByValue, copy.rename_behavior());
+        assert_eq!(
+            vec![("ID", "identifier")],
+            copy.manual_renames().collect::<Vec<_>>()
+        );
+        assert!(format!("{:?}", copy).contains("skip_prefixes: [\"$\"]"));
+
+        let rebuilt = Converter::from_spec(converter.to_spec()).unwrap();
+        let value = json!({"identifier": 1, "$Ref": {"user_name": 2}});
+        let expected = json!({"ID": 1, "$Ref": {"userName": 2}});
+        assert_eq!(expected, converter.convert_value(value.clone()).unwrap());
+        assert_eq!(expected, copy.convert_value(value.clone()).unwrap());
+        assert_eq!(expected, rebuilt.convert_value(value).unwrap());
+    }
+
+    #[test]
+    fn multi_target_conversion() {
+        let value = json!({"userName": "a", "orderItems": [{"itemId": 1}, {"itemId": 2}]});
+        let cases = [KeyCase::Snake, KeyCase::Camel, KeyCase::Kebab];
+
+        let multi = CaseChanger::new(value.clone(), KeyCase::Snake)
+            .unwrap()
+            .convert_multi(&cases);
+
+        assert_eq!(cases.len(), multi.len());
+        for (case, converted) in multi {
+            let single = CaseChanger::new(value.clone(), case).unwrap().convert();
+            assert_eq!(single, converted);
+        }
+    }
+
+    #[test]
+    fn multi_target_matches_single_conversions() {
+        let value = json!({
+            "eventType": "orderCreated",
+            "ID": 1,
+            "payload": r#"{"innerKey": [{"itemId": 2}]}"#,
+            "attributes": [["firstName", {"nickName": "Ada"}], {"key": "lastName", "value": 3}],
+            "zetaKey": 4,
+            "alphaKey": {"bKey": 5, "aKey": 6}
+        });
+        let converter = |case| {
+            let mut converter = Converter::new(case);
+            converter.with_variant_renames(
+                "eventType",
+                "orderCreated",
+                [("ID", "orderId")].iter().cloned().collect(),
+            );
+            converter.with_embedded_json_fields(vec!["payload"]);
+            converter.with_pair_array_keys(vec!["attributes"]);
+            converter.with_key_order(KeyOrder::SortAlphabetically);
+            converter
+        };
+        let mut changer = CaseChanger::builder(value.clone(), KeyCase::Snake)
+            .configure(|c| *c = converter(KeyCase::Snake))
+            .build();
+
+        let cases = [KeyCase::Snake, KeyCase::Camel, KeyCase::Kebab];
+        let multi = changer.try_convert_multi(&cases).unwrap();
+        assert_eq!(cases.len(), multi.len());
+        for (case, converted) in multi {
+            let single = converter(case).convert_value(value.clone()).unwrap();
+            assert_eq!(
+                serde_json::to_string(&single).unwrap(),
+                serde_json::to_string(&converted).unwrap()
+            );
+        }
+
+        let mut empty = CaseChanger::new(json!({"": 1}), KeyCase::Snake).unwrap();
+        empty.with_empty_key_policy(EmptyKeyPolicy::Error);
+        assert!(matches!(
+            empty.try_convert_multi(&cases),
+            Err(Error::EmptyKey { .. })
+        ));
+    }
+
+    #[test]
+    fn multi_target_case_renames() {
+        let value = json!({"userName": "a", "id": 1});
+
+        let mut case_changer = CaseChanger::builder(value, KeyCase::Snake)
+            .manual_renames([("id", "identifier")].iter().cloned().collect())
+            .build();
+        case_changer
+            .with_case_renames(KeyCase::Kebab, [("id", "the-id")].iter().cloned().collect());
+
+        let multi = case_changer.convert_multi(&[KeyCase::Snake, KeyCase::Kebab]);
+
+        assert_eq!(
+            json!({"user_name": "a", "identifier": 1}),
+            multi[&KeyCase::Snake]
+        );
+        assert_eq!(
+            json!({"user-name": "a", "the-id": 1}),
+            multi[&KeyCase::Kebab]
+        );
+    }
+
+    #[test]
+    fn trim_keys() {
+        let value = json!({" userName": 1, "total \t": 2, " first name ": 3, " id ": 4});
+
+        let converted = CaseChanger::builder(value, KeyCase::Snake)
+            .manual_renames([("id", "identifier")].iter().cloned().collect())
+            .configure(|converter| converter.with_trim_keys(true))
+            .convert();
+
+        let expected = json!({"user_name": 1, "total": 2, "first_name": 3, "identifier": 4});
+        assert_eq!(expected, converted);
+    }
+
+    #[test]
+    fn empty_key_policies() {
+        let value = json!({"": 1, "  ": 2, "someKey": {"\t": 3}});
+
+        let mut case_changer = CaseChanger::new(value, KeyCase::Snake).unwrap();
+        case_changer.with_trim_keys(true);
+        assert_eq!(
+            json!({"": 1, "  ": 2, "some_key": {"\t": 3}}),
+            case_changer.convert()
+        );
+
+        case_changer.with_empty_key_policy(EmptyKeyPolicy::Drop);
+        assert_eq!(json!({"some_key": {}}), case_changer.convert());
+
+        case_changer.with_empty_key_policy(EmptyKeyPolicy::Error);
+        match case_changer.try_convert() {
+            Err(Error::EmptyKey { path }) => assert_eq!("/", path),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let value = json!({"someKey": {"\t": 3}});
+        let mut case_changer = CaseChanger::new(value, KeyCase::Snake).unwrap();
+        case_changer.with_trim_keys(true);
+        case_changer.with_empty_key_policy(EmptyKeyPolicy::Error);
+        match case_changer.try_convert() {
+            Err(Error::EmptyKey { path }) => assert_eq!("/someKey/\t", path),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn strip_prefixes() {
+        let value = json!({
+            "strUserName": 1,
+            "m_count": 2,
+            "bIsActive": 3,
+            "string": 4,
+            "str": 5,
+            "strLogin": 6
+        });
+
+        // Manual renames see the key once its prefix is gone.
+        let mut case_changer = CaseChanger::builder(value, KeyCase::Snake)
+            .manual_renames([("Login", "user_login")].iter().cloned().collect())
+            .configure(|converter| converter.with_strip_prefixes(vec!["str", "m_", "b"]))
+            .build();
+
+        let expected = json!({
+            "user_name": 1,
+            "count": 2,
+            "is_active": 3,
+            "ing": 4,
+            "str": 5,
+            "user_login": 6
+        });
+        assert_eq!(expected, case_changer.convert());
+
+        case_changer.with_strip_prefix_boundary(true);
+        let expected = json!({
+            "user_name": 1,
+            "count": 2,
+            "is_active": 3,
+            "string": 4,
+            "str": 5,
+            "user_login": 6
+        });
+        assert_eq!(expected, case_changer.convert());
+    }
+
+    #[test]
+    fn top_level_prefix() {
+        let value = json!({"userName": {"firstName": 1}, "items": [{"itemId": 2}]});
+
+        let mut case_changer = CaseChanger::new(value, KeyCase::Snake).unwrap();
+        case_changer.with_key_prefix("acme_", KeyScope::TopLevel);
+
+        let expected = json!({"acme_user_name": {"first_name": 1}, "acme_items": [{"item_id": 2}]});
+        assert_eq!(expected, case_changer.convert());
+    }
+
+    #[test]
+    fn pointer_suffix() {
+        let value = json!({"user": {"apiKey": 1, "other": 2}, "apiKey": 3});
+
+        let mut case_changer = CaseChanger::new(value, KeyCase::Snake).unwrap();
+        case_changer.with_key_suffix("_v2", KeyScope::Pointer("/user/apiKey".to_owned()));
+
+        let expected = json!({"user": {"api_key_v2": 1, "other": 2}, "api_key": 3});
+        assert_eq!(expected, case_changer.convert());
+    }
+
+    #[test]
+    fn multi_target_scoped_suffix() {
+        let value = json!({"apiKey": 1, "user": {"apiKey": 2}});
+
+        let mut case_changer = CaseChanger::new(value, KeyCase::Snake).unwrap();
+        case_changer.with_key_suffix("_v2", KeyScope::Pointer("/user/apiKey".to_owned()));
+
+        let multi = case_changer.convert_multi(&[KeyCase::Snake, KeyCase::Camel]);
+
+        assert_eq!(
+            json!({"api_key": 1, "user": {"api_key_v2": 2}}),
+            multi[&KeyCase::Snake]
+        );
+        assert_eq!(
+            json!({"apiKey": 1, "user": {"apiKey_v2": 2}}),
+            multi[&KeyCase::Camel]
+        );
+    }
+
+    #[test]
+    fn prefix_collision() {
+        let value = json!({"acme_id": 1, "id": 2});
+
+        let mut case_changer = CaseChanger::new(value, KeyCase::Snake).unwrap();
+        case_changer.with_key_prefix("acme_", KeyScope::Pointer("/id".to_owned()));
+
+        // The prefixed `id` lands on `acme_id`, and like any other collision the
+        // entry converted last wins.
+        assert_eq!(json!({"acme_id": 2}), case_changer.convert());
+    }
+
+    #[test]
+    fn rename_chains() {
+        let value = json!({"userName": "ada", "login": "ada90"});
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames(
+            [("userName", "login"), ("login", "handle")]
+                .iter()
+                .cloned()
+                .collect(),
+        );
+        match converter.convert_value(value.clone()) {
+            Err(Error::RenameChain { path, chain }) => {
+                assert_eq!("/userName", path);
+                assert_eq!(vec!["userName", "login", "handle"], chain);
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+
+        converter.with_chain_policy(ChainPolicy::Single);
+        assert_eq!(
+            json!({"login": "ada", "handle": "ada90"}),
+            converter.convert_value(value).unwrap()
+        );
+
+        converter.with_chain_policy(ChainPolicy::Resolve);
+        assert_eq!(
+            json!({"handle": "ada"}),
+            converter.convert_value(json!({"userName": "ada"})).unwrap()
+        );
+    }
+
+    #[test]
+    fn rename_cycles() {
+        let value = json!({"from": "a", "to": "b"});
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("from", "to"), ("to", "from")].iter().cloned().collect());
+        converter.with_chain_policy(ChainPolicy::Resolve);
+        assert_eq!(
+            "renames of the key at `/from` form a cycle: `from` -> `to` -> `from`",
+            converter
+                .convert_value(value.clone())
+                .unwrap_err()
+                .to_string()
+        );
+
+        // Keys are only looked up among the originals, so they swap.
+        converter.with_chain_policy(ChainPolicy::Single);
+        assert_eq!(
+            json!({"to": "a", "from": "b"}),
+            converter.convert_value(value).unwrap()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn errors_propagate() -> Result<(), Box<dyn std::error::Error>> {
+        let converted = CaseChanger::new(json!({"aKey": 1}), KeyCase::Snake)?.try_convert()?;
+        assert_eq!(json!({"a_key": 1}), converted);
+
+        let mut changer = CaseChanger::new(json!({"": 1}), KeyCase::Snake)?;
+        changer.with_empty_key_policy(EmptyKeyPolicy::Error);
+        let err: Box<dyn std::error::Error> = changer.try_convert().unwrap_err().into();
+        assert_eq!("empty key at `/`", err.to_string());
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn fixture(name: &str) -> std::path::PathBuf {
+        [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", name]
+            .iter()
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn manual_renames_from_file() {
+        let value = json!({"ID": 1, "userURL": "u", "xmlHttpRequest": {}, "otherKey": 0});
+
+        let mut case_changer = CaseChanger::new(value, KeyCase::Snake).unwrap();
+        case_changer
+            .with_manual_renames_from_file(fixture("renames.json"))
+            .unwrap();
+
+        assert_eq!(
+            json!({"id": 1, "user_url": "u", "xhr": {}, "other_key": 0}),
+            case_changer.convert()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json5")]
+    fn json5_input() {
+        let input = std::fs::read_to_string(fixture("config.jsonc")).unwrap();
+        let mut case_changer = CaseChanger::from_json5_str(&input, KeyCase::Snake).unwrap();
+
+        assert_eq!(
+            json!({
+                "service_name": "billing",
+                "database_url": "postgres://localhost/billing",
+                "retry_policy": {"max_attempts": 3, "backoff_ms": [100, 200, 400]}
+            }),
+            case_changer.convert()
+        );
+
+        let err = CaseChanger::from_json5_str("{\n  a: 1,\n  b: }", KeyCase::Snake)
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::Json5(_)));
+        assert!(err.to_string().starts_with("invalid JSON5: "));
+        assert!(err.to_string().ends_with("at line 3 column 6"), "{}", err);
+    }
+
+    #[test]
+    fn manual_renames_from_pairs() {
+        let mut case_changer = CaseChanger::new(json!({"ID": 1}), KeyCase::Snake).unwrap();
+        case_changer
+            .with_manual_renames_from_value(&json!([["ID", "first"], ["ID", "second"]]))
+            .unwrap();
+
+        assert_eq!(json!({"second": 1}), case_changer.convert());
+    }
+
+    #[test]
+    fn malformed_manual_renames() {
+        let mut converter = Converter::new(KeyCase::Snake);
+
+        let err = converter
+            .with_manual_renames_from_value(&json!([["a", "b"], ["c"]]))
+            .unwrap_err();
+        assert_eq!(
+            "invalid manual renames at `/1`: expected a pair of strings",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn malformed_manual_renames_file() {
+        let mut converter = Converter::new(KeyCase::Snake);
+
+        let err = converter
+            .with_manual_renames_from_file(fixture("renames_malformed.json"))
+            .unwrap_err();
+        assert_eq!(
+            "invalid manual renames at `/address`: expected a string",
+            err.to_string()
+        );
+
+        let err = converter
+            .with_manual_renames_from_file(fixture("missing.json"))
+            .unwrap_err();
+        assert!(matches!(err, Error::Io(_)));
+    }
+}
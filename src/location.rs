@@ -0,0 +1,171 @@
+use crate::path::Path;
+use crate::prelude::*;
+use core::fmt;
+
+/// Where a key is written in a JSON text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Byte offset of the opening quote of the key.
+    pub offset: usize,
+
+    /// Line of the key, starting at 1.
+    pub line: usize,
+
+    /// Column of the opening quote, in characters, starting at 1.
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Finds where every key of the JSON text `input` is written, by JSON
+/// pointer. Of keys written twice in an object, the last one is kept, as
+/// `serde_json` keeps the last value.
+///
+/// `input` must already be known to be valid JSON: `serde_json::Value`
+/// doesn't keep spans, so this is a second pass over the text.
+pub(crate) fn key_locations(input: &str) -> HashMap<String, Location> {
+    let mut scanner = Scanner {
+        input,
+        pos: 0,
+        line: 1,
+        counted: 0,
+        chars: 0,
+        locations: HashMap::new(),
+    };
+    scanner.value(&Path::Root);
+    scanner.locations
+}
+
+struct Scanner<'i> {
+    input: &'i str,
+    pos: usize,
+    line: usize,
+
+    /// Byte offset up to which the characters of the line are counted, so
+    /// that each character is counted once however many keys a line has.
+    counted: usize,
+
+    /// How many characters the line has before `counted`.
+    chars: usize,
+
+    locations: HashMap<String, Location>,
+}
+
+impl<'i> Scanner<'i> {
+    fn value(&mut self, path: &Path) {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(path),
+            Some(b'[') => self.array(path),
+            Some(b'"') => {
+                self.string();
+            }
+            _ => {
+                // Numbers and literals end at a delimiter.
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b']' | b'}') || byte.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn object(&mut self, path: &Path) {
+        self.pos += 1;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'"') => {}
+                Some(b',') => {
+                    self.pos += 1;
+                    continue;
+                }
+                _ => {
+                    self.pos += 1;
+                    return;
+                }
+            }
+
+            let location = Location {
+                offset: self.pos,
+                line: self.line,
+                column: self.column(),
+            };
+            let key = self.string();
+            let key: String = serde_json::from_str(key).unwrap_or_default();
+            let key_path = path.key(&key);
+            self.locations.insert(key_path.to_string(), location);
+
+            self.skip_whitespace();
+            // The colon.
+            self.pos += 1;
+            self.value(&key_path);
+        }
+    }
+
+    fn array(&mut self, path: &Path) {
+        self.pos += 1;
+        let mut index = 0;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b']') | None => {
+                    self.pos += 1;
+                    return;
+                }
+                Some(b',') => self.pos += 1,
+                Some(_) => {
+                    self.value(&path.index(index));
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Skips a string, returning it with its quotes and escapes.
+    fn string(&mut self) -> &'i str {
+        let input = self.input;
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'\\' => self.pos += 1,
+                b'"' => break,
+                _ => {}
+            }
+        }
+        &input[start..self.pos]
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if !byte.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+            if byte == b'\n' {
+                self.line += 1;
+                self.counted = self.pos;
+                self.chars = 0;
+            }
+        }
+    }
+
+    /// The column of the current position, starting at 1.
+    fn column(&mut self) -> usize {
+        self.chars += self.input[self.counted..self.pos].chars().count();
+        self.counted = self.pos;
+        self.chars + 1
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+}
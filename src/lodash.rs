@@ -0,0 +1,428 @@
+//! The word splitting and casing of lodash 4's `camelCase`, `snakeCase`
+//! and `kebabCase`, for [`Compatibility::Lodash`](crate::Compatibility::Lodash).
+//!
+//! lodash first replaces the Latin-1 and Latin Extended-A letters with
+//! ASCII ones (`deburr`) and removes combining marks and apostrophes. It
+//! then finds words with one regular expression, whose alternatives are
+//! ported here one by one, in the same order and with the same
+//! backtracking. Character classes follow lodash's, which only know the
+//! letters of Latin-1: other letters, such as Greek or Cyrillic ones, are
+//! neither uppercase nor lowercase to it.
+
+use crate::prelude::*;
+use crate::KeyCase;
+
+/// Converts `key` as lodash does, or returns `None` when lodash has no
+/// function for `case`.
+pub(crate) fn lodash_case(key: &str, case: KeyCase) -> Option<String> {
+    let separator = match case {
+        KeyCase::Camel => None,
+        KeyCase::Snake => Some('_'),
+        KeyCase::Kebab => Some('-'),
+        _ => return None,
+    };
+
+    let mut new_key = String::with_capacity(key.len());
+    for (i, word) in words(&prepare(key)).into_iter().enumerate() {
+        let word: String = word.iter().collect::<String>().to_lowercase();
+        match separator {
+            None if i > 0 => push_upper_first(&mut new_key, &word),
+            Some(separator) if i > 0 => {
+                new_key.push(separator);
+                new_key.push_str(&word);
+            }
+            _ => new_key.push_str(&word),
+        }
+    }
+    Some(new_key)
+}
+
+/// `deburr(key).replace(reApos, '')`.
+fn prepare(key: &str) -> Vec<char> {
+    let mut chars = Vec::with_capacity(key.len());
+    for c in key.chars() {
+        match deburr(c) {
+            Some(letters) => chars.extend(letters.chars()),
+            None if is_combo(c) || c == '\'' || c == '\u{2019}' => {}
+            None => chars.push(c),
+        }
+    }
+    chars
+}
+
+/// `words`: the unicode word pattern when the key has anything but
+/// lowercase words of ASCII letters and digits separated by spaces, and a
+/// plain split otherwise.
+fn words(chars: &[char]) -> Vec<&[char]> {
+    let mut words = Vec::new();
+    if !has_unicode_word(chars) {
+        let is_word = |c: &char| !c.is_ascii() || c.is_ascii_alphanumeric();
+        let mut start = None;
+        for (i, c) in chars.iter().enumerate() {
+            match (is_word(c), start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    words.push(&chars[s..i]);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            words.push(&chars[s..]);
+        }
+        return words;
+    }
+
+    let mut i = 0;
+    while i < chars.len() {
+        let end = lower_word(chars, i)
+            .or_else(|| upper_word(chars, i))
+            .or_else(|| misc_word(chars, i))
+            .or_else(|| acronym(chars, i))
+            .or_else(|| ordinal(chars, i, ["1ST", "2ND", "3RD"], "TH"))
+            .or_else(|| ordinal(chars, i, ["1st", "2nd", "3rd"], "th"))
+            .or_else(|| Some(run(chars, i, is_digit)).filter(|&end| end > i))
+            .or_else(|| emoji(chars, i));
+        match end {
+            Some(end) => {
+                words.push(&chars[i..end]);
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+    words
+}
+
+/// `reHasUnicodeWord`.
+fn has_unicode_word(chars: &[char]) -> bool {
+    let at = |i: usize, f: fn(&char) -> bool| chars.get(i).is_some_and(f);
+    (0..chars.len()).any(|i| {
+        (at(i, char::is_ascii_lowercase) && at(i + 1, char::is_ascii_uppercase))
+            || (at(i, char::is_ascii_uppercase)
+                && at(i + 1, char::is_ascii_uppercase)
+                && at(i + 2, char::is_ascii_lowercase))
+            || (at(i, char::is_ascii_digit) && at(i + 1, char::is_ascii_alphabetic))
+            || (at(i, char::is_ascii_alphabetic) && at(i + 1, char::is_ascii_digit))
+            || !(chars[i].is_ascii_alphanumeric() || chars[i] == ' ')
+    })
+}
+
+/// `rsUpper? rsLower+ (?=rsBreak|rsUpper|$)`
+fn lower_word(chars: &[char], i: usize) -> Option<usize> {
+    let start = if is_upper(chars[i]) { i + 1 } else { i };
+    let end = run(chars, start, is_lower);
+    let followed = chars.get(end).is_none_or(|&c| is_break(c) || is_upper(c));
+    (end > start && followed).then_some(end)
+}
+
+/// `rsMiscUpper+ (?=rsBreak|rsUpper rsMiscLower|$)`, backtracking to
+/// leave the last capital of an acronym to the next word.
+fn upper_word(chars: &[char], i: usize) -> Option<usize> {
+    let end = run(chars, i, |c| is_upper(c) || is_misc(c));
+    if end == i {
+        return None;
+    }
+    if chars.get(end).is_none_or(|&c| is_break(c)) {
+        return Some(end);
+    }
+    (i + 1..end).rev().find(|&e| {
+        is_upper(chars[e]) && chars.get(e + 1).is_some_and(|&c| is_lower(c) || is_misc(c))
+    })
+}
+
+/// `rsUpper? rsMiscLower+`
+fn misc_word(chars: &[char], i: usize) -> Option<usize> {
+    let start = if is_upper(chars[i]) { i + 1 } else { i };
+    let end = run(chars, start, |c| is_lower(c) || is_misc(c));
+    (end > start).then_some(end)
+}
+
+/// `rsUpper+`
+fn acronym(chars: &[char], i: usize) -> Option<usize> {
+    let end = run(chars, i, is_upper);
+    (end > i).then_some(end)
+}
+
+/// `rsOrdUpper` or `rsOrdLower`: `\d*(?:1st|2nd|3rd|(?![123])\dth)`,
+/// followed by a word boundary or a letter of the other case or `_`.
+fn ordinal(chars: &[char], i: usize, suffixes: [&str; 3], th: &str) -> Option<usize> {
+    let matches = |at: usize, literal: &str| {
+        literal
+            .chars()
+            .enumerate()
+            .all(|(k, c)| chars.get(at + k) == Some(&c))
+    };
+    let other_case = if th == "TH" {
+        char::is_ascii_lowercase
+    } else {
+        char::is_ascii_uppercase
+    };
+    let followed = |end: usize| match chars.get(end) {
+        None => true,
+        Some(c) => !c.is_ascii_alphanumeric() || other_case(c),
+    };
+
+    let digits_end = run(chars, i, is_digit);
+    (i..=digits_end).rev().find_map(|at| {
+        let nth = chars
+            .get(at)
+            .is_some_and(|&c| is_digit(c) && !"123".contains(c))
+            && matches(at + 1, th);
+        let end = at + 3;
+        ((nth || suffixes.iter().any(|suffix| matches(at, suffix))) && followed(end)).then_some(end)
+    })
+}
+
+/// `rsEmoji`: a dingbat, a pair of regional indicators or a character
+/// outside the Basic Multilingual Plane, with its modifiers and what zero
+/// width joiners join to it.
+fn emoji(chars: &[char], i: usize) -> Option<usize> {
+    let end = if is_dingbat(chars[i]) {
+        i + 1
+    } else if is_regional_pair(chars, i) {
+        i + 2
+    } else if is_astral(chars[i]) {
+        i + 1
+    } else {
+        return None;
+    };
+    Some(sequence(chars, end))
+}
+
+/// The end of `rsSeq` starting at `i`: an optional variation selector, an
+/// optional modifier, and any number of joined characters.
+fn sequence(chars: &[char], mut i: usize) -> usize {
+    let modifiers = |mut i: usize| {
+        if chars
+            .get(i)
+            .is_some_and(|&c| c == '\u{fe0e}' || c == '\u{fe0f}')
+        {
+            i += 1;
+        }
+        if chars.get(i).is_some_and(|&c| is_combo(c) || is_fitz(c)) {
+            i += 1;
+        }
+        i
+    };
+    i = modifiers(i);
+    while chars.get(i) == Some(&'\u{200d}') && i + 1 < chars.len() {
+        let joined = if is_regional_pair(chars, i + 1) {
+            i + 3
+        } else {
+            i + 2
+        };
+        i = modifiers(joined);
+    }
+    i
+}
+
+/// `capitalize` of a lowercase word: its first symbol uppercased, the
+/// symbol spanning modifiers and joined characters when the word has any.
+fn push_upper_first(new_key: &mut String, word: &str) {
+    let chars: Vec<char> = word.chars().collect();
+    let has_unicode = chars.iter().any(|&c| {
+        c == '\u{200d}' || is_astral(c) || is_combo(c) || c == '\u{fe0e}' || c == '\u{fe0f}'
+    });
+    let end = if chars.is_empty() {
+        0
+    } else if !has_unicode || (is_fitz(chars[0]) && chars.get(1).is_some_and(|&c| is_fitz(c))) {
+        1
+    } else {
+        let mut end = if is_regional_pair(&chars, 0) { 2 } else { 1 };
+        if !is_astral(chars[0]) && chars.get(1).is_some_and(|&c| is_combo(c)) {
+            end += 1;
+        }
+        sequence(&chars, end)
+    };
+    let first: String = chars[..end].iter().collect();
+    new_key.push_str(&first.to_uppercase());
+    new_key.extend(&chars[end..]);
+}
+
+fn run(chars: &[char], start: usize, class: impl Fn(char) -> bool) -> usize {
+    chars[start.min(chars.len())..]
+        .iter()
+        .position(|&c| !class(c))
+        .map_or(chars.len(), |len| start + len)
+}
+
+fn is_upper(c: char) -> bool {
+    matches!(c, 'A'..='Z' | '\u{c0}'..='\u{d6}' | '\u{d8}'..='\u{de}')
+}
+
+fn is_lower(c: char) -> bool {
+    matches!(c, 'a'..='z' | '\u{df}'..='\u{f6}' | '\u{f8}'..='\u{ff}')
+}
+
+fn is_digit(c: char) -> bool {
+    c.is_ascii_digit()
+}
+
+/// `rsBreak`: ASCII and Latin-1 punctuation, math operators, general
+/// punctuation and spaces.
+fn is_break(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0}'..='\u{2f}'
+            | '\u{3a}'..='\u{40}'
+            | '\u{5b}'..='\u{60}'
+            | '\u{7b}'..='\u{bf}'
+            | '\u{d7}'
+            | '\u{f7}'
+            | '\u{1680}'
+            | '\u{180e}'
+            | '\u{2000}'..='\u{206f}'
+            | '\u{3000}'
+            | '\u{feff}'
+    )
+}
+
+fn is_dingbat(c: char) -> bool {
+    matches!(c, '\u{2700}'..='\u{27bf}')
+}
+
+/// Characters JavaScript strings hold as surrogate pairs.
+fn is_astral(c: char) -> bool {
+    c > '\u{ffff}'
+}
+
+/// `rsMisc`: everything no other class holds.
+fn is_misc(c: char) -> bool {
+    !(is_astral(c) || is_break(c) || is_digit(c) || is_dingbat(c) || is_lower(c) || is_upper(c))
+}
+
+fn is_combo(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36f}' | '\u{fe20}'..='\u{fe2f}' | '\u{20d0}'..='\u{20ff}')
+}
+
+/// Emoji skin tone modifiers.
+fn is_fitz(c: char) -> bool {
+    matches!(c, '\u{1f3fb}'..='\u{1f3ff}')
+}
+
+fn is_regional_pair(chars: &[char], i: usize) -> bool {
+    let is_regional = |c: Option<&char>| c.is_some_and(|c| ('\u{1f1e6}'..='\u{1f1ff}').contains(c));
+    is_regional(chars.get(i)) && is_regional(chars.get(i + 1))
+}
+
+/// `deburredLetters`: the ASCII letters lodash writes for the letters of
+/// Latin-1 and Latin Extended-A.
+fn deburr(c: char) -> Option<&'static str> {
+    Some(match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'ð' | 'ď' | 'đ' => "d",
+        'È'..='Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'Ĥ' | 'Ħ' => "H",
+        'ĥ' | 'ħ' => "h",
+        'Ì'..='Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'Ĵ' => "J",
+        'ĵ' => "j",
+        'Ķ' => "K",
+        'ķ' | 'ĸ' => "k",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' | 'Ŋ' => "N",
+        'ñ' | 'ń' | 'ņ' | 'ň' | 'ŋ' => "n",
+        'Ò'..='Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'ś' | 'ŝ' | 'ş' | 'š' | 'ſ' => "s",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'Ù'..='Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'Ŵ' => "W",
+        'ŵ' => "w",
+        'Ý' | 'Ŷ' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        'Æ' => "Ae",
+        'æ' => "ae",
+        'Þ' => "Th",
+        'þ' => "th",
+        'ß' => "ss",
+        'Ĳ' => "IJ",
+        'ĳ' => "ij",
+        'Œ' => "Oe",
+        'œ' => "oe",
+        'ŉ' => "'n",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lodash_case;
+    use crate::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Case {
+        key: String,
+        camel: String,
+        snake: String,
+        kebab: String,
+    }
+
+    /// Outputs of lodash 4.17.21 for the keys of `tests/fixtures/lodash.js`.
+    #[test]
+    fn matches_lodash() {
+        let cases: Vec<Case> =
+            serde_json::from_str(include_str!("../tests/fixtures/lodash.json")).unwrap();
+        assert!(cases.len() > 300);
+        for case in &cases {
+            for (expected, key_case) in [
+                (&case.camel, KeyCase::Camel),
+                (&case.snake, KeyCase::Snake),
+                (&case.kebab, KeyCase::Kebab),
+            ] {
+                assert_eq!(
+                    Some(expected.as_str()),
+                    lodash_case(&case.key, key_case).as_deref(),
+                    "{:?} to {}",
+                    case.key,
+                    key_case
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn selected_per_converter() {
+        let value = serde_json::json!({"user.id": 1, "2ndPlace": 2, "don't": 3});
+        let mut converter = Converter::new(KeyCase::Snake);
+        let default = converter.clone();
+        converter.with_compatibility(Compatibility::Lodash);
+        assert_eq!(
+            serde_json::json!({"user_id": 1, "2nd_place": 2, "dont": 3}),
+            converter.convert_value(value.clone()).unwrap()
+        );
+        assert_eq!(
+            serde_json::json!({"user.id": 1, "2_nd_place": 2, "don't": 3}),
+            default.convert_value(value).unwrap()
+        );
+
+        // Cases lodash has no function for are converted as usual.
+        let mut pascal = Converter::new(KeyCase::Pascal);
+        pascal.with_compatibility(Compatibility::Lodash);
+        assert_eq!(
+            serde_json::json!({"UserId": 1}),
+            pascal
+                .convert_value(serde_json::json!({"user_id": 1}))
+                .unwrap()
+        );
+    }
+}
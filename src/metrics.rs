@@ -0,0 +1,105 @@
+use crate::{DropReason, DroppedKey};
+use ::metrics::{counter, histogram, Label};
+use std::time::Duration;
+
+/// How many keys a conversion renamed, how many it kept, and how many values
+/// it redacted.
+#[derive(Default)]
+pub(crate) struct KeyCounts {
+    converted: u64,
+    unchanged: u64,
+    redacted: u64,
+}
+
+impl KeyCounts {
+    pub(crate) fn count(&mut self, key: &str, new_key: &str) {
+        if key == new_key {
+            self.unchanged += 1;
+        } else {
+            self.converted += 1;
+        }
+    }
+
+    pub(crate) fn count_redaction(&mut self) {
+        self.redacted += 1;
+    }
+}
+
+/// Records one conversion with the `metrics` facade.
+pub(crate) fn record(
+    label: Option<&str>,
+    counts: &KeyCounts,
+    dropped: &[DroppedKey],
+    elapsed: Duration,
+) {
+    let labels: Vec<Label> = label
+        .map(|label| Label::new("label", label.to_owned()))
+        .into_iter()
+        .collect();
+    let collisions = dropped
+        .iter()
+        .filter(|dropped| matches!(dropped.reason, DropReason::Collision { .. }))
+        .count();
+
+    counter!("jkcc_keys_converted_total", labels.clone()).increment(counts.converted);
+    counter!("jkcc_keys_unchanged_total", labels.clone()).increment(counts.unchanged);
+    counter!("jkcc_collisions_total", labels.clone()).increment(collisions as u64);
+    counter!("jkcc_values_redacted_total", labels.clone()).increment(counts.redacted);
+    histogram!("jkcc_convert_duration_seconds", labels).record(elapsed);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use ::metrics::Label;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use metrics_util::MetricKind;
+    use serde_json::json;
+
+    #[test]
+    fn records_counts() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_metrics_label("orders");
+        converter.with_redaction(RedactionRule::exact("unitPrice", Redaction::Null));
+        ::metrics::with_local_recorder(&recorder, || {
+            converter
+                .convert_value(json!({
+                    "orderId": 1,
+                    "items": [{"unitPrice": 2, "count": 3}],
+                    "order_id": 4
+                }))
+                .unwrap();
+        });
+
+        let label = Label::new("label", "orders");
+        let mut counters = Vec::new();
+        let mut durations = 0;
+        for (key, _, _, value) in snapshotter.snapshot().into_vec() {
+            assert_eq!(vec![&label], key.key().labels().collect::<Vec<_>>());
+            match (key.kind(), value) {
+                (MetricKind::Counter, DebugValue::Counter(count)) => {
+                    counters.push((key.key().name().to_owned(), count))
+                }
+                (MetricKind::Histogram, DebugValue::Histogram(values)) => {
+                    assert_eq!("jkcc_convert_duration_seconds", key.key().name());
+                    durations += values.len();
+                }
+                other => panic!("unexpected metric: {:?}", other),
+            }
+        }
+        counters.sort();
+        assert_eq!(
+            vec![
+                ("jkcc_collisions_total".to_owned(), 1),
+                ("jkcc_keys_converted_total".to_owned(), 2),
+                ("jkcc_keys_unchanged_total".to_owned(), 3),
+                ("jkcc_values_redacted_total".to_owned(), 1),
+            ],
+            counters
+        );
+        assert_eq!(1, durations);
+    }
+}
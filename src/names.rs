@@ -0,0 +1,120 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error, KeyRule};
+
+/// How [`Converter::convert_names`] converted one name of a list.
+#[derive(Debug)]
+pub struct NameMapping {
+    /// The name, as given.
+    pub name: String,
+
+    /// The new name, `None` when the name would be left out, or the error
+    /// the conversion failed with.
+    pub new_name: Result<Option<String>, Error>,
+
+    /// The rules that had a say, in the order they were applied, as in
+    /// [`KeyPreview::rules`](crate::KeyPreview::rules).
+    pub rules: Vec<KeyRule>,
+
+    /// The other names of the list converted to the same new name, in list
+    /// order.
+    pub collides_with: Vec<String>,
+}
+
+impl NameMapping {
+    /// The rule that decided the new name: a rule keeping the name as it
+    /// is, the empty key policy, a manual rename or the case conversion.
+    pub fn rule(&self) -> Option<&KeyRule> {
+        self.rules
+            .iter()
+            .find(|rule| !matches!(rule, KeyRule::Trimmed | KeyRule::StrippedPrefix(_)))
+    }
+}
+
+impl Converter<'_> {
+    /// Converts a plain list of names, such as CSV headers or database
+    /// columns, as the keys of a top-level object would be converted, so
+    /// they stay consistent with converted documents.
+    ///
+    /// Every mapping also tells which rules applied and which other names of
+    /// the list it collides with.
+    pub fn convert_names<'s>(&self, names: impl IntoIterator<Item = &'s str>) -> Vec<NameMapping> {
+        let mut mappings: Vec<NameMapping> = names
+            .into_iter()
+            .map(|name| {
+                let mut rules = Vec::new();
+                let new_name = self.trace_key(name, &Path::Root.key(name), &mut rules);
+                NameMapping {
+                    name: name.to_owned(),
+                    new_name,
+                    rules,
+                    collides_with: Vec::new(),
+                }
+            })
+            .collect();
+
+        for i in 0..mappings.len() {
+            let new_name = match &mappings[i].new_name {
+                Ok(Some(new_name)) => new_name,
+                _ => continue,
+            };
+            let collides_with = mappings
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| {
+                    j != i && matches!(&other.new_name, Ok(Some(other)) if other == new_name)
+                })
+                .map(|(_, other)| other.name.clone())
+                .collect();
+            mappings[i].collides_with = collides_with;
+        }
+        mappings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn converts_headers() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("CustNo", "customer_number")].iter().cloned().collect());
+        converter.with_skip_prefixes(vec!["_"]);
+
+        let headers = ["HTTPStatus", "CustNo", "First Name", "firstName", "_rowId"];
+        let mappings = converter.convert_names(headers.iter().copied());
+
+        let new_names: Vec<_> = mappings
+            .iter()
+            .map(|mapping| mapping.new_name.as_ref().unwrap().as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            vec![
+                "http_status",
+                "customer_number",
+                "first_name",
+                "first_name",
+                "_rowId"
+            ],
+            new_names
+        );
+
+        assert_eq!(Some(&KeyRule::Case(KeyCase::Snake)), mappings[0].rule());
+        assert_eq!(
+            Some(&KeyRule::ManualRename {
+                from: "CustNo".to_owned(),
+                to: "customer_number".to_owned(),
+            }),
+            mappings[1].rule()
+        );
+        assert_eq!(
+            Some(&KeyRule::SkipPrefix("_".to_owned())),
+            mappings[4].rule()
+        );
+
+        assert!(mappings[0].collides_with.is_empty());
+        assert_eq!(vec!["firstName"], mappings[2].collides_with);
+        assert_eq!(vec!["First Name"], mappings[3].collides_with);
+    }
+}
@@ -0,0 +1,100 @@
+use alloc::borrow::Cow;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{is_nfc_quick, is_nfd_quick, IsNormalized, UnicodeNormalization};
+
+/// A Unicode normalization form keys are brought to. See
+/// [`Converter::with_unicode_normalization`](crate::Converter::with_unicode_normalization).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NormalizationForm {
+    /// Canonical composition: `é` as the single code point U+00E9.
+    Nfc,
+
+    /// Canonical decomposition: `é` as `e` followed by U+0301 COMBINING
+    /// ACUTE ACCENT, as macOS file systems store names.
+    Nfd,
+}
+
+impl NormalizationForm {
+    /// `key` in this form, borrowed when it already is.
+    pub(crate) fn normalize(self, key: &str) -> Cow<'_, str> {
+        match self {
+            NormalizationForm::Nfc if is_nfc_quick(key.chars()) != IsNormalized::Yes => {
+                Cow::Owned(key.nfc().collect())
+            }
+            NormalizationForm::Nfd if is_nfd_quick(key.chars()) != IsNormalized::Yes => {
+                Cow::Owned(key.nfd().collect())
+            }
+            _ => Cow::Borrowed(key),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    const COMPOSED: &str = "pr\u{e9}nom_usuel";
+    const DECOMPOSED: &str = "pre\u{301}nom_usuel";
+
+    fn document() -> serde_json::Value {
+        let mut document = JsonMap::new();
+        document.insert(DECOMPOSED.to_owned(), json!("Zoé"));
+        document.insert("last_name".to_owned(), json!("Martin"));
+        document.insert(COMPOSED.to_owned(), json!("Zoe"));
+        serde_json::Value::Object(document)
+    }
+
+    #[test]
+    fn unifies_both_forms_of_a_key() {
+        // The two forms are told apart by default.
+        let mut converter = Converter::new(KeyCase::Camel);
+        assert_eq!(
+            3,
+            converter
+                .convert_value(document())
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .len()
+        );
+
+        converter.with_unicode_normalization(NormalizationForm::Nfc);
+        assert_eq!(
+            json!({"pr\u{e9}nomUsuel": "Zoe", "lastName": "Martin"}),
+            converter.convert_value(document()).unwrap()
+        );
+        assert_eq!(
+            vec![KeyCollision {
+                path: String::new(),
+                keys: vec![DECOMPOSED.to_owned(), COMPOSED.to_owned()],
+                new_key: "pr\u{e9}nomUsuel".to_owned(),
+            }],
+            converter.preflight(&document()).collisions
+        );
+
+        converter.with_manual_renames([(COMPOSED, "givenName")].iter().cloned().collect());
+        assert_eq!(
+            json!({"givenName": "Zoe", "lastName": "Martin"}),
+            converter.convert_value(document()).unwrap()
+        );
+    }
+
+    #[test]
+    fn decomposes_keys() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_unicode_normalization(NormalizationForm::Nfd);
+        converter.with_dictionary_keys(["labels"]);
+        assert_eq!(
+            json!({"pre\u{301}nom": 1, "labels": {"caf\u{e9}": 2}}),
+            converter
+                .convert_value(json!({"Pr\u{e9}nom": 1, "labels": {"caf\u{e9}": 2}}))
+                .unwrap()
+        );
+
+        let mut spec = ConversionSpec::new(KeyCase::Snake);
+        spec.unicode_normalization = Some(NormalizationForm::Nfd);
+        assert_eq!(spec, Converter::from_spec(spec.clone()).unwrap().to_spec());
+    }
+}
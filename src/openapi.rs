@@ -0,0 +1,418 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::schema::Dialect;
+use crate::{Converter, Error, JsonMap};
+use core::mem;
+use serde_json::Value;
+
+/// Whether [`Converter::convert_openapi`] converts example bodies along with
+/// property names.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OpenApiMode {
+    /// Convert property names and keep examples as they are.
+    #[default]
+    KeepExamples,
+
+    /// Also convert the bodies under `example` and `examples`.
+    ConvertExamples,
+}
+
+/// The keys of a Path Item Object holding operations.
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+impl Converter<'_> {
+    /// Converts an OpenAPI 3.x document, so that it describes the payloads
+    /// this converter produces instead of the ones it reads.
+    ///
+    /// What gets converted is property names: those of schemas, as
+    /// [`Converter::convert_schema`] converts them, the `propertyName` of
+    /// discriminators and the keys of their `mapping`, the names of query
+    /// parameters and the keys of `encoding`. Path templates, response
+    /// codes, media types, the names of schemas, headers and other
+    /// parameters, and extensions (`x-*`) are kept. Options scoped by
+    /// pointer see the position of each name in the document, such as
+    /// `"/components/schemas/User/properties/userName"`.
+    pub fn convert_openapi(&self, document: Value, mode: OpenApiMode) -> Result<Value, Error> {
+        let mut document = match document {
+            Value::Object(document) => document,
+            other => return Ok(other),
+        };
+        let walk = OpenApi {
+            converter: self,
+            mode,
+        };
+
+        let path = Path::Root;
+        walk.member(&mut document, "paths", &path, |paths, path| {
+            walk.each(paths, path, |item, path| walk.path_item(item, path))
+        })?;
+        walk.member(&mut document, "webhooks", &path, |webhooks, path| {
+            walk.each(webhooks, path, |item, path| walk.path_item(item, path))
+        })?;
+        walk.member(&mut document, "components", &path, |components, path| {
+            walk.components(components, path)
+        })?;
+        Ok(Value::Object(document))
+    }
+}
+
+struct OpenApi<'c> {
+    converter: &'c Converter<'c>,
+    mode: OpenApiMode,
+}
+
+impl OpenApi<'_> {
+    fn components(&self, components: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        self.member(components, "schemas", path, |schemas, path| {
+            self.each(schemas, path, |schema, path| self.schema(schema, path))
+        })?;
+        self.member(components, "parameters", path, |parameters, path| {
+            self.each(parameters, path, |parameter, path| {
+                self.parameter(parameter, path)
+            })
+        })?;
+        self.member(components, "requestBodies", path, |bodies, path| {
+            self.each(bodies, path, |body, path| self.with_content(body, path))
+        })?;
+        self.member(components, "responses", path, |responses, path| {
+            self.each(responses, path, |response, path| {
+                self.response(response, path)
+            })
+        })?;
+        self.member(components, "headers", path, |headers, path| {
+            self.each(headers, path, |header, path| self.parameter(header, path))
+        })?;
+        self.member(components, "examples", path, |examples, path| {
+            self.each(examples, path, |example, _| self.named_example(example))
+        })?;
+        self.member(components, "callbacks", path, |callbacks, path| {
+            self.each(callbacks, path, |callback, path| {
+                self.callback(callback, path)
+            })
+        })?;
+        self.member(components, "pathItems", path, |items, path| {
+            self.each(items, path, |item, path| self.path_item(item, path))
+        })
+    }
+
+    fn path_item(&self, item: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        self.parameters(item, path)?;
+        for method in METHODS.iter() {
+            self.member(item, method, path, |operation, path| {
+                self.operation(operation, path)
+            })?;
+        }
+        Ok(())
+    }
+
+    fn operation(&self, operation: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        self.parameters(operation, path)?;
+        self.member(operation, "requestBody", path, |body, path| {
+            self.with_content(body, path)
+        })?;
+        self.member(operation, "responses", path, |responses, path| {
+            self.each(responses, path, |response, path| {
+                self.response(response, path)
+            })
+        })?;
+        self.member(operation, "callbacks", path, |callbacks, path| {
+            self.each(callbacks, path, |callback, path| {
+                self.callback(callback, path)
+            })
+        })
+    }
+
+    fn callback(&self, callback: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        self.each(callback, path, |item, path| self.path_item(item, path))
+    }
+
+    fn parameters(&self, holder: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        if let Some(Value::Array(parameters)) = holder.get_mut("parameters") {
+            let path = path.key("parameters");
+            for (index, parameter) in parameters.iter_mut().enumerate() {
+                if let Value::Object(parameter) = parameter {
+                    self.parameter(parameter, &path.index(index))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts a Parameter Object, or a Header Object, which has no `in`.
+    fn parameter(&self, parameter: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        if parameter.get("in").and_then(Value::as_str) == Some("query") {
+            if let Some(Value::String(name)) = parameter.get("name").cloned() {
+                let name_path = path.key("name");
+                if let Some(new_name) = self.converter.rename_key(&name, &name_path.key(&name))? {
+                    parameter.insert("name".to_owned(), Value::String(new_name));
+                }
+            }
+        }
+        self.with_schema(parameter, path)?;
+        self.with_content(parameter, path)
+    }
+
+    fn response(&self, response: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        self.member(response, "headers", path, |headers, path| {
+            self.each(headers, path, |header, path| self.parameter(header, path))
+        })?;
+        self.with_content(response, path)
+    }
+
+    /// Converts the Media Type Objects under `content`.
+    fn with_content(&self, holder: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        self.member(holder, "content", path, |content, path| {
+            self.each(content, path, |media_type, path| {
+                self.with_schema(media_type, path)?;
+                self.member(media_type, "encoding", path, |encoding, path| {
+                    *encoding =
+                        self.converter
+                            .convert_named(mem::take(encoding), path, |value, _| Ok(value))?;
+                    Ok(())
+                })
+            })
+        })
+    }
+
+    /// Converts the `schema` of `holder`, and its examples when asked to.
+    fn with_schema(&self, holder: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        if let Some(schema) = holder.get_mut("schema") {
+            *schema = self.converter.convert_subschema(
+                mem::take(schema),
+                &path.key("schema"),
+                Dialect::OpenApi(self.mode),
+            )?;
+        }
+        if self.mode == OpenApiMode::ConvertExamples {
+            if let Some(example) = holder.get_mut("example") {
+                *example = self.converter.convert_value(mem::take(example))?;
+            }
+            self.member(holder, "examples", path, |examples, path| {
+                self.each(examples, path, |example, _| self.named_example(example))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn schema(&self, schema: &mut JsonMap, path: &Path) -> Result<(), Error> {
+        let taken = Value::Object(mem::take(schema));
+        if let Value::Object(converted) =
+            self.converter
+                .convert_subschema(taken, path, Dialect::OpenApi(self.mode))?
+        {
+            *schema = converted;
+        }
+        Ok(())
+    }
+
+    /// Converts the `value` of an Example Object.
+    fn named_example(&self, example: &mut JsonMap) -> Result<(), Error> {
+        if self.mode == OpenApiMode::ConvertExamples {
+            if let Some(value) = example.get_mut("value") {
+                *value = self.converter.convert_value(mem::take(value))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls `convert` on the object held in `member` of `object`, if there is
+    /// one.
+    fn member(
+        &self,
+        object: &mut JsonMap,
+        member: &str,
+        path: &Path,
+        convert: impl FnOnce(&mut JsonMap, &Path) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        match object.get_mut(member) {
+            Some(Value::Object(inner)) => convert(inner, &path.key(member)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Calls `convert` on every object value of `map`, leaving extensions and
+    /// references alone.
+    fn each(
+        &self,
+        map: &mut JsonMap,
+        path: &Path,
+        convert: impl Fn(&mut JsonMap, &Path) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        for (key, value) in map.iter_mut() {
+            match value {
+                Value::Object(inner) if !key.starts_with("x-") && !inner.contains_key("$ref") => {
+                    convert(inner, &path.key(key))?
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn document() -> serde_json::Value {
+        json!({
+            "openapi": "3.1.0",
+            "info": {"title": "Pets", "version": "1.0.0", "x-apiOwner": "pets"},
+            "x-rateLimit": {"perMinute": 60},
+            "paths": {
+                "/petOwners/{ownerId}/pets": {
+                    "parameters": [
+                        {"name": "ownerId", "in": "path", "required": true, "schema": {"type": "string"}}
+                    ],
+                    "get": {
+                        "operationId": "listPets",
+                        "parameters": [
+                            {"name": "pageSize", "in": "query", "schema": {"type": "integer"}},
+                            {"name": "X-Request-Id", "in": "header", "schema": {"type": "string"}}
+                        ],
+                        "responses": {
+                            "200": {
+                                "description": "The pets",
+                                "content": {
+                                    "application/json": {
+                                        "schema": {
+                                            "type": "array",
+                                            "items": {"$ref": "#/components/schemas/Pet"}
+                                        },
+                                        "example": [{"petType": "smallDog", "barkVolume": 3}]
+                                    }
+                                }
+                            },
+                            "x-internalNote": {"seenBy": "qa"}
+                        }
+                    },
+                    "x-codegenHints": {"groupName": "pets"}
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "type": "object",
+                        "required": ["petType", "petName"],
+                        "properties": {
+                            "petType": {"type": "string"},
+                            "petName": {"type": "string"}
+                        },
+                        "discriminator": {
+                            "propertyName": "petType",
+                            "mapping": {"smallDog": "#/components/schemas/SmallDog"}
+                        }
+                    },
+                    "SmallDog": {
+                        "allOf": [
+                            {"$ref": "#/components/schemas/Pet"},
+                            {"properties": {"barkVolume": {"type": "integer"}}}
+                        ],
+                        "example": {"petType": "smallDog", "barkVolume": 1}
+                    }
+                },
+                "examples": {
+                    "quietDog": {"value": {"petName": "Rex", "barkVolume": 0}}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn converts_property_names() {
+        let converted = Converter::new(KeyCase::Snake)
+            .convert_openapi(document(), OpenApiMode::KeepExamples)
+            .unwrap();
+
+        assert_eq!(
+            json!({
+                "openapi": "3.1.0",
+                "info": {"title": "Pets", "version": "1.0.0", "x-apiOwner": "pets"},
+                "x-rateLimit": {"perMinute": 60},
+                "paths": {
+                    "/petOwners/{ownerId}/pets": {
+                        "parameters": [
+                            {"name": "ownerId", "in": "path", "required": true, "schema": {"type": "string"}}
+                        ],
+                        "get": {
+                            "operationId": "listPets",
+                            "parameters": [
+                                {"name": "page_size", "in": "query", "schema": {"type": "integer"}},
+                                {"name": "X-Request-Id", "in": "header", "schema": {"type": "string"}}
+                            ],
+                            "responses": {
+                                "200": {
+                                    "description": "The pets",
+                                    "content": {
+                                        "application/json": {
+                                            "schema": {
+                                                "type": "array",
+                                                "items": {"$ref": "#/components/schemas/Pet"}
+                                            },
+                                            "example": [{"petType": "smallDog", "barkVolume": 3}]
+                                        }
+                                    }
+                                },
+                                "x-internalNote": {"seenBy": "qa"}
+                            }
+                        },
+                        "x-codegenHints": {"groupName": "pets"}
+                    }
+                },
+                "components": {
+                    "schemas": {
+                        "Pet": {
+                            "type": "object",
+                            "required": ["pet_type", "pet_name"],
+                            "properties": {
+                                "pet_type": {"type": "string"},
+                                "pet_name": {"type": "string"}
+                            },
+                            "discriminator": {
+                                "propertyName": "pet_type",
+                                "mapping": {"small_dog": "#/components/schemas/SmallDog"}
+                            }
+                        },
+                        "SmallDog": {
+                            "allOf": [
+                                {"$ref": "#/components/schemas/Pet"},
+                                {"properties": {"bark_volume": {"type": "integer"}}}
+                            ],
+                            "example": {"petType": "smallDog", "barkVolume": 1}
+                        }
+                    },
+                    "examples": {
+                        "quietDog": {"value": {"petName": "Rex", "barkVolume": 0}}
+                    }
+                }
+            }),
+            converted
+        );
+    }
+
+    #[test]
+    fn converts_examples() {
+        let converted = Converter::new(KeyCase::Snake)
+            .convert_openapi(document(), OpenApiMode::ConvertExamples)
+            .unwrap();
+
+        let content = &converted["paths"]["/petOwners/{ownerId}/pets"]["get"]["responses"]["200"]
+            ["content"]["application/json"];
+        assert_eq!(
+            json!([{"pet_type": "smallDog", "bark_volume": 3}]),
+            content["example"]
+        );
+        assert_eq!(
+            json!({"pet_type": "smallDog", "bark_volume": 1}),
+            converted["components"]["schemas"]["SmallDog"]["example"]
+        );
+        assert_eq!(
+            json!({"pet_name": "Rex", "bark_volume": 0}),
+            converted["components"]["examples"]["quietDog"]["value"]
+        );
+        assert_eq!(json!({"perMinute": 60}), converted["x-rateLimit"]);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::path::{escape_pointer_token, Path};
+use crate::prelude::*;
+use crate::scope::parse_pointer;
+use crate::{Converter, Error, JsonMap};
+use serde_json::Value;
+
+impl Converter<'_> {
+    /// Converts a JSON Patch (RFC 6902), so that it applies to documents this
+    /// converter produced instead of the ones it read.
+    ///
+    /// The members of each operation are kept. The keys in its `path` and
+    /// `from` pointers are converted as if they were keys of the patched
+    /// document, and its `value` is converted as the value found at `path`.
+    /// Tokens that look like array indices (`0`, `12`, `-`) are left alone,
+    /// including when they are object keys. Returns [`Error::InvalidPatch`]
+    /// if `patch` isn't an array of operations.
+    pub fn convert_patch(&self, patch: Value) -> Result<Value, Error> {
+        let operations = match patch {
+            Value::Array(operations) => operations,
+            _ => return Err(invalid("", "expected an array of operations")),
+        };
+
+        operations
+            .into_iter()
+            .enumerate()
+            .map(|(index, operation)| match operation {
+                Value::Object(operation) => {
+                    self.convert_operation(operation, index).map(Value::Object)
+                }
+                _ => Err(invalid(
+                    &format!("/{}", index),
+                    "expected an operation object",
+                )),
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::Array)
+    }
+
+    fn convert_operation(&self, mut operation: JsonMap, index: usize) -> Result<JsonMap, Error> {
+        let member_path = |member: &str| format!("/{}/{}", index, member);
+        if !matches!(operation.get("op"), Some(Value::String(_))) {
+            return Err(invalid(&member_path("op"), "expected a string"));
+        }
+
+        let value = operation.remove("value");
+        for member in &["path", "from"] {
+            let pointer = match operation.get(*member) {
+                Some(Value::String(pointer)) => pointer,
+                None if *member == "from" => continue,
+                _ => return Err(invalid(&member_path(member), "expected a string")),
+            };
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                return Err(invalid(
+                    &member_path(member),
+                    &format!("`{}` is not a JSON pointer", pointer),
+                ));
+            }
+
+            let mut new_pointer = String::new();
+            let value = match *member {
+                "path" => value.clone(),
+                _ => None,
+            };
+            let value = self.convert_pointer(
+                &parse_pointer(pointer),
+                &Path::Root,
+                &mut new_pointer,
+                value,
+            )?;
+            operation.insert((*member).to_owned(), Value::String(new_pointer));
+            if let Some(value) = value {
+                operation.insert("value".to_owned(), value);
+            }
+        }
+
+        Ok(operation)
+    }
+
+    /// Writes the converted `tokens` to `new_pointer`, then converts `value`
+    /// as the value they point to.
+    fn convert_pointer(
+        &self,
+        tokens: &[String],
+        path: &Path,
+        new_pointer: &mut String,
+        value: Option<Value>,
+    ) -> Result<Option<Value>, Error> {
+        let (token, rest) = match tokens.split_first() {
+            Some(split) => split,
+            None => {
+                return value
+                    .map(|value| self.convert_value_at(value, path))
+                    .transpose()
+            }
+        };
+
+        new_pointer.push('/');
+        match array_index(token) {
+            Some(index) => {
+                new_pointer.push_str(token);
+                self.convert_pointer(rest, &path.index(index), new_pointer, value)
+            }
+            None => {
+                let token_path = path.key(token);
+                let new_token = self
+                    .rename_key(token, &token_path)?
+                    .unwrap_or_else(|| token.clone());
+                new_pointer.push_str(&escape_pointer_token(&new_token));
+                self.convert_pointer(rest, &token_path, new_pointer, value)
+            }
+        }
+    }
+}
+
+/// The index a pointer token refers to if it is one, with `-` (past the end of
+/// the array) as `usize::MAX`.
+fn array_index(token: &str) -> Option<usize> {
+    if token == "-" {
+        Some(usize::MAX)
+    } else if token == "0" || !token.starts_with('0') {
+        token
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| token.parse().ok())
+            .flatten()
+    } else {
+        None
+    }
+}
+
+fn invalid(path: &str, message: &str) -> Error {
+    Error::InvalidPatch {
+        path: path.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn converted_patch_applies_to_converted_document() {
+        let document = json!({
+            "userName": "Ada",
+            "homeAddress": {"streetName": "Main", "a/b~c": 1},
+            "pastAddresses": [{"streetName": "Old"}]
+        });
+        let patch = json!([
+            {"op": "replace", "path": "/userName", "value": "Grace"},
+            {"op": "add", "path": "/pastAddresses/-", "value": {"streetName": "New", "zipCode": 1}},
+            {"op": "test", "path": "/pastAddresses/0/streetName", "value": "Old"},
+            {"op": "move", "from": "/homeAddress/a~1b~0c", "path": "/homeAddress/zipCode"},
+            {"op": "copy", "from": "/homeAddress", "path": "/workAddress"},
+            {"op": "remove", "path": "/pastAddresses/0"}
+        ]);
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        let renames: RenameMap = [("userName", "login")].iter().cloned().collect();
+        converter.with_manual_renames(renames);
+
+        let converted_patch = converter.convert_patch(patch).unwrap();
+        assert_eq!(
+            json!([
+                {"op": "replace", "path": "/login", "value": "Grace"},
+                {"op": "add", "path": "/past_addresses/-", "value": {"street_name": "New", "zip_code": 1}},
+                {"op": "test", "path": "/past_addresses/0/street_name", "value": "Old"},
+                {"op": "move", "from": "/home_address/a~1b~0c", "path": "/home_address/zip_code"},
+                {"op": "copy", "from": "/home_address", "path": "/work_address"},
+                {"op": "remove", "path": "/past_addresses/0"}
+            ]),
+            converted_patch
+        );
+
+        let mut converted = converter.convert_value(document).unwrap();
+        let converted_patch: json_patch::Patch = serde_json::from_value(converted_patch).unwrap();
+        json_patch::patch(&mut converted, &converted_patch).unwrap();
+        assert_eq!(
+            json!({
+                "login": "Grace",
+                "home_address": {"street_name": "Main", "zip_code": 1},
+                "work_address": {"street_name": "Main", "zip_code": 1},
+                "past_addresses": [{"street_name": "New", "zip_code": 1}]
+            }),
+            converted
+        );
+    }
+
+    #[test]
+    fn value_is_converted_at_its_path() {
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_dictionary_keys(vec!["labels"]);
+
+        let patch = json!([{"op": "add", "path": "/meta/labels", "value": {"app_name": "web"}}]);
+        assert_eq!(
+            json!([{"op": "add", "path": "/meta/labels", "value": {"app_name": "web"}}]),
+            converter.convert_patch(patch).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_patch() {
+        let converter = Converter::new(KeyCase::Snake);
+
+        let err = converter
+            .convert_patch(json!({"op": "remove", "path": "/a"}))
+            .unwrap_err();
+        assert_eq!(
+            "invalid JSON Patch at ``: expected an array of operations",
+            err.to_string()
+        );
+
+        let err = converter
+            .convert_patch(json!([{"op": "remove", "path": "/a"}, {"op": "remove", "path": "a"}]))
+            .unwrap_err();
+        assert_eq!(
+            "invalid JSON Patch at `/1/path`: `a` is not a JSON pointer",
+            err.to_string()
+        );
+    }
+}
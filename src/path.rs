@@ -0,0 +1,89 @@
+use crate::prelude::*;
+use core::fmt;
+
+/// Location of a value while walking a document, kept as a linked list on the
+/// stack so that it costs nothing unless it gets rendered.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Path<'p> {
+    Root,
+    Key(&'p Path<'p>, &'p str),
+    Index(&'p Path<'p>, usize),
+}
+
+impl<'p> Path<'p> {
+    pub(crate) fn key(&'p self, key: &'p str) -> Path<'p> {
+        Path::Key(self, key)
+    }
+
+    pub(crate) fn index(&'p self, index: usize) -> Path<'p> {
+        Path::Index(self, index)
+    }
+
+    /// The path of the object or array holding the value at this path.
+    pub(crate) fn parent(&self) -> Option<&'p Path<'p>> {
+        match self {
+            Path::Key(parent, _) | Path::Index(parent, _) => Some(parent),
+            Path::Root => None,
+        }
+    }
+
+    /// How many objects enclose the key this path ends with, counted like
+    /// [`KeyRef::depth`](crate::KeyRef::depth).
+    pub(crate) fn depth(&self) -> usize {
+        match self {
+            Path::Key(parent, _) => parent.key_count(),
+            Path::Index(..) | Path::Root => 0,
+        }
+    }
+
+    fn key_count(&self) -> usize {
+        match self {
+            Path::Root => 0,
+            Path::Key(parent, _) => parent.key_count() + 1,
+            Path::Index(parent, _) => parent.key_count(),
+        }
+    }
+
+    /// Whether the path is exactly the JSON pointer made of `tokens`, where a
+    /// `*` token stands for any single key or index.
+    pub(crate) fn matches_pointer(&self, tokens: &[String]) -> bool {
+        match (self, tokens.split_last()) {
+            (Path::Root, None) => true,
+            (Path::Key(parent, _), Some((token, rest))) if token == "*" => {
+                parent.matches_pointer(rest)
+            }
+            (Path::Index(parent, _), Some((token, rest))) if token == "*" => {
+                parent.matches_pointer(rest)
+            }
+            (Path::Key(parent, key), Some((token, rest))) => {
+                key == token && parent.matches_pointer(rest)
+            }
+            (Path::Index(parent, index), Some((token, rest))) => {
+                token.parse() == Ok(*index)
+                    && (token == "0" || !token.starts_with('0'))
+                    && parent.matches_pointer(rest)
+            }
+            _ => false,
+        }
+    }
+
+    /// Renders the path as a JSON pointer (RFC 6901).
+    pub(crate) fn to_pointer(self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Path<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Path::Root => Ok(()),
+            Path::Key(parent, key) => write!(f, "{}/{}", parent, escape_pointer_token(key)),
+            Path::Index(parent, index) => write!(f, "{}/{}", parent, index),
+        }
+    }
+}
+
+/// Escapes `~` and `/` in a JSON pointer reference token.
+pub(crate) fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
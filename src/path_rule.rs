@@ -0,0 +1,187 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::scope::parse_pointer;
+use crate::{Error, KeyCase};
+
+/// A subtree whose keys are converted, when the converter only converts
+/// some. See [`Converter::with_path_rules`](crate::Converter::with_path_rules).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathRule {
+    pattern: String,
+    tokens: Vec<String>,
+    case: Option<KeyCase>,
+}
+
+impl PathRule {
+    /// Converts the keys inside the values at `pattern`, and below them, to
+    /// the case of the converter.
+    ///
+    /// The pattern is either a JSON pointer written like
+    /// [`KeyScope::Pointer`](crate::KeyScope::Pointer), as in
+    /// `"/data/items/*/attrs"`, or a dotted path, as in
+    /// `"data.items[*].attrs"`. Both use the original keys of the input, and
+    /// `*` stands for any single key or index.
+    pub fn new(pattern: &str) -> Result<Self, Error> {
+        PathRule::parse(pattern, "path_rules")
+    }
+
+    /// Like [`PathRule::new`], naming `option` in errors.
+    pub(crate) fn parse(pattern: &str, option: &'static str) -> Result<Self, Error> {
+        Ok(PathRule {
+            pattern: pattern.to_owned(),
+            tokens: parse_path(pattern, option)?,
+            case: None,
+        })
+    }
+
+    /// Converts the keys of the subtree to `case` rather than to the case of
+    /// the converter.
+    pub fn with_case(mut self, case: KeyCase) -> Self {
+        self.case = Some(case);
+        self
+    }
+
+    /// The pattern, as given to [`PathRule::new`].
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The case of the subtree, if it has one of its own.
+    pub fn case(&self) -> Option<KeyCase> {
+        self.case
+    }
+
+    /// Whether the value at `path` is one the pattern selects.
+    pub(crate) fn selects(&self, path: &Path) -> bool {
+        path.matches_pointer(&self.tokens)
+    }
+}
+
+/// Splits a JSON pointer or a dotted path into pointer tokens, failing with
+/// an error naming `option` when the dotted path is invalid.
+pub(crate) fn parse_path(pattern: &str, option: &'static str) -> Result<Vec<String>, Error> {
+    if pattern.is_empty() || pattern.starts_with('/') {
+        return Ok(parse_pointer(pattern));
+    }
+    parse_dotted(pattern).map_err(|message| Error::InvalidOption {
+        option,
+        message: format!("`{}` is not a valid path: {}", pattern, message),
+    })
+}
+
+/// Splits a dotted path such as `data.items[0].attrs` into pointer tokens.
+fn parse_dotted(path: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    for segment in path.split('.') {
+        let (name, mut rest) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        if !name.is_empty() {
+            tokens.push(name.to_owned());
+        } else if rest.is_empty() || !tokens.is_empty() {
+            return Err("a segment is empty".to_owned());
+        }
+        while !rest.is_empty() {
+            let end = match (rest.strip_prefix('['), rest.find(']')) {
+                (Some(_), Some(end)) => end,
+                _ => return Err(format!("`{}` is not an index in brackets", rest)),
+            };
+            let index = &rest[1..end];
+            if index != "*" && (index.is_empty() || !index.bytes().all(|b| b.is_ascii_digit())) {
+                return Err(format!("`[{}]` is neither an index nor `[*]`", index));
+            }
+            tokens.push(index.to_owned());
+            rest = &rest[end + 1..];
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn converts_only_selected_subtrees() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_path_rules(vec![
+            PathRule::new("/data/attributes").unwrap(),
+            PathRule::new("data.items[*].attrs")
+                .unwrap()
+                .with_case(KeyCase::Kebab),
+        ]);
+
+        assert_eq!(
+            json!({
+                "data": {
+                    "attributes": {"first_name": "Ada", "home_address": {"zip_code": "1"}},
+                    "items": [{"attrs": {"unit-price": 2}, "itemId": 1}],
+                    "relationships": {"authorId": 3}
+                },
+                "meta": {"totalCount": 1}
+            }),
+            converter
+                .convert_value(json!({
+                    "data": {
+                        "attributes": {"firstName": "Ada", "homeAddress": {"zipCode": "1"}},
+                        "items": [{"attrs": {"unitPrice": 2}, "itemId": 1}],
+                        "relationships": {"authorId": 3}
+                    },
+                    "meta": {"totalCount": 1}
+                }))
+                .unwrap()
+        );
+        assert_eq!(
+            vec![KeyRule::OutsidePathRules],
+            converter.preview_key("totalCount", Some("/meta")).rules
+        );
+    }
+
+    #[test]
+    fn overrides_cases_by_path_and_depth() {
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter
+            .with_case_rule("services[*].config.*", KeyCase::ScreamingSnake)
+            .unwrap();
+        converter.with_depth_case(1, KeyCase::Snake);
+
+        assert_eq!(
+            json!({
+                "serviceCount": 1,
+                "services": [{
+                    "display_name": "api",
+                    "config": {"LOG_LEVEL": "debug", "RETRY_POLICY": {"maxRetries": 3}}
+                }]
+            }),
+            converter
+                .convert_value(json!({
+                    "service_count": 1,
+                    "services": [{
+                        "displayName": "api",
+                        "config": {"logLevel": "debug", "retryPolicy": {"max_retries": 3}}
+                    }]
+                }))
+                .unwrap()
+        );
+
+        let from_spec = Converter::from_spec(converter.to_spec()).unwrap();
+        assert_eq!(converter.to_spec(), from_spec.to_spec());
+        match converter.with_case_rule("a..b", KeyCase::Snake) {
+            Err(Error::InvalidOption { option, .. }) => assert_eq!("case_rules", option),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_dotted_paths() {
+        let rule = |pattern| PathRule::new(pattern).map(|rule| rule.tokens);
+        assert_eq!(
+            vec!["data", "items", "*", "attrs"],
+            rule("data.items[*].attrs").unwrap()
+        );
+        assert_eq!(vec!["0", "1", "id"], rule("[0][1].id").unwrap());
+        assert_eq!(vec!["a", "b"], rule("/a/b").unwrap());
+        for invalid in &["a..b", "a.[0]", "a[", "a[]", "a[x]", "a[0]b", "."] {
+            assert!(rule(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
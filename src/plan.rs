@@ -0,0 +1,398 @@
+use crate::converter::Renames;
+use crate::path::{escape_pointer_token, Path};
+use crate::prelude::*;
+use crate::{Converter, Error, JsonMap};
+use core::convert::TryFrom;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The version of [`ConversionPlan`] this release writes and applies.
+pub const PLAN_VERSION: u32 = 1;
+
+/// The renames a converter made on a sample document, written down so that
+/// they can be reviewed and then applied as they are with [`apply_plan`],
+/// whatever the options or the version of the crate at that time.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversionPlan {
+    /// The version of the plan format, [`PLAN_VERSION`] for plans made by
+    /// this release.
+    pub version: u32,
+
+    /// What [`apply_plan`] does with keys no operation covers.
+    #[serde(default)]
+    pub uncovered_keys: UncoveredKeys,
+
+    /// One operation per key, in document order.
+    pub operations: Vec<PlanOperation>,
+}
+
+/// What becomes of the keys found at `path`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlanOperation {
+    /// JSON pointer to the key in the input, where a `*` token stands for
+    /// any array index. A key that isn't converted the same way at every
+    /// index is listed once per index instead.
+    pub path: String,
+
+    /// The new key, the same as the original one when it is kept, or `None`
+    /// when its entry is dropped.
+    pub new_key: Option<String>,
+}
+
+/// What [`apply_plan`] does with a key that no operation of the plan covers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UncoveredKeys {
+    /// Fail with [`PlanError::UncoveredKey`].
+    #[default]
+    Error,
+
+    /// Keep the key as it is, and report it from
+    /// [`apply_plan_with_warnings`].
+    Warn,
+}
+
+/// Why a plan couldn't be read or applied.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PlanError {
+    /// The plan was written in a format this release doesn't know.
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    /// The plan is not a valid plan of its version.
+    Malformed(serde_json::Error),
+
+    /// A key isn't covered by the plan, under [`UncoveredKeys::Error`].
+    UncoveredKey {
+        /// JSON pointer to the key in the input.
+        path: String,
+    },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "conversion plan version {} is not supported (expected {})",
+                found, supported
+            ),
+            PlanError::Malformed(err) => write!(f, "malformed conversion plan: {}", err),
+            PlanError::UncoveredKey { path } => {
+                write!(f, "the key at `{}` is not covered by the plan", path)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PlanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PlanError::Malformed(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl ConversionPlan {
+    /// Reads a plan from JSON, checking its version before anything else so
+    /// that a plan of another version is rejected as such.
+    pub fn from_json_str(text: &str) -> Result<Self, PlanError> {
+        let value: Value = serde_json::from_str(text).map_err(PlanError::Malformed)?;
+        let found = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+        if found != u64::from(PLAN_VERSION) {
+            return Err(PlanError::UnsupportedVersion {
+                found: u32::try_from(found).unwrap_or(u32::MAX),
+                supported: PLAN_VERSION,
+            });
+        }
+        serde_json::from_value(value).map_err(PlanError::Malformed)
+    }
+
+    pub fn with_uncovered_keys(&mut self, uncovered_keys: UncoveredKeys) {
+        self.uncovered_keys = uncovered_keys;
+    }
+}
+
+impl Converter<'_> {
+    /// Records what converting `value` does to each of its keys, as a plan
+    /// to be applied later with [`apply_plan`]. Arrays of pairs are recorded
+    /// as plain arrays.
+    pub fn plan_for(&self, value: &Value) -> Result<ConversionPlan, Error> {
+        #[cfg(feature = "jsonpath")]
+        if let Some(selected) = self.select_jsonpaths(value) {
+            return selected.plan_for(value);
+        }
+        let mut planned = Vec::new();
+        self.plan_value(value, &Path::Root, "", None, &mut planned)?;
+
+        // Keys converted the same way at every index share one operation.
+        let mut patterns: HashMap<&str, (&Option<String>, bool)> = HashMap::new();
+        for (pattern, _, new_key) in &planned {
+            let (first, consistent) = patterns.entry(pattern).or_insert((new_key, true));
+            *consistent &= *first == new_key;
+        }
+        let mut operations = Vec::with_capacity(patterns.len());
+        for (pattern, pointer, new_key) in &planned {
+            match patterns.get(pattern.as_str()) {
+                Some((_, false)) => operations.push(PlanOperation {
+                    path: pointer.clone(),
+                    new_key: new_key.clone(),
+                }),
+                Some(_) => {
+                    operations.push(PlanOperation {
+                        path: pattern.clone(),
+                        new_key: new_key.clone(),
+                    });
+                    // Written once.
+                    patterns.remove(pattern.as_str());
+                }
+                None => {}
+            }
+        }
+        Ok(ConversionPlan {
+            version: PLAN_VERSION,
+            uncovered_keys: UncoveredKeys::default(),
+            operations,
+        })
+    }
+
+    /// Appends the pattern, pointer and new key of every key under `value`.
+    fn plan_value(
+        &self,
+        value: &Value,
+        path: &Path,
+        pattern: &str,
+        variant: Option<&Renames>,
+        planned: &mut Vec<(String, String, Option<String>)>,
+    ) -> Result<(), Error> {
+        match value {
+            Value::Array(items) => {
+                let pattern = format!("{}/*", pattern);
+                for (index, item) in items.iter().enumerate() {
+                    self.plan_value(item, &path.index(index), &pattern, variant, planned)?;
+                }
+            }
+            Value::Object(map) => {
+                let variant = self.object_variant(map).or(variant);
+                let detected = self.is_detected_dictionary(map);
+                for (key, value) in map {
+                    let key_path = path.key(key);
+                    let new_key = if detected {
+                        Some(key.clone())
+                    } else {
+                        self.rename_variant_key(key, variant, &key_path)?
+                    };
+                    let key_pattern = format!("{}/{}", pattern, escape_pointer_token(key));
+                    planned.push((key_pattern.clone(), key_path.to_pointer(), new_key));
+                    self.plan_value(value, &key_path, &key_pattern, variant, planned)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Converts the keys of `value` by the operations of `plan` alone, without
+/// any converter. See [`apply_plan_with_warnings`].
+pub fn apply_plan(value: &Value, plan: &ConversionPlan) -> Result<Value, PlanError> {
+    apply_plan_with_warnings(value, plan).map(|(value, _)| value)
+}
+
+/// Like [`apply_plan`], also returning the JSON pointers of the keys the
+/// plan doesn't cover, which are kept as they are under
+/// [`UncoveredKeys::Warn`].
+///
+/// An operation for the exact pointer of a key takes precedence over one
+/// whose `*` tokens match its array indices.
+pub fn apply_plan_with_warnings(
+    value: &Value,
+    plan: &ConversionPlan,
+) -> Result<(Value, Vec<String>), PlanError> {
+    if plan.version != PLAN_VERSION {
+        return Err(PlanError::UnsupportedVersion {
+            found: plan.version,
+            supported: PLAN_VERSION,
+        });
+    }
+    let operations: HashMap<&str, Option<&str>> = plan
+        .operations
+        .iter()
+        .map(|operation| (operation.path.as_str(), operation.new_key.as_deref()))
+        .collect();
+    let mut uncovered = Vec::new();
+    let value = apply_value(
+        value,
+        "",
+        "",
+        &operations,
+        plan.uncovered_keys,
+        &mut uncovered,
+    )?;
+    Ok((value, uncovered))
+}
+
+fn apply_value(
+    value: &Value,
+    pointer: &str,
+    pattern: &str,
+    operations: &HashMap<&str, Option<&str>>,
+    policy: UncoveredKeys,
+    uncovered: &mut Vec<String>,
+) -> Result<Value, PlanError> {
+    match value {
+        Value::Array(items) => {
+            let pattern = format!("{}/*", pattern);
+            items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let pointer = format!("{}/{}", pointer, index);
+                    apply_value(item, &pointer, &pattern, operations, policy, uncovered)
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Array)
+        }
+        Value::Object(map) => {
+            let mut new_map = JsonMap::new();
+            for (key, value) in map {
+                let token = escape_pointer_token(key);
+                let pointer = format!("{}/{}", pointer, token);
+                let pattern = format!("{}/{}", pattern, token);
+                let new_key = match operations
+                    .get(pointer.as_str())
+                    .or_else(|| operations.get(pattern.as_str()))
+                {
+                    Some(Some(new_key)) => (*new_key).to_owned(),
+                    Some(None) => continue,
+                    None if policy == UncoveredKeys::Warn => {
+                        uncovered.push(pointer.clone());
+                        key.clone()
+                    }
+                    None => return Err(PlanError::UncoveredKey { path: pointer }),
+                };
+                let new_value =
+                    apply_value(value, &pointer, &pattern, operations, policy, uncovered)?;
+                new_map.insert(new_key, new_value);
+            }
+            Ok(Value::Object(new_map))
+        }
+        value => Ok(value.clone()),
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn sample() -> serde_json::Value {
+        json!({
+            "userId": 7,
+            "orders": [
+                {"orderId": 1, "lineItems": [{"unitPrice": 3}]},
+                {"orderId": 2, "lineItems": []}
+            ]
+        })
+    }
+
+    #[test]
+    fn applies_a_reviewed_plan() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("userId", "customer_id")].iter().cloned().collect());
+        let plan = converter.plan_for(&sample()).unwrap();
+        assert_eq!(
+            vec![
+                ("/userId", Some("customer_id")),
+                ("/orders", Some("orders")),
+                ("/orders/*/orderId", Some("order_id")),
+                ("/orders/*/lineItems", Some("line_items")),
+                ("/orders/*/lineItems/*/unitPrice", Some("unit_price")),
+            ],
+            plan.operations
+                .iter()
+                .map(|operation| (operation.path.as_str(), operation.new_key.as_deref()))
+                .collect::<Vec<_>>()
+        );
+
+        let text = serde_json::to_string_pretty(&plan).unwrap();
+        let mut plan = ConversionPlan::from_json_str(&text).unwrap();
+
+        // More orders than in the sample are covered, but not a new key.
+        let document = json!({
+            "userId": 8,
+            "orders": [
+                {"orderId": 3, "lineItems": [{"unitPrice": 4}, {"unitPrice": 5}]},
+                {"orderId": 4, "lineItems": [], "giftWrap": true},
+                {"orderId": 5, "lineItems": []}
+            ]
+        });
+        match apply_plan(&document, &plan) {
+            Err(PlanError::UncoveredKey { path }) => assert_eq!("/orders/1/giftWrap", path),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+
+        plan.with_uncovered_keys(UncoveredKeys::Warn);
+        let (converted, uncovered) = apply_plan_with_warnings(&document, &plan).unwrap();
+        assert_eq!(
+            json!({
+                "customer_id": 8,
+                "orders": [
+                    {"order_id": 3, "line_items": [{"unit_price": 4}, {"unit_price": 5}]},
+                    {"order_id": 4, "line_items": [], "giftWrap": true},
+                    {"order_id": 5, "line_items": []}
+                ]
+            }),
+            converted
+        );
+        assert_eq!(vec!["/orders/1/giftWrap".to_owned()], uncovered);
+    }
+
+    #[test]
+    fn lists_keys_converted_differently_per_index() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_variant_renames(
+            "type",
+            "card",
+            [("lastDigits", "last4")].iter().cloned().collect(),
+        );
+        let plan = converter
+            .plan_for(&json!([
+                {"type": "card", "lastDigits": "4242"},
+                {"type": "bank", "lastDigits": "6789"}
+            ]))
+            .unwrap();
+        assert_eq!(
+            vec![
+                ("/*/type", Some("type")),
+                ("/0/lastDigits", Some("last4")),
+                ("/1/lastDigits", Some("last_digits")),
+            ],
+            plan.operations
+                .iter()
+                .map(|operation| (operation.path.as_str(), operation.new_key.as_deref()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let text = r#"{"version": 2, "steps": []}"#;
+        match ConversionPlan::from_json_str(text) {
+            Err(PlanError::UnsupportedVersion { found, supported }) => {
+                assert_eq!((2, PLAN_VERSION), (found, supported))
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+
+        let mut plan = Converter::new(KeyCase::Camel).plan_for(&json!({})).unwrap();
+        plan.version = 0;
+        assert_eq!(
+            "conversion plan version 0 is not supported (expected 1)",
+            apply_plan(&json!({}), &plan).unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,285 @@
+use crate::converter::Renames;
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error, KeyViolation};
+use serde_json::Value;
+
+/// What [`Converter::preflight`] found wrong with a configuration, for a
+/// sample document. Pointers are written with the keys of the sample.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PreflightReport {
+    /// Keys of one object converted to the same new key, of which only the
+    /// last would be kept.
+    pub collisions: Vec<KeyCollision>,
+
+    /// JSON pointers to empty keys that the empty key policy rejects.
+    pub empty_keys: Vec<String>,
+
+    /// Original keys of manual renames that no key of the sample goes
+    /// through, sorted.
+    pub unused_renames: Vec<String>,
+
+    /// Excluded pointers that match no key of the sample, in the order they
+    /// were set.
+    pub unmatched_pointers: Vec<String>,
+
+    /// JSON pointers to the objects whose keys are kept because the
+    /// dictionary detection found them to be dictionaries. This is not a
+    /// problem, but tells which objects the heuristic picked.
+    pub detected_dictionaries: Vec<String>,
+
+    /// New keys rejected by the validators, in document order.
+    pub violations: Vec<KeyViolation>,
+
+    /// JSON pointers to the values that redaction rules replace, in
+    /// document order. This is not a problem either.
+    pub redacted: Vec<String>,
+}
+
+/// Keys of one object that are converted to the same new key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyCollision {
+    /// JSON pointer to the object.
+    pub path: String,
+
+    /// The original keys, in document order.
+    pub keys: Vec<String>,
+
+    /// The key they are all converted to.
+    pub new_key: String,
+}
+
+impl PreflightReport {
+    /// Whether no problem was found. Detected dictionaries don't count.
+    pub fn is_empty(&self) -> bool {
+        self.collisions.is_empty()
+            && self.empty_keys.is_empty()
+            && self.unused_renames.is_empty()
+            && self.unmatched_pointers.is_empty()
+            && self.violations.is_empty()
+    }
+}
+
+impl Converter<'_> {
+    /// Checks the options against `sample` without converting it, and
+    /// reports every problem at once: collisions, rejected empty keys, manual
+    /// renames that match nothing, excluded pointers that match nothing and
+    /// keys the validators reject.
+    ///
+    /// Keys go through the same steps as in [`Converter::convert_value`], so
+    /// the report holds for any document shaped like `sample`.
+    pub fn preflight(&self, sample: &Value) -> PreflightReport {
+        let mut preflight = Preflight {
+            converter: self,
+            used_renames: Vec::new(),
+            matched_pointers: vec![false; self.excluded_pointers().len()],
+            report: PreflightReport::default(),
+        };
+        preflight.value(sample, &Path::Root, None);
+
+        let Preflight {
+            used_renames,
+            matched_pointers,
+            mut report,
+            ..
+        } = preflight;
+        report.unused_renames = self
+            .manual_renames()
+            .map(|(from, _)| from)
+            .filter(|from| !used_renames.contains(from))
+            .map(ToOwned::to_owned)
+            .collect();
+        report.unused_renames.sort();
+        report.unmatched_pointers = self
+            .excluded_pointers()
+            .iter()
+            .zip(matched_pointers)
+            .filter(|(_, matched)| !matched)
+            .filter_map(|(matcher, _)| match matcher.scope() {
+                crate::KeyScope::Pointer(pointer) => Some(pointer.clone()),
+                _ => None,
+            })
+            .collect();
+        report
+    }
+}
+
+struct Preflight<'c> {
+    converter: &'c Converter<'c>,
+    used_renames: Vec<&'c str>,
+    matched_pointers: Vec<bool>,
+    report: PreflightReport,
+}
+
+impl<'c> Preflight<'c> {
+    fn value(&mut self, value: &Value, path: &Path, variant: Option<&'c Renames<'c>>) {
+        match value {
+            Value::Array(arr) => {
+                for (index, value) in arr.iter().enumerate() {
+                    self.value(value, &path.index(index), variant);
+                }
+            }
+            Value::Object(map) => {
+                let variant = self.converter.object_variant(map).or(variant);
+                let detected = self.converter.is_detected_dictionary(map);
+                if detected {
+                    self.report.detected_dictionaries.push(path.to_string());
+                }
+                let mut new_keys: Vec<(String, Vec<String>)> = Vec::new();
+                for (key, value) in map {
+                    let key_path = path.key(key);
+                    if detected {
+                        // Dictionary keys are kept, so they can't collide.
+                    } else if let Some(new_key) = self.key(key, &key_path, variant) {
+                        match new_keys.iter_mut().find(|(k, _)| *k == new_key) {
+                            Some((_, keys)) => keys.push(key.clone()),
+                            None => new_keys.push((new_key, vec![key.clone()])),
+                        }
+                    }
+                    match value {
+                        _ if self.converter.redaction(key).is_some() => {
+                            self.report.redacted.push(key_path.to_string())
+                        }
+                        Value::Array(rows) if self.converter.is_pair_array_key(key) => {
+                            self.rows(rows, &key_path, variant)
+                        }
+                        value => self.value(value, &key_path, variant),
+                    }
+                }
+                self.collisions(new_keys, path);
+            }
+            _ => {}
+        }
+    }
+
+    /// Walks the rows of a map encoded as an array, as
+    /// [`Converter::with_pair_array_keys`] describes.
+    fn rows(&mut self, rows: &[Value], path: &Path, variant: Option<&'c Renames<'c>>) {
+        let mut new_keys: Vec<(String, Vec<String>)> = Vec::new();
+        for row in rows {
+            let (key, value) = match row {
+                Value::Array(pair) => match pair.as_slice() {
+                    [Value::String(key), value] => (key, value),
+                    _ => continue,
+                },
+                Value::Object(entry) if entry.len() == 2 => {
+                    match (entry.get("key"), entry.get("value")) {
+                        (Some(Value::String(key)), Some(value)) => (key, value),
+                        _ => continue,
+                    }
+                }
+                _ => continue,
+            };
+            let key_path = path.key(key);
+            if let Some(new_key) = self.key(key, &key_path, variant) {
+                match new_keys.iter_mut().find(|(k, _)| *k == new_key) {
+                    Some((_, keys)) => keys.push(key.clone()),
+                    None => new_keys.push((new_key, vec![key.clone()])),
+                }
+            }
+            if self.converter.redaction(key).is_some() {
+                self.report.redacted.push(key_path.to_string());
+            } else {
+                self.value(value, &key_path, variant);
+            }
+        }
+        self.collisions(new_keys, path);
+    }
+
+    /// Decides the new name of the key at `path`, noting which rules it went
+    /// through.
+    fn key(&mut self, key: &str, path: &Path, variant: Option<&Renames>) -> Option<String> {
+        let converter = self.converter;
+        for (matcher, matched) in converter
+            .excluded_pointers()
+            .iter()
+            .zip(&mut self.matched_pointers)
+        {
+            *matched |= matcher.matches(path);
+        }
+        if let Some(from) = converter.manual_rename_entry(key, variant, path) {
+            self.used_renames.push(from);
+        }
+        match converter.rename_variant_key(key, variant, path) {
+            Ok(new_key) => {
+                if let Some(new_key) = &new_key {
+                    let violations = converter.key_violations(key, new_key, path);
+                    self.report.violations.extend(violations);
+                }
+                new_key
+            }
+            Err(Error::InvalidKey { key: new_key, .. }) => {
+                let violations = converter.key_violations(key, &new_key, path);
+                self.report.violations.extend(violations);
+                Some(new_key)
+            }
+            Err(Error::EmptyKey { path }) => {
+                self.report.empty_keys.push(path);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    fn collisions(&mut self, new_keys: Vec<(String, Vec<String>)>, path: &Path) {
+        for (new_key, keys) in new_keys {
+            if keys.len() > 1 {
+                self.report.collisions.push(KeyCollision {
+                    path: path.to_string(),
+                    keys,
+                    new_key,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_every_problem() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames(
+            [
+                ("ID", "id"),
+                ("userURL", "user_url"),
+                ("zipCode", "postcode"),
+            ]
+            .iter()
+            .cloned()
+            .collect(),
+        );
+        converter.with_empty_key_policy(EmptyKeyPolicy::Error);
+        converter.with_excluded_pointers(vec!["/meta/Raw", "/items/*/Raw"]);
+
+        let sample = json!({
+            "ID": 1,
+            "meta": {"Raw": true, "": 0},
+            "items": [{"UserName": "a", "userName": "b"}]
+        });
+
+        assert_eq!(
+            PreflightReport {
+                collisions: vec![KeyCollision {
+                    path: "/items/0".to_owned(),
+                    keys: vec!["UserName".to_owned(), "userName".to_owned()],
+                    new_key: "user_name".to_owned(),
+                }],
+                empty_keys: vec!["/meta/".to_owned()],
+                unused_renames: vec!["userURL".to_owned(), "zipCode".to_owned()],
+                unmatched_pointers: vec!["/items/*/Raw".to_owned()],
+                detected_dictionaries: Vec::new(),
+                violations: Vec::new(),
+                redacted: Vec::new(),
+            },
+            converter.preflight(&sample)
+        );
+
+        let clean = json!({"ID": 1, "userURL": "u", "zipCode": 2, "meta": {"Raw": 0}, "items": [{"Raw": 1}]});
+        assert!(converter.preflight(&clean).is_empty());
+    }
+}
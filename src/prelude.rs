@@ -0,0 +1,13 @@
+//! The parts of the `std` prelude this crate uses, taken from `alloc` so that
+//! they are also there without the `std` feature.
+
+pub(crate) use alloc::borrow::ToOwned;
+pub(crate) use alloc::format;
+pub(crate) use alloc::string::{String, ToString};
+pub(crate) use alloc::vec;
+pub(crate) use alloc::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::BTreeMap as HashMap;
+#[cfg(feature = "std")]
+pub(crate) use std::collections::HashMap;
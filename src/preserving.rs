@@ -0,0 +1,326 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error};
+use core::fmt::Write;
+
+impl Converter<'_> {
+    /// Converts the keys of the JSON text `input` by rewriting only the key
+    /// tokens: whitespace, key order, the way numbers and strings are written
+    /// and every other byte are kept. With the `json5` feature, JSON5 and
+    /// JSONC are accepted too, and comments and trailing commas are kept.
+    ///
+    /// Keys written with escapes are decoded before going through the rules.
+    /// A converted key is written back with `\u` escapes for non-ASCII
+    /// characters if the original used any, and unquoted keys stay unquoted
+    /// when the new key is still an identifier. Keys are kept as they are
+    /// when the options would drop their entry. Rules that look at the whole
+    /// object, such as variant renames and map-encoded arrays, don't apply.
+    pub fn convert_text_preserving(&self, input: &str) -> Result<String, Error> {
+        validate(input)?;
+        let mut editor = Editor {
+            converter: self,
+            input,
+            pos: 0,
+            copied: 0,
+            output: String::with_capacity(input.len()),
+        };
+        editor.value(&Path::Root)?;
+        editor.output.push_str(&input[editor.copied..]);
+        Ok(editor.output)
+    }
+}
+
+#[cfg(feature = "json5")]
+fn validate(input: &str) -> Result<(), Error> {
+    if serde_json::from_str::<serde::de::IgnoredAny>(input).is_ok() {
+        return Ok(());
+    }
+    json5::from_str::<serde::de::IgnoredAny>(input)
+        .map(|_| ())
+        .map_err(Error::Json5)
+}
+
+#[cfg(not(feature = "json5"))]
+fn validate(input: &str) -> Result<(), Error> {
+    serde_json::from_str::<serde::de::IgnoredAny>(input)
+        .map(|_| ())
+        .map_err(Error::Parse)
+}
+
+/// Walks a text already known to be valid, copying it to `output` and
+/// replacing the key tokens that change.
+struct Editor<'i, 'c> {
+    converter: &'c Converter<'c>,
+    input: &'i str,
+    pos: usize,
+
+    /// How much of `input` is already in `output`.
+    copied: usize,
+    output: String,
+}
+
+impl<'i> Editor<'i, '_> {
+    fn value(&mut self, path: &Path) -> Result<(), Error> {
+        self.skip_trivia();
+        match self.peek() {
+            Some(b'{') => self.object(path),
+            Some(b'[') => self.array(path),
+            Some(quote @ b'"') | Some(quote @ b'\'') => {
+                self.string(quote);
+                Ok(())
+            }
+            _ => {
+                // Numbers and literals end at a delimiter.
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b']' | b'}' | b'/') || byte.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn object(&mut self, path: &Path) -> Result<(), Error> {
+        self.pos += 1;
+        loop {
+            self.skip_trivia();
+            let start = self.pos;
+            let (key, quote) = match self.peek() {
+                Some(b'}') | None => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(b',') => {
+                    self.pos += 1;
+                    continue;
+                }
+                Some(quote @ b'"') | Some(quote @ b'\'') => {
+                    let token = self.string(quote);
+                    (decode(token, quote), Some(quote))
+                }
+                Some(_) => (self.identifier().to_owned(), None),
+            };
+
+            let key_path = path.key(&key);
+            if let Some(new_key) = self.converter.rename_key(&key, &key_path)? {
+                if new_key != key {
+                    let token = &self.input[start..self.pos];
+                    self.output.push_str(&self.input[self.copied..start]);
+                    encode(&new_key, token, quote, &mut self.output);
+                    self.copied = self.pos;
+                }
+            }
+
+            self.skip_trivia();
+            // The colon.
+            self.pos += 1;
+            self.value(&key_path)?;
+        }
+    }
+
+    fn array(&mut self, path: &Path) -> Result<(), Error> {
+        self.pos += 1;
+        let mut index = 0;
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                Some(b']') | None => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some(b',') => self.pos += 1,
+                Some(_) => {
+                    self.value(&path.index(index))?;
+                    index += 1;
+                }
+            }
+        }
+    }
+
+    /// Skips a string, returning it with its quotes and escapes.
+    fn string(&mut self, quote: u8) -> &'i str {
+        let input = self.input;
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            if byte == b'\\' {
+                self.pos += 1;
+            } else if byte == quote {
+                break;
+            }
+        }
+        &input[start..self.pos]
+    }
+
+    /// Skips an unquoted JSON5 key.
+    fn identifier(&mut self) -> &'i str {
+        let input = self.input;
+        let start = self.pos;
+        while let Some(byte) = self.peek() {
+            if byte == b':' || byte == b'/' || byte.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+        &input[start..self.pos]
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = &self.input[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if trimmed.starts_with("/*") {
+                self.pos += trimmed.find("*/").map_or(trimmed.len(), |end| end + 2);
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+}
+
+/// Decodes a quoted key token.
+fn decode(token: &str, quote: u8) -> String {
+    if quote == b'"' {
+        return serde_json::from_str(token).unwrap_or_default();
+    }
+    #[cfg(feature = "json5")]
+    {
+        json5::from_str(token).unwrap_or_default()
+    }
+    #[cfg(not(feature = "json5"))]
+    {
+        token[1..token.len() - 1].to_owned()
+    }
+}
+
+/// Writes `key` as a key token in the style of `original`.
+fn encode(key: &str, original: &str, quote: Option<u8>, output: &mut String) {
+    let quote = match quote {
+        Some(quote) => quote as char,
+        None if is_identifier(key) => {
+            output.push_str(key);
+            return;
+        }
+        None => '"',
+    };
+    let escape_unicode = original.contains("\\u");
+
+    output.push(quote);
+    for c in key.chars() {
+        match c {
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c == quote => {
+                output.push('\\');
+                output.push(c);
+            }
+            c if c < ' ' || (escape_unicode && !c.is_ascii()) => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    let _ = write!(output, "\\u{:04x}", unit);
+                }
+            }
+            c => output.push(c),
+        }
+    }
+    output.push(quote);
+}
+
+/// Whether `key` can be written without quotes in JSON5. Only ASCII
+/// identifiers are recognized.
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn keeps_unusual_formatting() {
+        let input = "{\"userName\"   :\"Ada\",\n\t\t\"homeAddress\":{ \"zipCode\" : 1.50e3 ,\n\"tags\":[ {\"tagName\":\"a\"} ,{}]}\r\n ,  \"isAdmin\"\n:\nfalse}  \n";
+        let converted = Converter::new(KeyCase::Snake)
+            .convert_text_preserving(input)
+            .unwrap();
+
+        assert_eq!(
+            "{\"user_name\"   :\"Ada\",\n\t\t\"home_address\":{ \"zip_code\" : 1.50e3 ,\n\"tags\":[ {\"tag_name\":\"a\"} ,{}]}\r\n ,  \"is_admin\"\n:\nfalse}  \n",
+            converted
+        );
+    }
+
+    #[test]
+    fn decodes_and_encodes_escaped_keys() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("caféOwner", "owner_café")].iter().cloned().collect());
+        converter.with_excluded_pointers(vec!["/meta/rawValue"]);
+
+        let input = r#"{"caf\u00e9Owner": "\u00e9", "\u0075serId": 2, "meta": {"rawValue": {"zipCode": 3}}}"#;
+        let converted = converter.convert_text_preserving(input).unwrap();
+
+        assert_eq!(
+            r#"{"owner_caf\u00e9": "\u00e9", "user_id": 2, "meta": {"rawValue": {"zip_code": 3}}}"#,
+            converted
+        );
+        assert!(converter.convert_text_preserving(r#"{"a": }"#).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "json5")]
+    fn keeps_comments_and_trailing_commas() {
+        let path: std::path::PathBuf = [
+            env!("CARGO_MANIFEST_DIR"),
+            "tests",
+            "fixtures",
+            "config.jsonc",
+        ]
+        .iter()
+        .collect();
+        let input = std::fs::read_to_string(path).unwrap();
+        let converted = Converter::new(KeyCase::Snake)
+            .convert_text_preserving(&input)
+            .unwrap();
+
+        assert_eq!(
+            r#"// Service configuration, edited by hand.
+{
+  "service_name": "billing",
+  /* Connection settings */
+  "database_url": "postgres://localhost/billing",
+  "retry_policy": {
+    "max_attempts": 3, // before giving up
+    "backoff_ms": [100, 200, 400,],
+  },
+}
+"#,
+            converted
+        );
+
+        let input =
+            "{unquotedKey: 'single', 'quotedKey': 1, /* \"notAKey\": */ nested: {innerKey: 2,},}";
+        let mut converter = Converter::new(KeyCase::Kebab);
+        converter.with_manual_renames([("nested", "nestedObject")].iter().cloned().collect());
+        assert_eq!(
+            "{\"unquoted-key\": 'single', 'quoted-key': 1, /* \"notAKey\": */ nestedObject: {\"inner-key\": 2,},}",
+            converter.convert_text_preserving(input).unwrap()
+        );
+    }
+}
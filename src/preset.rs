@@ -0,0 +1,253 @@
+use crate::prelude::*;
+
+/// Rules for documents of well-known ecosystems, whose reserved or data keys
+/// must survive a conversion. Apply one with
+/// [`Converter::with_preset`](crate::Converter::with_preset); the rules are
+/// added to whatever is already configured, and options set afterwards apply
+/// on top of them.
+///
+/// To drop or change some of the rules, edit [`Preset::rules`] and apply the
+/// result with
+/// [`Converter::with_preset_rules`](crate::Converter::with_preset_rules).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Preset {
+    /// MongoDB documents and queries: keys starting with `$` (operators such
+    /// as `$set`, and `$oid` in extended JSON) and with `_` (`_id`, `__v`)
+    /// are skipped.
+    MongoDb,
+
+    /// Kubernetes manifests: the keys inside `labels`, `annotations`,
+    /// `matchLabels`, `nodeSelector`, `data`, `stringData`, `binaryData`,
+    /// `limits` and `requests` are user data and are kept.
+    Kubernetes,
+
+    /// JSON-LD documents: keywords starting with `@` and absolute IRIs
+    /// (`http://`, `https://`) are skipped, and the terms defined in
+    /// `@context` are kept. Terms used in the rest of the document are still
+    /// converted, so the context must define the converted names. Compact
+    /// IRIs such as `"schema:name"` are not recognized.
+    JsonLd,
+
+    /// JSON:API documents: the member names defined by the specification
+    /// (`data`, `included`, `type`, `id`, `attributes`, `relationships`,
+    /// `links`, `meta`, ...) are kept at the places the specification puts
+    /// them, so that only attribute and relationship names are converted.
+    JsonApi,
+}
+
+/// The rules a [`Preset`] applies, to be adjusted before applying them with
+/// [`Converter::with_preset_rules`](crate::Converter::with_preset_rules).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PresetRules {
+    /// See [`Converter::with_skip_prefixes`](crate::Converter::with_skip_prefixes).
+    pub skip_prefixes: Vec<String>,
+
+    /// See [`Converter::with_dictionary_keys`](crate::Converter::with_dictionary_keys).
+    pub dictionary_keys: Vec<String>,
+
+    /// See [`Converter::with_excluded_pointers`](crate::Converter::with_excluded_pointers).
+    pub excluded_pointers: Vec<String>,
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|&item| item.to_owned()).collect()
+}
+
+impl Preset {
+    /// The rules this preset applies.
+    pub fn rules(self) -> PresetRules {
+        match self {
+            Preset::MongoDb => PresetRules {
+                skip_prefixes: strings(&["$", "_"]),
+                ..PresetRules::default()
+            },
+            Preset::Kubernetes => PresetRules {
+                dictionary_keys: strings(&[
+                    "labels",
+                    "annotations",
+                    "matchLabels",
+                    "nodeSelector",
+                    "data",
+                    "stringData",
+                    "binaryData",
+                    "limits",
+                    "requests",
+                ]),
+                ..PresetRules::default()
+            },
+            Preset::JsonLd => PresetRules {
+                skip_prefixes: strings(&["@", "http://", "https://"]),
+                dictionary_keys: strings(&["@context"]),
+                ..PresetRules::default()
+            },
+            Preset::JsonApi => PresetRules {
+                excluded_pointers: json_api_pointers(),
+                ..PresetRules::default()
+            },
+        }
+    }
+}
+
+/// Pointers to the JSON:API member names, with `data` holding either a single
+/// resource or an array of them.
+fn json_api_pointers() -> Vec<String> {
+    const TOP_LEVEL: &[&str] = &["data", "errors", "meta", "links", "included", "jsonapi"];
+    const RESOURCE: &[&str] = &["type", "id", "attributes", "relationships", "links", "meta"];
+    const RELATIONSHIP: &[&str] = &["data", "links", "meta"];
+    const IDENTIFIER: &[&str] = &["type", "id", "meta"];
+
+    let mut pointers: Vec<String> = TOP_LEVEL.iter().map(|key| format!("/{}", key)).collect();
+    for resource in &["/data", "/data/*", "/included/*"] {
+        pointers.extend(RESOURCE.iter().map(|key| format!("{}/{}", resource, key)));
+
+        let relationship = format!("{}/relationships/*", resource);
+        pointers.extend(
+            RELATIONSHIP
+                .iter()
+                .map(|key| format!("{}/{}", relationship, key)),
+        );
+        for linkage in &["data", "data/*"] {
+            pointers.extend(
+                IDENTIFIER
+                    .iter()
+                    .map(|key| format!("{}/{}/{}", relationship, linkage, key)),
+            );
+        }
+    }
+    pointers
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn convert(preset: Preset, case: KeyCase, value: serde_json::Value) -> serde_json::Value {
+        let mut converter = Converter::new(case);
+        converter.with_preset(preset);
+        converter.convert_value(value).unwrap()
+    }
+
+    #[test]
+    fn mongo_db() {
+        let query = json!({
+            "_id": {"$oid": "5f1d"},
+            "userName": {"$regex": "^A"},
+            "$or": [{"createdAt": {"$gte": 1}}, {"isAdmin": true}]
+        });
+
+        assert_eq!(
+            json!({
+                "_id": {"$oid": "5f1d"},
+                "user_name": {"$regex": "^A"},
+                "$or": [{"created_at": {"$gte": 1}}, {"is_admin": true}]
+            }),
+            convert(Preset::MongoDb, KeyCase::Snake, query)
+        );
+    }
+
+    #[test]
+    fn kubernetes() {
+        let manifest = json!({
+            "apiVersion": "apps/v1",
+            "metadata": {
+                "labels": {"app.kubernetes.io/name": "web", "tier": "frontEnd"},
+                "annotations": {"deployment.kubernetes.io/revision": "3"}
+            },
+            "spec": {
+                "selector": {"matchLabels": {"appName": "web"}},
+                "template": {"spec": {"containers": [{
+                    "imagePullPolicy": "Always",
+                    "resources": {"limits": {"nvidia.com/gpu": 1}}
+                }]}}
+            }
+        });
+
+        assert_eq!(
+            json!({
+                "api_version": "apps/v1",
+                "metadata": {
+                    "labels": {"app.kubernetes.io/name": "web", "tier": "frontEnd"},
+                    "annotations": {"deployment.kubernetes.io/revision": "3"}
+                },
+                "spec": {
+                    "selector": {"match_labels": {"appName": "web"}},
+                    "template": {"spec": {"containers": [{
+                        "image_pull_policy": "Always",
+                        "resources": {"limits": {"nvidia.com/gpu": 1}}
+                    }]}}
+                }
+            }),
+            convert(Preset::Kubernetes, KeyCase::Snake, manifest)
+        );
+    }
+
+    #[test]
+    fn json_ld() {
+        let document = json!({
+            "@context": {"givenName": "http://schema.org/givenName", "@vocab": "http://schema.org/"},
+            "@id": "http://example.com/ada",
+            "givenName": "Ada",
+            "http://schema.org/birthDate": "1815-12-10",
+            "knowsAbout": {"@type": "Thing", "displayName": "Maths"}
+        });
+
+        assert_eq!(
+            json!({
+                "@context": {"givenName": "http://schema.org/givenName", "@vocab": "http://schema.org/"},
+                "@id": "http://example.com/ada",
+                "given_name": "Ada",
+                "http://schema.org/birthDate": "1815-12-10",
+                "knows_about": {"@type": "Thing", "display_name": "Maths"}
+            }),
+            convert(Preset::JsonLd, KeyCase::Snake, document)
+        );
+    }
+
+    #[test]
+    fn json_api() {
+        let document = json!({
+            "data": [{
+                "type": "articles",
+                "id": "1",
+                "attributes": {"title": "Hi", "published_at": "2020"},
+                "relationships": {"lead_author": {"data": {"type": "people", "id": "9"}}}
+            }],
+            "included": [{"type": "people", "id": "9", "attributes": {"first_name": "Ada"}}],
+            "meta": {"total_pages": 1}
+        });
+
+        assert_eq!(
+            json!({
+                "data": [{
+                    "type": "articles",
+                    "id": "1",
+                    "attributes": {"Title": "Hi", "PublishedAt": "2020"},
+                    "relationships": {"LeadAuthor": {"data": {"type": "people", "id": "9"}}}
+                }],
+                "included": [{"type": "people", "id": "9", "attributes": {"FirstName": "Ada"}}],
+                "meta": {"TotalPages": 1}
+            }),
+            convert(Preset::JsonApi, KeyCase::Pascal, document)
+        );
+    }
+
+    #[test]
+    fn adjusted_rules() {
+        let mut rules = Preset::MongoDb.rules();
+        rules.skip_prefixes.retain(|prefix| prefix != "_");
+
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_preset_rules(rules);
+        converter.with_dictionary_keys(vec!["tags"]);
+
+        let converted = converter
+            .convert_value(json!({"_private_note": 1, "$set": {"tags": {"a_b": 1}}}))
+            .unwrap();
+        assert_eq!(
+            json!({"privateNote": 1, "$set": {"tags": {"a_b": 1}}}),
+            converted
+        );
+    }
+}
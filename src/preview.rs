@@ -0,0 +1,209 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::scope::parse_pointer;
+use crate::{Converter, EmptyKeyPolicy, Error, IdentifierStyle, KeyCase, LengthPolicy};
+
+/// How [`Converter::preview_key`] would convert a key.
+#[derive(Debug)]
+pub struct KeyPreview {
+    /// The new key, `None` when the entry would be left out, or the error
+    /// the conversion would fail with.
+    pub key: Result<Option<String>, Error>,
+
+    /// The rules that had a say, in the order they were applied. The first
+    /// one that isn't [`KeyRule::Trimmed`] or [`KeyRule::StrippedPrefix`]
+    /// decides the name: a rule keeping the key as it is, the empty key
+    /// policy, a manual rename or the case conversion. Affixes and the other
+    /// rules after it then adjust that name.
+    pub rules: Vec<KeyRule>,
+}
+
+/// A step of converting a key, as reported by [`Converter::preview_key`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeyRule {
+    /// Kept as it is, being a key of one of the dictionary keys' objects.
+    Dictionary,
+
+    /// Kept as it is, starting with this skip prefix.
+    SkipPrefix(String),
+
+    /// Kept as it is, being one of the excluded keys.
+    ExcludedKey,
+
+    /// Kept as it is, being at this excluded pointer.
+    ExcludedPointer(String),
+
+    /// Kept as it is, being a key of a map in the schema guide.
+    SchemaMapKey,
+
+    /// Kept as it is, being undeclared in the schema guide.
+    SchemaUndeclared,
+
+    /// Whitespace was trimmed from the key.
+    Trimmed,
+
+    /// The key is empty, and handled by this policy.
+    EmptyKey(EmptyKeyPolicy),
+
+    /// This prefix was removed from the key.
+    StrippedPrefix(String),
+
+    /// Renamed by a manual rename, matched by `from` under the rename
+    /// behavior.
+    ManualRename { from: String, to: String },
+
+    /// Kept as it is, being inside a value selected by an exclude JSONPath
+    /// selector.
+    #[cfg(feature = "jsonpath")]
+    ExcludedJsonPath,
+
+    /// Kept as it is, being outside every value selected by the include
+    /// JSONPath selectors.
+    #[cfg(feature = "jsonpath")]
+    NotIncludedJsonPath,
+
+    /// Kept as it is, being below the maximum depth.
+    BelowMaxDepth,
+
+    /// Kept as it is, being outside every value the path rules select.
+    OutsidePathRules,
+
+    /// Rewritten by the regex rename with this pattern.
+    #[cfg(feature = "regex")]
+    RegexRename(String),
+
+    /// Kept as it is, not being one of the allowed keys nor manually
+    /// renamed.
+    NotAllowed,
+
+    /// Converted to this case.
+    Case(KeyCase),
+
+    /// This key prefix was prepended.
+    KeyPrefix(String),
+
+    /// This key suffix was appended.
+    KeySuffix(String),
+
+    /// Changed into a valid identifier of this language.
+    SafeIdentifier(IdentifierStyle),
+
+    /// Longer than this many bytes, and handled by this policy.
+    MaxKeyLength(usize, LengthPolicy),
+}
+
+/// Where [`Converter::convert_key`] notes the rules it goes through.
+pub(crate) trait Trace {
+    fn note(&mut self, rule: impl FnOnce() -> KeyRule);
+
+    fn note_if(&mut self, applies: impl FnOnce() -> bool, rule: impl FnOnce() -> KeyRule);
+}
+
+/// Notes nothing, for actual conversions.
+impl Trace for () {
+    fn note(&mut self, _: impl FnOnce() -> KeyRule) {}
+
+    fn note_if(&mut self, _: impl FnOnce() -> bool, _: impl FnOnce() -> KeyRule) {}
+}
+
+impl Trace for Vec<KeyRule> {
+    fn note(&mut self, rule: impl FnOnce() -> KeyRule) {
+        self.push(rule());
+    }
+
+    fn note_if(&mut self, applies: impl FnOnce() -> bool, rule: impl FnOnce() -> KeyRule) {
+        if applies() {
+            self.push(rule());
+        }
+    }
+}
+
+impl Converter<'_> {
+    /// Converts `key` alone, going through the same steps as a conversion,
+    /// and reports which rules were applied.
+    ///
+    /// `path` is the JSON pointer, written with the original keys, to the
+    /// object holding `key` (the top level object when `None`), so that
+    /// scoped options apply as they would there. Tokens made of digits are
+    /// taken as array indices. Variant renames don't apply, as there is no
+    /// object to find the variant in.
+    pub fn preview_key(&self, key: &str, path: Option<&str>) -> KeyPreview {
+        let tokens = path.map(parse_pointer).unwrap_or_default();
+        let mut rules = Vec::new();
+        let key = at_path(&Path::Root, &tokens, key, &mut |path| {
+            self.trace_key(key, path, &mut rules)
+        });
+        KeyPreview { key, rules }
+    }
+}
+
+/// Runs `f` with the path of `key` in the object at `tokens` below `parent`.
+fn at_path<T>(parent: &Path, tokens: &[String], key: &str, f: &mut dyn FnMut(&Path) -> T) -> T {
+    match tokens.split_first() {
+        None => f(&parent.key(key)),
+        Some((token, rest)) => match token.parse() {
+            Ok(index) if token == "0" || !token.starts_with('0') => {
+                at_path(&parent.index(index), rest, key, f)
+            }
+            _ => at_path(&parent.key(token), rest, key, f),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn converter() -> Converter<'static> {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("oAuth2Token", "oauth2_token")].iter().cloned().collect());
+        converter.with_excluded_pointers(vec!["/user/*/apiKey"]);
+        converter.with_strip_prefixes(vec!["str"]);
+        converter.with_key_suffix("_", KeyScope::TopLevel);
+        converter
+    }
+
+    #[test]
+    fn manual_rename() {
+        let preview = converter().preview_key("oAuth2Token", None);
+        assert_eq!(Some("oauth2_token_".to_owned()), preview.key.unwrap());
+        assert_eq!(
+            vec![
+                KeyRule::ManualRename {
+                    from: "oAuth2Token".to_owned(),
+                    to: "oauth2_token".to_owned()
+                },
+                KeyRule::KeySuffix("_".to_owned()),
+            ],
+            preview.rules
+        );
+    }
+
+    #[test]
+    fn excluded() {
+        let converter = converter();
+        let preview = converter.preview_key("apiKey", Some("/user/0"));
+        assert_eq!(Some("apiKey".to_owned()), preview.key.unwrap());
+        assert_eq!(
+            vec![KeyRule::ExcludedPointer("/user/*/apiKey".to_owned())],
+            preview.rules
+        );
+
+        let preview = converter.preview_key("apiKey", Some("/account/0"));
+        assert_eq!(Some("api_key".to_owned()), preview.key.unwrap());
+    }
+
+    #[test]
+    fn case_conversion() {
+        let preview = converter().preview_key("strUserName", Some("/user"));
+        assert_eq!(Some("user_name".to_owned()), preview.key.unwrap());
+        assert_eq!(
+            vec![
+                KeyRule::StrippedPrefix("str".to_owned()),
+                KeyRule::Case(KeyCase::Snake),
+            ],
+            preview.rules
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! A Python module over [`Converter`], built with maturin from
+//! `pyproject.toml`.
+//!
+//! Python objects cross the boundary as [`Value`]s: dicts with string keys,
+//! lists, tuples, strings, integers, floats, booleans and `None`. Errors are
+//! raised as `ValueError`, with the messages of [`Error`].
+
+use crate::prelude::*;
+use crate::{ConversionSpec, Converter, Error};
+use alloc::collections::BTreeMap;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use serde_json::{Number, Value};
+
+/// Converts the keys of dicts, or of JSON strings, to a case.
+#[pyclass(name = "CaseChanger", module = "json_keys_case_changer", frozen)]
+pub struct PyCaseChanger(Converter<'static>);
+
+#[pymethods]
+impl PyCaseChanger {
+    /// Converts to the case named `case`, such as `"snake_case"`, renaming
+    /// the keys of `manual_renames` to their values instead.
+    #[new]
+    #[pyo3(signature = (case, manual_renames = None))]
+    fn new(case: &str, manual_renames: Option<BTreeMap<String, String>>) -> PyResult<Self> {
+        let mut spec = ConversionSpec::new(case.parse().map_err(to_py_err)?);
+        spec.manual_renames = manual_renames.unwrap_or_default();
+        Converter::from_spec(spec)
+            .map(PyCaseChanger)
+            .map_err(to_py_err)
+    }
+
+    /// Converts the keys of `obj`, returning new dicts and lists.
+    fn convert(&self, py: Python<'_>, obj: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let converted = self.0.convert_value(to_value(obj)?).map_err(to_py_err)?;
+        to_object(py, &converted)
+    }
+
+    /// Converts the keys of the JSON document `s`, returning JSON.
+    fn convert_str(&self, s: &str) -> PyResult<String> {
+        let value: Value = serde_json::from_str(s)
+            .map_err(Error::Parse)
+            .map_err(to_py_err)?;
+        let converted = self.0.convert_value(value).map_err(to_py_err)?;
+        serde_json::to_string(&converted)
+            .map_err(Error::Serialize)
+            .map_err(to_py_err)
+    }
+}
+
+#[pymodule]
+fn json_keys_case_changer(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyCaseChanger>()
+}
+
+fn to_py_err(err: Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn to_value(obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+    // `bool` is a subclass of `int`, so it goes first.
+    if obj.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(boolean) = obj.downcast::<PyBool>() {
+        Ok(Value::Bool(boolean.is_true()))
+    } else if let Ok(int) = obj.downcast::<PyInt>() {
+        match int.extract::<i64>() {
+            Ok(int) => Ok(Value::from(int)),
+            Err(_) => int.extract::<u64>().map(Value::from),
+        }
+    } else if let Ok(float) = obj.downcast::<PyFloat>() {
+        Number::from_f64(float.value())
+            .map(Value::Number)
+            .ok_or_else(|| PyValueError::new_err("NaN and infinite floats are not JSON"))
+    } else if let Ok(string) = obj.downcast::<PyString>() {
+        Ok(Value::String(string.to_str()?.to_owned()))
+    } else if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut map = crate::JsonMap::new();
+        for (key, value) in dict.iter() {
+            let key = key
+                .downcast::<PyString>()
+                .map_err(|_| PyTypeError::new_err("dict keys must be strings"))?;
+            map.insert(key.to_str()?.to_owned(), to_value(&value)?);
+        }
+        Ok(Value::Object(map))
+    } else if let Ok(list) = obj.downcast::<PyList>() {
+        list.iter().map(|item| to_value(&item)).collect()
+    } else if let Ok(tuple) = obj.downcast::<PyTuple>() {
+        tuple.iter().map(|item| to_value(&item)).collect()
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "`{}` is not a JSON value",
+            obj.get_type().name()?
+        )))
+    }
+}
+
+fn to_object(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(boolean) => boolean.into_pyobject(py)?.to_owned().into_any().unbind(),
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(int), _) => int.into_pyobject(py)?.into_any().unbind(),
+            (None, Some(int)) => int.into_pyobject(py)?.into_any().unbind(),
+            _ => number
+                .as_f64()
+                .unwrap_or_default()
+                .into_pyobject(py)?
+                .into_any()
+                .unbind(),
+        },
+        Value::String(string) => string.into_pyobject(py)?.into_any().unbind(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(to_object(py, item)?)?;
+            }
+            list.into_any().unbind()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, value) in map {
+                dict.set_item(key, to_object(py, value)?)?;
+            }
+            dict.into_any().unbind()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::ffi::c_str;
+
+    fn case_changer(py: Python<'_>) -> Bound<'_, PyAny> {
+        let class = py.get_type::<PyCaseChanger>();
+        let renames = PyDict::new(py);
+        renames.set_item("ID", "id").unwrap();
+        class.call1(("snake_case", renames)).unwrap()
+    }
+
+    #[test]
+    fn converts_objects_and_strings() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let changer = case_changer(py);
+            let obj = py
+                .eval(
+                    c_str!("{'ID': 1, 'userName': 'Ada', 'pastAddresses': [{'zipCode': None}], 'isAdmin': True, 'score': 1.5}"),
+                    None,
+                    None,
+                )
+                .unwrap();
+            let converted = changer.call_method1("convert", (obj,)).unwrap();
+            let expected = py
+                .eval(
+                    c_str!("{'id': 1, 'user_name': 'Ada', 'past_addresses': [{'zip_code': None}], 'is_admin': True, 'score': 1.5}"),
+                    None,
+                    None,
+                )
+                .unwrap();
+            assert!(converted.eq(expected).unwrap());
+
+            let converted: String = changer
+                .call_method1("convert_str", (r#"{"ID":1,"userName":"Ada"}"#,))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(r#"{"id":1,"user_name":"Ada"}"#, converted);
+        });
+    }
+
+    #[test]
+    fn raises_value_error() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let err = py.get_type::<PyCaseChanger>().call1(("snek",)).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+            assert!(err.to_string().contains("did you mean `snake_case`?"));
+
+            let err = case_changer(py)
+                .call_method1("convert_str", ("{",))
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+            assert!(err.to_string().contains("failed to parse JSON"));
+        });
+    }
+}
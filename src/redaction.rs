@@ -0,0 +1,167 @@
+use crate::prelude::*;
+#[cfg(feature = "regex")]
+use crate::Error;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Replaces the values of the keys it matches, whatever happens to the keys
+/// themselves. See [`Converter::with_redaction`](crate::Converter::with_redaction).
+#[derive(Clone, Debug)]
+pub struct RedactionRule {
+    matcher: RedactionMatcher,
+    redaction: Redaction,
+}
+
+/// What a redacted value is replaced with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Redaction {
+    /// This string, such as `"[REDACTED]"`.
+    Fixed(String),
+
+    /// `"sha256:"` followed by the hex SHA-256 digest of the value written
+    /// as compact JSON. Equal values get equal digests, so redacted values
+    /// can still be joined on, but short values can be guessed back.
+    Hash,
+
+    /// `null`.
+    Null,
+}
+
+#[derive(Clone, Debug)]
+enum RedactionMatcher {
+    Exact(String),
+    #[cfg(feature = "regex")]
+    Pattern(regex::Regex),
+}
+
+impl RedactionRule {
+    /// Redacts the values of the keys equal to `key`, as they appear in the
+    /// input.
+    pub fn exact(key: impl Into<String>, redaction: Redaction) -> Self {
+        RedactionRule {
+            matcher: RedactionMatcher::Exact(key.into()),
+            redaction,
+        }
+    }
+
+    /// Redacts the values of the keys `pattern` matches, as they appear in
+    /// the input. The pattern isn't anchored: `(?i)token` matches
+    /// `accessToken` and `TOKEN_TYPE`.
+    #[cfg(feature = "regex")]
+    pub fn pattern(pattern: &str, redaction: Redaction) -> Result<Self, Error> {
+        let regex = regex::Regex::new(pattern).map_err(|err| Error::InvalidOption {
+            option: "redaction",
+            message: format!("`{}` is not a regular expression: {}", pattern, err),
+        })?;
+        Ok(RedactionRule {
+            matcher: RedactionMatcher::Pattern(regex),
+            redaction,
+        })
+    }
+
+    pub(crate) fn matches(&self, key: &str) -> bool {
+        match &self.matcher {
+            RedactionMatcher::Exact(exact) => exact == key,
+            #[cfg(feature = "regex")]
+            RedactionMatcher::Pattern(regex) => regex.is_match(key),
+        }
+    }
+
+    pub(crate) fn redact(&self, value: &Value) -> Value {
+        match &self.redaction {
+            Redaction::Fixed(text) => Value::String(text.clone()),
+            Redaction::Hash => {
+                let digest = Sha256::digest(value.to_string().as_bytes());
+                let mut hash = String::with_capacity(7 + 2 * digest.len());
+                hash.push_str("sha256:");
+                for byte in digest {
+                    hash.push(char::from(HEX[usize::from(byte >> 4)]));
+                    hash.push(char::from(HEX[usize::from(byte & 0xf)]));
+                }
+                Value::String(hash)
+            }
+            Redaction::Null => Value::Null,
+        }
+    }
+}
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_exact_keys() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_excluded_pointers(vec!["/apiKey"]);
+        converter.with_redaction(RedactionRule::exact(
+            "apiKey",
+            Redaction::Fixed("[REDACTED]".to_owned()),
+        ));
+        converter.with_redaction(RedactionRule::exact("password", Redaction::Null));
+
+        assert_eq!(
+            json!({
+                "apiKey": "[REDACTED]",
+                "user": {"password": null, "user_name": "ada"},
+                "api_keys": ["apiKey"]
+            }),
+            converter
+                .convert_value(json!({
+                    "apiKey": "k-123",
+                    "user": {"password": {"plain": "hunter2"}, "userName": "ada"},
+                    "apiKeys": ["apiKey"]
+                }))
+                .unwrap()
+        );
+
+        let sample = json!({"apiKey": "k", "users": [{"password": "p"}]});
+        assert_eq!(
+            vec!["/apiKey".to_owned(), "/users/0/password".to_owned()],
+            converter.preflight(&sample).redacted
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn redacts_keys_matching_a_pattern() {
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_redaction(RedactionRule::pattern("(?i)token$", Redaction::Null).unwrap());
+
+        assert_eq!(
+            json!({"sessions": [{"id": 1, "accessToken": null}, {"id": 2, "refreshToken": null}]}),
+            converter
+                .convert_value(json!({
+                    "sessions": [
+                        {"id": 1, "access_token": "a"},
+                        {"id": 2, "refresh_TOKEN": "b"}
+                    ]
+                }))
+                .unwrap()
+        );
+        assert!(RedactionRule::pattern("(", Redaction::Null).is_err());
+    }
+
+    #[test]
+    fn hashes_deterministically() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_redaction(RedactionRule::exact("email", Redaction::Hash));
+
+        let converted = converter
+            .convert_value(json!([
+                {"email": "ada@example.com"},
+                {"email": "ada@example.com"},
+                {"email": "bob@example.com"}
+            ]))
+            .unwrap();
+        assert_eq!(
+            json!({"email": "sha256:c525d356ee379bcf0bc280eab6d3a94fb85f7905b5b8e6419f7549379eb64413"}),
+            converted[0]
+        );
+        assert_eq!(converted[0], converted[1]);
+        assert_ne!(converted[0], converted[2]);
+    }
+}
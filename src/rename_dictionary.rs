@@ -0,0 +1,245 @@
+use crate::converter::Renames;
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error, JsonMap};
+use alloc::collections::BTreeMap;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The new key given to every original key seen so far, kept from one
+/// conversion to the next so that a key is always converted the same way,
+/// even after the options change.
+///
+/// It is written as a plain JSON object from original keys to new keys,
+/// which can also be loaded as manual renames.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RenameDictionary {
+    entries: BTreeMap<String, String>,
+}
+
+/// A key whose recorded new key differs from what the options would now
+/// give it, as reported by [`Converter::convert_with_dictionary`]. The
+/// recorded one was used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenameConflict {
+    /// The original key.
+    pub key: String,
+
+    /// JSON pointer, in the input, to the first entry with the key.
+    pub path: String,
+
+    /// The new key in the dictionary.
+    pub recorded: String,
+
+    /// What the options would convert the key to, `None` if they would drop
+    /// its entry.
+    pub configured: Option<String>,
+}
+
+impl fmt::Display for RenameConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` at `{}` is recorded as `{}` but would now ",
+            self.key, self.path, self.recorded
+        )?;
+        match &self.configured {
+            Some(configured) => write!(f, "become `{}`", configured),
+            None => write!(f, "be dropped"),
+        }
+    }
+}
+
+impl RenameDictionary {
+    pub fn new() -> Self {
+        RenameDictionary::default()
+    }
+
+    /// Reads a dictionary saved with [`RenameDictionary::save`].
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(Error::Io)?;
+        serde_json::from_str(&text).map_err(Error::Parse)
+    }
+
+    /// Writes the dictionary as a pretty-printed JSON object, sorted by
+    /// original key.
+    #[cfg(feature = "std")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), Error> {
+        let text = serde_json::to_string_pretty(self).map_err(Error::Serialize)?;
+        std::fs::write(path, text).map_err(Error::Io)
+    }
+
+    /// The new key recorded for `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// The recorded keys, from original to new, sorted by original key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(from, to)| (from.as_str(), to.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Converter<'_> {
+    /// Converts `value`, looking up every key in `dictionary` first: keys
+    /// found there get their recorded new key, and the others go through the
+    /// options and are recorded. Entries the options drop are not recorded.
+    ///
+    /// Recorded keys that the options would now convert differently are
+    /// returned, once per key, so that a change of options doesn't go
+    /// unnoticed. Keys are recorded by name alone, whatever their path, and
+    /// the rows of pair arrays are not looked at.
+    pub fn convert_with_dictionary(
+        &self,
+        value: Value,
+        dictionary: &mut RenameDictionary,
+    ) -> Result<(Value, Vec<RenameConflict>), Error> {
+        let mut conflicts = Vec::new();
+        let value = self.dictionary_value(value, &Path::Root, None, dictionary, &mut conflicts)?;
+        Ok((value, conflicts))
+    }
+
+    fn dictionary_value(
+        &self,
+        value: Value,
+        path: &Path,
+        variant: Option<&Renames>,
+        dictionary: &mut RenameDictionary,
+        conflicts: &mut Vec<RenameConflict>,
+    ) -> Result<Value, Error> {
+        match value {
+            Value::Array(arr) => arr
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    self.dictionary_value(value, &path.index(index), variant, dictionary, conflicts)
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Value::Object(map) => {
+                let variant = self.object_variant(&map).or(variant);
+                let detected = self.is_detected_dictionary(&map);
+                let mut new_map = JsonMap::new();
+                for (key, value) in map {
+                    let key_path = path.key(&key);
+                    let configured = if detected {
+                        Ok(Some(key.clone()))
+                    } else {
+                        self.rename_variant_key(&key, variant, &key_path)
+                    };
+                    let new_key = match dictionary.entries.get(&key) {
+                        Some(recorded) => {
+                            let configured = configured.unwrap_or(None);
+                            if configured.as_ref() != Some(recorded)
+                                && !conflicts.iter().any(|conflict| conflict.key == key)
+                            {
+                                conflicts.push(RenameConflict {
+                                    key: key.clone(),
+                                    path: key_path.to_pointer(),
+                                    recorded: recorded.clone(),
+                                    configured,
+                                });
+                            }
+                            recorded.clone()
+                        }
+                        None => match configured? {
+                            Some(new_key) => {
+                                dictionary.entries.insert(key.clone(), new_key.clone());
+                                new_key
+                            }
+                            None => continue,
+                        },
+                    };
+                    let new_value =
+                        self.dictionary_value(value, &key_path, variant, dictionary, conflicts)?;
+                    new_map.insert(new_key, new_value);
+                }
+                Ok(Value::Object(new_map))
+            }
+            value => Ok(value),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn keeps_keys_stable_across_batches() {
+        let path = std::env::temp_dir().join(format!("renames-{}.json", std::process::id()));
+
+        let converter = Converter::new(KeyCase::Snake);
+        let mut dictionary = RenameDictionary::new();
+        let (converted, conflicts) = converter
+            .convert_with_dictionary(
+                json!([{"userID": 1, "createdAt": "2024-03-01"}]),
+                &mut dictionary,
+            )
+            .unwrap();
+        assert_eq!(
+            json!([{"user_id": 1, "created_at": "2024-03-01"}]),
+            converted
+        );
+        assert!(conflicts.is_empty());
+        dictionary.save(&path).unwrap();
+
+        // The next night, `userID` gets a manual rename.
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("userID", "user_identifier")].iter().cloned().collect());
+        let mut dictionary = RenameDictionary::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let (converted, conflicts) = converter
+            .convert_with_dictionary(
+                json!([
+                    {"userID": 2, "createdAt": "2024-03-02", "orderTotal": 9},
+                    {"userID": 3}
+                ]),
+                &mut dictionary,
+            )
+            .unwrap();
+
+        assert_eq!(
+            json!([
+                {"user_id": 2, "created_at": "2024-03-02", "order_total": 9},
+                {"user_id": 3}
+            ]),
+            converted
+        );
+        assert_eq!(
+            vec![RenameConflict {
+                key: "userID".to_owned(),
+                path: "/0/userID".to_owned(),
+                recorded: "user_id".to_owned(),
+                configured: Some("user_identifier".to_owned()),
+            }],
+            conflicts
+        );
+        assert_eq!(
+            "`userID` at `/0/userID` is recorded as `user_id` but would now become `user_identifier`",
+            conflicts[0].to_string()
+        );
+        assert_eq!(
+            vec![
+                ("createdAt", "created_at"),
+                ("orderTotal", "order_total"),
+                ("userID", "user_id")
+            ],
+            dictionary.iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use crate::prelude::*;
+use alloc::borrow::Cow;
+use core::fmt;
+
+/// An entry of the input that didn't make it into the converted document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedKey {
+    /// The key, as it appears in the input.
+    pub key: String,
+
+    /// JSON pointer, in the input, to the dropped entry.
+    pub path: String,
+
+    /// JSON pointer, in the converted document, to the object the entry
+    /// would have been in.
+    pub parent: String,
+
+    /// Why the entry was dropped.
+    pub reason: DropReason,
+}
+
+/// Why an entry was left out of the converted document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DropReason {
+    /// The key was empty, or became empty after trimming, under
+    /// [`EmptyKeyPolicy::Drop`](crate::EmptyKeyPolicy::Drop).
+    EmptyKey,
+
+    /// A later key of the same object was converted to the same new key and
+    /// replaced this entry.
+    Collision {
+        /// The original key of the entry that was kept.
+        kept: String,
+
+        /// The new key both entries were converted to.
+        new_key: String,
+    },
+
+    /// The key is, or would be converted to, a key that can pollute object
+    /// prototypes, under [`GuardPolicy::Drop`](crate::GuardPolicy::Drop).
+    PrototypePollution,
+}
+
+/// The keys a conversion renamed, as returned by
+/// [`Converter::convert_with_report`](crate::Converter::convert_with_report).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RenameReport {
+    /// Every key of the converted document whose name changed, inner keys
+    /// before the keys of the objects holding them.
+    pub renamed: Vec<KeyRename>,
+
+    /// The entries left out of the converted document.
+    pub dropped: Vec<DroppedKey>,
+}
+
+/// A key that was renamed, with where it is before and after.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRename {
+    /// The key, as it appears in the input.
+    pub key: String,
+
+    /// JSON pointer, in the input, to the value stored under `key`.
+    pub path: String,
+
+    /// What the key became.
+    pub new_key: String,
+
+    /// JSON pointer, in the converted document, to the same value.
+    pub new_path: String,
+}
+
+impl RenameReport {
+    /// The JSON pointer, in the input, to the value at `new_path` in the
+    /// converted document, for pointing errors about the converted document
+    /// back to the input. Pointers that go through no renamed key are
+    /// returned as they are.
+    pub fn original_path<'r>(&'r self, new_path: &'r str) -> Cow<'r, str> {
+        // The deepest renamed key on the way: below it, keys kept their
+        // names, as renamed ones would be deeper.
+        let deepest = self
+            .renamed
+            .iter()
+            .filter_map(|rename| {
+                let rest = new_path.strip_prefix(rename.new_path.as_str())?;
+                (rest.is_empty() || rest.starts_with('/')).then_some((rename, rest))
+            })
+            .max_by_key(|(rename, _)| rename.new_path.len());
+        match deepest {
+            Some((rename, rest)) => Cow::Owned(format!("{}{}", rename.path, rest)),
+            None => Cow::Borrowed(new_path),
+        }
+    }
+}
+
+impl fmt::Display for KeyRename {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` at `{}` became `{}` at `{}`",
+            self.key, self.path, self.new_key, self.new_path
+        )
+    }
+}
+
+impl DroppedKey {
+    /// Whether the entry could explain a failure at `pointer` in the converted
+    /// document: the failure is on the object that lost it or on one of its
+    /// entries.
+    pub(crate) fn is_near(&self, pointer: &str) -> bool {
+        match pointer.strip_prefix(self.parent.as_str()) {
+            Some("") => true,
+            Some(rest) => rest
+                .strip_prefix('/')
+                .is_some_and(|token| !token.contains('/')),
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for DroppedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` at `{}` was ", self.key, self.path)?;
+        match &self.reason {
+            DropReason::EmptyKey => write!(f, "dropped by the empty key policy"),
+            DropReason::Collision { kept, new_key } => write!(
+                f,
+                "overwritten by `{}`, which was also converted to `{}`",
+                kept, new_key
+            ),
+            DropReason::PrototypePollution => {
+                write!(f, "dropped by the prototype pollution guard")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn rename_report() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_excluded_keys(vec!["keepMe"]);
+        let (converted, report) = converter
+            .convert_with_report(json!({
+                "userName": "ada",
+                "lineItems": [{"unitPrice": 2, "id": 1}],
+                "keepMe": {"innerKey": 3},
+                "myKey": 4,
+                "my_key": 5
+            }))
+            .unwrap();
+        assert_eq!(
+            json!({
+                "user_name": "ada",
+                "line_items": [{"unit_price": 2, "id": 1}],
+                "keepMe": {"inner_key": 3},
+                "my_key": 5
+            }),
+            converted
+        );
+
+        let mut renamed: Vec<_> = report
+            .renamed
+            .iter()
+            .map(|rename| rename.to_string())
+            .collect();
+        renamed.sort();
+        assert_eq!(
+            vec![
+                "`innerKey` at `/keepMe/innerKey` became `inner_key` at `/keepMe/inner_key`",
+                "`lineItems` at `/lineItems` became `line_items` at `/line_items`",
+                "`unitPrice` at `/lineItems/0/unitPrice` became `unit_price` at `/line_items/0/unit_price`",
+                "`userName` at `/userName` became `user_name` at `/user_name`",
+            ],
+            renamed
+        );
+        assert_eq!(
+            vec!["myKey"],
+            report.dropped.iter().map(|d| &d.key).collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            "/lineItems/0/unitPrice",
+            report.original_path("/line_items/0/unit_price")
+        );
+        assert_eq!("/lineItems/0/id", report.original_path("/line_items/0/id"));
+        assert_eq!("/my_key", report.original_path("/my_key"));
+        assert_eq!("/user_names", report.original_path("/user_names"));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{Converted, Converter, Error};
+use ::reqwest::header::{HeaderValue, CONTENT_TYPE};
+use ::reqwest::{RequestBuilder, Response};
+use core::future::Future;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Reads JSON response bodies with their keys converted.
+pub trait ResponseExt {
+    /// Reads the body as JSON, converts its keys with `converter`, and
+    /// deserializes the result into a `T`.
+    ///
+    /// Deserialization errors carry the JSON pointer of the value that failed,
+    /// as [`convert_typed`](crate::convert_typed) errors do.
+    fn json_with_keys<T: DeserializeOwned>(
+        self,
+        converter: &Converter<'_>,
+    ) -> impl Future<Output = Result<T, Error>> + Send;
+}
+
+impl ResponseExt for Response {
+    async fn json_with_keys<T: DeserializeOwned>(
+        self,
+        converter: &Converter<'_>,
+    ) -> Result<T, Error> {
+        let body = self.bytes().await.map_err(Error::Http)?;
+        let value: Value = serde_json::from_slice(&body).map_err(Error::Parse)?;
+        let (converted, dropped) = converter.convert_value_with_drops(value)?;
+        crate::typed::deserialize_value(converted, dropped)
+    }
+}
+
+/// Sends JSON request bodies with their keys converted.
+pub trait RequestBuilderExt: Sized {
+    /// Serializes `body` to JSON with its keys converted by `converter`, and
+    /// sets it as the request body, with a `content-type` of
+    /// `application/json`.
+    fn json_with_keys<B: Serialize + ?Sized>(
+        self,
+        body: &B,
+        converter: &Converter<'_>,
+    ) -> Result<Self, Error>;
+}
+
+impl RequestBuilderExt for RequestBuilder {
+    fn json_with_keys<B: Serialize + ?Sized>(
+        self,
+        body: &B,
+        converter: &Converter<'_>,
+    ) -> Result<Self, Error> {
+        let body =
+            serde_json::to_vec(&Converted::new(body, converter)).map_err(Error::Serialize)?;
+        Ok(self
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .body(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        user_id: u32,
+        display_name: String,
+    }
+
+    fn camel() -> Converter<'static> {
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_manual_renames([("user_id", "ID")].iter().cloned().collect());
+        converter
+    }
+
+    #[tokio::test]
+    async fn converts_both_directions() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/users"))
+            .and(header("content-type", "application/json"))
+            .and(body_json(json!({"ID": 1, "displayName": "Ada"})))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_json(json!({"ID": 1, "displayName": "Ada"})),
+            )
+            .mount(&server)
+            .await;
+
+        let user = User {
+            user_id: 1,
+            display_name: "Ada".to_owned(),
+        };
+        let mut snake = Converter::new(KeyCase::Snake);
+        snake.with_manual_renames([("ID", "user_id")].iter().cloned().collect());
+
+        let response = ::reqwest::Client::new()
+            .post(format!("{}/users", server.uri()))
+            .json_with_keys(&user, &camel())
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(201, response.status().as_u16());
+        assert_eq!(user, response.json_with_keys::<User>(&snake).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn reports_paths_of_bad_responses() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"userId": "x"})))
+            .mount(&server)
+            .await;
+
+        let err = ::reqwest::get(server.uri())
+            .await
+            .unwrap()
+            .json_with_keys::<User>(&Converter::new(KeyCase::Snake))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("failed to deserialize `/user_id`: "),
+            "{}",
+            err
+        );
+    }
+}
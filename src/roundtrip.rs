@@ -0,0 +1,246 @@
+use crate::converter::Renames;
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{keys, Converter};
+use serde_json::Value;
+
+/// A key that doesn't come back as it was after converting a document forward
+/// and backward, found by [`verify_roundtrip`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundtripViolation {
+    /// JSON pointer to the key in the original document.
+    pub path: String,
+
+    /// The original key.
+    pub key: String,
+
+    /// What the key became after both conversions, or `None` when its entry
+    /// was left out of either conversion, such as by a collision.
+    pub became: Option<String>,
+}
+
+/// Converts the keys of `value` with `forward`, then back with `backward`, and
+/// reports every key of `value` that doesn't come back the same, in document
+/// order. An empty list means the pair of converters round-trips `value`.
+///
+/// Each key is followed through both conversions with the options, scopes
+/// and variants of its own place in the document. Arrays of pairs are
+/// checked as plain arrays.
+pub fn verify_roundtrip(
+    value: &Value,
+    forward: &Converter<'_>,
+    backward: &Converter<'_>,
+) -> Vec<RoundtripViolation> {
+    let converted = forward.convert_value(value.clone()).ok();
+    let mut roundtrip = Roundtrip {
+        forward,
+        backward,
+        violations: Vec::new(),
+    };
+    roundtrip.value(
+        value,
+        converted.as_ref(),
+        &Path::Root,
+        &Path::Root,
+        (None, None),
+    );
+    roundtrip.violations
+}
+
+/// Like [`verify_roundtrip`], checking every distinct key of `value` once, as
+/// if it were a key of the outermost object. Violations point to the first
+/// occurrence of each key.
+///
+/// This covers the key strings of large documents quickly, but misses the
+/// options that depend on where a key is, such as scoped affixes, excluded
+/// pointers and variants.
+pub fn verify_roundtrip_keys(
+    value: &Value,
+    forward: &Converter<'_>,
+    backward: &Converter<'_>,
+) -> Vec<RoundtripViolation> {
+    let mut seen = Vec::new();
+    let mut violations = Vec::new();
+    for key_ref in keys(value) {
+        if seen.contains(&key_ref.key) {
+            continue;
+        }
+        seen.push(key_ref.key);
+
+        let became = forward
+            .rename_key(key_ref.key, &Path::Root.key(key_ref.key))
+            .ok()
+            .flatten()
+            .and_then(|new_key| {
+                backward
+                    .rename_key(&new_key, &Path::Root.key(&new_key))
+                    .ok()
+                    .flatten()
+            });
+        if became.as_deref() != Some(key_ref.key) {
+            violations.push(RoundtripViolation {
+                path: key_ref.path,
+                key: key_ref.key.to_owned(),
+                became,
+            });
+        }
+    }
+    violations
+}
+
+struct Roundtrip<'c> {
+    forward: &'c Converter<'c>,
+    backward: &'c Converter<'c>,
+    violations: Vec<RoundtripViolation>,
+}
+
+/// The variant renames in effect for the forward and backward conversions.
+type Variants<'r> = (Option<&'r Renames<'r>>, Option<&'r Renames<'r>>);
+
+impl<'c> Roundtrip<'c> {
+    /// Walks `value` along with `converted`, its forward conversion when
+    /// there is one, found at `path` and `converted_path`.
+    fn value<'v>(
+        &mut self,
+        value: &Value,
+        converted: Option<&'v Value>,
+        path: &Path,
+        converted_path: &Path,
+        variants: Variants<'v>,
+    ) where
+        'c: 'v,
+    {
+        match value {
+            Value::Array(arr) => {
+                for (index, value) in arr.iter().enumerate() {
+                    let converted = converted.and_then(|converted| converted.get(index));
+                    self.value(
+                        value,
+                        converted,
+                        &path.index(index),
+                        &converted_path.index(index),
+                        variants,
+                    );
+                }
+            }
+            Value::Object(map) => {
+                let forward_variant = self.forward.object_variant(map).or(variants.0);
+                let backward_variant = match converted {
+                    Some(Value::Object(converted)) => self.backward.object_variant(converted),
+                    _ => None,
+                }
+                .or(variants.1);
+
+                let new_keys: Vec<_> = map
+                    .keys()
+                    .map(|key| {
+                        self.forward
+                            .rename_variant_key(key, forward_variant, &path.key(key))
+                            .ok()
+                            .flatten()
+                    })
+                    .collect();
+                for (index, ((key, value), new_key)) in map.iter().zip(&new_keys).enumerate() {
+                    let key_path = path.key(key);
+                    let new_key = match new_key {
+                        // A later key converted to the same one takes its place.
+                        Some(new_key)
+                            if !new_keys[index + 1..].contains(&Some(new_key.clone())) =>
+                        {
+                            new_key
+                        }
+                        _ => {
+                            self.violation(key, &key_path, None);
+                            continue;
+                        }
+                    };
+
+                    let new_key_path = converted_path.key(new_key);
+                    let became = self
+                        .backward
+                        .rename_variant_key(new_key, backward_variant, &new_key_path)
+                        .ok()
+                        .flatten();
+                    if became.as_deref() != Some(key.as_str()) {
+                        self.violation(key, &key_path, became);
+                    }
+
+                    let converted = converted.and_then(|converted| converted.get(new_key));
+                    self.value(
+                        value,
+                        converted,
+                        &key_path,
+                        &new_key_path,
+                        (forward_variant, backward_variant),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn violation(&mut self, key: &str, path: &Path, became: Option<String>) {
+        self.violations.push(RoundtripViolation {
+            path: path.to_string(),
+            key: key.to_owned(),
+            became,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn clean_roundtrip() {
+        let value = json!({
+            "user_name": "Ada",
+            "home_address": {"street_name": "x", "tags": [{"tag_name": "a"}]}
+        });
+        let forward = Converter::new(KeyCase::Camel);
+        let backward = Converter::new(KeyCase::Snake);
+
+        assert_eq!(
+            Vec::<RoundtripViolation>::new(),
+            verify_roundtrip(&value, &forward, &backward)
+        );
+        assert!(verify_roundtrip_keys(&value, &forward, &backward).is_empty());
+    }
+
+    #[test]
+    fn lossy_roundtrip() {
+        let value = json!({
+            "user_name": "Ada",
+            "items": [{"userID": 1, "user_id": 2}],
+            "profile": {"userID": 3}
+        });
+        let forward = Converter::new(KeyCase::Camel);
+        let backward = Converter::new(KeyCase::Snake);
+
+        assert_eq!(
+            vec![
+                RoundtripViolation {
+                    path: "/items/0/userID".to_owned(),
+                    key: "userID".to_owned(),
+                    became: None,
+                },
+                RoundtripViolation {
+                    path: "/profile/userID".to_owned(),
+                    key: "userID".to_owned(),
+                    became: Some("user_id".to_owned()),
+                },
+            ],
+            verify_roundtrip(&value, &forward, &backward)
+        );
+        assert_eq!(
+            vec![RoundtripViolation {
+                path: "/items/0/userID".to_owned(),
+                key: "userID".to_owned(),
+                became: Some("user_id".to_owned()),
+            }],
+            verify_roundtrip_keys(&value, &forward, &backward)
+        );
+    }
+}
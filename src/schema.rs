@@ -0,0 +1,319 @@
+use crate::path::{escape_pointer_token, Path};
+use crate::prelude::*;
+use crate::scope::parse_pointer;
+use crate::{Converter, Error, JsonMap, OpenApiMode};
+use serde_json::Value;
+
+/// Keywords whose value is an object of property names to subschemas.
+const NAMED_SCHEMAS: &[&str] = &[
+    "properties",
+    "patternProperties",
+    "definitions",
+    "$defs",
+    "dependentSchemas",
+];
+
+/// Keywords whose value is a subschema, or an array of subschemas.
+const SUBSCHEMAS: &[&str] = &[
+    "items",
+    "additionalItems",
+    "prefixItems",
+    "contains",
+    "additionalProperties",
+    "propertyNames",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+    "not",
+    "if",
+    "then",
+    "else",
+    "allOf",
+    "anyOf",
+    "oneOf",
+];
+
+/// The keywords a schema may hold besides those of JSON Schema.
+#[derive(Copy, Clone)]
+pub(crate) enum Dialect {
+    JsonSchema,
+
+    /// The Schema Object of OpenAPI, with `discriminator` and examples.
+    OpenApi(OpenApiMode),
+}
+
+impl Converter<'_> {
+    /// Converts a JSON Schema (draft-07 or 2020-12), so that it describes the
+    /// documents this converter produces instead of the ones it reads.
+    ///
+    /// Schema keywords are kept. What gets converted is property names: the
+    /// keys of `properties`, `patternProperties`, `definitions`, `$defs`,
+    /// `dependentSchemas` and `dependentRequired`, the entries of `required`
+    /// and `dependentRequired`, and the tokens following those keywords in
+    /// local `$ref`s such as `"#/properties/userName"`. Values such as `enum`,
+    /// `const` and `default` are left alone, and so are the keys of
+    /// `dependencies`, whose values may be either names or schemas.
+    pub fn convert_schema(&self, schema: Value) -> Result<Value, Error> {
+        self.convert_subschema(schema, &Path::Root, Dialect::JsonSchema)
+    }
+
+    pub(crate) fn convert_subschema(
+        &self,
+        schema: Value,
+        path: &Path,
+        dialect: Dialect,
+    ) -> Result<Value, Error> {
+        let schema = match schema {
+            Value::Object(schema) => schema,
+            Value::Array(schemas) => {
+                return schemas
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, schema)| {
+                        self.convert_subschema(schema, &path.index(index), dialect)
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Value::Array)
+            }
+            other => return Ok(other),
+        };
+
+        let mut new_schema = JsonMap::new();
+        for (keyword, value) in schema {
+            let keyword_path = path.key(&keyword);
+            let new_value = match (keyword.as_str(), value) {
+                (named, Value::Object(schemas)) if NAMED_SCHEMAS.contains(&named) => {
+                    Value::Object(self.convert_named(schemas, &keyword_path, |schema, path| {
+                        self.convert_subschema(schema, path, dialect)
+                    })?)
+                }
+                ("dependentRequired", Value::Object(names)) => {
+                    Value::Object(self.convert_named(names, &keyword_path, |names, path| {
+                        self.convert_name_array(names, path)
+                    })?)
+                }
+                ("required", names) => self.convert_name_array(names, &keyword_path)?,
+                ("$ref", Value::String(reference)) => Value::String(self.convert_ref(&reference)?),
+                (subschema, value) if SUBSCHEMAS.contains(&subschema) => {
+                    self.convert_subschema(value, &keyword_path, dialect)?
+                }
+                ("discriminator", Value::Object(discriminator))
+                    if matches!(dialect, Dialect::OpenApi(_)) =>
+                {
+                    Value::Object(self.convert_discriminator(discriminator, &keyword_path)?)
+                }
+                ("example", example)
+                    if matches!(dialect, Dialect::OpenApi(OpenApiMode::ConvertExamples)) =>
+                {
+                    self.convert_value(example)?
+                }
+                ("examples", Value::Array(examples))
+                    if matches!(dialect, Dialect::OpenApi(OpenApiMode::ConvertExamples)) =>
+                {
+                    examples
+                        .into_iter()
+                        .map(|example| self.convert_value(example))
+                        .collect::<Result<_, _>>()
+                        .map(Value::Array)?
+                }
+                (_, value) => value,
+            };
+            new_schema.insert(keyword, new_value);
+        }
+
+        Ok(Value::Object(new_schema))
+    }
+
+    /// Renames the property named by `propertyName`, and the keys of
+    /// `mapping`, of an OpenAPI discriminator.
+    fn convert_discriminator(
+        &self,
+        mut discriminator: JsonMap,
+        path: &Path,
+    ) -> Result<JsonMap, Error> {
+        if let Some(Value::String(name)) = discriminator.get("propertyName").cloned() {
+            let name_path = path.key("propertyName");
+            if let Some(new_name) = self.rename_key(&name, &name_path.key(&name))? {
+                discriminator.insert("propertyName".to_owned(), Value::String(new_name));
+            }
+        }
+        if let Some(Value::Object(mapping)) = discriminator.get_mut("mapping") {
+            let taken = core::mem::take(mapping);
+            *mapping = self.convert_named(taken, &path.key("mapping"), |target, _| Ok(target))?;
+        }
+        Ok(discriminator)
+    }
+
+    /// Renames the keys of an object of property names, converting each value
+    /// with `convert_value`.
+    pub(crate) fn convert_named(
+        &self,
+        named: JsonMap,
+        path: &Path,
+        convert_value: impl Fn(Value, &Path) -> Result<Value, Error>,
+    ) -> Result<JsonMap, Error> {
+        let mut new_named = JsonMap::new();
+        for (name, value) in named {
+            let name_path = path.key(&name);
+            if let Some(new_name) = self.rename_key(&name, &name_path)? {
+                new_named.insert(new_name, convert_value(value, &name_path)?);
+            }
+        }
+        Ok(new_named)
+    }
+
+    /// Renames the property names in an array such as `required`.
+    pub(crate) fn convert_name_array(&self, names: Value, path: &Path) -> Result<Value, Error> {
+        let names = match names {
+            Value::Array(names) => names,
+            other => return Ok(other),
+        };
+
+        let mut new_names = Vec::with_capacity(names.len());
+        for (index, name) in names.into_iter().enumerate() {
+            match name {
+                Value::String(name) => {
+                    let index_path = path.index(index);
+                    let name_path = index_path.key(&name);
+                    if let Some(new_name) = self.rename_key(&name, &name_path)? {
+                        new_names.push(Value::String(new_name));
+                    }
+                }
+                other => new_names.push(other),
+            }
+        }
+        Ok(Value::Array(new_names))
+    }
+
+    /// Renames the property names in a local `$ref`, leaving any other
+    /// reference as it is.
+    fn convert_ref(&self, reference: &str) -> Result<String, Error> {
+        match reference.strip_prefix('#') {
+            Some(pointer) if pointer.starts_with('/') => {
+                let tokens = parse_pointer(pointer);
+                let mut new_ref = String::from("#");
+                self.convert_ref_tokens(&tokens, &Path::Root, false, &mut new_ref)?;
+                Ok(new_ref)
+            }
+            _ => Ok(reference.to_owned()),
+        }
+    }
+
+    fn convert_ref_tokens(
+        &self,
+        tokens: &[String],
+        path: &Path,
+        is_name: bool,
+        new_ref: &mut String,
+    ) -> Result<(), Error> {
+        let (token, rest) = match tokens.split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        let token_path = path.key(token);
+        let new_token = if is_name {
+            self.rename_key(token, &token_path)?
+                .unwrap_or_else(|| token.clone())
+        } else {
+            token.clone()
+        };
+        new_ref.push('/');
+        new_ref.push_str(&escape_pointer_token(&new_token));
+
+        let next_is_name = !is_name && NAMED_SCHEMAS.contains(&token.as_str());
+        self.convert_ref_tokens(rest, &token_path, next_is_name, new_ref)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn schema() -> serde_json::Value {
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "required": ["userName", "homeAddress"],
+            "properties": {
+                "userName": {"type": "string", "minLength": 1},
+                "accountType": {"enum": ["freeTier", "paidTier"], "default": "freeTier"},
+                "homeAddress": {"$ref": "#/$defs/postalAddress"},
+                "pastAddresses": {
+                    "type": "array",
+                    "items": {"$ref": "#/$defs/postalAddress"}
+                },
+                "nickName": {"$ref": "#/properties/userName"}
+            },
+            "additionalProperties": false,
+            "$defs": {
+                "postalAddress": {
+                    "type": "object",
+                    "required": ["streetName"],
+                    "properties": {
+                        "streetName": {"type": "string"},
+                        "zipCode": {"anyOf": [{"type": "string"}, {"type": "integer"}]}
+                    },
+                    "additionalProperties": false
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn converts_property_names() {
+        let converted = Converter::new(KeyCase::Snake)
+            .convert_schema(schema())
+            .unwrap();
+
+        assert_eq!(
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "required": ["user_name", "home_address"],
+                "properties": {
+                    "user_name": {"type": "string", "minLength": 1},
+                    "account_type": {"enum": ["freeTier", "paidTier"], "default": "freeTier"},
+                    "home_address": {"$ref": "#/$defs/postal_address"},
+                    "past_addresses": {
+                        "type": "array",
+                        "items": {"$ref": "#/$defs/postal_address"}
+                    },
+                    "nick_name": {"$ref": "#/properties/user_name"}
+                },
+                "additionalProperties": false,
+                "$defs": {
+                    "postal_address": {
+                        "type": "object",
+                        "required": ["street_name"],
+                        "properties": {
+                            "street_name": {"type": "string"},
+                            "zip_code": {"anyOf": [{"type": "string"}, {"type": "integer"}]}
+                        },
+                        "additionalProperties": false
+                    }
+                }
+            }),
+            converted
+        );
+    }
+
+    #[test]
+    fn converted_instance_matches_converted_schema() {
+        let instance = json!({
+            "userName": "Ada",
+            "accountType": "paidTier",
+            "homeAddress": {"streetName": "Main", "zipCode": 12345},
+            "pastAddresses": [{"streetName": "Old", "zipCode": "A1"}],
+            "nickName": "A"
+        });
+        assert!(jsonschema::is_valid(&schema(), &instance));
+
+        let converter = Converter::new(KeyCase::Snake);
+        let schema = converter.convert_schema(schema()).unwrap();
+        let converted = converter.convert_value(instance.clone()).unwrap();
+
+        assert!(jsonschema::is_valid(&schema, &converted));
+        assert!(!jsonschema::is_valid(&schema, &instance));
+    }
+}
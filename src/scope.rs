@@ -0,0 +1,68 @@
+use crate::path::Path;
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Which keys an option applies to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyScope {
+    /// Keys of the outermost objects, including objects directly inside a
+    /// root array (depth 0, as reported by [`keys`](crate::keys)).
+    TopLevel,
+
+    /// Every key in the document.
+    All,
+
+    /// The keys at this JSON pointer, written with the original keys of the
+    /// input document (e.g. `"/user/apiKey"` or `"/items/0/id"`). A `*`
+    /// token stands for any single key or index, as in `"/items/*/id"`.
+    Pointer(String),
+}
+
+/// A [`KeyScope`] with its pointer already split into tokens.
+#[derive(Clone, Debug)]
+pub(crate) struct ScopeMatcher {
+    scope: KeyScope,
+    tokens: Vec<String>,
+}
+
+impl ScopeMatcher {
+    pub(crate) fn new(scope: KeyScope) -> Self {
+        let tokens = match &scope {
+            KeyScope::Pointer(pointer) => parse_pointer(pointer),
+            _ => Vec::new(),
+        };
+        ScopeMatcher { scope, tokens }
+    }
+
+    pub(crate) fn scope(&self) -> &KeyScope {
+        &self.scope
+    }
+
+    pub(crate) fn is_all(&self) -> bool {
+        self.scope == KeyScope::All
+    }
+
+    /// Whether the key at `path` is in scope.
+    pub(crate) fn matches(&self, path: &Path) -> bool {
+        match self.scope {
+            KeyScope::TopLevel => path.depth() == 0,
+            KeyScope::All => true,
+            KeyScope::Pointer(_) => path.matches_pointer(&self.tokens),
+        }
+    }
+}
+
+/// Splits a JSON pointer into its unescaped reference tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Vec<String> {
+    if pointer.is_empty() {
+        return Vec::new();
+    }
+
+    pointer
+        .strip_prefix('/')
+        .unwrap_or(pointer)
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
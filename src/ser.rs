@@ -0,0 +1,732 @@
+use crate::converter::Context;
+use crate::prelude::*;
+use crate::{Converter, JsonMap};
+use serde::ser::{
+    self, Impossible, Serialize, SerializeMap, SerializeSeq, SerializeStruct,
+    SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    Serializer,
+};
+use serde_json::Value;
+
+/// Serializes a `T` renaming every map and struct key with a [`Converter`]
+/// on the way out, without building a [`Value`] first:
+///
+/// ```
+/// # use json_keys_case_changer::{Converted, Converter};
+/// # use json_keys_case_changer::KeyCase;
+/// # use serde::Serialize;
+/// #[derive(Serialize)]
+/// struct User {
+///     user_name: String,
+/// }
+///
+/// let user = User { user_name: "Ada".to_owned() };
+/// let converter = Converter::new(KeyCase::Camel);
+/// let json = serde_json::to_string(&Converted::new(&user, &converter)).unwrap();
+///
+/// assert_eq!(r#"{"userName":"Ada"}"#, json);
+/// ```
+///
+/// The output is the same as converting the serialized [`Value`]. That is
+/// why structs are written as maps, and newtype, tuple and struct variants
+/// are written in serde's externally tagged form, `{"Variant": ...}`, with
+/// the variant name going through the converter like any other key. The
+/// contents of tuple and struct variants are buffered as [`Value`]s, since
+/// their fields arrive one at a time.
+pub struct Converted<'c, T: ?Sized> {
+    value: &'c T,
+    converter: &'c Converter<'c>,
+}
+
+impl<'c, T: ?Sized> Converted<'c, T> {
+    pub fn new(value: &'c T, converter: &'c Converter<'c>) -> Self {
+        Converted { value, converter }
+    }
+}
+
+impl<T: ?Sized + Serialize> Serialize for Converted<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(Wrap {
+            inner: serializer,
+            cx: Context::new(self.converter),
+        })
+    }
+}
+
+/// A value to serialize somewhere below the root.
+struct Sub<'p, T: ?Sized> {
+    value: &'p T,
+    cx: Context<'p>,
+}
+
+impl<T: ?Sized + Serialize> Serialize for Sub<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(Wrap {
+            inner: serializer,
+            cx: self.cx,
+        })
+    }
+}
+
+struct Wrap<'p, S> {
+    inner: S,
+    cx: Context<'p>,
+}
+
+struct Seq<'p, S> {
+    inner: S,
+    cx: Context<'p>,
+    index: usize,
+}
+
+struct Map<'p, S> {
+    inner: S,
+    cx: Context<'p>,
+    key: Option<MapKey>,
+}
+
+/// What happened to the key of the entry being written.
+enum MapKey {
+    /// Written under a new name; holds the original one.
+    Kept(String),
+    /// Dropped by the converter, along with its value.
+    Dropped,
+    /// Not a string, written as it is.
+    Other,
+}
+
+/// Fields of a tuple or struct variant, written once complete.
+struct Variant<'p, S> {
+    inner: S,
+    cx: Context<'p>,
+    variant: &'static str,
+    elements: Vec<Value>,
+    fields: JsonMap,
+}
+
+macro_rules! forward_serialize {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'p, S: Serializer> Serializer for Wrap<'p, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Seq<'p, S::SerializeSeq>;
+    type SerializeTuple = Seq<'p, S::SerializeTuple>;
+    type SerializeTupleStruct = Seq<'p, S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Variant<'p, S>;
+    type SerializeMap = Map<'p, S::SerializeMap>;
+    type SerializeStruct = Map<'p, S::SerializeMap>;
+    type SerializeStructVariant = Variant<'p, S>;
+
+    forward_serialize! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.inner.serialize_some(&Sub { value, cx: self.cx })
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.inner.serialize_unit_struct(name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.inner
+            .serialize_unit_variant(name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.inner
+            .serialize_newtype_struct(name, &Sub { value, cx: self.cx })
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let mut map = self.inner.serialize_map(Some(1))?;
+        let path = self.cx.path.key(variant);
+        if let Some(new_variant) = rename::<S::Error>(self.cx, variant)? {
+            map.serialize_entry(
+                &new_variant,
+                &Sub {
+                    value,
+                    cx: self.cx.at(&path),
+                },
+            )?;
+        }
+        map.end()
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(Seq {
+            inner: self.inner.serialize_seq(len)?,
+            cx: self.cx,
+            index: 0,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(Seq {
+            inner: self.inner.serialize_tuple(len)?,
+            cx: self.cx,
+            index: 0,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(Seq {
+            inner: self.inner.serialize_tuple_struct(name, len)?,
+            cx: self.cx,
+            index: 0,
+        })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(Variant {
+            inner: self.inner,
+            cx: self.cx,
+            variant,
+            elements: Vec::with_capacity(len),
+            fields: JsonMap::new(),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(Map {
+            inner: self.inner.serialize_map(len)?,
+            cx: self.cx,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(Variant {
+            inner: self.inner,
+            cx: self.cx,
+            variant,
+            elements: Vec::new(),
+            fields: JsonMap::new(),
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+fn rename<E: ser::Error>(cx: Context, key: &str) -> Result<Option<String>, E> {
+    let path = cx.path.key(key);
+    cx.converter
+        .rename_key(key, &path)
+        .map_err(ser::Error::custom)
+}
+
+macro_rules! impl_seq {
+    ($($trait:ident::$method:ident,)*) => {
+        $(
+            impl<S: $trait> $trait for Seq<'_, S> {
+                type Ok = S::Ok;
+                type Error = S::Error;
+
+                fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+                    let path = self.cx.path.index(self.index);
+                    self.index += 1;
+                    self.inner.$method(&Sub {
+                        value,
+                        cx: self.cx.at(&path),
+                    })
+                }
+
+                fn end(self) -> Result<Self::Ok, Self::Error> {
+                    self.inner.end()
+                }
+            }
+        )*
+    };
+}
+
+impl_seq! {
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+}
+
+impl<S: SerializeMap> SerializeMap for Map<'_, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.key = match key.serialize(KeyCapture) {
+            Ok(Some(original)) => match rename(self.cx, &original)? {
+                Some(new_key) => {
+                    self.inner.serialize_key(&new_key)?;
+                    Some(MapKey::Kept(original))
+                }
+                None => Some(MapKey::Dropped),
+            },
+            _ => {
+                self.inner.serialize_key(key)?;
+                Some(MapKey::Other)
+            }
+        };
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        match self.key.take() {
+            Some(MapKey::Kept(original)) => {
+                let path = self.cx.path.key(&original);
+                self.inner.serialize_value(&Sub {
+                    value,
+                    cx: self.cx.at(&path),
+                })
+            }
+            Some(MapKey::Dropped) => Ok(()),
+            Some(MapKey::Other) | None => self.inner.serialize_value(&Sub { value, cx: self.cx }),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeMap> SerializeStruct for Map<'_, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let path = self.cx.path.key(key);
+        match rename(self.cx, key)? {
+            Some(new_key) => self.inner.serialize_entry(
+                &new_key,
+                &Sub {
+                    value,
+                    cx: self.cx.at(&path),
+                },
+            ),
+            None => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: Serializer> Variant<'_, S> {
+    fn write(self, content: Value) -> Result<S::Ok, S::Error> {
+        let mut map = self.inner.serialize_map(Some(1))?;
+        if let Some(new_variant) = rename(self.cx, self.variant)? {
+            map.serialize_entry(&new_variant, &content)?;
+        }
+        map.end()
+    }
+}
+
+impl<S: Serializer> SerializeTupleVariant for Variant<'_, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let variant_path = self.cx.path.key(self.variant);
+        let path = variant_path.index(self.elements.len());
+        let element = serde_json::to_value(Sub {
+            value,
+            cx: self.cx.at(&path),
+        })
+        .map_err(ser::Error::custom)?;
+        self.elements.push(element);
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        let elements = core::mem::take(&mut self.elements);
+        self.write(Value::Array(elements))
+    }
+}
+
+impl<S: Serializer> SerializeStructVariant for Variant<'_, S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        let variant_path = self.cx.path.key(self.variant);
+        let cx = self.cx.at(&variant_path);
+        let path = variant_path.key(key);
+        if let Some(new_key) = rename(cx, key)? {
+            let field = serde_json::to_value(Sub {
+                value,
+                cx: cx.at(&path),
+            })
+            .map_err(ser::Error::custom)?;
+            self.fields.insert(new_key, field);
+        }
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        let fields = core::mem::take(&mut self.fields);
+        self.write(Value::Object(fields))
+    }
+}
+
+/// Reads a map key back as a string, or `None` when it isn't one.
+struct KeyCapture;
+
+type KeyError = serde::de::value::Error;
+
+macro_rules! not_a_string_key {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method(self, _: $ty) -> Result<Self::Ok, Self::Error> {
+                Ok(None)
+            }
+        )*
+    };
+}
+
+impl Serializer for KeyCapture {
+    type Ok = Option<String>;
+    type Error = KeyError;
+    type SerializeSeq = Impossible<Self::Ok, KeyError>;
+    type SerializeTuple = Impossible<Self::Ok, KeyError>;
+    type SerializeTupleStruct = Impossible<Self::Ok, KeyError>;
+    type SerializeTupleVariant = Impossible<Self::Ok, KeyError>;
+    type SerializeMap = Impossible<Self::Ok, KeyError>;
+    type SerializeStruct = Impossible<Self::Ok, KeyError>;
+    type SerializeStructVariant = Impossible<Self::Ok, KeyError>;
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(v.to_owned()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(v.to_string()))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    not_a_string_key! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_bytes(&[u8]);
+        serialize_unit_struct(&'static str);
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(ser::Error::custom("not a string key"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(ser::Error::custom("not a string key"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(ser::Error::custom("not a string key"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(ser::Error::custom("not a string key"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(ser::Error::custom("not a string key"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(ser::Error::custom("not a string key"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(ser::Error::custom("not a string key"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::HashMap;
+    use crate::*;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Account {
+        user_name: String,
+        home_address: Address,
+        past_orders: Vec<Order>,
+        last_event: Event,
+        first_event: Event,
+        nick_name: Option<String>,
+    }
+
+    #[derive(Serialize)]
+    struct Address {
+        street_name: String,
+        zip_code: (u32, u32),
+    }
+
+    #[derive(Serialize)]
+    struct Order {
+        order_id: u32,
+        line_items: Vec<LineItem>,
+    }
+
+    #[derive(Serialize)]
+    struct LineItem {
+        item_name: String,
+    }
+
+    #[derive(Serialize)]
+    enum Event {
+        LoggedIn,
+        PasswordChanged { changed_at: u64, by_admin: bool },
+        Moved(Address),
+        Renamed(String, String),
+    }
+
+    fn account() -> Account {
+        Account {
+            user_name: "Ada".to_owned(),
+            home_address: Address {
+                street_name: "Main".to_owned(),
+                zip_code: (12, 345),
+            },
+            past_orders: vec![
+                Order {
+                    order_id: 1,
+                    line_items: vec![LineItem {
+                        item_name: "tea".to_owned(),
+                    }],
+                },
+                Order {
+                    order_id: 2,
+                    line_items: Vec::new(),
+                },
+            ],
+            last_event: Event::PasswordChanged {
+                changed_at: 10,
+                by_admin: false,
+            },
+            first_event: Event::LoggedIn,
+            nick_name: None,
+        }
+    }
+
+    /// Checks that `streamed` is `converted`, down to the order of the keys
+    /// when objects keep it.
+    fn assert_same_output(converted: &serde_json::Value, streamed: &str) {
+        if cfg!(feature = "preserve_order") {
+            assert_eq!(serde_json::to_string(converted).unwrap(), streamed);
+        } else {
+            assert_eq!(
+                *converted,
+                serde_json::from_str::<serde_json::Value>(streamed).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn same_as_converting_the_value() {
+        let converter = Converter::new(KeyCase::Camel);
+
+        let streamed = serde_json::to_string(&Converted::new(&account(), &converter)).unwrap();
+
+        let value = serde_json::to_value(account()).unwrap();
+        let converted = converter.convert_value(value).unwrap();
+        assert_same_output(&converted, &streamed);
+
+        for event in [
+            Event::Moved(account().home_address),
+            Event::Renamed("a".to_owned(), "b".to_owned()),
+        ] {
+            let streamed = serde_json::to_string(&Converted::new(&event, &converter)).unwrap();
+            let converted = converter
+                .convert_value(serde_json::to_value(&event).unwrap())
+                .unwrap();
+            assert_same_output(&converted, &streamed);
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Inventory {
+        warehouse_name: String,
+        stock_by_sku: HashMap<String, Stock>,
+    }
+
+    #[derive(Serialize)]
+    struct Stock {
+        units_left: u32,
+    }
+
+    #[test]
+    fn dictionary_field() {
+        let mut stock_by_sku = HashMap::new();
+        stock_by_sku.insert("SKU_one".to_owned(), Stock { units_left: 1 });
+        stock_by_sku.insert("sku-two".to_owned(), Stock { units_left: 2 });
+        let inventory = Inventory {
+            warehouse_name: "north".to_owned(),
+            stock_by_sku,
+        };
+
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_dictionary_keys(vec!["stock_by_sku"]);
+
+        let streamed = serde_json::to_string(&Converted::new(&inventory, &converter)).unwrap();
+
+        let value = serde_json::to_value(&inventory).unwrap();
+        let converted = converter.convert_value(value).unwrap();
+        assert_same_output(&converted, &streamed);
+
+        let expected = serde_json::json!({
+            "warehouseName": "north",
+            "stockBySku": {"SKU_one": {"unitsLeft": 1}, "sku-two": {"unitsLeft": 2}}
+        });
+        assert_eq!(
+            expected,
+            serde_json::from_str::<serde_json::Value>(&streamed).unwrap()
+        );
+    }
+}
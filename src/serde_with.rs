@@ -0,0 +1,168 @@
+//! Helpers for `#[serde(with = "...")]` on [`Value`] fields whose keys
+//! should be converted while the parent struct is (de)serialized.
+//!
+//! Each module is named after the case the keys have on the wire. Reading
+//! the field converts its keys to snake_case, and writing it converts them
+//! back, so a key survives the round trip as long as the two conversions
+//! undo each other:
+//!
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! # use serde_json::{json, Value};
+//! #[derive(Serialize, Deserialize)]
+//! struct Event {
+//!     #[serde(with = "json_keys_case_changer::serde_with::camel")]
+//!     payload: Value,
+//! }
+//!
+//! let event: Event = serde_json::from_value(json!({"payload": {"userId": 1}})).unwrap();
+//! assert_eq!(json!({"user_id": 1}), event.payload);
+//!
+//! let json = serde_json::to_value(&event).unwrap();
+//! assert_eq!(json!({"payload": {"userId": 1}}), json);
+//! ```
+//!
+//! For a case without a module, implement [`WireCase`] on a marker and pass
+//! it to the generic functions:
+//!
+//! ```
+//! # use json_keys_case_changer::KeyCase;
+//! # use serde::{Deserialize, Serialize};
+//! # use serde_json::Value;
+//! use json_keys_case_changer::serde_with::{self, WireCase};
+//!
+//! enum Train {}
+//!
+//! impl WireCase for Train {
+//!     const CASE: KeyCase = KeyCase::Train;
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Request {
+//!     #[serde(
+//!         serialize_with = "serde_with::serialize::<Train, _>",
+//!         deserialize_with = "serde_with::deserialize::<Train, _>"
+//!     )]
+//!     headers: Value,
+//! }
+//! ```
+
+use crate::KeyCase;
+use crate::{CaseChangingDeserializer, Converted, Converter};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// A case known at compile time, for the generic [`serialize`] and
+/// [`deserialize`].
+pub trait WireCase {
+    const CASE: KeyCase;
+}
+
+macro_rules! wire_cases {
+    ($($(#[$doc:meta])* $marker:ident => $case:ident, $module:ident;)*) => {
+        $(
+            $(#[$doc])*
+            pub enum $marker {}
+
+            impl WireCase for $marker {
+                const CASE: KeyCase = KeyCase::$case;
+            }
+
+            $(#[$doc])*
+            pub mod $module {
+                use super::*;
+
+                pub fn serialize<S: Serializer>(
+                    value: &Value,
+                    serializer: S,
+                ) -> Result<S::Ok, S::Error> {
+                    super::serialize::<$marker, S>(value, serializer)
+                }
+
+                pub fn deserialize<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                ) -> Result<Value, D::Error> {
+                    super::deserialize::<$marker, D>(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+wire_cases! {
+    /// `snake_case` keys on the wire.
+    Snake => Snake, snake;
+    /// `camelCase` keys on the wire.
+    Camel => Camel, camel;
+    /// `PascalCase` keys on the wire.
+    Pascal => Pascal, pascal;
+    /// `kebab-case` keys on the wire.
+    Kebab => Kebab, kebab;
+    /// `SCREAMING_SNAKE_CASE` keys on the wire.
+    ScreamingSnake => ScreamingSnake, screaming_snake;
+}
+
+/// The case keys are kept in while the field is in memory.
+const MEMORY_CASE: KeyCase = KeyCase::Snake;
+
+/// Writes `value` with its keys converted to `C`.
+pub fn serialize<C: WireCase, S: Serializer>(
+    value: &Value,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let converter = Converter::new(C::CASE);
+    Converted::new(value, &converter).serialize(serializer)
+}
+
+/// Reads a [`Value`] with its keys converted to snake_case. Keys are
+/// converted whatever their case on the wire; `C` is only there to pair
+/// with [`serialize`].
+pub fn deserialize<'de, C: WireCase, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Value, D::Error> {
+    let converter = Converter::new(MEMORY_CASE);
+    Value::deserialize(CaseChangingDeserializer::new(deserializer, &converter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Envelope {
+        #[serde(rename = "messageId")]
+        message_id: u32,
+
+        #[serde(with = "camel")]
+        body: Value,
+
+        #[serde(
+            serialize_with = "serialize::<Kebab, _>",
+            deserialize_with = "deserialize::<Kebab, _>"
+        )]
+        headers: Value,
+    }
+
+    #[test]
+    fn round_trip() {
+        let wire = json!({
+            "messageId": 7,
+            "body": {"userName": "Ada", "pastOrders": [{"orderId": 1}]},
+            "headers": {"content-type": "json", "x-request-id": "abc"}
+        });
+
+        let envelope: Envelope = serde_json::from_value(wire.clone()).unwrap();
+        assert_eq!(
+            Envelope {
+                message_id: 7,
+                body: json!({"user_name": "Ada", "past_orders": [{"order_id": 1}]}),
+                headers: json!({"content_type": "json", "x_request_id": "abc"}),
+            },
+            envelope
+        );
+
+        let text = serde_json::to_string(&envelope).unwrap();
+        assert_eq!(wire, serde_json::from_str::<Value>(&text).unwrap());
+    }
+}
@@ -0,0 +1,290 @@
+//! An HTTP service converting JSON documents, for clients that can't link
+//! the crate. The `json-case-server` binary serves [`router`].
+//!
+//! - `POST /convert` converts the JSON body to the case named by the `case`
+//!   query parameter or the `x-target-case` header, applying the rules of
+//!   the [`Preset`] named by `preset` or `x-preset` when there is one.
+//! - `POST /convert/spec` converts the `document` of a
+//!   `{"spec": {...}, "document": ...}` body with the converter its
+//!   [`ConversionSpec`] describes.
+//! - `GET /healthz` answers `ok`.
+//!
+//! Converted documents are sent back as they are. Errors are sent as
+//! `{"error": {"code": ..., "message": ...}}`, with the problems of an
+//! invalid spec under `diagnostics`: `400 Bad Request` when the body isn't
+//! JSON or the case or preset is missing or unknown, `413 Payload Too Large`
+//! when the body is over the limit, and `422 Unprocessable Entity` when the
+//! spec is invalid or the document can't be converted.
+
+use crate::prelude::*;
+use crate::{ConfigDiagnostic, ConversionSpec, Converter, Error, KeyCase, Preset};
+use ::axum::body::Bytes;
+use ::axum::extract::rejection::{BytesRejection, QueryRejection};
+use ::axum::extract::{DefaultBodyLimit, Query, State};
+use ::axum::http::{header, HeaderMap, StatusCode};
+use ::axum::response::{IntoResponse, Response};
+use ::axum::routing::{get, post};
+use ::axum::Router;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// Settings of the service.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct ServerConfig {
+    /// The largest request body accepted, in bytes. Defaults to 2 MiB.
+    pub body_limit: usize,
+
+    /// How many converters built from specs are kept for later requests
+    /// with the same spec. Defaults to 64; the cache is emptied when full.
+    pub cache_capacity: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            body_limit: 2 * 1024 * 1024,
+            cache_capacity: 64,
+        }
+    }
+}
+
+/// The routes of the service, sharing one cache of converters.
+pub fn router(config: ServerConfig) -> Router {
+    let cache = Arc::new(ConverterCache {
+        capacity: config.cache_capacity,
+        converters: RwLock::default(),
+    });
+    Router::new()
+        .route("/convert", post(convert))
+        .route("/convert/spec", post(convert_with_spec))
+        .route("/healthz", get(|| async { "ok" }))
+        .layer(DefaultBodyLimit::max(config.body_limit))
+        .with_state(cache)
+}
+
+/// Converters built from specs, by the hash of their spec. The spec is kept
+/// next to its converter, so that a hash collision only costs a rebuild.
+struct ConverterCache {
+    capacity: usize,
+    converters: RwLock<HashMap<u64, (ConversionSpec, Arc<Converter<'static>>)>>,
+}
+
+impl ConverterCache {
+    fn get(&self, spec: ConversionSpec) -> Result<Arc<Converter<'static>>, Error> {
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(&spec)
+            .map_err(Error::Serialize)?
+            .hash(&mut hasher);
+        let hash = hasher.finish();
+
+        if let Some((cached, converter)) = self
+            .converters
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&hash)
+        {
+            if *cached == spec {
+                return Ok(Arc::clone(converter));
+            }
+        }
+
+        let converter = Arc::new(Converter::from_spec(spec.clone())?);
+        if self.capacity > 0 {
+            let mut converters = self
+                .converters
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            if converters.len() >= self.capacity {
+                converters.clear();
+            }
+            converters.insert(hash, (spec, Arc::clone(&converter)));
+        }
+        Ok(converter)
+    }
+}
+
+#[derive(Deserialize)]
+struct ConvertParams {
+    case: Option<String>,
+    preset: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpecEnvelope {
+    spec: ConversionSpec,
+    document: Value,
+}
+
+async fn convert(
+    State(cache): State<Arc<ConverterCache>>,
+    params: Result<Query<ConvertParams>, QueryRejection>,
+    headers: HeaderMap,
+    body: Result<Bytes, BytesRejection>,
+) -> Result<Response, ApiError> {
+    let Query(params) = params
+        .map_err(|err| ApiError::new(StatusCode::BAD_REQUEST, "invalid_query", err.body_text()))?;
+    let parameter = |query: Option<String>, name: &str| {
+        query.or_else(|| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_owned)
+        })
+    };
+
+    let case = parameter(params.case, "x-target-case").ok_or_else(|| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "missing_case",
+            "name the target case with the `case` query parameter or the `x-target-case` header",
+        )
+    })?;
+    let case: KeyCase = case
+        .parse()
+        .map_err(|err: Error| ApiError::new(StatusCode::BAD_REQUEST, "unknown_case", err))?;
+
+    let mut spec = ConversionSpec::new(case);
+    if let Some(name) = parameter(params.preset, "x-preset") {
+        let rules = parse_preset(&name)
+            .ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    "unknown_preset",
+                    format!(
+                        "unknown preset `{}`, expected one of `mongodb`, `kubernetes`, `json-ld`, `json-api`",
+                        name
+                    ),
+                )
+            })?
+            .rules();
+        spec.skip_prefixes = rules.skip_prefixes;
+        spec.dictionary_keys = rules.dictionary_keys;
+        spec.excluded_pointers = rules.excluded_pointers;
+    }
+
+    let document = parse_body(body)?;
+    respond(&cache, spec, document)
+}
+
+async fn convert_with_spec(
+    State(cache): State<Arc<ConverterCache>>,
+    body: Result<Bytes, BytesRejection>,
+) -> Result<Response, ApiError> {
+    let envelope = parse_body(body)?;
+    let SpecEnvelope { spec, document } = serde_json::from_value(envelope)
+        .map_err(|err| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "invalid_envelope", err))?;
+    respond(&cache, spec, document)
+}
+
+fn parse_body(body: Result<Bytes, BytesRejection>) -> Result<Value, ApiError> {
+    let body = body.map_err(|err| {
+        let code = if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            "body_too_large"
+        } else {
+            "unreadable_body"
+        };
+        ApiError::new(err.status(), code, err.body_text())
+    })?;
+    serde_json::from_slice(&body)
+        .map_err(|err| ApiError::new(StatusCode::BAD_REQUEST, "invalid_json", err))
+}
+
+fn respond(
+    cache: &ConverterCache,
+    spec: ConversionSpec,
+    document: Value,
+) -> Result<Response, ApiError> {
+    let converted = cache
+        .get(spec)
+        .and_then(|converter| converter.convert_value(document))
+        .map_err(ApiError::from)?;
+    let body = serde_json::to_vec(&converted)
+        .map_err(|err| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "serialize_failed", err))?;
+    Ok(([(header::CONTENT_TYPE, "application/json")], body).into_response())
+}
+
+/// The presets by the names the service accepts, ignoring case, `-` and
+/// `_`.
+fn parse_preset(name: &str) -> Option<Preset> {
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, '-' | '_'))
+        .collect::<String>()
+        .to_ascii_lowercase();
+    match name.as_str() {
+        "mongodb" => Some(Preset::MongoDb),
+        "kubernetes" => Some(Preset::Kubernetes),
+        "jsonld" => Some(Preset::JsonLd),
+        "jsonapi" => Some(Preset::JsonApi),
+        _ => None,
+    }
+}
+
+/// An error response, sent as `{"error": {...}}`.
+#[derive(Debug)]
+struct ApiError {
+    status: StatusCode,
+    body: ErrorBody,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    diagnostics: Vec<ConfigDiagnostic>,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: &'static str, message: impl ToString) -> Self {
+        ApiError {
+            status,
+            body: ErrorBody {
+                code,
+                message: message.to_string(),
+                diagnostics: Vec::new(),
+            },
+        }
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::InvalidConfig { diagnostics } => {
+                let mut api_error = ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "invalid_spec",
+                    Error::InvalidConfig {
+                        diagnostics: diagnostics.clone(),
+                    },
+                );
+                api_error.body.diagnostics = diagnostics;
+                api_error
+            }
+            err => ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "conversion_failed", err),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        #[derive(Serialize)]
+        struct Envelope<'a> {
+            error: &'a ErrorBody,
+        }
+
+        let body = serde_json::to_vec(&Envelope { error: &self.body }).unwrap_or_default();
+        (
+            self.status,
+            [(header::CONTENT_TYPE, "application/json")],
+            body,
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,166 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error};
+use alloc::borrow::Cow;
+use simd_json::{BorrowedValue, OwnedValue};
+
+impl Converter<'_> {
+    /// Converts the keys of the objects in `value`, as
+    /// [`Converter::convert_value`] does for `serde_json` values.
+    pub fn convert_simd_value(&self, value: OwnedValue) -> Result<OwnedValue, Error> {
+        self.convert_simd(value, &Path::Root)
+    }
+
+    /// Converts the keys of the objects in `value`, as
+    /// [`Converter::convert_value`] does for `serde_json` values. Keys that
+    /// are kept as they are still borrow from the input.
+    pub fn convert_simd_borrowed_value<'v>(
+        &self,
+        value: BorrowedValue<'v>,
+    ) -> Result<BorrowedValue<'v>, Error> {
+        self.convert_simd(value, &Path::Root)
+    }
+
+    fn convert_simd<V: SimdValue>(&self, value: V, path: &Path) -> Result<V, Error> {
+        match value.into_node() {
+            Node::Array(values) => values
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| self.convert_simd(value, &path.index(index)))
+                .collect::<Result<_, _>>()
+                .map(V::from_array),
+            Node::Object(entries) => {
+                let mut new_entries = Vec::with_capacity(entries.len());
+                for (key, value) in entries {
+                    let key_path = path.key(key.as_ref());
+                    let new_key = match self.rename_key(key.as_ref(), &key_path)? {
+                        Some(new_key) => new_key,
+                        None => continue,
+                    };
+                    let value = self.convert_simd(value, &key_path)?;
+                    let new_key = if new_key == key.as_ref() {
+                        key
+                    } else {
+                        new_key.into()
+                    };
+                    new_entries.push((new_key, value));
+                }
+                Ok(V::from_object(new_entries))
+            }
+            Node::Other(value) => Ok(value),
+        }
+    }
+}
+
+/// The two `simd-json` value types, as far as converting them is concerned.
+trait SimdValue: Sized {
+    type Key: AsRef<str> + From<String>;
+
+    fn into_node(self) -> Node<Self>;
+    fn from_array(values: Vec<Self>) -> Self;
+    fn from_object(entries: Vec<(Self::Key, Self)>) -> Self;
+}
+
+enum Node<V: SimdValue> {
+    Array(Vec<V>),
+    Object(Vec<(V::Key, V)>),
+    Other(V),
+}
+
+impl SimdValue for OwnedValue {
+    type Key = String;
+
+    fn into_node(self) -> Node<Self> {
+        match self {
+            OwnedValue::Array(values) => Node::Array(*values),
+            OwnedValue::Object(object) => Node::Object(object.into_iter().collect()),
+            value => Node::Other(value),
+        }
+    }
+
+    fn from_array(values: Vec<Self>) -> Self {
+        OwnedValue::Array(Box::new(values))
+    }
+
+    fn from_object(entries: Vec<(String, Self)>) -> Self {
+        OwnedValue::Object(Box::new(entries.into_iter().collect()))
+    }
+}
+
+impl<'v> SimdValue for BorrowedValue<'v> {
+    type Key = Cow<'v, str>;
+
+    fn into_node(self) -> Node<Self> {
+        match self {
+            BorrowedValue::Array(values) => Node::Array(*values),
+            BorrowedValue::Object(object) => Node::Object(object.into_iter().collect()),
+            value => Node::Other(value),
+        }
+    }
+
+    fn from_array(values: Vec<Self>) -> Self {
+        BorrowedValue::Array(Box::new(values))
+    }
+
+    fn from_object(entries: Vec<(Cow<'v, str>, Self)>) -> Self {
+        BorrowedValue::Object(Box::new(entries.into_iter().collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use alloc::borrow::Cow;
+    use serde_json::json;
+    use simd_json::BorrowedValue;
+
+    /// Converts `value` both as a `serde_json` value and as `simd-json` values,
+    /// and checks that all three agree.
+    fn assert_converts(value: serde_json::Value, expected: serde_json::Value) {
+        let converter = Converter::new(KeyCase::Snake);
+        let mut text = value.to_string().into_bytes();
+
+        let owned = simd_json::to_owned_value(&mut text.clone()).unwrap();
+        let owned = converter.convert_simd_value(owned).unwrap();
+        assert_eq!(expected, serde_json::to_value(&owned).unwrap());
+
+        let borrowed = simd_json::to_borrowed_value(&mut text).unwrap();
+        let borrowed = converter.convert_simd_borrowed_value(borrowed).unwrap();
+        assert_eq!(expected, serde_json::to_value(&borrowed).unwrap());
+
+        assert_eq!(expected, converter.convert_value(value).unwrap());
+    }
+
+    #[test]
+    fn root_array() {
+        assert_converts(
+            json!([{"myCamel": 1}, {"myCamel": 2}]),
+            json!([{"my_camel": 1}, {"my_camel": 2}]),
+        );
+    }
+
+    #[test]
+    fn array_of_strings_in_map() {
+        assert_converts(
+            json!({"anArray": ["ObjectOne", "ObjectTwo"]}),
+            json!({"an_array": ["ObjectOne", "ObjectTwo"]}),
+        );
+    }
+
+    #[test]
+    fn keeps_borrowing_unchanged_keys() {
+        let mut text = br#"{"user_name": {"zipCode": 1}}"#.to_vec();
+        let value = simd_json::to_borrowed_value(&mut text).unwrap();
+        let value = Converter::new(KeyCase::Snake)
+            .convert_simd_borrowed_value(value)
+            .unwrap();
+
+        let object = match value {
+            BorrowedValue::Object(object) => object,
+            _ => panic!("not an object"),
+        };
+        let (key, value) = object.iter().next().unwrap();
+        assert!(matches!(key, Cow::Borrowed("user_name")));
+        assert_eq!(json!({"zip_code": 1}), serde_json::to_value(value).unwrap());
+    }
+}
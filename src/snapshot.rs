@@ -0,0 +1,90 @@
+use crate::converter::expect_converted;
+use crate::prelude::*;
+use crate::{Converter, JsonMap, KeyCase};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Renders `value` for a snapshot test, such as `insta::assert_snapshot!`,
+/// with its keys converted to `case`: pretty-printed, with the keys of every
+/// object sorted, so the output is the same whatever the key order and key
+/// case of the input.
+///
+/// # Panics
+///
+/// Panics if `value` can't be serialized to JSON.
+pub fn normalize_for_snapshot(value: impl Serialize, case: KeyCase) -> String {
+    normalize_for_snapshot_with(value, &Converter::new(case))
+}
+
+/// Like [`normalize_for_snapshot`], converting keys with `converter`, so
+/// that dictionary keys, such as generated IDs, and excluded pointers are
+/// kept as they are.
+///
+/// Lines end with `\n` on every platform, and line breaks inside strings are
+/// escaped, so snapshots don't depend on where they were taken.
+///
+/// # Panics
+///
+/// Panics if `value` can't be serialized to JSON, or if `converter` rejects
+/// one of its keys.
+pub fn normalize_for_snapshot_with(value: impl Serialize, converter: &Converter) -> String {
+    let value = serde_json::to_value(value)
+        .unwrap_or_else(|err| panic!("value can't be serialized to JSON: {}", err));
+    let value = expect_converted(converter.convert_value(value));
+    // Writing a `Value` can't fail: its keys are strings.
+    serde_json::to_string_pretty(&sorted(value)).unwrap_or_default()
+}
+
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Array(arr) => Value::Array(arr.into_iter().map(sorted).collect()),
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sorted(value)))
+                    .collect::<JsonMap>(),
+            )
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn same_output_for_any_key_order_and_case() {
+        let camel = json!({"userName": "Ada", "homeAddress": {"zipCode": "1000", "cityName": "Paris"}, "tagList": ["b", "a"]});
+        let snake = json!({"tag_list": ["b", "a"], "home_address": {"city_name": "Paris", "zip_code": "1000"}, "user_name": "Ada"});
+
+        let expected = "{\n  \"home_address\": {\n    \"city_name\": \"Paris\",\n    \"zip_code\": \"1000\"\n  },\n  \"tag_list\": [\n    \"b\",\n    \"a\"\n  ],\n  \"user_name\": \"Ada\"\n}";
+        assert_eq!(expected, normalize_for_snapshot(&camel, KeyCase::Snake));
+        assert_eq!(expected, normalize_for_snapshot(&snake, KeyCase::Snake));
+    }
+
+    #[test]
+    fn escapes_line_breaks() {
+        let value = json!({"noteText": "first\r\nsecond"});
+        let snapshot = normalize_for_snapshot(value, KeyCase::Snake);
+
+        assert_eq!("{\n  \"note_text\": \"first\\r\\nsecond\"\n}", snapshot);
+        assert!(!snapshot.contains('\r'));
+    }
+
+    #[test]
+    fn keeps_dictionary_keys() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_dictionary_keys(vec!["usersById"]);
+        let value = json!({"usersById": {"userB": {"displayName": "Bob"}, "userA": {"displayName": "Ada"}}});
+
+        assert_eq!(
+            "{\n  \"users_by_id\": {\n    \"userA\": {\n      \"display_name\": \"Ada\"\n    },\n    \"userB\": {\n      \"display_name\": \"Bob\"\n    }\n  }\n}",
+            normalize_for_snapshot_with(value, &converter)
+        );
+    }
+}
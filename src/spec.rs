@@ -0,0 +1,1021 @@
+use crate::prelude::*;
+#[cfg(feature = "normalization")]
+use crate::NormalizationForm;
+use crate::{
+    ChainPolicy, CollisionStrategy, Compatibility, DictionaryHeuristic, EmptyKeyPolicy, Error,
+    GuardPolicy, IdentifierStyle, KeyCase, KeyOrder, KeyScope, LengthPolicy, PathRule,
+    RenameBehavior, UndeclaredKeys,
+};
+use alloc::collections::BTreeMap;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Every option of a [`Converter`](crate::Converter), in a form that can be
+/// kept in a configuration file:
+///
+/// ```
+/// # use json_keys_case_changer::{ConversionSpec, Converter};
+/// let spec: ConversionSpec = serde_json::from_str(r#"{
+///     "case": "snake_case",
+///     "manual_renames": {"ID": "id"},
+///     "trim_keys": true
+/// }"#).unwrap();
+/// let converter = Converter::from_spec(spec).unwrap();
+/// ```
+///
+/// Every field is optional: `case` defaults to `snake_case`, and everything
+/// else to what [`Converter::new`](crate::Converter::new) starts with, as in
+/// [`ConversionSpec::default_base`]. Unknown fields are rejected. Cases are written by name, as parsed by
+/// [`KeyCase::from_str`](std::str::FromStr::from_str).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct ConversionSpec {
+    #[serde(default = "default_case")]
+    pub case: KeyCase,
+
+    #[serde(default)]
+    pub manual_renames: BTreeMap<String, String>,
+
+    #[serde(default)]
+    pub rename_behavior: RenameBehavior,
+
+    #[serde(default)]
+    pub chain_policy: ChainPolicy,
+
+    #[serde(default)]
+    pub case_renames: BTreeMap<KeyCase, BTreeMap<String, String>>,
+
+    #[serde(default)]
+    pub trim_keys: bool,
+
+    #[serde(default)]
+    pub empty_key_policy: EmptyKeyPolicy,
+
+    #[serde(default)]
+    pub prototype_pollution_guard: Option<GuardPolicy>,
+
+    #[serde(default)]
+    pub collision_strategy: CollisionStrategy,
+
+    #[serde(default)]
+    pub key_order: KeyOrder,
+
+    #[serde(default)]
+    pub identifier_style: Option<IdentifierStyle>,
+
+    #[serde(default)]
+    pub strip_prefixes: Vec<String>,
+
+    #[serde(default)]
+    pub strip_prefix_boundary: bool,
+
+    #[serde(default)]
+    pub max_key_length: Option<MaxKeyLengthSpec>,
+
+    #[serde(default)]
+    pub key_prefix: Option<AffixSpec>,
+
+    #[serde(default)]
+    pub key_suffix: Option<AffixSpec>,
+
+    #[serde(default)]
+    pub dictionary_keys: Vec<String>,
+
+    #[serde(default)]
+    pub dictionary_detection: Option<DictionaryHeuristic>,
+
+    #[serde(default)]
+    pub pair_array_keys: Vec<String>,
+
+    #[serde(default)]
+    pub embedded_json_fields: Vec<String>,
+
+    #[serde(default)]
+    pub skip_prefixes: Vec<String>,
+
+    #[serde(default)]
+    pub excluded_keys: Vec<String>,
+
+    #[serde(default)]
+    pub excluded_pointers: Vec<String>,
+
+    #[serde(default)]
+    pub path_rules: Vec<PathRuleSpec>,
+
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+
+    #[serde(default)]
+    pub case_rules: Vec<CaseRuleSpec>,
+
+    #[serde(default)]
+    pub depth_cases: BTreeMap<usize, KeyCase>,
+
+    #[serde(default)]
+    pub allowed_keys: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub schema_guide: Option<Value>,
+
+    #[serde(default)]
+    pub undeclared_keys: UndeclaredKeys,
+
+    #[serde(default)]
+    pub variant_renames: Vec<VariantRenamesSpec>,
+
+    #[serde(default)]
+    pub compatibility: Compatibility,
+
+    #[cfg(feature = "jsonpath")]
+    #[serde(default)]
+    pub include_jsonpaths: Vec<String>,
+
+    #[cfg(feature = "jsonpath")]
+    #[serde(default)]
+    pub exclude_jsonpaths: Vec<String>,
+
+    #[cfg(feature = "normalization")]
+    #[serde(default)]
+    pub unicode_normalization: Option<NormalizationForm>,
+}
+
+/// A key prefix or suffix, and the keys it applies to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AffixSpec {
+    pub text: String,
+    pub scope: KeyScope,
+}
+
+/// A subtree whose keys are converted, as set by
+/// [`Converter::with_path_rules`](crate::Converter::with_path_rules).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PathRuleSpec {
+    /// A JSON pointer or a dotted path, as taken by
+    /// [`PathRule::new`](crate::PathRule::new).
+    pub path: String,
+
+    #[serde(default)]
+    pub case: Option<KeyCase>,
+}
+
+/// The case of the keys at a path, as set by
+/// [`Converter::with_case_rule`](crate::Converter::with_case_rule).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CaseRuleSpec {
+    pub path: String,
+    pub case: KeyCase,
+}
+
+/// The longest new key allowed, in bytes, and what to do with longer ones.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaxKeyLengthSpec {
+    pub max: usize,
+    pub policy: LengthPolicy,
+}
+
+/// A problem with an option of a [`ConversionSpec`], as found by
+/// [`ConversionSpec::validate`]. It serializes to an object with a field
+/// for each of its own, for interfaces to show.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ConfigDiagnostic {
+    pub code: DiagnosticCode,
+
+    /// The option, as named in the spec. Renames of one case or variant are
+    /// named after it, as in `case_renames.kebab-case`.
+    pub field: String,
+
+    /// The offending value.
+    pub value: Value,
+
+    /// What is wrong with it.
+    pub message: String,
+
+    /// How to fix it.
+    pub suggestion: String,
+}
+
+/// What kind of problem a [`ConfigDiagnostic`] reports, serialized in
+/// snake case.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DiagnosticCode {
+    /// An empty prefix, which every key starts with.
+    EmptyPrefix,
+
+    /// A JSON pointer not starting with `/`.
+    InvalidPointer,
+
+    /// A maximum key length of zero.
+    ZeroMaxKeyLength,
+
+    /// Several keys of one table of renames renamed to the same new key.
+    DuplicateRenameTarget,
+
+    /// A key renamed to a key the same table renames again, under
+    /// [`ChainPolicy::Error`].
+    RenameChain,
+
+    /// Renames of one table that lead back to where they started.
+    RenameCycle,
+
+    /// A JSONPath selector that doesn't parse.
+    InvalidJsonPath,
+
+    /// A JSONPath selector both included and excluded.
+    OverlappingJsonPaths,
+
+    /// A path rule or case rule that is neither a JSON pointer nor a dotted
+    /// path.
+    InvalidPathRule,
+}
+
+impl fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid `{}`: {}", self.field, self.message)
+    }
+}
+
+/// Manual renames for the objects of one variant, as set by
+/// [`Converter::with_variant_renames`](crate::Converter::with_variant_renames).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VariantRenamesSpec {
+    pub discriminator_key: String,
+    pub variant: String,
+    pub manual_renames: BTreeMap<String, String>,
+}
+
+impl ConversionSpec {
+    /// A spec converting to `case` with every other option left at its
+    /// default.
+    pub fn new(case: KeyCase) -> Self {
+        ConversionSpec {
+            case,
+            manual_renames: BTreeMap::new(),
+            rename_behavior: RenameBehavior::default(),
+            chain_policy: ChainPolicy::default(),
+            case_renames: BTreeMap::new(),
+            trim_keys: false,
+            empty_key_policy: EmptyKeyPolicy::default(),
+            prototype_pollution_guard: None,
+            collision_strategy: CollisionStrategy::default(),
+            key_order: KeyOrder::default(),
+            identifier_style: None,
+            strip_prefixes: Vec::new(),
+            strip_prefix_boundary: false,
+            max_key_length: None,
+            key_prefix: None,
+            key_suffix: None,
+            dictionary_keys: Vec::new(),
+            dictionary_detection: None,
+            pair_array_keys: Vec::new(),
+            embedded_json_fields: Vec::new(),
+            skip_prefixes: Vec::new(),
+            excluded_keys: Vec::new(),
+            excluded_pointers: Vec::new(),
+            path_rules: Vec::new(),
+            max_depth: None,
+            case_rules: Vec::new(),
+            depth_cases: BTreeMap::new(),
+            allowed_keys: None,
+            schema_guide: None,
+            undeclared_keys: UndeclaredKeys::default(),
+            variant_renames: Vec::new(),
+            compatibility: Compatibility::default(),
+            #[cfg(feature = "jsonpath")]
+            include_jsonpaths: Vec::new(),
+            #[cfg(feature = "jsonpath")]
+            exclude_jsonpaths: Vec::new(),
+            #[cfg(feature = "normalization")]
+            unicode_normalization: None,
+        }
+    }
+
+    /// The spec with every option at its default, converting to snake case.
+    /// It is what [`ConversionSpec::merge`] compares overlays against.
+    pub fn default_base() -> Self {
+        ConversionSpec::new(default_case())
+    }
+
+    /// Layers `overlay` over `self`, such as an endpoint's options over a
+    /// service's:
+    ///
+    /// - Manual renames, case renames and variant renames are unioned; the
+    ///   overlay wins on keys present in both.
+    /// - Prefix lists, dictionary keys, pair array keys, excluded pointers,
+    ///   allowed keys and JSONPath selectors are unioned, keeping the order
+    ///   of `self` then `overlay`. Allowed keys set on either side stay set.
+    /// - Every other option is taken from the overlay when it differs from
+    ///   [`ConversionSpec::default_base`], and from `self` otherwise. An
+    ///   overlay can't set an option back to its default.
+    pub fn merge(mut self, overlay: ConversionSpec) -> ConversionSpec {
+        let base = ConversionSpec::default_base();
+        let ConversionSpec {
+            case,
+            manual_renames,
+            rename_behavior,
+            chain_policy,
+            case_renames,
+            trim_keys,
+            empty_key_policy,
+            prototype_pollution_guard,
+            collision_strategy,
+            key_order,
+            identifier_style,
+            strip_prefixes,
+            strip_prefix_boundary,
+            max_key_length,
+            key_prefix,
+            key_suffix,
+            dictionary_keys,
+            dictionary_detection,
+            pair_array_keys,
+            embedded_json_fields,
+            skip_prefixes,
+            excluded_keys,
+            excluded_pointers,
+            path_rules,
+            max_depth,
+            case_rules,
+            depth_cases,
+            allowed_keys,
+            schema_guide,
+            undeclared_keys,
+            variant_renames,
+            compatibility,
+            #[cfg(feature = "jsonpath")]
+            include_jsonpaths,
+            #[cfg(feature = "jsonpath")]
+            exclude_jsonpaths,
+            #[cfg(feature = "normalization")]
+            unicode_normalization,
+        } = overlay;
+
+        fn set<T: PartialEq>(target: &mut T, value: T, default: T) {
+            if value != default {
+                *target = value;
+            }
+        }
+        fn union<T: PartialEq>(target: &mut Vec<T>, values: Vec<T>) {
+            for value in values {
+                if !target.contains(&value) {
+                    target.push(value);
+                }
+            }
+        }
+
+        set(&mut self.case, case, base.case);
+        self.manual_renames.extend(manual_renames);
+        set(
+            &mut self.rename_behavior,
+            rename_behavior,
+            base.rename_behavior,
+        );
+        set(&mut self.chain_policy, chain_policy, base.chain_policy);
+        for (case, renames) in case_renames {
+            self.case_renames.entry(case).or_default().extend(renames);
+        }
+        set(&mut self.trim_keys, trim_keys, base.trim_keys);
+        set(
+            &mut self.empty_key_policy,
+            empty_key_policy,
+            base.empty_key_policy,
+        );
+        set(
+            &mut self.prototype_pollution_guard,
+            prototype_pollution_guard,
+            base.prototype_pollution_guard,
+        );
+        set(
+            &mut self.collision_strategy,
+            collision_strategy,
+            base.collision_strategy,
+        );
+        set(&mut self.key_order, key_order, base.key_order);
+        set(
+            &mut self.identifier_style,
+            identifier_style,
+            base.identifier_style,
+        );
+        union(&mut self.strip_prefixes, strip_prefixes);
+        set(
+            &mut self.strip_prefix_boundary,
+            strip_prefix_boundary,
+            base.strip_prefix_boundary,
+        );
+        set(
+            &mut self.max_key_length,
+            max_key_length,
+            base.max_key_length,
+        );
+        set(&mut self.key_prefix, key_prefix, base.key_prefix);
+        set(&mut self.key_suffix, key_suffix, base.key_suffix);
+        union(&mut self.dictionary_keys, dictionary_keys);
+        set(
+            &mut self.dictionary_detection,
+            dictionary_detection,
+            base.dictionary_detection,
+        );
+        union(&mut self.pair_array_keys, pair_array_keys);
+        union(&mut self.embedded_json_fields, embedded_json_fields);
+        union(&mut self.skip_prefixes, skip_prefixes);
+        union(&mut self.excluded_keys, excluded_keys);
+        union(&mut self.excluded_pointers, excluded_pointers);
+        union(&mut self.path_rules, path_rules);
+        set(&mut self.max_depth, max_depth, base.max_depth);
+        union(&mut self.case_rules, case_rules);
+        self.depth_cases.extend(depth_cases);
+        if let Some(allowed_keys) = allowed_keys {
+            union(self.allowed_keys.get_or_insert_with(Vec::new), allowed_keys);
+        }
+        set(&mut self.schema_guide, schema_guide, base.schema_guide);
+        set(
+            &mut self.undeclared_keys,
+            undeclared_keys,
+            base.undeclared_keys,
+        );
+        for overlay in variant_renames {
+            match self.variant_renames.iter_mut().find(|v| {
+                v.discriminator_key == overlay.discriminator_key && v.variant == overlay.variant
+            }) {
+                Some(v) => v.manual_renames.extend(overlay.manual_renames),
+                None => self.variant_renames.push(overlay),
+            }
+        }
+        set(&mut self.compatibility, compatibility, base.compatibility);
+        #[cfg(feature = "jsonpath")]
+        {
+            union(&mut self.include_jsonpaths, include_jsonpaths);
+            union(&mut self.exclude_jsonpaths, exclude_jsonpaths);
+        }
+        #[cfg(feature = "normalization")]
+        set(
+            &mut self.unicode_normalization,
+            unicode_normalization,
+            base.unicode_normalization,
+        );
+        self
+    }
+
+    /// Checks the options that serde can't, such as the syntax of pointers,
+    /// and reports every problem found rather than only the first one.
+    /// [`Converter::from_spec`](crate::Converter::from_spec) calls it, and
+    /// fails with [`Error::InvalidConfig`](crate::Error::InvalidConfig) holding the same list.
+    pub fn validate(&self) -> Result<(), Vec<ConfigDiagnostic>> {
+        let mut diagnostics = Vec::new();
+
+        let prefixes = [
+            ("strip_prefixes", &self.strip_prefixes),
+            ("skip_prefixes", &self.skip_prefixes),
+        ];
+        for (field, prefixes) in prefixes.iter() {
+            if prefixes.iter().any(String::is_empty) {
+                diagnostics.push(ConfigDiagnostic {
+                    code: DiagnosticCode::EmptyPrefix,
+                    field: (*field).to_owned(),
+                    value: Value::from(""),
+                    message: "prefixes can't be empty".to_owned(),
+                    suggestion: "remove the empty prefix".to_owned(),
+                });
+            }
+        }
+
+        let affixes = [
+            ("key_prefix", &self.key_prefix),
+            ("key_suffix", &self.key_suffix),
+        ];
+        for (field, affix) in affixes.iter() {
+            if let Some(AffixSpec {
+                scope: KeyScope::Pointer(pointer),
+                ..
+            }) = affix
+            {
+                check_pointer(&mut diagnostics, field, pointer);
+            }
+        }
+        for pointer in &self.excluded_pointers {
+            check_pointer(&mut diagnostics, "excluded_pointers", pointer);
+        }
+
+        for rule in &self.path_rules {
+            check_path(&mut diagnostics, "path_rules", &rule.path);
+        }
+        for rule in &self.case_rules {
+            check_path(&mut diagnostics, "case_rules", &rule.path);
+        }
+
+        if let Some(MaxKeyLengthSpec { max: 0, .. }) = self.max_key_length {
+            diagnostics.push(ConfigDiagnostic {
+                code: DiagnosticCode::ZeroMaxKeyLength,
+                field: "max_key_length".to_owned(),
+                value: Value::from(0),
+                message: "would leave every new key empty".to_owned(),
+                suggestion: "allow at least one byte, or remove the option".to_owned(),
+            });
+        }
+
+        let mut tables = vec![("manual_renames".to_owned(), &self.manual_renames)];
+        for (case, renames) in &self.case_renames {
+            tables.push((format!("case_renames.{}", case), renames));
+        }
+        for variant in &self.variant_renames {
+            let field = format!(
+                "variant_renames.{}={}",
+                variant.discriminator_key, variant.variant
+            );
+            tables.push((field, &variant.manual_renames));
+        }
+        for (field, renames) in tables {
+            check_rename_targets(&mut diagnostics, &field, renames);
+            check_rename_chains(
+                &mut diagnostics,
+                &field,
+                renames,
+                self.rename_behavior,
+                self.chain_policy,
+            );
+        }
+
+        #[cfg(feature = "jsonpath")]
+        {
+            let selectors = [
+                ("include_jsonpaths", &self.include_jsonpaths),
+                ("exclude_jsonpaths", &self.exclude_jsonpaths),
+            ];
+            for (field, exprs) in selectors.iter() {
+                for expr in exprs.iter() {
+                    if let Err(err) = serde_json_path::JsonPath::parse(expr) {
+                        diagnostics.push(ConfigDiagnostic {
+                            code: DiagnosticCode::InvalidJsonPath,
+                            field: (*field).to_owned(),
+                            value: Value::from(expr.as_str()),
+                            message: format!("is not a JSONPath expression: {}", err),
+                            suggestion:
+                                "write the expression as RFC 9535 describes, such as `$..metadata`"
+                                    .to_owned(),
+                        });
+                    }
+                }
+            }
+            for expr in &self.include_jsonpaths {
+                if self.exclude_jsonpaths.contains(expr) {
+                    diagnostics.push(ConfigDiagnostic {
+                        code: DiagnosticCode::OverlappingJsonPaths,
+                        field: "exclude_jsonpaths".to_owned(),
+                        value: Value::from(expr.as_str()),
+                        message: "is also an include selector, so it includes nothing".to_owned(),
+                        suggestion: "keep the expression in only one of the two lists".to_owned(),
+                    });
+                }
+            }
+        }
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(diagnostics)
+        }
+    }
+}
+
+fn default_case() -> KeyCase {
+    KeyCase::Snake
+}
+
+fn check_pointer(diagnostics: &mut Vec<ConfigDiagnostic>, field: &str, pointer: &str) {
+    if !pointer.is_empty() && !pointer.starts_with('/') {
+        diagnostics.push(ConfigDiagnostic {
+            code: DiagnosticCode::InvalidPointer,
+            field: field.to_owned(),
+            value: Value::from(pointer),
+            message: format!("`{}` is not a JSON pointer", pointer),
+            suggestion: format!("start the pointer with `/`, as in `/{}`", pointer),
+        });
+    }
+}
+
+fn check_path(diagnostics: &mut Vec<ConfigDiagnostic>, field: &str, path: &str) {
+    if let Err(Error::InvalidOption { message, .. }) = PathRule::new(path) {
+        diagnostics.push(ConfigDiagnostic {
+            code: DiagnosticCode::InvalidPathRule,
+            field: field.to_owned(),
+            value: Value::from(path),
+            message,
+            suggestion:
+                "write a JSON pointer such as `/data/*/attrs`, or a dotted path such as `data[*].attrs`"
+                    .to_owned(),
+        });
+    }
+}
+
+/// Reports the new keys that several keys of `renames` are renamed to, as
+/// only one of them can come out of an object.
+fn check_rename_targets(
+    diagnostics: &mut Vec<ConfigDiagnostic>,
+    field: &str,
+    renames: &BTreeMap<String, String>,
+) {
+    let mut by_target: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (from, to) in renames {
+        by_target.entry(to).or_default().push(from);
+    }
+    for (to, froms) in by_target {
+        if froms.len() > 1 {
+            diagnostics.push(ConfigDiagnostic {
+                code: DiagnosticCode::DuplicateRenameTarget,
+                field: field.to_owned(),
+                value: Value::from(to),
+                message: format!("`{}` are all renamed to `{}`", froms.join("`, `"), to),
+                suggestion: "rename each key to a different name".to_owned(),
+            });
+        }
+    }
+}
+
+/// Reports the keys of `renames` that the chain policy won't rename: every
+/// key starting a chain under [`ChainPolicy::Error`], and every cycle, once,
+/// unless renames aren't chained.
+fn check_rename_chains(
+    diagnostics: &mut Vec<ConfigDiagnostic>,
+    field: &str,
+    renames: &BTreeMap<String, String>,
+    rename_behavior: RenameBehavior,
+    chain_policy: ChainPolicy,
+) {
+    if chain_policy == ChainPolicy::Single {
+        return;
+    }
+    let lookup: BTreeMap<&str, &str> = match rename_behavior {
+        RenameBehavior::ByKey => renames
+            .iter()
+            .map(|(from, to)| (from.as_str(), to.as_str()))
+            .collect(),
+        RenameBehavior::ByValue => renames
+            .iter()
+            .map(|(from, to)| (to.as_str(), from.as_str()))
+            .collect(),
+    };
+    for &start in lookup.keys() {
+        let mut chain = vec![start];
+        let mut current = start;
+        let mut cycle = false;
+        while let Some(&next) = lookup.get(current).filter(|&&next| next != current) {
+            cycle = chain.contains(&next);
+            chain.push(next);
+            if cycle {
+                break;
+            }
+            current = next;
+        }
+        let written = chain.join("` -> `");
+        if cycle && chain.last() == Some(&start) {
+            // Reported from the first of its keys.
+            if chain.iter().all(|&key| start <= key) {
+                diagnostics.push(ConfigDiagnostic {
+                    code: DiagnosticCode::RenameCycle,
+                    field: field.to_owned(),
+                    value: Value::from(start),
+                    message: format!("renames form a cycle: `{}`", written),
+                    suggestion: "set `chain_policy` to `single` to swap the keys".to_owned(),
+                });
+            }
+        } else if chain.len() > 2 && chain_policy == ChainPolicy::Error {
+            diagnostics.push(ConfigDiagnostic {
+                code: DiagnosticCode::RenameChain,
+                field: field.to_owned(),
+                value: Value::from(start),
+                message: format!("renames form a chain: `{}`", written),
+                suggestion:
+                    "set `chain_policy` to `single` or `resolve`, or rename the key to the end of the chain"
+                        .to_owned(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    const SPEC: &str = r#"{
+        "case": "camelCase",
+        "manual_renames": {"ID": "identifier"},
+        "rename_behavior": "by_key",
+        "case_renames": {"kebab-case": {"ID": "the-id"}},
+        "trim_keys": true,
+        "empty_key_policy": "drop",
+        "prototype_pollution_guard": "prefix",
+        "collision_strategy": "keep_first",
+        "key_order": "sort_alphabetically",
+        "identifier_style": "javascript",
+        "strip_prefixes": ["str"],
+        "strip_prefix_boundary": true,
+        "max_key_length": {"max": 24, "policy": "truncate_with_hash"},
+        "key_prefix": {"text": "x", "scope": {"pointer": "/meta"}},
+        "key_suffix": {"text": "_", "scope": "top_level"},
+        "dictionary_keys": ["by_sku"],
+        "dictionary_detection": {"threshold": 0.5, "detectors": ["uuid", {"longer_than": 40}]},
+        "pair_array_keys": ["attributes"],
+        "embedded_json_fields": ["payload"],
+        "skip_prefixes": ["$"],
+        "excluded_keys": ["en_US"],
+        "excluded_pointers": ["/meta/*/Raw"],
+        "path_rules": [{"path": "", "case": null}],
+        "max_depth": 8,
+        "case_rules": [{"path": "/meta/*/Raw", "case": "SCREAMING_SNAKE_CASE"}],
+        "depth_cases": {"7": "kebab-case"},
+        "schema_guide": {"properties": {"by_sku": {"additionalProperties": {}}}},
+        "undeclared_keys": "convert",
+        "variant_renames": [
+            {"discriminator_key": "type", "variant": "user", "manual_renames": {"ID": "userId"}}
+        ]
+    }"#;
+
+    fn document() -> serde_json::Value {
+        json!({
+            " ID ": 1,
+            "": 2,
+            "strUser_name": "Ada",
+            "string": "s",
+            "meta": {"class": true, "tags": {"Raw": 0}},
+            "$Ref": "r",
+            "by_sku": {"SKU_1": {"unit_count": 3}},
+            "attributes": [["first_name", "Ada"]],
+            "owner": {"type": "user", "ID": 7}
+        })
+    }
+
+    #[test]
+    fn round_trip_and_convert() {
+        let spec: ConversionSpec = serde_json::from_str(SPEC).unwrap();
+        let text = serde_json::to_string(&spec).unwrap();
+        assert_eq!(spec, serde_json::from_str(&text).unwrap());
+
+        let from_spec = Converter::from_spec(spec.clone()).unwrap();
+        assert_eq!(spec, from_spec.to_spec());
+
+        let renames: RenameMap = [("ID", "identifier")].iter().cloned().collect();
+        let kebab_renames: RenameMap = [("ID", "the-id")].iter().cloned().collect();
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_manual_renames(renames);
+        converter.with_case_renames(KeyCase::Kebab, kebab_renames);
+        converter.with_trim_keys(true);
+        converter.with_empty_key_policy(EmptyKeyPolicy::Drop);
+        converter.with_prototype_pollution_guard(GuardPolicy::Prefix);
+        converter.with_collision_strategy(CollisionStrategy::KeepFirst);
+        converter.with_key_order(KeyOrder::SortAlphabetically);
+        converter.with_safe_identifiers(IdentifierStyle::JavaScript);
+        converter.with_strip_prefixes(vec!["str"]);
+        converter.with_strip_prefix_boundary(true);
+        converter.with_max_key_length(24, LengthPolicy::TruncateWithHash);
+        converter.with_key_prefix("x", KeyScope::Pointer("/meta".to_owned()));
+        converter.with_key_suffix("_", KeyScope::TopLevel);
+        converter.with_dictionary_keys(vec!["by_sku"]);
+        converter.with_dictionary_detection(DictionaryHeuristic {
+            threshold: 0.5,
+            detectors: vec![KeyDetector::Uuid, KeyDetector::LongerThan(40)],
+        });
+        converter.with_pair_array_keys(vec!["attributes"]);
+        converter.with_embedded_json_fields(vec!["payload"]);
+        converter.with_skip_prefixes(vec!["$"]);
+        converter.with_excluded_keys(vec!["en_US"]);
+        converter.with_excluded_pointers(vec!["/meta/*/Raw"]);
+        converter.with_path_rules(vec![PathRule::new("").unwrap()]);
+        converter.with_max_depth(8);
+        converter
+            .with_case_rule("/meta/*/Raw", KeyCase::ScreamingSnake)
+            .unwrap();
+        converter.with_depth_case(7, KeyCase::Kebab);
+        converter.with_schema_guide(&json!({
+            "properties": {"by_sku": {"additionalProperties": {}}}
+        }));
+        converter.with_undeclared_keys(UndeclaredKeys::Convert);
+        let user_renames: RenameMap = [("ID", "userId")].iter().cloned().collect();
+        converter.with_variant_renames("type", "user", user_renames);
+        assert_eq!(spec, converter.to_spec());
+
+        let expected = converter.convert_value(document()).unwrap();
+        assert_eq!(expected, from_spec.convert_value(document()).unwrap());
+        assert_eq!(
+            json!({
+                "identifier_": 1,
+                "userName_": "Ada",
+                "string_": "s",
+                "xmeta_": {"class_": true, "tags": {"Raw": 0}},
+                "$Ref": "r",
+                "bySku_": {"SKU_1": {"unitCount": 3}},
+                "attributes_": [["firstName", "Ada"]],
+                "owner_": {"type": "user", "userId": 7}
+            }),
+            expected
+        );
+    }
+
+    #[test]
+    fn merges_layers() {
+        let organization: ConversionSpec = serde_json::from_str(
+            r#"{
+                "case": "camelCase",
+                "manual_renames": {"ID": "id", "URL": "url"},
+                "skip_prefixes": ["$"]
+            }"#,
+        )
+        .unwrap();
+        let service: ConversionSpec = serde_json::from_str(
+            r#"{
+                "trim_keys": true,
+                "skip_prefixes": ["$", "@"],
+                "excluded_pointers": ["/meta/*"]
+            }"#,
+        )
+        .unwrap();
+        let endpoint: ConversionSpec =
+            serde_json::from_str(r#"{"manual_renames": {"ID": "orderId"}}"#).unwrap();
+
+        let merged = ConversionSpec::default_base()
+            .merge(organization)
+            .merge(service)
+            .merge(endpoint);
+        assert_eq!(KeyCase::Camel, merged.case);
+        assert_eq!(vec!["$", "@"], merged.skip_prefixes);
+
+        let converter = Converter::from_spec(merged).unwrap();
+        assert_eq!(
+            json!({
+                "orderId": 1,
+                "url": "u",
+                "userName": "Ada",
+                "$Ref": "r",
+                "@Type": "t",
+                "meta": {"Raw_Name": 0}
+            }),
+            converter
+                .convert_value(json!({
+                    " ID ": 1,
+                    "URL": "u",
+                    "user_name": "Ada",
+                    "$Ref": "r",
+                    "@Type": "t",
+                    "meta": {"Raw_Name": 0}
+                }))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let err = serde_json::from_str::<ConversionSpec>(r#"{"case": "snake", "trim": true}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown field `trim`"));
+
+        let err = serde_json::from_str::<ConversionSpec>(r#"{"case": "snek"}"#).unwrap_err();
+        assert!(err.to_string().contains("did you mean `snake_case`?"));
+    }
+
+    #[test]
+    fn rejects_invalid_pointer() {
+        let mut spec = ConversionSpec::new(KeyCase::Snake);
+        spec.key_prefix = Some(AffixSpec {
+            text: "x".to_owned(),
+            scope: KeyScope::Pointer("meta".to_owned()),
+        });
+
+        match Converter::from_spec(spec) {
+            Err(err @ Error::InvalidConfig { .. }) => assert_eq!(
+                "invalid configuration: invalid `key_prefix`: `meta` is not a JSON pointer",
+                err.to_string()
+            ),
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("invalid spec accepted"),
+        }
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let spec: ConversionSpec = serde_json::from_str(
+            r#"{
+                "manual_renames": {"ID": "id", "Id": "id", "name": "title"},
+                "skip_prefixes": ["$", ""],
+                "excluded_pointers": ["/meta", "meta/raw"],
+                "max_key_length": {"max": 0, "policy": "truncate"}
+            }"#,
+        )
+        .unwrap();
+
+        let diagnostics = spec.validate().unwrap_err();
+        assert_eq!(
+            json!([
+                {
+                    "code": "empty_prefix",
+                    "field": "skip_prefixes",
+                    "value": "",
+                    "message": "prefixes can't be empty",
+                    "suggestion": "remove the empty prefix"
+                },
+                {
+                    "code": "invalid_pointer",
+                    "field": "excluded_pointers",
+                    "value": "meta/raw",
+                    "message": "`meta/raw` is not a JSON pointer",
+                    "suggestion": "start the pointer with `/`, as in `/meta/raw`"
+                },
+                {
+                    "code": "zero_max_key_length",
+                    "field": "max_key_length",
+                    "value": 0,
+                    "message": "would leave every new key empty",
+                    "suggestion": "allow at least one byte, or remove the option"
+                },
+                {
+                    "code": "duplicate_rename_target",
+                    "field": "manual_renames",
+                    "value": "id",
+                    "message": "`ID`, `Id` are all renamed to `id`",
+                    "suggestion": "rename each key to a different name"
+                }
+            ]),
+            serde_json::to_value(&diagnostics).unwrap()
+        );
+
+        match Converter::from_spec(spec) {
+            Err(Error::InvalidConfig { diagnostics: errs }) => assert_eq!(diagnostics, errs),
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("invalid spec accepted"),
+        }
+    }
+
+    #[test]
+    fn reports_rename_chains() {
+        let mut spec = ConversionSpec::new(KeyCase::Snake);
+        spec.manual_renames = [("a", "b"), ("b", "c"), ("x", "y"), ("y", "x")]
+            .iter()
+            .map(|&(from, to)| (from.to_owned(), to.to_owned()))
+            .collect();
+        let problems = |spec: &ConversionSpec| -> Vec<(DiagnosticCode, String)> {
+            spec.validate()
+                .err()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|diagnostic| (diagnostic.code, diagnostic.message))
+                .collect()
+        };
+
+        assert_eq!(
+            vec![
+                (
+                    DiagnosticCode::RenameChain,
+                    "renames form a chain: `a` -> `b` -> `c`".to_owned()
+                ),
+                (
+                    DiagnosticCode::RenameCycle,
+                    "renames form a cycle: `x` -> `y` -> `x`".to_owned()
+                ),
+            ],
+            problems(&spec)
+        );
+
+        spec.chain_policy = ChainPolicy::Resolve;
+        assert_eq!(
+            vec![(
+                DiagnosticCode::RenameCycle,
+                "renames form a cycle: `x` -> `y` -> `x`".to_owned()
+            )],
+            problems(&spec)
+        );
+
+        spec.chain_policy = ChainPolicy::Single;
+        assert!(problems(&spec).is_empty());
+        assert_eq!(spec, Converter::from_spec(spec.clone()).unwrap().to_spec());
+    }
+
+    #[cfg(feature = "jsonpath")]
+    #[test]
+    fn reports_jsonpath_problems() {
+        let mut spec = ConversionSpec::new(KeyCase::Snake);
+        spec.include_jsonpaths = vec!["$.orders".to_owned(), "$..[".to_owned()];
+        spec.exclude_jsonpaths = vec!["$.orders".to_owned()];
+
+        let codes: Vec<_> = spec
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect();
+        assert_eq!(
+            vec![
+                DiagnosticCode::InvalidJsonPath,
+                DiagnosticCode::OverlappingJsonPaths
+            ],
+            codes
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! A wrapper storing values in JSON columns with the keys of the database,
+//! for structs that follow another naming convention.
+//!
+//! The column is treated as the wire: values read from it go through the
+//! `from_wire` converter of a [`WireConverters`], and values written to it
+//! through `to_wire`. Where the converters come from is the
+//! [`ConverterSource`] type parameter, the process-wide ones by default.
+
+use crate::typed::deserialize_value;
+use crate::{Error, WireConverters};
+use ::sqlx::encode::IsNull;
+use ::sqlx::error::BoxDynError;
+use ::sqlx::types::Json;
+use ::sqlx::{Database, Decode, Encode, Type};
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Where [`CaseNormalized`] finds its converters.
+///
+/// Implement it to configure them per thread, or per column type:
+///
+/// ```
+/// use json_keys_case_changer::sqlx::ConverterSource;
+/// use json_keys_case_changer::{Converter, Error, KeyCase, WireConverters};
+///
+/// thread_local! {
+///     static CONVERTERS: WireConverters = WireConverters {
+///         to_wire: Converter::new(KeyCase::Camel),
+///         from_wire: Converter::new(KeyCase::Snake),
+///     };
+/// }
+///
+/// struct Local;
+///
+/// impl ConverterSource for Local {
+///     fn with_converters<R>(f: impl FnOnce(&WireConverters) -> R) -> Result<R, Error> {
+///         Ok(CONVERTERS.with(f))
+///     }
+/// }
+/// ```
+pub trait ConverterSource {
+    /// Runs `f` with the converters, failing with [`Error::NotSet`] if there
+    /// are none.
+    fn with_converters<R>(f: impl FnOnce(&WireConverters) -> R) -> Result<R, Error>;
+}
+
+/// The process-wide converters, set with [`set_global`](crate::set_global).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+impl ConverterSource for Global {
+    fn with_converters<R>(f: impl FnOnce(&WireConverters) -> R) -> Result<R, Error> {
+        crate::global().map(f).ok_or(Error::NotSet)
+    }
+}
+
+/// A `T` stored as JSON, whose keys are converted on the way in and out of
+/// the database, for any database whose `Json` type `sqlx` supports. On
+/// Postgres, it is written as `jsonb`.
+pub struct CaseNormalized<T, S = Global> {
+    inner: T,
+    source: PhantomData<fn() -> S>,
+}
+
+impl<T, S> CaseNormalized<T, S> {
+    pub fn new(inner: T) -> Self {
+        CaseNormalized {
+            inner,
+            source: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T, S> From<T> for CaseNormalized<T, S> {
+    fn from(inner: T) -> Self {
+        CaseNormalized::new(inner)
+    }
+}
+
+impl<T, S> Deref for CaseNormalized<T, S> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T, S> DerefMut for CaseNormalized<T, S> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: fmt::Debug, S> fmt::Debug for CaseNormalized<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CaseNormalized").field(&self.inner).finish()
+    }
+}
+
+impl<T: Clone, S> Clone for CaseNormalized<T, S> {
+    fn clone(&self) -> Self {
+        CaseNormalized::new(self.inner.clone())
+    }
+}
+
+impl<T: PartialEq, S> PartialEq for CaseNormalized<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T, S, DB> Type<DB> for CaseNormalized<T, S>
+where
+    DB: Database,
+    Json<Value>: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <Json<Value> as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <Json<Value> as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'r, T, S, DB> Decode<'r, DB> for CaseNormalized<T, S>
+where
+    T: DeserializeOwned,
+    S: ConverterSource,
+    DB: Database,
+    Json<Value>: Decode<'r, DB>,
+{
+    fn decode(value: DB::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        let Json(value) = <Json<Value> as Decode<'r, DB>>::decode(value)?;
+        let (converted, dropped) =
+            S::with_converters(|converters| converters.from_wire.convert_value_with_drops(value))??;
+        Ok(CaseNormalized::new(deserialize_value(converted, dropped)?))
+    }
+}
+
+impl<'q, T, S, DB> Encode<'q, DB> for CaseNormalized<T, S>
+where
+    T: Serialize,
+    S: ConverterSource,
+    DB: Database,
+    Json<Value>: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut DB::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+        let value = serde_json::to_value(&self.inner).map_err(Error::Serialize)?;
+        let converted = S::with_converters(|converters| converters.to_wire.convert_value(value))??;
+        Json(converted).encode(buf)
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use super::{CaseNormalized, ConverterSource};
+    use crate::*;
+    use ::sqlx::{Connection, Row, SqliteConnection};
+    use serde::{Deserialize, Serialize};
+
+    thread_local! {
+        static CONVERTERS: WireConverters = WireConverters {
+            to_wire: Converter::new(KeyCase::Camel),
+            from_wire: Converter::new(KeyCase::Snake),
+        };
+    }
+
+    struct Local;
+
+    impl ConverterSource for Local {
+        fn with_converters<R>(f: impl FnOnce(&WireConverters) -> R) -> Result<R, Error> {
+            Ok(CONVERTERS.with(f))
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Profile {
+        display_name: String,
+        home_address: Address,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Address {
+        zip_code: String,
+    }
+
+    async fn connection() -> SqliteConnection {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        ::sqlx::query("CREATE TABLE users (profile TEXT NOT NULL)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        conn
+    }
+
+    #[tokio::test]
+    async fn writes_wire_keys() {
+        let mut conn = connection().await;
+        let profile = Profile {
+            display_name: "Ada".to_owned(),
+            home_address: Address {
+                zip_code: "1000".to_owned(),
+            },
+        };
+
+        ::sqlx::query("INSERT INTO users (profile) VALUES (?)")
+            .bind(CaseNormalized::<_, Local>::new(profile.clone()))
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let row = ::sqlx::query("SELECT profile FROM users")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        assert_eq!(
+            r#"{"displayName":"Ada","homeAddress":{"zipCode":"1000"}}"#,
+            row.get::<String, _>(0)
+        );
+        let read: CaseNormalized<Profile, Local> = row.get(0);
+        assert_eq!(profile, read.into_inner());
+    }
+
+    #[tokio::test]
+    async fn reports_mismatched_documents() {
+        let mut conn = connection().await;
+        ::sqlx::query(r#"INSERT INTO users (profile) VALUES ('{"displayName": "Ada"}')"#)
+            .execute(&mut conn)
+            .await
+            .unwrap();
+
+        let row = ::sqlx::query("SELECT profile FROM users")
+            .fetch_one(&mut conn)
+            .await
+            .unwrap();
+        let err = row
+            .try_get::<CaseNormalized<Profile, Local>, _>(0)
+            .unwrap_err();
+        assert!(err.to_string().contains("missing field `home_address`"));
+
+        // The process-wide converters are never set in these tests.
+        assert!(row.try_get::<CaseNormalized<Profile>, _>(0).is_err());
+    }
+}
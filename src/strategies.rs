@@ -0,0 +1,77 @@
+use crate::fast_case::to_case;
+use crate::{JsonMap, KeyCase};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use serde_json::Value;
+
+/// Words keys are made of: common field names, acronyms, numbers and single
+/// letters, which case conversions find the hardest.
+const WORDS: &[&str] = &[
+    "user", "name", "id", "created", "at", "order", "item", "total", "price", "address", "zip",
+    "code", "is", "active", "HTTP", "URL", "API", "IO", "UUID", "v2", "2", "10", "a", "x",
+];
+
+/// Words of a single character, the only ones that `convert_case` reads
+/// back from toggle and alternating case keys.
+const LETTERS: &[&str] = &["a", "b", "x", "y", "2", "7"];
+
+/// A [`proptest`] strategy for keys in `case`: one to four words taken from
+/// field names, acronyms, numbers and single letters, converted to `case`.
+///
+/// Every key is one that converting to `case` keeps as it is. Keys whose
+/// words `convert_case` would split differently, such as `aBC` in camel
+/// case, are never generated; toggle and alternating case keys are only made
+/// of one-character words, since `convert_case` reads longer words back in
+/// pieces.
+pub fn arb_key_in_case(case: KeyCase) -> impl Strategy<Value = String> {
+    let words = match case {
+        KeyCase::Toggle | KeyCase::Alternating => LETTERS,
+        _ => WORDS,
+    };
+    vec(proptest::sample::select(words), 1..=4)
+        .prop_map(move |words| to_case(&words.join(" "), case))
+        .prop_filter("not kept by converting to its case", move |key| {
+            to_case(key, case) == *key
+        })
+}
+
+/// A [`proptest`] strategy for JSON documents nested up to `depth` levels,
+/// whose objects only have keys generated by [`arb_key_in_case`].
+pub fn arb_json_with_case(case: KeyCase, depth: usize) -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<i64>().prop_map(Value::from),
+        "[a-zA-Z0-9 ]{0,8}".prop_map(Value::String),
+    ];
+    leaf.prop_recursive(depth as u32, 64, 6, move |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..6).prop_map(Value::Array),
+            vec((arb_key_in_case(case), inner), 0..6)
+                .prop_map(|entries| Value::Object(entries.into_iter().collect::<JsonMap>())),
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn converting_to_the_same_case_is_a_fixed_point(
+            (case, value) in proptest::sample::select(KeyCase::all())
+                .prop_flat_map(|case| (Just(case), arb_json_with_case(case, 4)))
+        ) {
+            let converted = Converter::new(case).convert_value(value.clone()).unwrap();
+            prop_assert_eq!(value, converted);
+        }
+
+        #[test]
+        fn keys_are_in_case(key in arb_key_in_case(KeyCase::Snake)) {
+            prop_assert!(!key.is_empty());
+            prop_assert!(!key.contains(|c: char| c.is_uppercase() || c == ' '));
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{CaseChanger, Converter, Error, JsonMap, KeyCase};
+use core::fmt;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+
+/// Which keys [`Converter::parse_strict`] counts as duplicates.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateCheck {
+    /// Keys written more than once in the same object.
+    #[default]
+    Exact,
+
+    /// Also keys of the same object that the converter would give the same
+    /// new key.
+    AfterConversion,
+}
+
+/// A key found more than once in an object of a JSON text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateKey {
+    /// JSON pointer to the later entry, with its original key.
+    pub path: String,
+
+    /// The key of the later entry.
+    pub key: String,
+
+    /// The key of the earlier entry, which is `key` itself for an exact
+    /// duplicate.
+    pub earlier: String,
+}
+
+impl<'a> CaseChanger<'a> {
+    /// Builds a changer over the JSON text `input`, failing with
+    /// [`Error::DuplicateKey`] if an object has a key twice, or two keys
+    /// converted to the same `new_case` key, instead of keeping the last one.
+    ///
+    /// Collisions are checked with a plain conversion to `new_case`: options
+    /// set on the changer afterwards are not taken into account.
+    pub fn from_str_strict(input: &str, new_case: KeyCase) -> Result<Self, Error> {
+        let converter = Converter::new(new_case);
+        let json_in = converter.parse_strict(input, DuplicateCheck::AfterConversion)?;
+        Ok(Self { json_in, converter })
+    }
+}
+
+impl Converter<'_> {
+    /// Parses the JSON text `input`, failing with [`Error::DuplicateKey`] at
+    /// the first duplicate key, where `serde_json` would silently keep the
+    /// last entry.
+    pub fn parse_strict(&self, input: &str, check: DuplicateCheck) -> Result<Value, Error> {
+        let (value, duplicates) = self.parse_checked(input, check, true)?;
+        debug_assert!(duplicates.is_empty());
+        Ok(value)
+    }
+
+    /// Like [`Converter::parse_strict`], but keeps the last entry of
+    /// duplicate keys, as `serde_json` does, and returns every duplicate
+    /// found alongside the document.
+    pub fn parse_lenient(
+        &self,
+        input: &str,
+        check: DuplicateCheck,
+    ) -> Result<(Value, Vec<DuplicateKey>), Error> {
+        self.parse_checked(input, check, false)
+    }
+
+    fn parse_checked(
+        &self,
+        input: &str,
+        check: DuplicateCheck,
+        strict: bool,
+    ) -> Result<(Value, Vec<DuplicateKey>), Error> {
+        let mut state = State {
+            converter: self,
+            check,
+            strict,
+            duplicates: Vec::new(),
+            failure: None,
+        };
+        let mut deserializer = serde_json::Deserializer::from_str(input);
+        let parsed = Checked {
+            state: &mut state,
+            path: &Path::Root,
+        }
+        .deserialize(&mut deserializer)
+        .and_then(|value| deserializer.end().map(|()| value));
+
+        match (parsed, state.failure) {
+            (_, Some(failure)) => Err(failure),
+            (Err(err), None) => Err(Error::Parse(err)),
+            (Ok(value), None) => Ok((value, state.duplicates)),
+        }
+    }
+}
+
+struct State<'c> {
+    converter: &'c Converter<'c>,
+    check: DuplicateCheck,
+    strict: bool,
+    duplicates: Vec<DuplicateKey>,
+
+    /// Why parsing was stopped, when it's not a syntax error.
+    failure: Option<Error>,
+}
+
+impl State<'_> {
+    /// Records `key` if it duplicates an earlier key of `map`, whose new keys
+    /// so far are `new_keys`. Returns `false` if parsing must stop.
+    fn check_key(
+        &mut self,
+        map: &JsonMap,
+        new_keys: &mut Vec<(String, String)>,
+        key: &str,
+        path: &Path,
+    ) -> bool {
+        let mut earlier = None;
+        if map.contains_key(key) {
+            earlier = Some(key.to_owned());
+        } else if self.check == DuplicateCheck::AfterConversion {
+            match self.converter.rename_key(key, path) {
+                Ok(Some(new_key)) => match new_keys.iter().find(|(new, _)| *new == new_key) {
+                    Some((_, original)) => earlier = Some(original.clone()),
+                    None => new_keys.push((new_key, key.to_owned())),
+                },
+                Ok(None) => {}
+                Err(err) => {
+                    self.failure = Some(err);
+                    return false;
+                }
+            }
+        }
+
+        let earlier = match earlier {
+            Some(earlier) => earlier,
+            None => return true,
+        };
+        if self.strict {
+            self.failure = Some(Error::DuplicateKey {
+                path: path.to_string(),
+                key: key.to_owned(),
+            });
+            return false;
+        }
+        self.duplicates.push(DuplicateKey {
+            path: path.to_string(),
+            key: key.to_owned(),
+            earlier,
+        });
+        true
+    }
+}
+
+/// Builds a [`Value`] like its own `Deserialize` implementation, checking the
+/// keys of objects on the way.
+struct Checked<'s, 'c> {
+    state: &'s mut State<'c>,
+    path: &'s Path<'s>,
+}
+
+impl<'de> DeserializeSeed<'de> for Checked<'_, '_> {
+    type Value = Value;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Checked<'_, '_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut array = Vec::new();
+        loop {
+            let item_path = self.path.index(array.len());
+            let seed = Checked {
+                state: &mut *self.state,
+                path: &item_path,
+            };
+            match seq.next_element_seed(seed)? {
+                Some(value) => array.push(value),
+                None => return Ok(Value::Array(array)),
+            }
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Value, A::Error> {
+        let mut map = JsonMap::new();
+        let mut new_keys = Vec::new();
+        while let Some(key) = access.next_key::<String>()? {
+            let key_path = self.path.key(&key);
+            if !self.state.check_key(&map, &mut new_keys, &key, &key_path) {
+                return Err(de::Error::custom("duplicate key"));
+            }
+            let value = access.next_value_seed(Checked {
+                state: &mut *self.state,
+                path: &key_path,
+            })?;
+            map.insert(key, value);
+        }
+        Ok(Value::Object(map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn rejects_exact_duplicate() {
+        let input = r#"{"users": [{"userId": 1, "userName": "Ada", "userId": 2}]}"#;
+        match CaseChanger::from_str_strict(input, KeyCase::Snake) {
+            Err(err @ Error::DuplicateKey { .. }) => {
+                assert_eq!(
+                    "duplicate key `userId` at `/users/0/userId`",
+                    err.to_string()
+                )
+            }
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("duplicate key accepted"),
+        }
+    }
+
+    #[test]
+    fn rejects_collision_after_conversion() {
+        let input = r#"{"meta": {"userId": 1, "user_id": 2}}"#;
+        match CaseChanger::from_str_strict(input, KeyCase::Snake) {
+            Err(Error::DuplicateKey { path, key }) => {
+                assert_eq!("/meta/user_id", path);
+                assert_eq!("user_id", key);
+            }
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("colliding keys accepted"),
+        }
+
+        let converter = Converter::new(KeyCase::Snake);
+        assert_eq!(
+            json!({"meta": {"userId": 1, "user_id": 2}}),
+            converter
+                .parse_strict(input, DuplicateCheck::Exact)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn accepts_clean_document() {
+        let input = r#"{"userId": 1, "tags": [{"tagName": "a"}, {"tagName": "b"}], "score": -1.5}"#;
+        let mut case_changer = CaseChanger::from_str_strict(input, KeyCase::Snake).unwrap();
+        assert_eq!(
+            json!({"user_id": 1, "tags": [{"tag_name": "a"}, {"tag_name": "b"}], "score": -1.5}),
+            case_changer.convert()
+        );
+
+        match CaseChanger::from_str_strict(r#"{"a": 1} x"#, KeyCase::Snake) {
+            Err(Error::Parse(_)) => {}
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("trailing characters accepted"),
+        }
+    }
+
+    #[test]
+    fn lists_duplicates() {
+        let input = r#"{"userId": 1, "userId": 2, "UserId": 3}"#;
+        let (value, duplicates) = Converter::new(KeyCase::Snake)
+            .parse_lenient(input, DuplicateCheck::AfterConversion)
+            .unwrap();
+
+        assert_eq!(json!({"userId": 2, "UserId": 3}), value);
+        assert_eq!(
+            vec![
+                DuplicateKey {
+                    path: "/userId".to_owned(),
+                    key: "userId".to_owned(),
+                    earlier: "userId".to_owned(),
+                },
+                DuplicateKey {
+                    path: "/UserId".to_owned(),
+                    key: "UserId".to_owned(),
+                    earlier: "userId".to_owned(),
+                },
+            ],
+            duplicates
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error};
+use toml::{Table, Value};
+
+/// Converts the keys of a TOML document.
+///
+/// Dotted keys are converted segment by segment, as the nested tables they
+/// define. Values, including dates and times, are kept as they are, but
+/// comments and formatting are not: the output is written the way `toml`
+/// writes a [`Table`], so dotted keys and inline tables may come back as
+/// table headers.
+pub fn convert_toml_str(input: &str, converter: &Converter) -> Result<String, Error> {
+    let table = input.parse::<Table>().map_err(Error::TomlParse)?;
+    let converted = converter.convert_toml_table(table)?;
+    toml::to_string(&converted).map_err(Error::TomlWrite)
+}
+
+impl Converter<'_> {
+    /// Converts the keys of the tables in `value`, as
+    /// [`Converter::convert_value`] does for JSON.
+    pub fn convert_toml_value(&self, value: Value) -> Result<Value, Error> {
+        self.convert_toml(value, &Path::Root)
+    }
+
+    /// Converts the keys of a TOML document already parsed into a [`Table`].
+    pub fn convert_toml_table(&self, table: Table) -> Result<Table, Error> {
+        self.convert_toml_map(table, &Path::Root)
+    }
+
+    fn convert_toml(&self, value: Value, path: &Path) -> Result<Value, Error> {
+        match value {
+            Value::Array(array) => array
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| self.convert_toml(value, &path.index(index)))
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Value::Table(table) => self.convert_toml_map(table, path).map(Value::Table),
+            value => Ok(value),
+        }
+    }
+
+    fn convert_toml_map(&self, table: Table, path: &Path) -> Result<Table, Error> {
+        let mut new_table = Table::new();
+        for (key, value) in table {
+            let key_path = path.key(&key);
+            if let Some(new_key) = self.rename_key(&key, &key_path)? {
+                let value = self.convert_toml(value, &key_path)?;
+                new_table.insert(new_key, value);
+            }
+        }
+        Ok(new_table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn converts_tables() {
+        let input = r#"
+appName = "web"
+serverConfig.maxConnections = 10
+createdAt = 1979-05-27T07:32:00-08:00
+logLevel = { defaultLevel = "info", perModule = { httpClient = "debug" } }
+
+[[servers]]
+hostName = "alpha"
+portNumber = 8080
+
+[[servers]]
+hostName = "beta"
+startDate = 2020-01-01
+"#;
+        let converted = convert_toml_str(input, &Converter::new(KeyCase::Snake)).unwrap();
+
+        assert_eq!(
+            r#"app_name = "web"
+created_at = 1979-05-27T07:32:00-08:00
+
+[server_config]
+max_connections = 10
+
+[log_level]
+default_level = "info"
+
+[log_level.per_module]
+http_client = "debug"
+
+[[servers]]
+host_name = "alpha"
+port_number = 8080
+
+[[servers]]
+host_name = "beta"
+start_date = 2020-01-01
+"#,
+            converted
+        );
+    }
+
+    #[test]
+    fn applies_the_key_pipeline() {
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_dictionary_keys(vec!["env"]);
+        converter.with_excluded_pointers(vec!["/servers/*/host_name"]);
+
+        let table: toml::Table = r#"
+env = { RUST_LOG = "info" }
+servers = [{ host_name = "alpha", port_number = 1 }]
+"#
+        .parse()
+        .unwrap();
+        let expected: toml::Table = r#"
+env = { RUST_LOG = "info" }
+servers = [{ host_name = "alpha", portNumber = 1 }]
+"#
+        .parse()
+        .unwrap();
+        assert_eq!(expected, converter.convert_toml_table(table).unwrap());
+    }
+
+    #[test]
+    fn reports_invalid_toml() {
+        let err = convert_toml_str("a = ", &Converter::new(KeyCase::Snake)).unwrap_err();
+        assert!(err.to_string().starts_with("invalid TOML: "));
+    }
+}
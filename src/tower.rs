@@ -0,0 +1,322 @@
+//! A `tower` layer converting the keys of JSON request and response bodies,
+//! so that services only ever see one naming convention.
+
+use crate::prelude::*;
+use crate::{Converter, Error};
+use ::bytes::Bytes;
+use ::http::header::{self, HeaderMap, HeaderValue};
+use ::http::{Request, Response, StatusCode};
+use ::http_body::Body;
+use ::http_body_util::{BodyExt, Either, Full, LengthLimitError, Limited};
+use ::tower_layer::Layer;
+use ::tower_service::Service;
+use core::fmt;
+use core::future::Future;
+use core::mem;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// The body of requests and responses going through a [`CaseConvert`]: the
+/// converted JSON, or the original body when it isn't JSON.
+pub type CaseConvertBody<B> = Either<Full<Bytes>, B>;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Wraps services in a [`CaseConvert`].
+#[derive(Clone)]
+pub struct CaseConvertLayer {
+    request_converter: Arc<Converter<'static>>,
+    response_converter: Arc<Converter<'static>>,
+    body_limit: usize,
+    error_status: StatusCode,
+}
+
+impl CaseConvertLayer {
+    /// Converts JSON request bodies with `request_converter` before the inner
+    /// service sees them, and JSON response bodies with `response_converter`.
+    ///
+    /// Bodies are limited to 2 MiB, and requests that fail to convert are
+    /// answered with `400 Bad Request`.
+    pub fn new(
+        request_converter: Converter<'static>,
+        response_converter: Converter<'static>,
+    ) -> Self {
+        CaseConvertLayer {
+            request_converter: Arc::new(request_converter),
+            response_converter: Arc::new(response_converter),
+            body_limit: 2 * 1024 * 1024,
+            error_status: StatusCode::BAD_REQUEST,
+        }
+    }
+
+    /// Sets the largest body, in bytes, that is buffered to be converted.
+    /// Larger requests are answered with `413 Payload Too Large`, and larger
+    /// responses are replaced by a `500 Internal Server Error`.
+    pub fn with_body_limit(&mut self, limit: usize) {
+        self.body_limit = limit;
+    }
+
+    /// Sets the status of the response to requests whose body can't be read,
+    /// parsed or converted.
+    pub fn with_error_status(&mut self, status: StatusCode) {
+        self.error_status = status;
+    }
+}
+
+impl<S> Layer<S> for CaseConvertLayer {
+    type Service = CaseConvert<S>;
+
+    fn layer(&self, inner: S) -> CaseConvert<S> {
+        CaseConvert {
+            inner,
+            config: self.clone(),
+        }
+    }
+}
+
+/// A service converting the keys of JSON bodies around `S`, made by
+/// [`CaseConvertLayer`].
+///
+/// Requests and responses whose `content-type` isn't `application/json` or
+/// `application/*+json` are passed through untouched.
+#[derive(Clone)]
+pub struct CaseConvert<S> {
+    inner: S,
+    config: CaseConvertLayer,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for CaseConvert<S>
+where
+    S: Service<Request<CaseConvertBody<ReqBody>>, Response = Response<ResBody>>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+    ReqBody: Body<Data = Bytes> + Send + 'static,
+    ReqBody::Error: Into<BoxError>,
+    ResBody: Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<CaseConvertBody<ResBody>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        // `inner` is the clone that was driven to readiness.
+        let clone = self.inner.clone();
+        let mut inner = mem::replace(&mut self.inner, clone);
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            let req = if is_json(req.headers()) {
+                let (mut parts, body) = req.into_parts();
+                match convert_body(body, &config.request_converter, config.body_limit).await {
+                    Ok(body) => {
+                        set_content_length(&mut parts.headers, &body);
+                        Request::from_parts(parts, Either::Left(Full::new(body)))
+                    }
+                    Err(err) => {
+                        let status = match err {
+                            BodyError::TooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+                            _ => config.error_status,
+                        };
+                        return Ok(error_response(status, &err));
+                    }
+                }
+            } else {
+                req.map(Either::Right)
+            };
+
+            let res = inner.call(req).await?;
+            if !is_json(res.headers()) {
+                return Ok(res.map(Either::Right));
+            }
+            let (mut parts, body) = res.into_parts();
+            Ok(
+                match convert_body(body, &config.response_converter, config.body_limit).await {
+                    Ok(body) => {
+                        set_content_length(&mut parts.headers, &body);
+                        Response::from_parts(parts, Either::Left(Full::new(body)))
+                    }
+                    Err(err) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &err),
+                },
+            )
+        })
+    }
+}
+
+/// Why a body couldn't be converted.
+enum BodyError {
+    TooLarge(usize),
+    Read(BoxError),
+    Parse(serde_json::Error),
+    Convert(Error),
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::TooLarge(limit) => write!(f, "body is larger than {} bytes", limit),
+            BodyError::Read(err) => write!(f, "failed to read body: {}", err),
+            BodyError::Parse(err) => write!(f, "invalid JSON body: {}", err),
+            BodyError::Convert(err) => write!(f, "failed to convert keys: {}", err),
+        }
+    }
+}
+
+async fn convert_body<B>(
+    body: B,
+    converter: &Converter<'_>,
+    limit: usize,
+) -> Result<Bytes, BodyError>
+where
+    B: Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    let body = match Limited::new(body, limit).collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(err) if err.is::<LengthLimitError>() => return Err(BodyError::TooLarge(limit)),
+        Err(err) => return Err(BodyError::Read(err)),
+    };
+    let value: Value = serde_json::from_slice(&body).map_err(BodyError::Parse)?;
+    let converted = converter.convert_value(value).map_err(BodyError::Convert)?;
+    Ok(Bytes::from(converted.to_string()))
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    let content_type = match headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(content_type) => content_type,
+        None => return false,
+    };
+    let essence = content_type.split(';').next().unwrap_or("").trim();
+    let essence = essence.to_ascii_lowercase();
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+fn set_content_length(headers: &mut HeaderMap, body: &Bytes) {
+    headers.remove(header::TRANSFER_ENCODING);
+    headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+}
+
+fn error_response<B>(status: StatusCode, err: &BodyError) -> Response<CaseConvertBody<B>> {
+    let body = Bytes::from(err.to_string());
+    let mut res = Response::new(Either::Left(Full::new(body.clone())));
+    *res.status_mut() = status;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    set_content_length(res.headers_mut(), &body);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyCase;
+    use core::convert::Infallible;
+    use serde_json::json;
+    use tower::{service_fn, ServiceExt};
+
+    /// Answers with the body it got, under the content type it got, after
+    /// checking that JSON bodies arrive in snake_case.
+    async fn echo(
+        req: Request<CaseConvertBody<Full<Bytes>>>,
+    ) -> Result<Response<Full<Bytes>>, Infallible> {
+        let json = is_json(req.headers());
+        let content_type = req.headers()[header::CONTENT_TYPE].clone();
+        let length = req.headers().get(header::CONTENT_LENGTH).cloned();
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        if json {
+            assert_eq!(
+                json!({"user_name": "Ada", "home_address": {"zip_code": 1}}),
+                serde_json::from_slice::<Value>(&body).unwrap()
+            );
+            assert_eq!(Some(HeaderValue::from(body.len())), length);
+        }
+        let mut res = Response::new(Full::new(body));
+        res.headers_mut().insert(header::CONTENT_TYPE, content_type);
+        Ok(res)
+    }
+
+    fn layer() -> CaseConvertLayer {
+        CaseConvertLayer::new(
+            Converter::new(KeyCase::Snake),
+            Converter::new(KeyCase::Camel),
+        )
+    }
+
+    async fn send(
+        layer: &CaseConvertLayer,
+        content_type: &'static str,
+        body: &'static str,
+    ) -> (StatusCode, HeaderMap, String) {
+        let req = Request::post("/")
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, body.len())
+            .body(Full::new(Bytes::from(body)))
+            .unwrap();
+        let res = layer.layer(service_fn(echo)).oneshot(req).await.unwrap();
+        let (parts, body) = res.into_parts();
+        let body = body.collect().await.unwrap().to_bytes();
+        (
+            parts.status,
+            parts.headers,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn converts_both_directions() {
+        let body = r#"{"userName": "Ada", "home-address": {"ZipCode": 1}}"#;
+        let (status, headers, body) = send(&layer(), "application/json; charset=utf-8", body).await;
+
+        assert_eq!(StatusCode::OK, status);
+        assert_eq!(
+            json!({"userName": "Ada", "homeAddress": {"zipCode": 1}}),
+            serde_json::from_str::<Value>(&body).unwrap()
+        );
+        assert_eq!(
+            HeaderValue::from(body.len()),
+            headers[header::CONTENT_LENGTH]
+        );
+    }
+
+    #[tokio::test]
+    async fn passes_other_content_types_through() {
+        let body = r#"{"userName": "Ada"}"#;
+        let (status, _, echoed) = send(&layer(), "text/plain", body).await;
+        assert_eq!(StatusCode::OK, status);
+        assert_eq!(body, echoed);
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_over_the_limit_and_bad_json() {
+        let mut layer = layer();
+        layer.with_body_limit(16);
+        layer.with_error_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+        let (status, _, body) = send(
+            &layer,
+            "application/json",
+            r#"{"userName": "Ada Lovelace"}"#,
+        )
+        .await;
+        assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, status);
+        assert_eq!("body is larger than 16 bytes", body);
+
+        let (status, _, body) = send(&layer, "application/json", r#"{"a": "#).await;
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, status);
+        assert!(body.starts_with("invalid JSON body: "), "{}", body);
+    }
+}
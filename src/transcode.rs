@@ -0,0 +1,460 @@
+use crate::prelude::*;
+use crate::{Converted, Converter};
+use core::cell::RefCell;
+use core::convert::TryFrom;
+use core::fmt;
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+/// Reads a document from any self-describing serde format and writes it to
+/// another, renaming map keys with `converter` on the way, as
+/// [`Converted`] does.
+///
+/// Values are copied as the deserializer reports them, so byte strings stay
+/// byte strings if both formats have them. Map keys that aren't strings are
+/// written without renaming, but must not be sequences or maps. Errors of the deserializer and of the
+/// conversion are reported as errors of the serializer.
+pub fn convert_with_format<'de, D, S>(
+    deserializer: D,
+    serializer: S,
+    converter: &Converter,
+) -> Result<S::Ok, S::Error>
+where
+    D: Deserializer<'de>,
+    S: Serializer,
+{
+    Converted::new(&Transcoder::new(deserializer), converter).serialize(serializer)
+}
+
+/// Converts the keys of a MessagePack document.
+#[cfg(feature = "msgpack")]
+pub fn convert_msgpack(input: &[u8], converter: &Converter) -> Result<Vec<u8>, crate::Error> {
+    let mut output = Vec::new();
+    convert_with_format(
+        &mut rmp_serde::Deserializer::from_read_ref(input),
+        &mut rmp_serde::Serializer::new(&mut output),
+        converter,
+    )
+    .map_err(crate::Error::MsgPack)?;
+    Ok(output)
+}
+
+impl Converter<'_> {
+    /// Reads a JSON document from `input` and writes it to `output` with its
+    /// keys renamed, as [`convert_with_format`] does, one token at a time
+    /// rather than through a [`Value`](serde_json::Value).
+    #[cfg(feature = "std")]
+    pub fn convert_reader(
+        &self,
+        input: impl std::io::Read,
+        output: impl std::io::Write,
+    ) -> Result<(), crate::Error> {
+        let mut deserializer =
+            serde_json::Deserializer::from_reader(std::io::BufReader::new(input));
+        convert_with_format(
+            &mut deserializer,
+            &mut serde_json::Serializer::new(output),
+            self,
+        )
+        .map_err(crate::Error::Parse)?;
+        deserializer.end().map_err(crate::Error::Parse)
+    }
+}
+
+/// Reads a JSON document from `reader` and writes it to `writer` with its
+/// keys renamed by `converter`, as [`Converter::convert_reader`] does,
+/// buffering the writes. Values are written as soon as they are read, so
+/// the whole document is never held in memory, only the key being renamed
+/// and the path to it:
+///
+/// ```
+/// # use json_keys_case_changer::{convert_reader_to_writer, Converter, KeyCase};
+/// let input = r#"[{"userName": "Ada"}, {"userName": "Bob"}]"#;
+/// let mut output = Vec::new();
+/// convert_reader_to_writer(input.as_bytes(), &mut output, &Converter::new(KeyCase::Snake))?;
+/// assert_eq!(r#"[{"user_name":"Ada"},{"user_name":"Bob"}]"#.as_bytes(), &output[..]);
+/// # Ok::<(), json_keys_case_changer::Error>(())
+/// ```
+///
+/// Entries are written in input order as they stream past, so objects
+/// whose keys collide after conversion keep every entry, whatever the
+/// [`CollisionStrategy`](crate::CollisionStrategy).
+#[cfg(feature = "std")]
+pub fn convert_reader_to_writer<R, W>(
+    reader: R,
+    writer: W,
+    converter: &Converter,
+) -> Result<(), crate::Error>
+where
+    R: std::io::Read,
+    W: std::io::Write,
+{
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(writer);
+    converter.convert_reader(reader, &mut writer)?;
+    writer.flush().map_err(crate::Error::Io)
+}
+
+/// Serializes whatever `D` holds, consuming it on first use.
+struct Transcoder<D>(RefCell<Option<D>>);
+
+impl<D> Transcoder<D> {
+    fn new(deserializer: D) -> Self {
+        Transcoder(RefCell::new(Some(deserializer)))
+    }
+}
+
+impl<'de, D: Deserializer<'de>> Serialize for Transcoder<D> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let deserializer = self
+            .0
+            .borrow_mut()
+            .take()
+            .ok_or_else(|| ser::Error::custom("a transcoder can only be serialized once"))?;
+        deserializer
+            .deserialize_any(Transcode(serializer))
+            .map_err(ser::Error::custom)
+    }
+}
+
+/// Writes every value it visits to `S`.
+struct Transcode<S>(S);
+
+macro_rules! forward_visit {
+    ($($visit:ident($ty:ty) => $serialize:ident;)*) => {
+        $(
+            fn $visit<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                self.0.$serialize(v).map_err(de::Error::custom)
+            }
+        )*
+    };
+}
+
+impl<'de, S: Serializer> Visitor<'de> for Transcode<S> {
+    type Value = S::Ok;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any value")
+    }
+
+    forward_visit! {
+        visit_bool(bool) => serialize_bool;
+        visit_i8(i8) => serialize_i8;
+        visit_i16(i16) => serialize_i16;
+        visit_i32(i32) => serialize_i32;
+        visit_i64(i64) => serialize_i64;
+        visit_i128(i128) => serialize_i128;
+        visit_u8(u8) => serialize_u8;
+        visit_u16(u16) => serialize_u16;
+        visit_u32(u32) => serialize_u32;
+        visit_u64(u64) => serialize_u64;
+        visit_u128(u128) => serialize_u128;
+        visit_f32(f32) => serialize_f32;
+        visit_f64(f64) => serialize_f64;
+        visit_char(char) => serialize_char;
+        visit_str(&str) => serialize_str;
+        visit_bytes(&[u8]) => serialize_bytes;
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.serialize_unit().map_err(de::Error::custom)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.serialize_none().map_err(de::Error::custom)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.0
+            .serialize_some(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        Transcoder::new(deserializer)
+            .serialize(self.0)
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = self
+            .0
+            .serialize_seq(seq.size_hint())
+            .map_err(de::Error::custom)?;
+        while seq.next_element_seed(Element(&mut out))?.is_some() {}
+        out.end().map_err(de::Error::custom)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut out = self
+            .0
+            .serialize_map(map.size_hint())
+            .map_err(de::Error::custom)?;
+        while map.next_key_seed(Key(&mut out))?.is_some() {
+            map.next_value_seed(Value(&mut out))?;
+        }
+        out.end().map_err(de::Error::custom)
+    }
+}
+
+struct Element<'a, S>(&'a mut S);
+
+impl<'de, S: SerializeSeq> DeserializeSeed<'de> for Element<'_, S> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.0
+            .serialize_element(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
+
+struct Key<'a, S>(&'a mut S);
+
+impl<'de, S: SerializeMap> DeserializeSeed<'de> for Key<'_, S> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        let key = deserializer.deserialize_any(KeyVisitor)?;
+        self.0.serialize_key(&key).map_err(de::Error::custom)
+    }
+}
+
+/// A map key, read before it is written since [`Converted`] may need to look
+/// at it twice.
+enum MapKey {
+    Bool(bool),
+    Signed(i128),
+    Unsigned(u128),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    Unit,
+}
+
+impl Serialize for MapKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            MapKey::Bool(v) => serializer.serialize_bool(*v),
+            MapKey::Signed(v) => match i64::try_from(*v) {
+                Ok(v) => serializer.serialize_i64(v),
+                Err(_) => serializer.serialize_i128(*v),
+            },
+            MapKey::Unsigned(v) => match u64::try_from(*v) {
+                Ok(v) => serializer.serialize_u64(v),
+                Err(_) => serializer.serialize_u128(*v),
+            },
+            MapKey::F32(v) => serializer.serialize_f32(*v),
+            MapKey::F64(v) => serializer.serialize_f64(*v),
+            MapKey::Char(v) => serializer.serialize_char(*v),
+            MapKey::Str(v) => serializer.serialize_str(v),
+            MapKey::Bytes(v) => serializer.serialize_bytes(v),
+            MapKey::Unit => serializer.serialize_unit(),
+        }
+    }
+}
+
+struct KeyVisitor;
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = MapKey;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map key that is not a sequence or a map")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<MapKey, E> {
+        Ok(MapKey::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<MapKey, E> {
+        Ok(MapKey::Signed(v.into()))
+    }
+
+    fn visit_i128<E: de::Error>(self, v: i128) -> Result<MapKey, E> {
+        Ok(MapKey::Signed(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<MapKey, E> {
+        Ok(MapKey::Unsigned(v.into()))
+    }
+
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<MapKey, E> {
+        Ok(MapKey::Unsigned(v))
+    }
+
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<MapKey, E> {
+        Ok(MapKey::F32(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<MapKey, E> {
+        Ok(MapKey::F64(v))
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<MapKey, E> {
+        Ok(MapKey::Char(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<MapKey, E> {
+        Ok(MapKey::Str(v.to_owned()))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<MapKey, E> {
+        Ok(MapKey::Bytes(v.to_owned()))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<MapKey, E> {
+        Ok(MapKey::Unit)
+    }
+}
+
+struct Value<'a, S>(&'a mut S);
+
+impl<'de, S: SerializeMap> DeserializeSeed<'de> for Value<'_, S> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.0
+            .serialize_value(&Transcoder::new(deserializer))
+            .map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn transcodes_between_formats() {
+        let input =
+            r#"{"userName": "Ada", "pastAddresses": [{"streetName": "Old"}], "isAdmin": null}"#;
+        let mut output = Vec::new();
+        convert_with_format(
+            &mut serde_json::Deserializer::from_str(input),
+            &mut serde_json::Serializer::new(&mut output),
+            &Converter::new(KeyCase::Snake),
+        )
+        .unwrap();
+
+        let output: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            json!({"user_name": "Ada", "past_addresses": [{"street_name": "Old"}], "is_admin": null}),
+            output
+        );
+    }
+
+    /// Yields a JSON array of `count` objects without ever holding it.
+    #[cfg(feature = "std")]
+    struct Generated {
+        count: usize,
+        next: usize,
+        pending: Vec<u8>,
+    }
+
+    #[cfg(feature = "std")]
+    impl std::io::Read for Generated {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() {
+                self.pending = match self.next {
+                    0 => b"[".to_vec(),
+                    n if n > self.count => return Ok(0),
+                    n if n == self.count => format!(r#"{{"itemId": {}}}]"#, n).into_bytes(),
+                    n => format!(r#"{{"itemId": {}}},"#, n).into_bytes(),
+                };
+                self.next += 1;
+            }
+            let len = buf.len().min(self.pending.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            Ok(len)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn streams_large_documents() {
+        let input = Generated {
+            count: 100_000,
+            next: 0,
+            pending: Vec::new(),
+        };
+        let mut output = Vec::new();
+        convert_reader_to_writer(input, &mut output, &Converter::new(KeyCase::Snake)).unwrap();
+
+        assert!(output.starts_with(br#"[{"item_id":1},{"item_id":2},"#));
+        assert!(output.ends_with(br#"{"item_id":100000}]"#));
+
+        let mut output = Vec::new();
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_collision_strategy(CollisionStrategy::KeepFirst);
+        convert_reader_to_writer(&br#"{"aB": 1, "a_b": 2}"#[..], &mut output, &converter).unwrap();
+        assert_eq!(br#"{"a_b":1,"a_b":2}"#, &output[..]);
+
+        let mut output = Vec::new();
+        let err = convert_reader_to_writer(
+            &br#"{"userName": 1} {}"#[..],
+            &mut output,
+            &Converter::new(KeyCase::Snake),
+        );
+        assert!(matches!(err, Err(Error::Parse(_))));
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn msgpack_keeps_bytes() {
+        use alloc::collections::BTreeMap;
+        use serde::{Deserialize, Serialize};
+        use serde_bytes::ByteBuf;
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Upload {
+            file_name: String,
+            file_contents: ByteBuf,
+            chunk_sizes: BTreeMap<u32, BTreeMap<&'static str, u32>>,
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Output {
+            file_name: String,
+            file_contents: ByteBuf,
+            chunk_sizes: BTreeMap<u32, BTreeMap<String, u32>>,
+        }
+
+        let contents = vec![0x00, 0xff, 0xc4, 0x7b];
+        let input = rmp_serde::to_vec_named(&Upload {
+            file_name: "a.bin".to_owned(),
+            file_contents: ByteBuf::from(contents.clone()),
+            chunk_sizes: [(1, [("byteCount", 4)].iter().cloned().collect())]
+                .iter()
+                .cloned()
+                .collect(),
+        })
+        .unwrap();
+
+        let output = convert_msgpack(&input, &Converter::new(KeyCase::Snake)).unwrap();
+
+        // A bin 8 header: the contents are still bytes, not an array.
+        let mut bin = vec![0xc4, contents.len() as u8];
+        bin.extend_from_slice(&contents);
+        assert!(output.windows(bin.len()).any(|window| window == bin));
+        assert_eq!(
+            Output {
+                file_name: "a.bin".to_owned(),
+                file_contents: ByteBuf::from(contents),
+                chunk_sizes: [(1, [("byte_count".to_owned(), 4)].iter().cloned().collect())]
+                    .iter()
+                    .cloned()
+                    .collect(),
+            },
+            rmp_serde::from_slice(&output).unwrap()
+        );
+    }
+}
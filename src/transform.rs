@@ -0,0 +1,127 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::scope::ScopeMatcher;
+use crate::{Error, KeyScope};
+use alloc::sync::Arc;
+use core::fmt;
+use serde_json::Value;
+
+/// The function of a value transform.
+pub(crate) type TransformFn = dyn Fn(Value) -> Result<Value, String> + Send + Sync;
+
+/// A function applied to the values of some entries while converting. See
+/// [`Converter::with_value_transform`](crate::Converter::with_value_transform).
+#[derive(Clone)]
+pub(crate) struct ValueTransform {
+    target: TransformTarget,
+    transform: Arc<TransformFn>,
+}
+
+#[derive(Clone, Debug)]
+enum TransformTarget {
+    Key(String),
+    Pointer(ScopeMatcher),
+}
+
+impl ValueTransform {
+    pub(crate) fn new(key_or_pointer: &str, transform: Arc<TransformFn>) -> Self {
+        let target = if key_or_pointer.starts_with('/') {
+            TransformTarget::Pointer(ScopeMatcher::new(KeyScope::Pointer(
+                key_or_pointer.to_owned(),
+            )))
+        } else {
+            TransformTarget::Key(key_or_pointer.to_owned())
+        };
+        ValueTransform { target, transform }
+    }
+
+    /// Whether the entry with the original key `key`, at `path`, is
+    /// transformed.
+    pub(crate) fn matches(&self, key: &str, path: &Path) -> bool {
+        match &self.target {
+            TransformTarget::Key(target) => target == key,
+            TransformTarget::Pointer(matcher) => matcher.matches(path),
+        }
+    }
+
+    pub(crate) fn apply(&self, value: Value, path: &Path) -> Result<Value, Error> {
+        (self.transform)(value).map_err(|message| Error::ValueTransform {
+            path: path.to_pointer(),
+            message,
+        })
+    }
+}
+
+impl fmt::Debug for ValueTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValueTransform")
+            .field("target", &self.target)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    #[test]
+    fn coerces_values() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_value_transform("quantity", |value| match value {
+            Value::String(text) => text
+                .parse::<u64>()
+                .map(Value::from)
+                .map_err(|err| format!("`{}` is not a quantity: {}", text, err)),
+            value => Ok(value),
+        });
+        converter.with_value_transform("eMail", |value| {
+            Ok(match value {
+                Value::String(text) => Value::String(text.to_lowercase()),
+                value => value,
+            })
+        });
+
+        assert_eq!(
+            json!({"lines": [{"quantity": 42}, {"quantity": 3}], "e_mail": "ada@example.com"}),
+            converter
+                .convert_value(json!({
+                    "lines": [{"quantity": "42"}, {"quantity": 3}],
+                    "eMail": "Ada@Example.com"
+                }))
+                .unwrap()
+        );
+
+        match converter.convert_value(json!({"lines": [{"quantity": "many"}]})) {
+            Err(Error::ValueTransform { path, message }) => {
+                assert_eq!("/lines/0/quantity", path);
+                assert!(message.starts_with("`many` is not a quantity"));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn transforms_values_at_pointers() {
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_value_transform("/orders/*/created_at", |value| {
+            Ok(match value.as_u64() {
+                Some(millis) if millis % 1000 == 0 => Value::from(millis / 1000),
+                _ => value,
+            })
+        });
+
+        assert_eq!(
+            json!({
+                "orders": [{"createdAt": 1700000000}],
+                "user": {"createdAt": 1700000000000u64}
+            }),
+            converter
+                .convert_value(json!({
+                    "orders": [{"created_at": 1700000000000u64}],
+                    "user": {"created_at": 1700000000000u64}
+                }))
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::prelude::*;
+use crate::{Converter, DroppedKey, Error, KeyCase};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Converts `input` into a `D` by serializing it, converting its keys with
+/// `config`, and deserializing the result.
+///
+/// This is meant for two types that only differ in their naming convention:
+/// serializing an `OldRequest` in camelCase and reading it back as a
+/// snake_case `NewRequest`. Deserialization errors carry the JSON pointer of
+/// the value that failed, in the converted document, along with the entries
+/// the conversion dropped around it.
+pub fn convert_typed<S, D>(input: &S, config: &Converter) -> Result<D, Error>
+where
+    S: Serialize + ?Sized,
+    D: DeserializeOwned,
+{
+    let value = serde_json::to_value(input).map_err(Error::Serialize)?;
+    let (converted, dropped) = config.convert_value_with_drops(value)?;
+    deserialize_value(converted, dropped)
+}
+
+/// Converts `input` into a `D` as [`convert_typed`] does, with the keys
+/// converted to `new_case` and no other option.
+pub fn convert_typed_keys<S, D>(input: &S, new_case: KeyCase) -> Result<D, Error>
+where
+    S: Serialize + ?Sized,
+    D: DeserializeOwned,
+{
+    convert_typed(input, &Converter::new(new_case))
+}
+
+/// Deserializes `value`, tracking the path of any failure and keeping the
+/// entries of `dropped` that may explain it.
+pub(crate) fn deserialize_value<D: DeserializeOwned>(
+    value: Value,
+    dropped: Vec<DroppedKey>,
+) -> Result<D, Error> {
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let path = pointer_from_path(err.path());
+        let dropped = dropped
+            .into_iter()
+            .filter(|dropped_key| dropped_key.is_near(&path))
+            .collect();
+        Error::Deserialize {
+            path,
+            source: err.into_inner(),
+            dropped,
+        }
+    })
+}
+
+fn pointer_from_path(path: &serde_path_to_error::Path) -> String {
+    use crate::path::escape_pointer_token;
+    use serde_path_to_error::Segment;
+
+    path.iter()
+        .filter_map(|segment| match segment {
+            Segment::Seq { index } => Some(format!("/{}", index)),
+            Segment::Map { key } => Some(format!("/{}", escape_pointer_token(key))),
+            Segment::Enum { .. } | Segment::Unknown => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct OldRequest {
+        request_id: u32,
+        user: OldUser,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct OldUser {
+        display_name: String,
+        favorite_colors: Vec<String>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct NewRequest {
+        request_id: u32,
+        user: NewUser,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct NewUser {
+        display_name: String,
+        favorite_colors: Vec<String>,
+    }
+
+    fn old_request() -> OldRequest {
+        OldRequest {
+            request_id: 7,
+            user: OldUser {
+                display_name: "Ada".to_owned(),
+                favorite_colors: vec!["red".to_owned()],
+            },
+        }
+    }
+
+    #[test]
+    fn between_naming_conventions() {
+        let converter = Converter::new(KeyCase::Snake);
+
+        let new_request: NewRequest = convert_typed(&old_request(), &converter).unwrap();
+
+        let expected = NewRequest {
+            request_id: 7,
+            user: NewUser {
+                display_name: "Ada".to_owned(),
+                favorite_colors: vec!["red".to_owned()],
+            },
+        };
+        assert_eq!(expected, new_request);
+        assert_eq!(
+            expected,
+            convert_typed_keys::<_, NewRequest>(&old_request(), KeyCase::Snake).unwrap()
+        );
+    }
+
+    #[test]
+    fn missing_field_after_conversion() {
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_manual_renames([("displayName", "name")].iter().cloned().collect());
+
+        match convert_typed::<_, NewRequest>(&old_request(), &converter) {
+            Err(Error::Deserialize {
+                path,
+                source,
+                dropped,
+            }) => {
+                assert_eq!("/user", path);
+                assert!(source.to_string().contains("display_name"));
+                assert!(dropped.is_empty());
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Order {
+        order_id: u32,
+        line_items: Vec<LineItem>,
+        tags: Vec<String>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct LineItem {
+        item_id: u32,
+        unit_price: f64,
+    }
+
+    #[test]
+    fn convert_into_nested() {
+        let value = serde_json::json!({
+            "orderId": 1,
+            "lineItems": [{"itemId": 10, "unitPrice": 2.5}, {"itemId": 11, "unitPrice": 4.0}],
+            "tags": ["gift"]
+        });
+
+        let order: Order = CaseChanger::new(value, KeyCase::Snake)
+            .unwrap()
+            .convert_into()
+            .unwrap();
+
+        let expected = Order {
+            order_id: 1,
+            line_items: vec![
+                LineItem {
+                    item_id: 10,
+                    unit_price: 2.5,
+                },
+                LineItem {
+                    item_id: 11,
+                    unit_price: 4.0,
+                },
+            ],
+            tags: vec!["gift".to_owned()],
+        };
+        assert_eq!(expected, order);
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Profile {
+        display_name: String,
+        nick_name: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Account {
+        profile: Profile,
+    }
+
+    #[test]
+    fn convert_into_key_lost_to_collision() {
+        let value = serde_json::json!({"profile": {"displayName": "Ada", "nickName": "A"}});
+
+        let case_changer = CaseChanger::builder(value, KeyCase::Snake)
+            .manual_renames([("nickName", "display_name")].iter().cloned().collect())
+            .build();
+
+        let err = case_changer.convert_into::<Account>().unwrap_err();
+        assert_eq!(
+            "failed to deserialize `/profile`: missing field `nick_name` \
+             (note: `displayName` at `/profile/displayName` was overwritten by `nickName`, \
+             which was also converted to `display_name`)",
+            err.to_string()
+        );
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Settings {
+        user_id: u32,
+        #[serde(rename = "")]
+        unnamed: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Config {
+        user_settings: Settings,
+    }
+
+    #[test]
+    fn missing_field_explained_by_drop_rule() {
+        let input = serde_json::json!({
+            "userSettings": {"userId": 1, "  ": "x"},
+            "other": {" ": "y"}
+        });
+
+        let mut converter = Converter::new(KeyCase::Snake);
+        converter.with_trim_keys(true);
+        converter.with_empty_key_policy(EmptyKeyPolicy::Drop);
+
+        let err = convert_typed::<_, Config>(&input, &converter).unwrap_err();
+        assert_eq!(
+            "failed to deserialize `/user_settings`: missing field `` \
+             (note: `  ` at `/userSettings/  ` was dropped by the empty key policy)",
+            err.to_string()
+        );
+        match err {
+            Error::Deserialize { path, dropped, .. } => {
+                assert_eq!("/user_settings", path);
+                assert_eq!(
+                    vec![DroppedKey {
+                        key: "  ".to_owned(),
+                        path: "/userSettings/  ".to_owned(),
+                        parent: "/user_settings".to_owned(),
+                        reason: DropReason::EmptyKey,
+                    }],
+                    dropped
+                );
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::prelude::*;
+use alloc::sync::Arc;
+use core::fmt;
+
+/// A check run against every new key, after every other step. See
+/// [`Converter::with_validators`](crate::Converter::with_validators).
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum KeyValidator {
+    /// The key follows this style guide.
+    Style(StyleGuide),
+
+    /// The key is at most this many bytes long.
+    MaxLen(usize),
+
+    /// The key is made of ASCII characters only.
+    AsciiOnly,
+
+    /// A check of its own, made with [`KeyValidator::custom`].
+    Custom(Arc<KeyCheck>),
+}
+
+/// The check of a [`KeyValidator::Custom`].
+pub type KeyCheck = dyn Fn(&str, &KeyContext) -> Result<(), String> + Send + Sync;
+
+/// Naming rules of published style guides.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StyleGuide {
+    /// Google's JSON style guide: lowerCamelCase, ASCII letters and digits
+    /// only, starting with a letter.
+    GoogleJson,
+}
+
+/// Where a validated key comes from.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct KeyContext<'k> {
+    /// The key, as it appears in the input.
+    pub original_key: &'k str,
+
+    /// JSON pointer, in the input, to the entry.
+    pub path: &'k str,
+}
+
+/// A new key that a validator rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyViolation {
+    /// JSON pointer, in the input, to the entry.
+    pub path: String,
+
+    /// The new key.
+    pub key: String,
+
+    /// What is wrong with it, as told by the validator.
+    pub message: String,
+}
+
+impl KeyValidator {
+    /// A validator calling `check`, which returns what is wrong with the
+    /// key, to be read after it, such as `"is reserved"`.
+    pub fn custom(
+        check: impl Fn(&str, &KeyContext) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        KeyValidator::Custom(Arc::new(check))
+    }
+
+    pub(crate) fn validate(&self, key: &str, context: &KeyContext) -> Result<(), String> {
+        match self {
+            KeyValidator::Style(StyleGuide::GoogleJson) => {
+                let mut chars = key.chars();
+                let starts_lower = chars.next().is_some_and(|c| c.is_ascii_lowercase());
+                if starts_lower && chars.all(|c| c.is_ascii_alphanumeric()) {
+                    Ok(())
+                } else {
+                    Err("is not lowerCamelCase".to_owned())
+                }
+            }
+            KeyValidator::MaxLen(max) if key.len() > *max => {
+                Err(format!("is longer than {} bytes", max))
+            }
+            KeyValidator::AsciiOnly if !key.is_ascii() => Err("is not ASCII".to_owned()),
+            KeyValidator::Custom(check) => check(key, context),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for KeyValidator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyValidator::Style(style) => f.debug_tuple("Style").field(style).finish(),
+            KeyValidator::MaxLen(max) => f.debug_tuple("MaxLen").field(max).finish(),
+            KeyValidator::AsciiOnly => f.write_str("AsciiOnly"),
+            KeyValidator::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl fmt::Display for KeyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "key `{}` at `{}` {}", self.key, self.path, self.message)
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::*;
+    use serde_json::json;
+
+    fn converter() -> Converter<'static> {
+        let mut converter = Converter::new(KeyCase::Camel);
+        converter.with_manual_renames([("id", "ID")].iter().cloned().collect());
+        converter.with_key_suffix("_v2", KeyScope::Pointer("/legacy".to_owned()));
+        converter.with_validators(vec![
+            KeyValidator::Style(StyleGuide::GoogleJson),
+            KeyValidator::MaxLen(64),
+            KeyValidator::AsciiOnly,
+            KeyValidator::custom(|key, context| {
+                if key.starts_with("tmp") {
+                    Err(format!("is reserved (from `{}`)", context.original_key))
+                } else {
+                    Ok(())
+                }
+            }),
+        ]);
+        converter
+    }
+
+    fn document() -> serde_json::Value {
+        json!({"user_name": "Ada", "id": 1, "legacy": 2, "tmp_file": "a"})
+    }
+
+    #[test]
+    fn reports_violations() {
+        let converter = converter();
+        assert_eq!(
+            vec![
+                KeyViolation {
+                    path: "/id".to_owned(),
+                    key: "ID".to_owned(),
+                    message: "is not lowerCamelCase".to_owned(),
+                },
+                KeyViolation {
+                    path: "/legacy".to_owned(),
+                    key: "legacy_v2".to_owned(),
+                    message: "is not lowerCamelCase".to_owned(),
+                },
+                KeyViolation {
+                    path: "/tmp_file".to_owned(),
+                    key: "tmpFile".to_owned(),
+                    message: "is reserved (from `tmp_file`)".to_owned(),
+                },
+            ],
+            converter.preflight(&document()).violations
+        );
+
+        // Without the strict flag, conversions go through.
+        assert_eq!(
+            json!({"userName": "Ada", "ID": 1, "legacy_v2": 2, "tmpFile": "a"}),
+            converter.convert_value(document()).unwrap()
+        );
+    }
+
+    #[test]
+    fn strict_validation_fails_conversions() {
+        let mut converter = converter();
+        converter.with_strict_validation(true);
+
+        let err = converter.convert_value(document()).unwrap_err();
+        assert_eq!("key `ID` at `/id` is not lowerCamelCase", err.to_string());
+        assert_eq!(3, converter.preflight(&document()).violations.len());
+    }
+}
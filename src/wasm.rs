@@ -0,0 +1,69 @@
+use crate::prelude::*;
+use crate::{ConversionSpec, Converter, Error, KeyCase};
+use serde_json::Value;
+use wasm_bindgen::prelude::*;
+
+/// Converts the keys of the JSON document `json` to the case named `case`, as
+/// parsed by [`KeyCase::from_str`](core::str::FromStr::from_str). Errors are
+/// thrown as strings.
+#[wasm_bindgen(js_name = convertString)]
+pub fn convert_string(json: &str, case: &str) -> Result<String, JsValue> {
+    convert_with_case(json, case).map_err(to_js)
+}
+
+/// Converts the keys of the JSON document `json` with the options of a
+/// [`ConversionSpec`] written as JSON. Errors are thrown as strings.
+#[wasm_bindgen(js_name = convertWithSpec)]
+pub fn convert_with_spec(json: &str, spec: &str) -> Result<String, JsValue> {
+    convert_with_spec_str(json, spec).map_err(to_js)
+}
+
+fn convert_with_case(json: &str, case: &str) -> Result<String, Error> {
+    let case: KeyCase = case.parse()?;
+    convert(json, &Converter::new(case))
+}
+
+fn convert_with_spec_str(json: &str, spec: &str) -> Result<String, Error> {
+    let spec: ConversionSpec = serde_json::from_str(spec).map_err(Error::Parse)?;
+    convert(json, &Converter::from_spec(spec)?)
+}
+
+fn convert(json: &str, converter: &Converter) -> Result<String, Error> {
+    let value: Value = serde_json::from_str(json).map_err(Error::Parse)?;
+    let converted = converter.convert_value(value)?;
+    serde_json::to_string(&converted).map_err(Error::Serialize)
+}
+
+fn to_js(err: Error) -> JsValue {
+    JsValue::from_str(&err.to_string())
+}
+
+// `JsValue`s only exist on wasm32, see `tests/wasm.rs` for the exported
+// functions themselves.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_with_case_and_spec() {
+        assert_eq!(
+            r#"{"user_name":{"zip_code":1}}"#,
+            convert_with_case(r#"{"userName":{"zipCode":1}}"#, "snake_case").unwrap()
+        );
+
+        let spec = r#"{"case": "camel", "manual_renames": {"ID": "id"}}"#;
+        assert_eq!(
+            r#"{"id":1,"userName":"Ada"}"#,
+            convert_with_spec_str(r#"{"ID":1,"user_name":"Ada"}"#, spec).unwrap()
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        let err = convert_with_case("{}", "snek").unwrap_err();
+        assert!(err.to_string().starts_with("unknown case `snek`"));
+
+        let err = convert_with_spec_str("{}", r#"{"case": "snake", "trim": true}"#).unwrap_err();
+        assert!(err.to_string().contains("unknown field `trim`"));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::path::Path;
+use crate::prelude::*;
+use crate::{Converter, Error};
+use serde_yaml::{Mapping, Value};
+
+/// Converts the keys of a YAML document.
+///
+/// Anchors and aliases are resolved while parsing, so the output repeats the
+/// aliased values instead of referring to them, but merge keys (`<<`) are
+/// kept as they are. Comments and formatting are not kept, and the input must
+/// hold a single document.
+pub fn convert_yaml_str(input: &str, converter: &Converter) -> Result<String, Error> {
+    let value = serde_yaml::from_str(input).map_err(Error::Yaml)?;
+    let converted = converter.convert_yaml_value(value)?;
+    serde_yaml::to_string(&converted).map_err(Error::Yaml)
+}
+
+impl Converter<'_> {
+    /// Converts the keys of the mappings in `value`, as
+    /// [`Converter::convert_value`] does for JSON. Keys that aren't strings are
+    /// kept, and the values under them are still converted. Tags are kept.
+    pub fn convert_yaml_value(&self, value: Value) -> Result<Value, Error> {
+        self.convert_yaml(value, &Path::Root)
+    }
+
+    fn convert_yaml(&self, value: Value, path: &Path) -> Result<Value, Error> {
+        match value {
+            Value::Sequence(sequence) => sequence
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| self.convert_yaml(value, &path.index(index)))
+                .collect::<Result<_, _>>()
+                .map(Value::Sequence),
+            Value::Mapping(mapping) => self.convert_yaml_mapping(mapping, path).map(Value::Mapping),
+            Value::Tagged(mut tagged) => {
+                let value = core::mem::replace(&mut tagged.value, Value::Null);
+                tagged.value = self.convert_yaml(value, path)?;
+                Ok(Value::Tagged(tagged))
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn convert_yaml_mapping(&self, mapping: Mapping, path: &Path) -> Result<Mapping, Error> {
+        let mut new_mapping = Mapping::new();
+        for (key, value) in mapping {
+            let key_text = match &key {
+                Value::String(key) => {
+                    let key_path = path.key(key);
+                    if let Some(new_key) = self.rename_key(key, &key_path)? {
+                        let value = self.convert_yaml(value, &key_path)?;
+                        new_mapping.insert(Value::String(new_key), value);
+                    }
+                    continue;
+                }
+                Value::Number(number) => number.to_string(),
+                Value::Bool(boolean) => boolean.to_string(),
+                _ => String::new(),
+            };
+            let value = self.convert_yaml(value, &path.key(&key_text))?;
+            new_mapping.insert(key, value);
+        }
+        Ok(new_mapping)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn converts_nested_mappings_and_sequences() {
+        let input = "\
+userName: Ada
+homeAddress:
+  streetName: Main
+  zipCode: 12345
+pastAddresses:
+  - streetName: Old
+    isCurrent: false
+  - streetName: Older
+";
+        let converted = convert_yaml_str(input, &Converter::new(KeyCase::Snake)).unwrap();
+
+        assert_eq!(
+            "\
+user_name: Ada
+home_address:
+  street_name: Main
+  zip_code: 12345
+past_addresses:
+- street_name: Old
+  is_current: false
+- street_name: Older
+",
+            converted
+        );
+    }
+
+    #[test]
+    fn keeps_non_string_keys() {
+        let input = "\
+404:
+  errorMessage: Not found
+true: yes
+[a, b]: pair
+";
+        let converted = convert_yaml_str(input, &Converter::new(KeyCase::Snake)).unwrap();
+
+        assert_eq!(
+            "\
+404:
+  error_message: Not found
+true: yes
+? - a
+  - b
+: pair
+",
+            converted
+        );
+    }
+
+    #[test]
+    fn applies_the_key_pipeline() {
+        let mut converter = Converter::new(KeyCase::Camel);
+        let renames: RenameMap = [("ID", "identifier")].iter().cloned().collect();
+        converter.with_manual_renames(renames);
+        converter.with_dictionary_keys(vec!["labels"]);
+
+        let input = "\
+defaults: &defaults
+  retry_count: 3
+ID: 1
+labels:
+  app_name: web
+job:
+  <<: *defaults
+  max_runtime: 10
+";
+        let converted = convert_yaml_str(input, &converter).unwrap();
+
+        assert_eq!(
+            "\
+defaults:
+  retryCount: 3
+identifier: 1
+labels:
+  app_name: web
+job:
+  <<:
+    retryCount: 3
+  maxRuntime: 10
+",
+            converted
+        );
+    }
+
+    #[test]
+    fn reports_invalid_yaml() {
+        let err = convert_yaml_str("a: [b", &Converter::new(KeyCase::Snake)).unwrap_err();
+        assert!(err.to_string().starts_with("invalid YAML: "));
+    }
+}
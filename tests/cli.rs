@@ -0,0 +1,141 @@
+#![cfg(feature = "cli")]
+
+use assert_cmd::Command;
+use json_keys_case_changer::Compression;
+use std::io::Read;
+
+fn json_case() -> Command {
+    Command::cargo_bin("json-case").unwrap()
+}
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn stdin_to_stdout() {
+    json_case()
+        .args(["--to", "snake"])
+        .write_stdin(r#"{"userName": "Ada", "pastOrders": [{"orderId": 1}]}"#)
+        .assert()
+        .success()
+        .stdout("{\"user_name\":\"Ada\",\"past_orders\":[{\"order_id\":1}]}\n");
+}
+
+#[test]
+fn file_input_with_options() {
+    json_case()
+        .args([
+            "--to",
+            "kebab-case",
+            "--pretty",
+            "--exclude-pointer",
+            "/homeAddress/zipCode",
+        ])
+        .args(["--renames", &fixture("renames.json"), &fixture("user.json")])
+        .assert()
+        .success()
+        .stdout(
+            r#"{
+  "user-name": "Ada",
+  "home-address": {
+    "zipCode": "1"
+  },
+  "id": 7
+}
+"#,
+        );
+}
+
+#[test]
+fn inline_options() {
+    json_case()
+        .args(["--case", "snake", "--rename", "ID=id", "--rename", "userName=login"])
+        .args(["--exclude", "en_US", "--depth", "2"])
+        .write_stdin(
+            r#"{"ID": 1, "userName": "Ada", "labels": {"en_US": "Hi", "deDE": {"shortText": "Hallo"}}}"#,
+        )
+        .assert()
+        .success()
+        .stdout(
+            "{\"id\":1,\"login\":\"Ada\",\"labels\":{\"en_US\":\"Hi\",\"de_de\":{\"shortText\":\"Hallo\"}}}\n",
+        );
+
+    json_case()
+        .args(["--to", "snake", "--rename", "ID"])
+        .write_stdin("{}")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn check_mode() {
+    json_case()
+        .args(["--to", "camel", "--check"])
+        .write_stdin(r#"{"userName": "Ada", "home_address": {"zip_code": "1"}}"#)
+        .assert()
+        .code(1)
+        .stdout(
+            "1:21: `home_address` at `/home_address` would become `homeAddress`\n\
+             1:38: `zip_code` at `/home_address/zip_code` would become `zipCode`\n",
+        );
+
+    json_case()
+        .args(["--to", "camel", "--check"])
+        .write_stdin(r#"{"userName": "Ada"}"#)
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn invalid_input() {
+    let assert = json_case()
+        .args(["--to", "snake"])
+        .write_stdin(r#"{"userName": "#)
+        .assert()
+        .code(2)
+        .stdout("");
+    let stderr = String::from_utf8_lossy(&assert.get_output().stderr).into_owned();
+    assert!(
+        stderr.starts_with("json-case: invalid JSON input: "),
+        "{}",
+        stderr
+    );
+
+    json_case().args(["--to", "snek"]).assert().code(2);
+}
+
+#[test]
+fn compressed_files() {
+    let expected = "{\"user_name\":\"Ada\",\"home_address\":{\"zip_code\":\"1\"},\"id\":7}\n";
+    let output = std::env::temp_dir().join(format!("json-case-{}.json.zst", std::process::id()));
+
+    json_case()
+        .args(["--to", "snake", "--level", "19", "--output"])
+        .arg(&output)
+        .arg(fixture("user.json.gz"))
+        .assert()
+        .success()
+        .stdout("");
+    let read_back = json_case().args(["--to", "snake"]).arg(&output).assert();
+    std::fs::remove_file(&output).unwrap();
+    read_back.success().stdout(expected);
+
+    // An explicit compression applies to stdin and stdout too.
+    let compressed = json_case()
+        .args(["--to", "snake", "--compression", "gzip"])
+        .write_stdin(std::fs::read(fixture("user.json.gz")).unwrap())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let mut converted = String::new();
+    Compression::Gzip
+        .reader(compressed.as_slice())
+        .unwrap()
+        .read_to_string(&mut converted)
+        .unwrap();
+    assert_eq!(expected, converted);
+}
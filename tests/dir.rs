@@ -0,0 +1,141 @@
+#![cfg(feature = "dir")]
+
+use json_keys_case_changer::{Converter, DirOptions, DirOutput, Error, KeyCase};
+use std::fs;
+use std::path::Path;
+
+fn write(root: &Path, name: &str, contents: &str) {
+    let path = root.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+fn read(root: &Path, name: &str) -> String {
+    fs::read_to_string(root.join(name)).unwrap()
+}
+
+fn fixture_tree() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "users.json", "{\"userName\": \"Ada\"}\n");
+    write(dir.path(), "nested/orders.json", r#"[{"orderId": 1}]"#);
+    write(dir.path(), "nested/done.json", r#"{"already_snake": true}"#);
+    write(dir.path(), "nested/broken.json", r#"{"userName": "#);
+    write(dir.path(), "notes.txt", r#"{"userName": "Ada"}"#);
+    dir
+}
+
+#[test]
+fn in_place_with_backups() {
+    let dir = fixture_tree();
+    let root = dir.path();
+    let report = Converter::new(KeyCase::Snake)
+        .convert_dir(DirOptions::new(root, DirOutput::InPlace { backup: true }))
+        .unwrap();
+
+    assert_eq!(
+        vec![root.join("nested/orders.json"), root.join("users.json")],
+        report.converted
+    );
+    assert_eq!(vec![root.join("nested/done.json")], report.unchanged);
+    assert_eq!(1, report.errors.len());
+    assert_eq!(root.join("nested/broken.json"), report.errors[0].0);
+    assert!(matches!(report.errors[0].1, Error::Parse(_)));
+    assert!(!report.is_success());
+
+    assert_eq!("{\"user_name\":\"Ada\"}\n", read(root, "users.json"));
+    assert_eq!("{\"userName\": \"Ada\"}\n", read(root, "users.json.bak"));
+    assert_eq!(r#"[{"order_id":1}]"#, read(root, "nested/orders.json"));
+    assert_eq!(r#"{"userName": "#, read(root, "nested/broken.json"));
+    assert_eq!(r#"{"userName": "Ada"}"#, read(root, "notes.txt"));
+    // Unchanged and failed files are neither rewritten nor backed up.
+    assert!(!root.join("nested/done.json.bak").exists());
+    assert!(!root.join("nested/broken.json.bak").exists());
+
+    let mut names: Vec<_> = fs::read_dir(root)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    names.sort();
+    assert_eq!(
+        vec!["nested", "notes.txt", "users.json", "users.json.bak"],
+        names
+    );
+}
+
+#[test]
+fn into_another_directory() {
+    let input = fixture_tree();
+    let output = tempfile::tempdir().unwrap();
+    let mut options = DirOptions::new(input.path(), DirOutput::Dir(output.path().to_owned()));
+    options.glob = "nested/*.json".to_owned();
+    options.parallel = true;
+    options.pretty = true;
+
+    let report = Converter::new(KeyCase::Camel).convert_dir(options).unwrap();
+    assert_eq!(1, report.converted.len());
+    assert_eq!(1, report.unchanged.len());
+    assert_eq!(1, report.errors.len());
+
+    assert_eq!(
+        "{\n  \"alreadySnake\": true\n}",
+        read(output.path(), "nested/done.json")
+    );
+    assert!(!output.path().join("users.json").exists());
+    assert!(!output.path().join("nested/broken.json").exists());
+    assert_eq!(
+        r#"{"already_snake": true}"#,
+        read(input.path(), "nested/done.json")
+    );
+}
+
+#[test]
+fn fail_fast_stops_at_the_first_failure() {
+    let dir = tempfile::tempdir().unwrap();
+    write(dir.path(), "a.json", "{");
+    write(dir.path(), "b.json", r#"{"userName": "Ada"}"#);
+    let mut options = DirOptions::new(dir.path(), DirOutput::InPlace { backup: false });
+    options.fail_fast = true;
+
+    let report = Converter::new(KeyCase::Snake).convert_dir(options).unwrap();
+    assert_eq!(1, report.errors.len());
+    assert!(report.converted.is_empty());
+    assert_eq!(r#"{"userName": "Ada"}"#, read(dir.path(), "b.json"));
+
+    let mut options = DirOptions::new(dir.path(), DirOutput::InPlace { backup: false });
+    options.glob = "[".to_owned();
+    assert!(Converter::new(KeyCase::Snake).convert_dir(options).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn skips_symlinks() {
+    let dir = fixture_tree();
+    let root = dir.path();
+    std::os::unix::fs::symlink(root.join("users.json"), root.join("link.json")).unwrap();
+
+    let report = Converter::new(KeyCase::Snake)
+        .convert_dir(DirOptions::new(root, DirOutput::InPlace { backup: false }))
+        .unwrap();
+    assert_eq!(vec![root.join("link.json")], report.skipped);
+    assert!(report.converted.contains(&root.join("users.json")));
+}
+
+#[cfg(unix)]
+#[test]
+fn converts_non_utf8_paths() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(OsStr::from_bytes(b"caf\xe9.json"));
+    fs::write(&path, r#"{"userName": "Ada"}"#).unwrap();
+
+    let report = Converter::new(KeyCase::Snake)
+        .convert_dir(DirOptions::new(
+            dir.path(),
+            DirOutput::InPlace { backup: false },
+        ))
+        .unwrap();
+    assert_eq!(vec![path.clone()], report.converted);
+    assert_eq!(r#"{"user_name":"Ada"}"#, fs::read_to_string(&path).unwrap());
+}
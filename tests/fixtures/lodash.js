@@ -0,0 +1,76 @@
+// Writes lodash.json, the keys of the lodash compatibility test with what
+// lodash's camelCase, snakeCase and kebabCase make of them:
+//
+//     npm install lodash@4.17.21 && node lodash.js > lodash.json
+
+const _ = require('lodash');
+
+const phrases = [
+  'user id', 'first name', 'last name', 'email address', 'created at',
+  'updated at', 'is active', 'has children', 'order line items', 'unit price',
+  'http server', 'xml http request', 'api key', 'oauth2 token', 'ipv4 address',
+  'ipv6', 'sha256 sum', 'base64 data', 'utf8 string', 'v2', 'v2 api',
+  'foo2 bar', 'a1 b2 c3', 'address line1', 'address line 2', 'page 10 of 20',
+  'top 3 items', 'item 1st', '1st place', '2nd place', '3rd place', '4th floor',
+  '21st century', '11th hour', 'x', 'a b c', 'id', 'url', 'uuid',
+  'mime type', 'e tag', 'e2e test', 'h264 codec', '3d model', 'mp3 file',
+  'covid19 cases', 'x y z', 'rgb a', 'k8s pod', 'i18n key', 'web3 wallet',
+];
+
+const styles = [
+  (words) => words.join(' '),
+  (words) => words.join('_'),
+  (words) => words.join('-'),
+  (words) => words.join('.'),
+  (words) => words.map((w) => w.toUpperCase()).join('_'),
+  (words) => words.map((w, i) => (i ? w[0].toUpperCase() + w.slice(1) : w)).join(''),
+  (words) => words.map((w) => w[0].toUpperCase() + w.slice(1)).join(''),
+  (words) => words.map((w) => w.toUpperCase()).join(''),
+];
+
+const keys = new Set();
+for (const phrase of phrases) {
+  for (const style of styles) {
+    keys.add(style(phrase.split(' ')));
+  }
+}
+
+for (const key of [
+  'HTTPServer', 'XMLHttpRequest', 'IOError', 'getHTTPResponseCode', 'ABCdef',
+  'AbcDEF', 'ABC123def', 'abc123DEF', 'userID', 'UserIDs', 'iPhone', 'iOS',
+  'eBay', 'McDonald', 'PDFFile', 'fooBAR', 'FOObar', 'foo2Bar', 'Foo2bar',
+  'foo22bar', 'foo_2_bar', 'foo__bar', '_private', '__proto__', 'trailing_',
+  '-leading-dash', 'trailing-', '  spaced  out  ', 'tab\tseparated',
+  'line\nbreak', 'dots.in.key', 'slash/key', 'colon:key', 'at@sign',
+  'hash#key', 'dollar$key', '$ref', '@type', 'percent%', 'plus+key',
+  'equals=key', 'brace{key}', 'pipe|key', 'tilde~key', 'back\\slash',
+  'quote"key', "don't", "doesn't_matter", "it's", 'rock’n’roll',
+  "O'Reilly", "you'll", "we've", "I'd", "they're", "DON'T", 'café',
+  'crème brûlée', 'naïveté', 'Ünïcödé Kéy', 'straße', 'Æsir', 'Œuvre',
+  'ĳssel', 'Łódź', 'İstanbul', 'ðæ', 'Þorn', 'señorÑandú', 'garçon',
+  'Ångström', 'smörgåsbord', 'résumé_v2', 'ΣΑΣ_key', 'ελληνικά',
+  'русскийКлюч', 'ключ_два', '日本語キー', '键_值', 'emoji😀key',
+  '👍🏽likes', 'ab😀cd', '🇫🇷flag', '👨‍👩‍👧family', '✈️flight', '❤heart',
+  'user‍id', 'zero​width', 'nb sp', 'nfd café',
+  'mixed_Case-key.name', 'a', 'A', '1', '123', '1a', 'a1', 'A1', '1A',
+  '10x', 'x10', 'X10Y', 'v1.2.3', '1stPlace', 'FIRST1ST', '1ST_PLACE',
+  '2ndTry', '3RDTime', '4THWall', '21st', '22ND', '23rd', '111th',
+  'abc1stDef', 'my1stKey', 'getV2APIKey', 'v2API', 'API2Key', 'APIv2',
+  'html5Parser', 'HTML5Parser', 'int32Value', 'Int32Value', 'uint8Array',
+  'Float64Array', 'x86_64', 'arm64v8', 'utf-8', 'UTF8', 'ISO8859_1',
+  'snake_case_key', 'kebab-case-key', 'camelCaseKey', 'PascalCaseKey',
+  'SCREAMING_SNAKE_KEY', 'Train-Case-Key', 'COBOL-CASE-KEY', 'flatcasekey',
+  'UPPERFLATKEY', 'Title Case Key', 'sentence case key', 'aLtErNaTiNg',
+  'tOGGLE cASE', '___', '---', '', '  ', '×÷', 'a×b', 'price±tax',
+  'µs', 'º_degree', '½half', '¿question', '¡bang', '©copy', '§section',
+]) {
+  keys.add(key);
+}
+
+const cases = [...keys].map((key) => ({
+  key,
+  camel: _.camelCase(key),
+  snake: _.snakeCase(key),
+  kebab: _.kebabCase(key),
+}));
+console.log('[\n' + cases.map((c) => ' ' + JSON.stringify(c)).join(',\n') + '\n]');
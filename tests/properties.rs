@@ -0,0 +1,28 @@
+//! Property tests of a pipeline that converts camelCase documents to
+//! snake_case, using the strategies of the `test-utils` feature.
+#![cfg(feature = "test-utils")]
+
+use json_keys_case_changer::{arb_json_with_case, arb_key_in_case, Converter, KeyCase};
+use proptest::prelude::*;
+use serde_json::Value;
+
+/// Whether every key of `value` is kept by converting it to snake case.
+fn has_snake_case_keys(value: &Value) -> bool {
+    let snake = Converter::new(KeyCase::Snake);
+    snake.check_value(value).unwrap().is_empty()
+}
+
+proptest! {
+    #[test]
+    fn pipeline_outputs_snake_case(value in arb_json_with_case(KeyCase::Camel, 3)) {
+        let converted = Converter::new(KeyCase::Snake).convert_value(value).unwrap();
+        prop_assert!(has_snake_case_keys(&converted));
+    }
+
+    #[test]
+    fn single_keys_lose_their_capitals(key in arb_key_in_case(KeyCase::Pascal)) {
+        let preview = Converter::new(KeyCase::Snake).preview_key(&key, None);
+        let new_key = preview.key.unwrap().unwrap();
+        prop_assert_eq!(key.to_lowercase(), new_key.replace('_', ""));
+    }
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use json_keys_case_changer::server::{router, ServerConfig};
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+async fn send(config: ServerConfig, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router(config).oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn post(uri: &str, body: impl Into<Body>) -> Request<Body> {
+    Request::post(uri)
+        .header("content-type", "application/json")
+        .body(body.into())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn converts_with_query_and_headers() {
+    let (status, body) = send(
+        ServerConfig::default(),
+        post(
+            "/convert?case=snake_case",
+            r#"{"userName": "Ada", "zipCode": 1}"#,
+        ),
+    )
+    .await;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(json!({"user_name": "Ada", "zip_code": 1}), body);
+
+    let request = Request::post("/convert")
+        .header("x-target-case", "camelCase")
+        .header("x-preset", "mongodb")
+        .body(Body::from(r#"{"_id": 1, "first_name": "Ada"}"#))
+        .unwrap();
+    let (status, body) = send(ServerConfig::default(), request).await;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(json!({"_id": 1, "firstName": "Ada"}), body);
+
+    let (status, body) = send(ServerConfig::default(), post("/convert", "{}")).await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert_eq!("missing_case", body["error"]["code"]);
+}
+
+#[tokio::test]
+async fn converts_with_a_spec() {
+    let envelope = json!({
+        "spec": {"case": "kebab-case", "manual_renames": {"ID": "identifier"}},
+        "document": {"ID": 7, "orderTotal": 9}
+    });
+    let (status, body) = send(
+        ServerConfig::default(),
+        post("/convert/spec", envelope.to_string()),
+    )
+    .await;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(json!({"identifier": 7, "order-total": 9}), body);
+}
+
+#[tokio::test]
+async fn rejects_bad_specs() {
+    let envelope = json!({
+        "spec": {"case": "snake_case", "excluded_pointers": ["meta"]},
+        "document": {}
+    });
+    let (status, body) = send(
+        ServerConfig::default(),
+        post("/convert/spec", envelope.to_string()),
+    )
+    .await;
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, status);
+    assert_eq!("invalid_spec", body["error"]["code"]);
+    assert_eq!("invalid_pointer", body["error"]["diagnostics"][0]["code"]);
+
+    let envelope = json!({"spec": {"case": "shouting"}, "document": {}});
+    let (status, body) = send(
+        ServerConfig::default(),
+        post("/convert/spec", envelope.to_string()),
+    )
+    .await;
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, status);
+    assert_eq!("invalid_envelope", body["error"]["code"]);
+
+    let (status, body) = send(
+        ServerConfig::default(),
+        post("/convert/spec", r#"{"spec": "#),
+    )
+    .await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert_eq!("invalid_json", body["error"]["code"]);
+}
+
+#[tokio::test]
+async fn rejects_oversized_bodies() {
+    let mut config = ServerConfig::default();
+    config.body_limit = 64;
+    let document = json!({"padding": "x".repeat(100)});
+    let (status, body) = send(config, post("/convert?case=snake", document.to_string())).await;
+    assert_eq!(StatusCode::PAYLOAD_TOO_LARGE, status);
+    assert_eq!("body_too_large", body["error"]["code"]);
+}
+
+#[tokio::test]
+async fn answers_probes() {
+    let request = Request::get("/healthz").body(Body::empty()).unwrap();
+    let response = router(ServerConfig::default())
+        .oneshot(request)
+        .await
+        .unwrap();
+    assert_eq!(StatusCode::OK, response.status());
+}
@@ -0,0 +1,25 @@
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use json_keys_case_changer::wasm::{convert_string, convert_with_spec};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn converts_a_string() {
+    assert_eq!(
+        Ok(r#"{"user_name":"Ada"}"#.to_owned()),
+        convert_string(r#"{"userName":"Ada"}"#, "snake").map_err(drop)
+    );
+
+    let spec = r#"{"case": "kebab", "manual_renames": {"ID": "id"}}"#;
+    assert_eq!(
+        Ok(r#"{"id":1,"user-name":"Ada"}"#.to_owned()),
+        convert_with_spec(r#"{"ID":1,"userName":"Ada"}"#, spec).map_err(drop)
+    );
+}
+
+#[wasm_bindgen_test]
+fn invalid_case_name_is_an_error() {
+    let err = convert_string("{}", "snek").unwrap_err();
+    let message = err.as_string().unwrap();
+    assert!(message.starts_with("unknown case `snek`, did you mean `snake_case`?"));
+}